@@ -157,6 +157,17 @@ const SCREEN_ROUTE: Record<Screen, string> = {
   battle: '/battleship/battle',
 };
 
+// Attacked cells arrive as a bitmap: cell `i` is bit `i % 8` of byte `i / 8`.
+function bitmapCells(bitmap: Buffer): number[] {
+  const cells: number[] = [];
+  bitmap.forEach((byte, index) => {
+    for (let bit = 0; bit < 8; bit += 1) {
+      if (byte & (1 << bit)) cells.push(index * 8 + bit);
+    }
+  });
+  return cells;
+}
+
 function screenFromRoute(route: string): Screen {
   if (route.endsWith('/setup')) return 'setup';
   if (/\/placement(\/\d+)?$/.test(route)) return 'placement';
@@ -527,10 +538,12 @@ export function BattleshipGame() {
       };
     });
 
-    const commitments = secrets.map((cell) => {
-      const payload = new Uint8Array(33);
+    // Commitment v2: the cell index is part of the preimage.
+    const commitments = secrets.map((cell, index) => {
+      const payload = new Uint8Array(37);
       payload[0] = cell.isShip ? 1 : 0;
-      payload.set(cell.salt, 1);
+      payload.set(toU32Bytes(index), 1);
+      payload.set(cell.salt, 5);
       return Buffer.from(keccak_256.arrayBuffer(payload));
     });
 
@@ -562,10 +575,10 @@ export function BattleshipGame() {
 
   function syncBoardVisualsFromOnChain(game: ContractGame, myAddress: string, occupiedMap?: boolean[]) {
     const meIsPlayer1 = game.player1 === myAddress;
-    const myAttacks = (meIsPlayer1 ? game.player1_attacks : game.player2_attacks).map(Number);
-    const myHitAttacks = new Set((meIsPlayer1 ? game.player1_hit_attacks : game.player2_hit_attacks).map(Number));
-    const opponentAttacks = (meIsPlayer1 ? game.player2_attacks : game.player1_attacks).map(Number);
-    const opponentHitAttacks = new Set((meIsPlayer1 ? game.player2_hit_attacks : game.player1_hit_attacks).map(Number));
+    const myAttacks = bitmapCells(meIsPlayer1 ? game.player1_attacks : game.player2_attacks);
+    const myHitAttacks = new Set(bitmapCells(meIsPlayer1 ? game.player1_hit_attacks : game.player2_hit_attacks));
+    const opponentAttacks = bitmapCells(meIsPlayer1 ? game.player2_attacks : game.player1_attacks);
+    const opponentHitAttacks = new Set(bitmapCells(meIsPlayer1 ? game.player2_hit_attacks : game.player1_hit_attacks));
 
     const nextEnemyShots = new Set<string>();
     const nextEnemyHits = new Set<string>();
@@ -1843,7 +1856,7 @@ export function BattleshipGame() {
     const pendingAttacker = optionValue<string>(game.pending_attacker);
     if (turn !== aiAddress || typeof pendingAttacker === 'string') return;
 
-    const aiAttacks = (game.player1 === aiAddress ? game.player1_attacks : game.player2_attacks).map(Number);
+    const aiAttacks = bitmapCells(game.player1 === aiAddress ? game.player1_attacks : game.player2_attacks);
     const used = new Set(aiAttacks);
 
     let chosenIndex = -1;
@@ -46,18 +46,18 @@ export interface Game {
   pending_x: Option<u32>;
   pending_y: Option<u32>;
   player1: string;
-  player1_attacks: Array<u32>;
+  player1_attacks: Buffer;
   player1_board: Option<Array<Buffer>>;
   player1_deposited: boolean;
-  player1_hit_attacks: Array<u32>;
+  player1_hit_attacks: Buffer;
   player1_hits: u32;
   player1_points: i128;
   player1_ship_cells: Option<u32>;
   player2: string;
-  player2_attacks: Array<u32>;
+  player2_attacks: Buffer;
   player2_board: Option<Array<Buffer>>;
   player2_deposited: boolean;
-  player2_hit_attacks: Array<u32>;
+  player2_hit_attacks: Buffer;
   player2_hits: u32;
   player2_points: i128;
   player2_ship_cells: Option<u32>;
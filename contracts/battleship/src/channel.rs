@@ -0,0 +1,241 @@
+use battlefield_common::digest::{checkpoint_message, state_digest};
+use soroban_sdk::{contractimpl, contracttype, Address, Bytes, BytesN, Env};
+
+use crate::{
+  apply_reward_multiplier, dev_window, end_game_hub, game_cell_commitment, has_bit, is_over, missing_game, require_phase, settle_wager,
+  start_turn_clock, store_game, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, Checkpointed, DataKey, Error, FraudProven, Game,
+  GamePhase, PERSISTENT_STATE_TTL_LEDGERS, PLAY_PHASES,
+};
+
+// A state channel over one game. Players sign checkpoints with the ed25519 keys given at open;
+// `state_digest` is the `get_game_digest` of the latest co-signed state, starting with the
+// game as it stood when the channel opened. Once forced on-chain, the opponent of `forced_by`
+// has until `challenge_deadline` to prove the checkpoint stale or the state dishonest.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Channel {
+  pub player1_key: BytesN<32>,
+  pub player2_key: BytesN<32>,
+  pub state_digest: BytesN<32>,
+  pub move_count: u32,
+  pub checkpoint_ledger: u32,
+  pub open: bool,
+  pub forced_by: Option<Address>,
+  pub challenge_deadline: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChannelKey { Channel(u32) }
+
+pub const CHANNEL_CHALLENGE_LEDGERS: u32 = 720;
+
+#[contractimpl]
+impl BattleshipContract {
+  // Moves off-chain once both boards are committed and any stakes funded. The on-chain turn is
+  // parked, so attacks, resolutions, skips and timeouts are unavailable until `force_onchain`.
+  // Resigning, draws and adjudication still work. Returns the opening digest.
+  pub fn open_channel(env: Env, session_id: u32, player1_key: BytesN<32>, player2_key: BytesN<32>) -> Result<BytesN<32>, Error> {
+    let mut game = load_game(&env, session_id)?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+    require_phase(&game, &PLAY_PHASES)?;
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if game.shot_tax > 0 || game.queued_attacker.is_some() { return Err(Error::InvalidConfig); }
+    let key = ChannelKey::Channel(session_id);
+    if env.storage().persistent().has(&key) { return Err(Error::ChannelOpen); }
+
+    let digest = state_digest(&env, &env.current_contract_address(), session_id, game.clone());
+    let channel = Channel { player1_key, player2_key, state_digest: digest.clone(), move_count: 0, checkpoint_ledger: env.ledger().sequence(), open: true, forced_by: None, challenge_deadline: 0 };
+    store_channel(&env, session_id, &channel);
+
+    game.turn = None;
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.phase = GamePhase::InProgress;
+    store_game(&env, session_id, &game);
+    Ok(digest)
+  }
+
+  // Anyone may submit; both signatures are over `checkpoint_message(state_digest, move_count)`
+  // and `move_count` must increase.
+  pub fn checkpoint(env: Env, session_id: u32, state_digest: BytesN<32>, sig1: BytesN<64>, sig2: BytesN<64>, move_count: u32) -> Result<(), Error> {
+    let game = load_game(&env, session_id)?;
+    require_phase(&game, &PLAY_PHASES)?;
+    let mut channel = load_open_channel(&env, session_id)?;
+    if move_count <= channel.move_count { return Err(Error::StaleCheckpoint); }
+
+    let message = checkpoint_message(&env, &state_digest, move_count);
+    env.crypto().ed25519_verify(&channel.player1_key, &message, &sig1);
+    env.crypto().ed25519_verify(&channel.player2_key, &message, &sig2);
+
+    channel.state_digest = state_digest.clone();
+    channel.move_count = move_count;
+    channel.checkpoint_ledger = env.ledger().sequence();
+    store_channel(&env, session_id, &channel);
+    Checkpointed { session_id, state_digest, move_count }.publish(&env);
+    Ok(())
+  }
+
+  // Either player closes the channel by revealing the state behind the latest checkpoint. Only
+  // shots, hits, the turn and the pending attack are taken from it; everything else must match
+  // the game on-chain. Play resumes on-chain with a fresh turn clock, and the challenge window
+  // opens.
+  pub fn force_onchain(env: Env, session_id: u32, player: Address, state: Game) -> Result<(), Error> {
+    player.require_auth();
+    let mut game = load_game(&env, session_id)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    require_phase(&game, &PLAY_PHASES)?;
+    let mut channel = load_open_channel(&env, session_id)?;
+
+    if state_digest(&env, &env.current_contract_address(), session_id, state.clone()) != channel.state_digest { return Err(Error::InvalidCheckpoint); }
+    if !same_setup(&game, &state) || is_over(&state) || (state.turn.is_none() && state.pending_attacker.is_none()) { return Err(Error::InvalidCheckpoint); }
+    let player1_cells = game.player1_ship_cells.unwrap_or(0);
+    let player2_cells = game.player2_ship_cells.unwrap_or(0);
+    if state.player1_hits >= player2_cells || state.player2_hits >= player1_cells { return Err(Error::InvalidCheckpoint); }
+
+    game.player1_hits = state.player1_hits;
+    game.player2_hits = state.player2_hits;
+    game.player1_attacks = state.player1_attacks;
+    game.player2_attacks = state.player2_attacks;
+    game.player1_hit_attacks = state.player1_hit_attacks;
+    game.player2_hit_attacks = state.player2_hit_attacks;
+    game.turn = state.turn;
+    game.pending_attacker = state.pending_attacker;
+    game.pending_defender = state.pending_defender;
+    game.pending_x = state.pending_x;
+    game.pending_y = state.pending_y;
+    game.phase = if game.pending_attacker.is_some() { GamePhase::AwaitingResolve } else { GamePhase::InProgress };
+    start_turn_clock(&env, &mut game);
+    store_game(&env, session_id, &game);
+
+    channel.open = false;
+    channel.forced_by = Some(player);
+    channel.challenge_deadline = env.ledger().sequence().saturating_add(dev_window(&env, CHANNEL_CHALLENGE_LEDGERS));
+    store_channel(&env, session_id, &channel);
+    Ok(())
+  }
+
+  // The opponent of whoever forced the game on-chain shows a later co-signed checkpoint. The
+  // forcing player withheld it and forfeits.
+  pub fn challenge_stale_checkpoint(
+    env: Env,
+    session_id: u32,
+    challenger: Address,
+    state_digest: BytesN<32>,
+    sig1: BytesN<64>,
+    sig2: BytesN<64>,
+    move_count: u32,
+  ) -> Result<(), Error> {
+    challenger.require_auth();
+    let game = load_game(&env, session_id)?;
+    require_phase(&game, &PLAY_PHASES)?;
+    let channel = load_challengeable_channel(&env, session_id)?;
+    let cheater = channel.forced_by.clone().ok_or(Error::ChannelNotOpen)?;
+    if challenger == cheater || (challenger != game.player1 && challenger != game.player2) { return Err(Error::NotPlayer); }
+    if move_count <= channel.move_count { return Err(Error::StaleCheckpoint); }
+
+    let message = checkpoint_message(&env, &state_digest, move_count);
+    env.crypto().ed25519_verify(&channel.player1_key, &message, &sig1);
+    env.crypto().ed25519_verify(&channel.player2_key, &message, &sig2);
+    award_fraud(&env, session_id, game, challenger, cheater)
+  }
+
+  // The challenger opens one of their shots from the checkpointed state against the opponent's
+  // committed board. If the reveal contradicts the recorded hit or miss, the opponent answered
+  // dishonestly off-chain and forfeits. Shots resolved on-chain were already verified.
+  pub fn prove_invalid_transition(env: Env, session_id: u32, challenger: Address, cell: u32, is_ship: bool, salt: Bytes) -> Result<(), Error> {
+    challenger.require_auth();
+    let game = load_game(&env, session_id)?;
+    require_phase(&game, &PLAY_PHASES)?;
+    load_challengeable_channel(&env, session_id)?;
+
+    let (cheater, board, attacks, hits) = if challenger == game.player1 {
+      (game.player2.clone(), &game.player2_board, &game.player1_attacks, &game.player1_hit_attacks)
+    } else if challenger == game.player2 {
+      (game.player1.clone(), &game.player1_board, &game.player2_attacks, &game.player2_hit_attacks)
+    } else {
+      return Err(Error::NotPlayer);
+    };
+    if !has_bit(attacks, cell) { return Err(Error::NoFraud); }
+    let expected = board.as_ref().and_then(|board| board.get(cell)).ok_or(Error::InvalidCoordinate)?;
+    if expected != game_cell_commitment(&env, &game, cell, is_ship, &salt) { return Err(Error::InvalidCellReveal); }
+    if has_bit(hits, cell) == is_ship { return Err(Error::NoFraud); }
+    award_fraud(&env, session_id, game, challenger, cheater)
+  }
+
+  pub fn get_channel(env: Env, session_id: u32) -> Option<Channel> {
+    env.storage().persistent().get(&ChannelKey::Channel(session_id))
+  }
+}
+
+// The parts of a game a checkpoint may not rewrite: who plays, what is staked and which boards
+// were committed.
+fn same_setup(game: &Game, state: &Game) -> bool {
+  game.player1 == state.player1
+    && game.player2 == state.player2
+    && game.player1_stake == state.player1_stake
+    && game.player2_stake == state.player2_stake
+    && game.board_size == state.board_size
+    && game.board_count == state.board_count
+    && game.player1_board == state.player1_board
+    && game.player2_board == state.player2_board
+    && game.player1_board_root == state.player1_board_root
+    && game.player2_board_root == state.player2_board_root
+    && game.player1_ship_cells == state.player1_ship_cells
+    && game.player2_ship_cells == state.player2_ship_cells
+    && game.hash_algorithm == state.hash_algorithm
+    && game.board_transforms == state.board_transforms
+    && game.player1_transform == state.player1_transform
+    && game.player2_transform == state.player2_transform
+    && game.capabilities == state.capabilities
+    && game.fleet == state.fleet
+}
+
+fn load_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+  env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(env, session_id))
+}
+
+fn load_challengeable_channel(env: &Env, session_id: u32) -> Result<Channel, Error> {
+  let channel: Channel = env.storage().persistent().get(&ChannelKey::Channel(session_id)).ok_or(Error::ChannelNotOpen)?;
+  if channel.forced_by.is_none() { return Err(Error::ChannelNotOpen); }
+  if env.ledger().sequence() > channel.challenge_deadline { return Err(Error::ChallengeWindowClosed); }
+  Ok(channel)
+}
+
+fn award_fraud(env: &Env, session_id: u32, mut game: Game, winner: Address, cheater: Address) -> Result<(), Error> {
+  game.pending_attacker = None;
+  game.pending_defender = None;
+  game.pending_x = None;
+  game.pending_y = None;
+  game.queued_attacker = None;
+  game.queued_commitment = None;
+  game.turn = None;
+  game.winner = Some(winner.clone());
+  game.phase = GamePhase::Finished;
+  settle_wager(env, session_id, &mut game)?;
+  apply_reward_multiplier(env, session_id, &mut game);
+  FraudProven { session_id, winner: winner.clone(), cheater }.publish(env);
+  end_game_hub(env, session_id, &game, winner == game.player1);
+  store_game(env, session_id, &game);
+  Ok(())
+}
+
+fn load_open_channel(env: &Env, session_id: u32) -> Result<Channel, Error> {
+  let channel: Channel = env.storage().persistent().get(&ChannelKey::Channel(session_id)).ok_or(Error::ChannelNotOpen)?;
+  if !channel.open { return Err(Error::ChannelNotOpen); }
+  Ok(channel)
+}
+
+// Once the game is over no checkpoint or fraud proof can apply, so the channel goes with it.
+pub(crate) fn discard_channel(env: &Env, session_id: u32) {
+  env.storage().persistent().remove(&ChannelKey::Channel(session_id));
+}
+
+pub(crate) fn store_channel(env: &Env, session_id: u32, channel: &Channel) {
+  let key = ChannelKey::Channel(session_id);
+  env.storage().persistent().set(&key, channel);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
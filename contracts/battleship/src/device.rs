@@ -0,0 +1,162 @@
+use battlefield_common::domain::signing_domain;
+use soroban_sdk::{contractimpl, contracttype, Address, Bytes, BytesN, Env, Vec};
+
+use crate::{delegated_attack, delegated_resolve, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, Error, PERSISTENT_STATE_TTL_LEDGERS};
+
+// A gameplay key bound to a player. `scopes` is a mask of `DEVICE_SCOPE_*`; `expires_ledger`
+// is the last ledger the key signs for, 0 meaning it never expires.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeviceKey {
+  pub key: BytesN<32>,
+  pub scopes: u32,
+  pub expires_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DeviceKeyStore { Keys(Address), Nonce(Address) }
+
+pub const MAX_DEVICE_KEYS: u32 = 8;
+pub const DEVICE_SCOPE_ATTACK: u32 = 1;
+pub const DEVICE_SCOPE_RESOLVE: u32 = 2;
+pub const DEVICE_SCOPE_ALL: u32 = DEVICE_SCOPE_ATTACK | DEVICE_SCOPE_RESOLVE;
+
+const DEVICE_ATTACK: u8 = 1;
+const DEVICE_RESOLVE: u8 = 2;
+
+#[contractimpl]
+impl BattleshipContract {
+  // Binds an ed25519 key to `player` so a lightweight client can sign moves with it instead of
+  // building Soroban auth entries. Adding a key that is already bound updates its scopes and
+  // expiry.
+  pub fn add_device_key(env: Env, player: Address, key: BytesN<32>, scopes: u32, expires_ledger: u32) -> Result<(), Error> {
+    player.require_auth();
+    if scopes == 0 || scopes & !DEVICE_SCOPE_ALL != 0 { return Err(Error::InvalidConfig); }
+    if expires_ledger != 0 && expires_ledger <= env.ledger().sequence() { return Err(Error::InvalidConfig); }
+    let mut keys = load_keys(&env, &player);
+    let device = DeviceKey { key, scopes, expires_ledger };
+    match position(&keys, &device.key) {
+      Some(index) => keys.set(index, device),
+      None if keys.len() >= MAX_DEVICE_KEYS => return Err(Error::DeviceKeyLimit),
+      None => keys.push_back(device),
+    }
+    store_device(&env, &DeviceKeyStore::Keys(player), &keys);
+    Ok(())
+  }
+
+  pub fn remove_device_key(env: Env, player: Address, key: BytesN<32>) -> Result<(), Error> {
+    player.require_auth();
+    let mut keys = load_keys(&env, &player);
+    let index = position(&keys, &key).ok_or(Error::UnknownDeviceKey)?;
+    keys.remove(index);
+    store_device(&env, &DeviceKeyStore::Keys(player), &keys);
+    Ok(())
+  }
+
+  // Swaps `old_key` for `new_key` in place, keeping its scopes and expiry.
+  pub fn rotate_device_key(env: Env, player: Address, old_key: BytesN<32>, new_key: BytesN<32>) -> Result<(), Error> {
+    player.require_auth();
+    let mut keys = load_keys(&env, &player);
+    if position(&keys, &new_key).is_some() { return Err(Error::InvalidConfig); }
+    let index = position(&keys, &old_key).ok_or(Error::UnknownDeviceKey)?;
+    let mut device = keys.get_unchecked(index);
+    device.key = new_key;
+    keys.set(index, device);
+    store_device(&env, &DeviceKeyStore::Keys(player), &keys);
+    Ok(())
+  }
+
+  // Expired keys are listed until removed.
+  pub fn list_device_keys(env: Env, player: Address) -> Vec<DeviceKey> {
+    load_keys(&env, &player)
+  }
+
+  // The nonce the player's next device-signed move must carry. It is shared by all of the
+  // player's keys and never resets, so re-adding a key cannot replay old moves.
+  pub fn get_device_nonce(env: Env, player: Address) -> u32 {
+    env.storage().persistent().get(&DeviceKeyStore::Nonce(player)).unwrap_or(0)
+  }
+
+  // `attack_by_session` with the authorization replaced by a device signature over
+  // `device_attack_message`. Anyone may relay the call.
+  pub fn attack_signed(env: Env, session_id: u32, attacker: Address, device_key: BytesN<32>, x: u32, y: u32, nonce: u32, signature: BytesN<64>) -> Result<(), Error> {
+    let message = device_attack_message(&env, &env.current_contract_address(), session_id, nonce, x, y);
+    consume_device_signature(&env, &attacker, &device_key, DEVICE_SCOPE_ATTACK, nonce, &message, &signature)?;
+    delegated_attack(&env, session_id, attacker, x, y)
+  }
+
+  // The signature covers the proof hash, which already commits to the salt and the target.
+  pub fn resolve_attack_signed(
+    env: Env,
+    session_id: u32,
+    defender: Address,
+    device_key: BytesN<32>,
+    is_ship: bool,
+    salt: Bytes,
+    zk_proof_hash: BytesN<32>,
+    zk_proof_signature: Option<BytesN<64>>,
+    nonce: u32,
+    signature: BytesN<64>,
+  ) -> Result<(), Error> {
+    let message = device_resolve_message(&env, &env.current_contract_address(), session_id, nonce, is_ship, &zk_proof_hash);
+    consume_device_signature(&env, &defender, &device_key, DEVICE_SCOPE_RESOLVE, nonce, &message, &signature)?;
+    delegated_resolve(&env, session_id, defender, is_ship, salt, zk_proof_hash, zk_proof_signature)
+  }
+}
+
+pub fn device_attack_message(env: &Env, contract: &Address, session_id: u32, nonce: u32, x: u32, y: u32) -> Bytes {
+  let mut message = device_message(env, contract, DEVICE_ATTACK, session_id, nonce);
+  message.extend_from_array(&x.to_be_bytes());
+  message.extend_from_array(&y.to_be_bytes());
+  message
+}
+
+pub fn device_resolve_message(env: &Env, contract: &Address, session_id: u32, nonce: u32, is_ship: bool, zk_proof_hash: &BytesN<32>) -> Bytes {
+  let mut message = device_message(env, contract, DEVICE_RESOLVE, session_id, nonce);
+  message.push_back(u8::from(is_ship));
+  message.append(&Bytes::from(zk_proof_hash.clone()));
+  message
+}
+
+fn device_message(env: &Env, contract: &Address, action: u8, session_id: u32, nonce: u32) -> Bytes {
+  let mut message = Bytes::from_array(env, &[action]);
+  message.append(&signing_domain(env, contract));
+  message.extend_from_array(&session_id.to_be_bytes());
+  message.extend_from_array(&nonce.to_be_bytes());
+  message
+}
+
+fn consume_device_signature(
+  env: &Env,
+  player: &Address,
+  device_key: &BytesN<32>,
+  scope: u32,
+  nonce: u32,
+  message: &Bytes,
+  signature: &BytesN<64>,
+) -> Result<(), Error> {
+  let keys = load_keys(env, player);
+  let device = position(&keys, device_key).map(|index| keys.get_unchecked(index)).ok_or(Error::UnknownDeviceKey)?;
+  if device.expires_ledger != 0 && env.ledger().sequence() > device.expires_ledger { return Err(Error::DeviceKeyExpired); }
+  if device.scopes & scope == 0 { return Err(Error::DeviceKeyScope); }
+  let nonce_key = DeviceKeyStore::Nonce(player.clone());
+  let expected: u32 = env.storage().persistent().get(&nonce_key).unwrap_or(0);
+  if nonce != expected { return Err(Error::InvalidNonce); }
+  env.crypto().ed25519_verify(device_key, message, signature);
+  store_device(env, &nonce_key, &nonce.saturating_add(1));
+  Ok(())
+}
+
+fn load_keys(env: &Env, player: &Address) -> Vec<DeviceKey> {
+  env.storage().persistent().get(&DeviceKeyStore::Keys(player.clone())).unwrap_or(Vec::new(env))
+}
+
+fn position(keys: &Vec<DeviceKey>, key: &BytesN<32>) -> Option<u32> {
+  keys.iter().position(|device| device.key == *key).map(|index| index as u32)
+}
+
+fn store_device<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &DeviceKeyStore, value: &V) {
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
@@ -1,10 +1,46 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{
-  contract, contractclient, contracterror, contractimpl, contracttype, vec,
-  token, Address, Bytes, BytesN, Env, IntoVal, Vec,
+  contract, contractclient, contracterror, contractimpl, contractmeta, contracttype, vec,
+  token, xdr::{ScErrorType, ToXdr}, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
 };
 
+pub use battlefield_common::events::{
+  AnnotationAdded, AttackMade, AttackResolved, BoardCommitted, Checkpointed, ConfigChanged, EarlyTermination, EscrowRecovered, FraudProven, GameAdjudicated,
+  GameDrawn, GameEnded, GameStarted, GameVoided, HubNotificationQueued, HubRotated, KeeperPaid, OperatorGameStarted, Payout, RandomnessFulfilled,
+  ReplayAttached, Resigned, RewardMultiplied, SessionVoided, Settlement, SolvencyAlert, StakeDeposited, TimeoutWin, TipReceived, TournamentResultForced,
+  UpgradeExecuted, UpgradeProposed, VerifierOutage, WeakBoardCommitment, EVENT_SCHEMA_VERSION,
+};
+pub use battlefield_common::ConfigBundle;
+use battlefield_common::domain::signing_domain;
+use battlefield_common::placement::{self, CLASSIC_FLEET};
+
+mod channel;
+mod device;
+mod lobby;
+mod movelog;
+mod operator;
+mod prediction;
+mod prover;
+mod rematch;
+mod snapshot;
+mod spectator;
+mod tournament;
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+pub use channel::{Channel, ChannelKey, CHANNEL_CHALLENGE_LEDGERS};
+pub use device::{device_attack_message, device_resolve_message, DeviceKey, DeviceKeyStore, DEVICE_SCOPE_ALL, DEVICE_SCOPE_ATTACK, DEVICE_SCOPE_RESOLVE, MAX_DEVICE_KEYS};
+pub use lobby::{Challenge, LobbyKey, MatchPolicy, QueueStatus, WaitStats, MAX_CHALLENGE_TTL_LEDGERS, MAX_OPEN_CHALLENGES};
+pub use movelog::{LoggedMove, MoveLogIndex, MoveLogKey, MOVES_PER_PAGE};
+pub use operator::{Operator, OperatorKey};
+pub use prediction::{score_picks, PredictionContest, PredictionEntry, PredictionKey};
+pub use prover::{ProverEngagement, ProverKey, ProverListing, CIRCUIT_ATTACK};
+pub use rematch::{RematchKey, RematchOffer};
+pub use snapshot::{GameSnapshot, SNAPSHOT_VERSION};
+pub use spectator::{LiveView, PayPerView, SpectatorKey, MAX_SPECTATORS};
+pub use tournament::{nonce_commitment, Bracket, GameInit, PoolStanding, PrizeSplit, Tournament, TournamentFormat, TournamentKey, TournamentMatch, TournamentSchedule, TournamentStatus};
+
 #[contractclient(name = "GameHubClient")]
 pub trait GameHub {
   fn start_game(
@@ -17,6 +53,12 @@ pub trait GameHub {
     player2_points: i128,
   );
   fn end_game(env: Env, session_id: u32, player1_won: bool);
+  // v2 result interface; hubs without it are detected and sent `end_game` instead.
+  fn end_game_v2(env: Env, session_id: u32, result: GameResult);
+  // Optional extension; only called when the admin has enabled stats reporting for this hub.
+  fn report_stats(env: Env, session_id: u32, player1_hits: u32, player2_hits: u32, moves: u32, duration: u32);
+  // Drawn sessions; a hub that rejects it is retried like a failed `end_game`.
+  fn end_game_draw(env: Env, session_id: u32);
 }
 
 #[contractclient(name = "ZkVerifierClient")]
@@ -37,6 +79,35 @@ pub trait ZkVerifier {
     expected_commitment: BytesN<32>,
     proof: Bytes,
   ) -> bool;
+
+  // Covers one defender's buffered resolutions `from_move..to_move` (0-based, end exclusive)
+  // in a single aggregated proof; `transcript_root` is `compute_transcript_root` over them.
+  fn verify_transcript(
+    env: Env,
+    session_id: u32,
+    from_move: u32,
+    to_move: u32,
+    transcript_root: BytesN<32>,
+    proof: Bytes,
+  ) -> bool;
+}
+
+#[contractclient(name = "EligibilityCheckerClient")]
+pub trait EligibilityChecker {
+  fn is_eligible(env: Env, player: Address) -> bool;
+}
+
+// Item-ownership view of an NFT collection, used by `TokenGate::HoldsItem`.
+#[contractclient(name = "NftCollectionClient")]
+pub trait NftCollection {
+  fn owner_of(env: Env, token_id: u32) -> Address;
+}
+
+// Beacons verify their own output (drand BLS signature, VRF proof, ...) and then call
+// `fulfill_randomness` back on the consumer with the request id.
+#[contractclient(name = "RandomnessBeaconClient")]
+pub trait RandomnessBeacon {
+  fn request_randomness(env: Env, consumer: Address, request_id: u32);
 }
 
 #[contracterror]
@@ -70,6 +141,82 @@ pub enum Error {
   InvalidSession = 25,
   SessionExpired = 26,
   InvalidSessionConfig = 27,
+  InvalidConfig = 28,
+  VerifierChanged = 29,
+  InvalidMerkleProof = 30,
+  ShotTaxRequiresPlayer = 31,
+  InvalidBoardIndex = 32,
+  BoardModeMismatch = 33,
+  GameNotFinished = 34,
+  InvalidAnnotation = 35,
+  VerifierUnavailable = 36,
+  PassNotAvailable = 37,
+  CommitWindowOpen = 38,
+  TournamentNotFound = 39,
+  InvalidTournamentState = 40,
+  InvalidReveal = 41,
+  TournamentFull = 42,
+  InvalidMatch = 43,
+  AlreadyJoined = 44,
+  RegistrationClosed = 45,
+  DeadlineNotReached = 46,
+  PredictionsLocked = 47,
+  InvalidPrediction = 48,
+  InvalidContentHash = 49,
+  PlayerNotEligible = 50,
+  AbandonCooldown = 51,
+  SessionBlacklisted = 52,
+  InvalidBoardSize = 53,
+  BeaconNotConfigured = 54,
+  RandomnessAlreadyFulfilled = 55,
+  BackingClosed = 56,
+  TooManyBackers = 57,
+  GameExpired = 58,
+  GameNotExpired = 59,
+  NothingToRecover = 60,
+  UnsupportedHashAlgorithm = 61,
+  SessionInUse = 62,
+  TranscriptDisabled = 63,
+  AggregationDisabled = 64,
+  TranscriptBufferFull = 65,
+  ProverNotRegistered = 66,
+  UnsupportedCircuit = 67,
+  CapabilityNotNegotiated = 68,
+  RateLimited = 69,
+  InvalidSnapshot = 70,
+  CasualGameOnly = 71,
+  SkipPolicyNotSet = 72,
+  LobbyFull = 73,
+  ChallengeNotFound = 74,
+  ChallengeExpired = 75,
+  NoPendingUpgrade = 76,
+  CreationFrozen = 77,
+  UpgradeDeferred = 78,
+  GrantNotTransferable = 79,
+  NoMatchFound = 80,
+  TimeControlNotFound = 81,
+  TicketRequired = 82,
+  SpectatorsFull = 83,
+  MoveTimeoutNotSet = 84,
+  TokenGateNotMet = 85,
+  NoDrawOffer = 86,
+  ChannelOpen = 87,
+  ChannelNotOpen = 88,
+  StaleCheckpoint = 89,
+  InvalidCheckpoint = 90,
+  ChallengeWindowClosed = 91,
+  NoFraud = 92,
+  FleetMismatch = 93,
+  UnknownDeviceKey = 94,
+  DeviceKeyLimit = 95,
+  InvalidNonce = 96,
+  DeviceKeyExpired = 97,
+  DeviceKeyScope = 98,
+  NoRematchOffer = 99,
+  UnknownOperator = 100,
+  OperatorTaken = 101,
+  VerifierAvailable = 102,
+  NoQueuedAttack = 103,
 }
 
 #[contracttype]
@@ -79,6 +226,8 @@ pub struct Game {
   pub player2: Address,
   pub player1_points: i128,
   pub player2_points: i128,
+  pub player1_stake: i128,
+  pub player2_stake: i128,
   pub board_size: u32,
   pub player1_board: Option<Vec<BytesN<32>>>,
   pub player2_board: Option<Vec<BytesN<32>>>,
@@ -86,10 +235,10 @@ pub struct Game {
   pub player2_ship_cells: Option<u32>,
   pub player1_hits: u32,
   pub player2_hits: u32,
-  pub player1_attacks: Vec<u32>,
-  pub player2_attacks: Vec<u32>,
-  pub player1_hit_attacks: Vec<u32>,
-  pub player2_hit_attacks: Vec<u32>,
+  pub player1_attacks: BytesN<32>,
+  pub player2_attacks: BytesN<32>,
+  pub player1_hit_attacks: BytesN<32>,
+  pub player2_hit_attacks: BytesN<32>,
   pub turn: Option<Address>,
   pub pending_attacker: Option<Address>,
   pub pending_defender: Option<Address>,
@@ -99,6 +248,176 @@ pub struct Game {
   pub player1_deposited: bool,
   pub player2_deposited: bool,
   pub payout_processed: bool,
+  pub player1_board_verifier: Option<Address>,
+  pub player1_board_vk_version: Option<u32>,
+  pub player2_board_verifier: Option<Address>,
+  pub player2_board_vk_version: Option<u32>,
+  pub player1_board_root: Option<BytesN<32>>,
+  pub player2_board_root: Option<BytesN<32>>,
+  pub shot_tax: i128,
+  pub player1_shot_tax_paid: i128,
+  pub player2_shot_tax_paid: i128,
+  pub board_count: u32,
+  pub player1_boards: Vec<ParallelBoard>,
+  pub player2_boards: Vec<ParallelBoard>,
+  pub pending_board: Option<u32>,
+  pub queued_attacker: Option<Address>,
+  pub queued_commitment: Option<BytesN<32>>,
+  pub organizer: Option<Address>,
+  pub organizer_bps: u32,
+  pub first_commit_ledger: Option<u32>,
+  pub voided: bool,
+  pub reward_multiplier_bps: u32,
+  pub randomness_beacon: Option<Address>,
+  pub randomness: Option<BytesN<32>>,
+  pub board_transforms: bool,
+  pub player1_transform: u32,
+  pub player2_transform: u32,
+  pub player1_backers: Vec<Backing>,
+  pub player2_backers: Vec<Backing>,
+  pub player1_backed: i128,
+  pub player2_backed: i128,
+  pub hash_algorithm: HashAlgorithm,
+  pub exhibition: bool,
+  pub transcript_hash: Option<BytesN<32>>,
+  pub start_ledger: u32,
+  pub player1_zk_proofs: u32,
+  pub player2_zk_proofs: u32,
+  pub aggregated_proofs: bool,
+  pub player1_buffer: Vec<BufferedMove>,
+  pub player2_buffer: Vec<BufferedMove>,
+  pub player1_proven_moves: u32,
+  pub player2_proven_moves: u32,
+  pub capabilities: u32,
+  pub turn_started_ledger: u32,
+  pub skip_turn_ledgers: u32,
+  pub skip_penalty: i128,
+  pub player1_skip_bond: i128,
+  pub player2_skip_bond: i128,
+  pub hub: Option<Address>,
+  pub ttl_ledgers: u32,
+  pub commitment_version: u32,
+  pub time_control: Option<Symbol>,
+  pub max_duration_ledgers: u32,
+  pub pinned_verifier: Option<Address>,
+  pub pinned_vk_version: Option<u32>,
+  pub move_timeout_ledgers: u32,
+  pub move_deadline_ledger: u32,
+  pub draw_offered_by: Option<Address>,
+  pub drawn: bool,
+  pub fleet: FleetConfig,
+  pub operator: Option<Symbol>,
+  pub phase: GamePhase,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BufferedMove {
+  pub x: u32,
+  pub y: u32,
+  pub expected_commitment: BytesN<32>,
+  pub is_ship: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveReveal {
+  pub board: u32,
+  pub x: u32,
+  pub y: u32,
+  pub is_ship: bool,
+  pub salt: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Backing {
+  pub backer: Address,
+  pub amount: i128,
+}
+
+// A player's tips for one game. With `hold_until_end` set, tips collect in `held` and move to the
+// withdrawable balance through `release_tips` once the game is over.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TipJar {
+  pub token: Option<Address>,
+  pub hold_until_end: bool,
+  pub held: i128,
+}
+
+// An entry requirement a tournament organizer or challenge poster can impose: a minimum balance
+// of a token, or ownership of one item of an NFT collection. Every gate in a list must be met.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TokenGate {
+  MinBalance(Address, i128),
+  HoldsItem(Address, u32),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameStatus {
+  Active,
+  Finished,
+  Voided,
+}
+
+// Where a game stands. Each transition sets it explicitly and every play entrypoint checks it
+// with `require_phase`. Draws count as finished; voided games, including those voided with a
+// winner for a missed commit, as cancelled.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GamePhase {
+  AwaitingDeposits,
+  AwaitingBoards,
+  InProgress,
+  AwaitingResolve,
+  Finished,
+  Cancelled,
+}
+
+const LIVE_PHASES: [GamePhase; 4] = [GamePhase::AwaitingDeposits, GamePhase::AwaitingBoards, GamePhase::InProgress, GamePhase::AwaitingResolve];
+const SETUP_PHASES: [GamePhase; 2] = [GamePhase::AwaitingDeposits, GamePhase::AwaitingBoards];
+const PLAY_PHASES: [GamePhase; 2] = [GamePhase::InProgress, GamePhase::AwaitingResolve];
+const ENDED_PHASES: [GamePhase; 2] = [GamePhase::Finished, GamePhase::Cancelled];
+
+// What a keeper should call next for an escrowed game: release what is left of the skip bonds,
+// or refund an expired game. Payouts go out in the call that decides the winner, so a finished
+// game never waits on a keeper to settle.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeeperAction {
+  ReleaseBonds,
+  RecoverExpired,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsettledGame {
+  pub session_id: u32,
+  pub status: GameStatus,
+  pub action: KeeperAction,
+  pub hub_pending: bool,
+}
+
+// Outlives the temporary game entry so an expired session can be told apart from a bad id.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameTombstone {
+  pub status: GameStatus,
+  pub escrowed: bool,
+  pub created_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParallelBoard {
+  pub cells: Vec<BytesN<32>>,
+  pub root: BytesN<32>,
+  pub ship_cells: u32,
+  pub hits: u32,
+  pub attacked: BytesN<32>,
 }
 
 #[contracttype]
@@ -106,34 +425,393 @@ pub struct Game {
 pub struct SessionGrant {
   pub expires_ledger: u32,
   pub uses_left: u32,
+  pub transferable: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameResult {
+  pub player1_won: bool,
+  pub player1_hits: u32,
+  pub player2_hits: u32,
+  pub moves: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HubNotification {
+  pub session_id: u32,
+  pub hub: Address,
+  pub player1_won: bool,
+  pub drawn: bool,
+  pub attempts: u32,
+  pub next_retry_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey { Game(u32), GameHubAddress, Admin, VerifierPubKey, ZkVerifierContract, Session(Address, Address, u32), HubRetryQueue, PassExpiry(Address), Reputation(Address), VoidedSession(u32), BoardChunks(u32, Address), Tombstone(u32), RateCounter(Address) }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+  pub max_actions: u32,
+  pub window_ledgers: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateCounter {
+  pub window_start: u32,
+  pub count: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlPolicy {
+  pub active_game_ledgers: u32,
+  pub finished_game_ledgers: u32,
+  pub session_grant_ledgers: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeeperConfig {
+  pub bounty_per_unit: i128,
+  pub max_per_call: i128,
+  pub min_interval_ledgers: u32,
+  pub pool_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PassConfig {
+  pub price: i128,
+  pub duration_ledgers: u32,
+  pub fee_discount_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HashAlgorithm {
+  Keccak256,
+  Sha256,
+}
+
+impl HashAlgorithm {
+  pub fn digest(self, env: &Env, payload: &Bytes) -> BytesN<32> {
+    match self {
+      HashAlgorithm::Keccak256 => env.crypto().keccak256(payload).into(),
+      HashAlgorithm::Sha256 => env.crypto().sha256(payload).into(),
+    }
+  }
+
+  // Keccak messages keep their original layout so existing signers stay valid; any other
+  // algorithm is bound into the signed message by a trailing tag.
+  fn append_tag(self, msg: &mut Bytes) {
+    if self == HashAlgorithm::Sha256 { msg.push_back(1u8); }
+  }
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RandomnessSource {
+  Prng,
+  Beacon,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Obligation {
+  Deposit,
+  Commit,
+  Resolve,
+  Move,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deadline {
+  pub obligation: Obligation,
+  pub player: Address,
+  pub expires_ledger: u32,
+}
+
+// What a player could call right now by the game rules alone; token balances and auth are not
+// checked. `attack_targets` counts the opponent cells (across every board) not yet fired at.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegalActions {
+  pub deposit: bool,
+  pub commit: bool,
+  pub attack: bool,
+  pub attack_targets: u32,
+  pub queue_attack: bool,
+  pub resolve: bool,
+}
+
+// Both targeting grids, 2 bits per cell in canonical cell order (four cells a byte, lowest
+// bits first): `GRID_OPEN`, `GRID_MISS`, `GRID_HIT`, or `GRID_PENDING` for the shot awaiting a
+// reveal. `player1` is what player1 has learned about player2's board, and vice versa.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BoardsView {
+  pub board_size: u32,
+  pub player1: Bytes,
+  pub player2: Bytes,
+}
+
+// What a client needs on each poll, without the commitments and attack maps of the full `Game`.
+// `player1_ready` and `player2_ready` are whether each board is committed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameSummary {
+  pub player1: Address,
+  pub player2: Address,
+  pub status: GameStatus,
+  pub phase: GamePhase,
+  pub player1_ready: bool,
+  pub player2_ready: bool,
+  pub turn: Option<Address>,
+  pub pending_defender: Option<Address>,
+  pub player1_hits: u32,
+  pub player2_hits: u32,
+  pub winner: Option<Address>,
+  pub drawn: bool,
+  pub deadlines: Vec<Deadline>,
+}
+
+// Upper-bound cost of a prospective game, assuming every cell of every board is shot at.
+// `bytes_written` counts cell commitments, board roots and attack lists; `transactions` counts
+// both players' calls from `start_game` through the final reveal. Fees use the current rate
+// without pass discounts; `max_zk_rebate` is what the rebate could return if fees cover it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CostQuote {
+  pub bytes_written: u32,
+  pub transactions: u32,
+  pub max_moves: u32,
+  pub fee_bps: u32,
+  pub protocol_fee: i128,
+  pub max_zk_rebate: i128,
+}
+
+// A named preset for `start_game_with_time_control`. `move_ledgers` becomes the game's
+// unbonded skip-turn deadline, `total_ledgers` its own cap for `adjudicate` (0 falls back to
+// `get_max_game_duration`), and `ttl_ledgers` its active-storage TTL.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeControl {
+  pub move_ledgers: u32,
+  pub total_ledgers: u32,
+  pub ttl_ledgers: u32,
+}
+
+// The ships both players agreed on. Boards must then hold exactly `total_cells` ship cells; an
+// empty fleet leaves the count to each player, as before.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FleetConfig {
+  pub ship_lengths: Vec<u32>,
+  pub total_cells: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AbandonPolicy {
+  pub free_abandons: u32,
+  pub base_cooldown_ledgers: u32,
+  pub max_cooldown_ledgers: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reputation {
+  pub abandons: u32,
+  pub last_abandon_ledger: u32,
+  pub cooldown_until: u32,
+  pub flagged: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardWindow {
+  pub start_ledger: u32,
+  pub end_ledger: u32,
+  pub multiplier_bps: u32,
+}
+
+// A code change announced ahead of time; it can be executed from `executable_ledger` on.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUpgrade {
+  pub wasm_hash: BytesN<32>,
+  pub proposed_ledger: u32,
+  pub executable_ledger: u32,
+}
+
+// While enabled, a pending upgrade pauses game creation, and `execute_upgrade` waits until at
+// most `max_escrowed_games` games (if set) still hold funds in escrow.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeFreeze {
+  pub enabled: bool,
+  pub max_escrowed_games: Option<u32>,
 }
 
 #[contracttype]
 #[derive(Clone)]
-pub enum DataKey { Game(u32), GameHubAddress, Admin, VerifierPubKey, ZkVerifierContract, Session(Address, Address, u32) }
+pub enum ConfigKey { BetToken, FeeRecipient, FeeBps, Keeper, ZkVkVersion, AnnotationFee, Pass, CommitWindow, RewardWindows, EligibilityChecker, AbandonPolicy, RandomnessBeacon, MaxGameDuration, ZkRebate, TtlPolicy, HubStats, RateLimit, HubV2(Address), MaxGameTtl, UpgradeDelay, PendingUpgrade, UpgradeFreeze, EscrowedGames, MatchPolicy, TimeControls, DrawFeeBps, DevMode }
 
 #[contracttype]
 #[derive(Clone)]
-pub enum ConfigKey { BetToken, FeeRecipient, FeeBps }
+pub enum ConfigHistoryKey { Len, Entry(u32) }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigRecord {
+  pub ledger: u32,
+  pub change: ConfigBundle,
+  // Set for changes made through a single-setting entrypoint; `value` then holds the new
+  // value as XDR (`()` for a clear) and `change` is left empty.
+  pub setting: Option<Symbol>,
+  pub value: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum KeeperKey { Pool(Address), LastPaid(Address) }
+
+#[contracttype]
+#[derive(Clone)]
+pub enum EscrowKey { Liabilities(Address), Held(u32), Withdrawable(Address, Address), Tips(u32, Address), OwnerTokens(Address), IndexLen, IndexEntry(u32), IndexSlot(u32) }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowShare {
+  pub owner: Address,
+  pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowRecord {
+  pub token: Address,
+  pub shares: Vec<EscrowShare>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolvencyReport {
+  pub token: Address,
+  pub balance: i128,
+  pub liabilities: i128,
+  pub keeper_pool: i128,
+  pub solvent: bool,
+  pub balanced: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AnnotationKey { Game(u32), Replays(u32) }
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Annotation {
+  pub author: Address,
+  pub move_index: u32,
+  pub note: String,
+  pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayAttachment {
+  pub player: Address,
+  pub content_hash: Bytes,
+  pub ledger: u32,
+}
 
-const GAME_TTL_LEDGERS: u32 = 518_400;
+const DEFAULT_GAME_TTL_LEDGERS: u32 = 518_400;
 const DEFAULT_BOARD_SIZE: u32 = 10;
+const MIN_BOARD_SIZE: u32 = 5;
+const MAX_BOARD_SIZE: u32 = 16;
+const BOARD_TRANSFORMS: u32 = 8;
+const MAX_BACKERS_PER_SIDE: u32 = 16;
+const MAX_BUFFERED_MOVES: u32 = 32;
+const MAX_FLEET_SHIPS: u32 = 16;
+pub const CAP_ZK: u32 = 1;
+pub const CAP_SALVO: u32 = 2;
+pub const CAP_TIMEOUTS: u32 = 4;
+pub const CAP_ALL: u32 = CAP_ZK | CAP_SALVO | CAP_TIMEOUTS;
+
+// Cell commitment preimages: v1 is `is_ship || salt`, v2 is `is_ship || index || salt`. New
+// games use v2; the version is kept on the game so reveals are checked the way it committed.
+pub const COMMITMENT_V1: u32 = 1;
+pub const COMMITMENT_V2: u32 = 2;
+
+pub const GRID_OPEN: u8 = 0;
+pub const GRID_MISS: u8 = 1;
+pub const GRID_HIT: u8 = 2;
+pub const GRID_PENDING: u8 = 3;
+
+const MAX_CONFIG_HISTORY_PAGE: u32 = 50;
+const MAX_TIME_CONTROLS: u32 = 16;
+const WEAK_BOARD_DISTINCT_COMMITMENTS: u32 = 4;
 const DEFAULT_SHIP_CELLS: u32 = 17;
 const DEFAULT_FEE_BPS: u32 = 0;
+const MAX_FEE_BPS: u32 = 2_000;
 const BPS_DENOMINATOR: i128 = 10_000;
 const MAX_SESSION_TTL_LEDGERS: u32 = 172_800;
-const SESSION_GRANT_TTL_LEDGERS: u32 = 172_800;
+const DEFAULT_SESSION_GRANT_TTL_LEDGERS: u32 = 172_800;
+const MIN_POLICY_TTL_LEDGERS: u32 = 17_280;
+const MAX_POLICY_TTL_LEDGERS: u32 = 3_110_400;
+const MIN_GAME_TTL_LEDGERS: u32 = 720;
+const PERSISTENT_STATE_TTL_LEDGERS: u32 = 518_400;
+const HUB_RETRY_BASE_LEDGERS: u32 = 12;
+const HUB_RETRY_MAX_BACKOFF_SHIFT: u32 = 10;
+const MAX_BOARDS_PER_PLAYER: u32 = 2;
+const DEFAULT_COMMIT_WINDOW_LEDGERS: u32 = 17_280;
+const DEFAULT_UPGRADE_DELAY_LEDGERS: u32 = 120_960;
+const MIN_UPGRADE_DELAY_LEDGERS: u32 = 720;
+const MAX_ANNOTATION_BYTES: u32 = 280;
+const MAX_ANNOTATIONS_PER_GAME: u32 = 64;
+pub const MAX_TOKEN_GATES: u32 = 4;
+const MAX_CONTENT_HASH_BYTES: u32 = 64;
+const MAX_VOID_REASON_BYTES: u32 = 280;
+const BASE_REWARD_MULTIPLIER_BPS: u32 = 10_000;
+const MAX_REWARD_MULTIPLIER_BPS: u32 = 50_000;
+const MAX_REWARD_WINDOWS: u32 = 16;
+const DEFAULT_FREE_ABANDONS: u32 = 1;
+const DEFAULT_ABANDON_COOLDOWN_LEDGERS: u32 = 720;
+const DEFAULT_MAX_ABANDON_COOLDOWN_LEDGERS: u32 = 120_960;
+const PUBLIC_NETWORK_PASSPHRASE: &[u8] = b"Public Global Stellar Network ; September 2015";
+
+contractmeta!(key = "Description", val = "Two-player Battleship with Game Hub lifecycle, wager escrow and tournaments");
+contractmeta!(key = "binver", val = env!("CARGO_PKG_VERSION"));
 
 #[contract]
 pub struct BattleshipContract;
 
 #[contractimpl]
 impl BattleshipContract {
-  pub fn __constructor(env: Env, admin: Address, game_hub: Address) {
+  pub fn __constructor(env: Env, admin: Address, game_hub: Address, config: Option<ConfigBundle>) -> Result<(), Error> {
     env.storage().instance().set(&DataKey::Admin, &admin);
     env.storage().instance().set(&DataKey::GameHubAddress, &game_hub);
     env.storage().instance().set(&ConfigKey::FeeRecipient, &admin);
     env.storage().instance().set(&ConfigKey::FeeBps, &DEFAULT_FEE_BPS);
+    record_config_change(&env, ConfigBundle {
+      hub: Some(game_hub),
+      fee_bps: Some(DEFAULT_FEE_BPS),
+      fee_recipient: Some(admin),
+      ..unchanged_config()
+    });
+    if let Some(config) = config {
+      apply_config(&env, &config)?;
+      record_config_change(&env, config.clone());
+      ConfigChanged { config }.publish(&env);
+    }
+    Ok(())
   }
 
   pub fn start_game(
@@ -145,98 +823,457 @@ impl BattleshipContract {
     player2_points: i128,
   ) -> Result<(), Error> {
     if player1 == player2 { return Err(Error::NotPlayer); }
-    if player1_points < 0 || player2_points < 0 { return Err(Error::InvalidStakeAmount); }
-
-    let is_wager = player1_points > 0 || player2_points > 0;
-
     player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env)]);
     player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env)]);
-
-    let game_hub_addr: Address = env.storage().instance().get(&DataKey::GameHubAddress).expect("GameHub address not set");
-    let game_hub = GameHubClient::new(&env, &game_hub_addr);
-    game_hub.start_game(&env.current_contract_address(), &session_id, &player1, &player2, &player1_points, &player2_points);
-
-    let game = Game {
-      player1, player2, player1_points, player2_points,
-      board_size: DEFAULT_BOARD_SIZE,
-      player1_board: None, player2_board: None,
-      player1_ship_cells: None, player2_ship_cells: None,
-      player1_hits: 0, player2_hits: 0,
-      player1_attacks: Vec::new(&env), player2_attacks: Vec::new(&env),
-      player1_hit_attacks: Vec::new(&env), player2_hit_attacks: Vec::new(&env),
-      turn: None, pending_attacker: None, pending_defender: None, pending_x: None, pending_y: None,
-      winner: None,
-      player1_deposited: !is_wager || player1_points == 0,
-      player2_deposited: !is_wager || player2_points == 0,
-      payout_processed: !is_wager,
-    };
-
-    let key = DataKey::Game(session_id);
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
-    Ok(())
+    // Classic games escrow exactly the points they report to the hub.
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_points, player2_points, CAP_ALL, 0)
   }
 
-  pub fn commit_board(
+  pub fn start_staked_game(
     env: Env,
     session_id: u32,
-    player: Address,
-    cell_commitments: Vec<BytesN<32>>,
-    ship_cells: u32,
-    board_proof_hash: Option<BytesN<32>>,
-    board_proof_signature: Option<BytesN<64>>,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    player1_stake: i128,
+    player2_stake: i128,
   ) -> Result<(), Error> {
-    player.require_auth();
-    let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
-
-    let board_cells = game.board_size.saturating_mul(game.board_size);
-    if cell_commitments.len() != board_cells { return Err(Error::InvalidBoardCommitmentLength); }
-    if ship_cells == 0 || ship_cells > board_cells { return Err(Error::InvalidShipCount); }
-    if is_wager_game(&game) && !(game.player1_deposited && game.player2_deposited) {
-      return Err(Error::StakesNotFunded);
-    }
-
-    if env.storage().instance().has(&DataKey::ZkVerifierContract) {
-      return Err(Error::ZkProofRequired);
-    }
-
-    if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
-      let proof_hash = board_proof_hash.ok_or(Error::MissingProofSignature)?;
-      let proof_signature = board_proof_signature.ok_or(Error::MissingProofSignature)?;
-      let commitment_root = compute_commitment_root(&env, &cell_commitments);
-      let message = build_board_proof_message(&env, session_id, ship_cells, &commitment_root, &proof_hash);
-      env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
-    }
-
-    apply_board_commit(&mut game, player, cell_commitments, ship_cells)?;
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), player1_stake.into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), player2_stake.into_val(&env)]);
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_stake, player2_stake, CAP_ALL, 0)
+  }
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
-    Ok(())
+  // Each client declares the optional features it supports; only those both support are
+  // permitted for the game. Plain `start_game` negotiates everything.
+  pub fn start_game_with_capabilities(
+    env: Env,
+    session_id: u32,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    player1_capabilities: u32,
+    player2_capabilities: u32,
+  ) -> Result<(), Error> {
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), player1_capabilities.into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), player2_capabilities.into_val(&env)]);
+    let capabilities = player1_capabilities & player2_capabilities & CAP_ALL;
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_points, player2_points, capabilities, 0)
   }
 
-  pub fn commit_board_zk(
+  // Lets a game pick its own active-storage TTL: long for correspondence play, short for blitz
+  // to save rent. Bounded by `get_max_game_ttl`; finished games still use the policy TTL.
+  pub fn start_game_with_ttl(
     env: Env,
     session_id: u32,
-    player: Address,
-    cell_commitments: Vec<BytesN<32>>,
-    ship_cells: u32,
-    zk_board_proof: Bytes,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    ttl_ledgers: u32,
   ) -> Result<(), Error> {
-    player.require_auth();
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), ttl_ledgers.into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), ttl_ledgers.into_val(&env)]);
+    if !(MIN_GAME_TTL_LEDGERS..=max_game_ttl(&env)).contains(&ttl_ledgers) { return Err(Error::InvalidConfig); }
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_points, player2_points, CAP_ALL, ttl_ledgers)
+  }
 
-    let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
+  pub fn get_capabilities(env: Env, session_id: u32) -> Result<u32, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    Ok(game.capabilities)
+  }
 
+  // Bundles the move deadline, total clock and TTL under one admin-defined name, so clients
+  // pick `Blitz` or `Rapid` instead of agreeing on raw ledger counts.
+  pub fn start_game_with_time_control(
+    env: Env,
+    session_id: u32,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    time_control: Symbol,
+  ) -> Result<(), Error> {
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), time_control.into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), time_control.into_val(&env)]);
+    let control = time_controls(&env).get(time_control.clone()).ok_or(Error::TimeControlNotFound)?;
+    if control.ttl_ledgers > max_game_ttl(&env) { return Err(Error::InvalidConfig); }
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_points, player2_points, CAP_ALL, control.ttl_ledgers)?;
+
+    let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(Error::GameNotFound)?;
+    game.time_control = Some(time_control);
+    game.skip_turn_ledgers = control.move_ledgers;
+    game.max_duration_ledgers = control.total_ledgers;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Fixes the fleet for both boards, so neither player can commit fewer ship cells than agreed.
+  pub fn start_game_with_fleet(
+    env: Env,
+    session_id: u32,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    fleet: FleetConfig,
+  ) -> Result<(), Error> {
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), fleet.clone().into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), fleet.clone().into_val(&env)]);
+    let lengths = fleet.ship_lengths.iter().fold(0u32, |total, length| total.saturating_add(length));
+    if fleet.ship_lengths.is_empty() || fleet.ship_lengths.len() > MAX_FLEET_SHIPS || fleet.ship_lengths.contains(0) || lengths != fleet.total_cells {
+      return Err(Error::InvalidConfig);
+    }
+    if fleet.ship_lengths.iter().any(|length| length > MAX_BOARD_SIZE) { return Err(Error::InvalidConfig); }
+    create_game(&env, session_id, player1, player2, player1_points, player2_points, player1_points, player2_points, CAP_ALL, 0)?;
+
+    let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(Error::GameNotFound)?;
+    game.fleet = fleet;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Attributes the game to a registered front-end operator, which earns its fee share when the
+  // wager settles.
+  pub fn start_game_with_operator(
+    env: Env,
+    session_id: u32,
+    player1: Address,
+    player2: Address,
+    player1_points: i128,
+    player2_points: i128,
+    operator_id: Symbol,
+  ) -> Result<(), Error> {
+    if player1 == player2 { return Err(Error::NotPlayer); }
+    player1.require_auth_for_args(vec![&env, session_id.into_val(&env), player1_points.into_val(&env), operator_id.into_val(&env)]);
+    player2.require_auth_for_args(vec![&env, session_id.into_val(&env), player2_points.into_val(&env), operator_id.into_val(&env)]);
+    let mut operator = operator::load_operator(&env, &operator_id)?;
+    create_game(&env, session_id, player1.clone(), player2.clone(), player1_points, player2_points, player1_points, player2_points, CAP_ALL, 0)?;
+
+    let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(Error::GameNotFound)?;
+    game.operator = Some(operator_id.clone());
+    store_game(&env, session_id, &game);
+    operator.games = operator.games.saturating_add(1);
+    operator::store_operator(&env, &operator_id, &operator);
+    OperatorGameStarted { session_id, operator: operator_id, player1, player2 }.publish(&env);
+    Ok(())
+  }
+
+  pub fn get_time_controls(env: Env) -> Map<Symbol, TimeControl> {
+    time_controls(&env)
+  }
+
+  pub fn set_time_control(env: Env, name: Symbol, control: TimeControl) -> Result<(), Error> {
+    require_admin(&env);
+    if !(MIN_GAME_TTL_LEDGERS..=MAX_POLICY_TTL_LEDGERS).contains(&control.ttl_ledgers) { return Err(Error::InvalidConfig); }
+    // The clock has to run out while the game is still stored, and a move fit inside it.
+    if control.total_ledgers > control.ttl_ledgers { return Err(Error::InvalidConfig); }
+    if control.total_ledgers > 0 && control.move_ledgers > control.total_ledgers { return Err(Error::InvalidConfig); }
+    let mut controls = time_controls(&env);
+    if !controls.contains_key(name.clone()) && controls.len() >= MAX_TIME_CONTROLS { return Err(Error::InvalidConfig); }
+    controls.set(name.clone(), control.clone());
+    env.storage().instance().set(&ConfigKey::TimeControls, &controls);
+    record_setting_change(&env, "time_control", (name, control));
+    Ok(())
+  }
+
+  pub fn remove_time_control(env: Env, name: Symbol) -> Result<(), Error> {
+    require_admin(&env);
+    let mut controls = time_controls(&env);
+    if controls.remove(name.clone()).is_none() { return Err(Error::TimeControlNotFound); }
+    env.storage().instance().set(&ConfigKey::TimeControls, &controls);
+    record_setting_change(&env, "time_control", (name, ()));
+    Ok(())
+  }
+
+  pub fn commit_board(
+    env: Env,
+    session_id: u32,
+    player: Address,
+    cell_commitments: Vec<BytesN<32>>,
+    ship_cells: u32,
+    board_proof_hash: Option<BytesN<32>>,
+    board_proof_signature: Option<BytesN<64>>,
+  ) -> Result<(), Error> {
+    player.require_auth();
+    commit_single_board(&env, session_id, player, cell_commitments, ship_cells, board_proof_hash, board_proof_signature)
+  }
+
+  pub fn commit_board_by_session(
+    env: Env,
+    session_id: u32,
+    player: Address,
+    delegate: Address,
+    cell_commitments: Vec<BytesN<32>>,
+    ship_cells: u32,
+    board_proof_hash: Option<BytesN<32>>,
+    board_proof_signature: Option<BytesN<64>>,
+  ) -> Result<(), Error> {
+    consume_session_authorization(&env, session_id, &player, &delegate)?;
+    commit_single_board(&env, session_id, player, cell_commitments, ship_cells, board_proof_hash, board_proof_signature)
+  }
+
+  // Exhibitions are unranked and unstaked: the hub never hears of them, and the bot seats are
+  // never linked to tournaments or reputation, so any two distinct addresses can stand in.
+  pub fn start_exhibition(env: Env, session_id: u32, controller: Address, bot1: Address, bot2: Address, ttl_ledgers: u32) -> Result<(), Error> {
+    controller.require_auth();
+    if bot1 == bot2 { return Err(Error::NotPlayer); }
+    if controller == bot1 || controller == bot2 || ttl_ledgers == 0 || ttl_ledgers > MAX_SESSION_TTL_LEDGERS {
+      return Err(Error::InvalidSessionConfig);
+    }
+    if env.storage().persistent().has(&DataKey::VoidedSession(session_id)) { return Err(Error::SessionBlacklisted); }
+    if env.storage().persistent().has(&DataKey::Tombstone(session_id)) { return Err(Error::SessionInUse); }
+    require_creation_open(&env)?;
+    consume_rate_limit(&env, &controller)?;
+
+    let mut game = new_game(&env, bot1.clone(), bot2.clone(), 0, 0, 0, 0);
+    game.exhibition = true;
+    store_game(&env, session_id, &game);
+    GameStarted { session_id, player1: bot1.clone(), player2: bot2.clone(), player1_stake: 0, player2_stake: 0 }.publish(&env);
+
+    let grant = SessionGrant { expires_ledger: env.ledger().sequence().saturating_add(ttl_ledgers), uses_left: 0, transferable: false };
+    for bot in [bot1, bot2] {
+      let session_key = DataKey::Session(bot, controller.clone(), session_id);
+      env.storage().persistent().set(&session_key, &grant);
+      extend_session_ttl(&env, &session_key);
+    }
+    Ok(())
+  }
+
+  pub fn set_board_size(env: Env, session_id: u32, board_size: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&board_size) { return Err(Error::InvalidBoardSize); }
+
+    game.board_size = board_size;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn enable_board_transforms(env: Env, session_id: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+
+    game.board_transforms = true;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn set_hash_algorithm(env: Env, session_id: u32, algorithm: HashAlgorithm) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+
+    game.hash_algorithm = algorithm;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn enable_transcript(env: Env, session_id: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+
+    game.transcript_hash = Some(BytesN::from_array(&env, &[0u8; 32]));
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn check_transcript(env: Env, session_id: u32, move_log: Vec<MoveReveal>) -> Result<bool, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    let recorded = game.transcript_hash.ok_or(Error::TranscriptDisabled)?;
+    let mut transcript = BytesN::from_array(&env, &[0u8; 32]);
+    for reveal in move_log.iter() {
+      transcript = transcript_step(&env, game.hash_algorithm, &transcript, &reveal);
+    }
+    Ok(transcript == recorded)
+  }
+
+  pub fn enable_aggregated_proofs(env: Env, session_id: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    require_capability(&game, CAP_ZK)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if !env.storage().instance().has(&DataKey::ZkVerifierContract) { return Err(Error::ZkVerifierNotConfigured); }
+
+    game.aggregated_proofs = true;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Records the defender's claimed result and passes the turn, but the hit only counts once
+  // `resolve_transcript` proves it.
+  pub fn resolve_attack_buffered(env: Env, session_id: u32, defender: Address, is_ship: bool) -> Result<(), Error> {
+    defender.require_auth();
+
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+    if !game.aggregated_proofs { return Err(Error::AggregationDisabled); }
+
+    let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
+    let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
+    let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
+    if pending_defender != defender { return Err(Error::NotPendingDefender); }
+
+    let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
+    let board = if defender == game.player1 { game.player1_board.clone() } else { game.player2_board.clone() };
+    let expected_commitment = board.ok_or(Error::BoardsNotReady)?.get(target_index).ok_or(Error::InvalidCoordinate)?;
+    let buffered = BufferedMove { x: pending_x, y: pending_y, expected_commitment, is_ship };
+    let buffer = if defender == game.player1 { &mut game.player1_buffer } else { &mut game.player2_buffer };
+    if buffer.len() >= MAX_BUFFERED_MOVES { return Err(Error::TranscriptBufferFull); }
+    buffer.push_back(buffered);
+
+    record_transcript(&env, &mut game, 0, pending_x, pending_y, is_ship, &Bytes::new(&env));
+    record_reveal(&env, session_id, &game, 0, pending_x, pending_y, is_ship);
+    record_attack(&mut game, target_index, false)?;
+    drop_queued_attack_if_decided(&mut game);
+    start_turn_clock(&env, &mut game);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn resolve_transcript(env: Env, session_id: u32, defender: Address, proof: Bytes) -> Result<(), Error> {
+    defender.require_auth();
+
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &PLAY_PHASES)?;
+    if !game.aggregated_proofs { return Err(Error::AggregationDisabled); }
+
+    let (buffer, from_move) = if defender == game.player1 {
+      (game.player1_buffer.clone(), game.player1_proven_moves)
+    } else if defender == game.player2 {
+      (game.player2_buffer.clone(), game.player2_proven_moves)
+    } else {
+      return Err(Error::NotPlayer);
+    };
+    if buffer.is_empty() { return Err(Error::NoPendingAttack); }
+
+    let verifier_addr: Address = env
+      .storage()
+      .instance()
+      .get(&DataKey::ZkVerifierContract)
+      .ok_or(Error::ZkVerifierNotConfigured)?;
+    let to_move = from_move.saturating_add(buffer.len());
+    let transcript_root = compute_transcript_root(&env, game.hash_algorithm, &buffer);
+    let verifier = ZkVerifierClient::new(&env, &verifier_addr);
+    if !zk_verdict(verifier.try_verify_transcript(&session_id, &from_move, &to_move, &transcript_root, &proof))? {
+      return Err(Error::ZkVerificationFailed);
+    }
+
+    let attacker_is_player1 = defender == game.player2;
+    for buffered in buffer.iter() {
+      let target_index = buffered.y.saturating_mul(game.board_size).saturating_add(buffered.x);
+      if buffered.is_ship { credit_hit(&mut game, attacker_is_player1, target_index); }
+    }
+    if attacker_is_player1 {
+      game.player2_buffer = Vec::new(&env);
+      game.player2_proven_moves = to_move;
+    } else {
+      game.player1_buffer = Vec::new(&env);
+      game.player1_proven_moves = to_move;
+    }
+    count_zk_proof(&mut game, &defender);
+    settle_if_decided(&env, session_id, &mut game)?;
+    drop_queued_attack_if_decided(&mut game);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn commit_board_chunk(env: Env, session_id: u32, player: Address, offset: u32, commitments_chunk: Vec<BytesN<32>>) -> Result<u32, Error> {
+    player.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingBoards])?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if has_committed(&game, &player) { return Err(Error::BoardAlreadyCommitted); }
+
+    // A chunk may restart at any earlier offset, so a failed or wrong upload can be resent.
+    let key = DataKey::BoardChunks(session_id, player);
+    let staged: Vec<BytesN<32>> = env.storage().temporary().get(&key).unwrap_or(Vec::new(&env));
+    if offset > staged.len() { return Err(Error::InvalidBoardCommitmentLength); }
+    let mut staged = staged.slice(0..offset);
+    staged.append(&commitments_chunk);
+    if staged.len() > game.board_size.saturating_mul(game.board_size) { return Err(Error::InvalidBoardCommitmentLength); }
+
+    env.storage().temporary().set(&key, &staged);
+    extend_game_ttl(&env, &key, ttl_policy(&env).active_game_ledgers);
+    Ok(staged.len())
+  }
+
+  pub fn finalize_board_commit(
+    env: Env,
+    session_id: u32,
+    player: Address,
+    ship_cells: u32,
+    board_proof_hash: Option<BytesN<32>>,
+    board_proof_signature: Option<BytesN<64>>,
+  ) -> Result<BytesN<32>, Error> {
+    player.require_auth();
+    let chunks_key = DataKey::BoardChunks(session_id, player.clone());
+    let cell_commitments: Vec<BytesN<32>> = env.storage().temporary().get(&chunks_key).ok_or(Error::InvalidBoardCommitmentLength)?;
+    commit_single_board(&env, session_id, player.clone(), cell_commitments, ship_cells, board_proof_hash, board_proof_signature)?;
+    env.storage().temporary().remove(&chunks_key);
+
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    let root = if player == game.player1 { game.player1_board_root } else { game.player2_board_root };
+    root.ok_or(Error::BoardsNotReady)
+  }
+
+  pub fn commit_board_zk(
+    env: Env,
+    session_id: u32,
+    player: Address,
+    cell_commitments: Vec<BytesN<32>>,
+    ship_cells: u32,
+    zk_board_proof: Bytes,
+  ) -> Result<(), Error> {
+    player.require_auth();
+
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingBoards])?;
+    require_capability(&game, CAP_ZK)?;
+
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    // The ZkVerifier interface carries no algorithm, so circuits are assumed to hash with keccak.
+    if game.hash_algorithm != HashAlgorithm::Keccak256 { return Err(Error::UnsupportedHashAlgorithm); }
     let board_cells = game.board_size.saturating_mul(game.board_size);
     if cell_commitments.len() != board_cells { return Err(Error::InvalidBoardCommitmentLength); }
     if ship_cells == 0 || ship_cells > board_cells { return Err(Error::InvalidShipCount); }
-    if is_wager_game(&game) && !(game.player1_deposited && game.player2_deposited) {
-      return Err(Error::StakesNotFunded);
-    }
+    if game.fleet.total_cells > 0 && ship_cells != game.fleet.total_cells { return Err(Error::FleetMismatch); }
 
     let verifier_addr: Address = env
       .storage()
@@ -244,27 +1281,47 @@ impl BattleshipContract {
       .get(&DataKey::ZkVerifierContract)
       .ok_or(Error::ZkVerifierNotConfigured)?;
     let verifier = ZkVerifierClient::new(&env, &verifier_addr);
-    let commitment_root = compute_commitment_root(&env, &cell_commitments);
-    let board_ok = verifier.verify_board(&session_id, &ship_cells, &commitment_root, &zk_board_proof);
+    let commitment_root = compute_commitment_root(&env, game.hash_algorithm, &cell_commitments);
+    let vk_version: u32 = env.storage().instance().get(&ConfigKey::ZkVkVersion).unwrap_or(0);
+    // ZK tournaments pin the verifier when the game is linked, so every entrant is checked by the same circuit.
+    if game.pinned_verifier.as_ref().is_some_and(|pinned| *pinned != verifier_addr) || game.pinned_vk_version.is_some_and(|pinned| pinned != vk_version) {
+      return Err(Error::VerifierChanged);
+    }
+    let (opponent_committed, opponent_verifier, opponent_vk_version) = if player == game.player1 {
+      (game.player2_board.is_some(), game.player2_board_verifier.clone(), game.player2_board_vk_version)
+    } else {
+      (game.player1_board.is_some(), game.player1_board_verifier.clone(), game.player1_board_vk_version)
+    };
+    if opponent_committed && (opponent_verifier != Some(verifier_addr.clone()) || opponent_vk_version != Some(vk_version)) {
+      return Err(Error::VerifierChanged);
+    }
+
+    let board_ok = zk_verdict(verifier.try_verify_board(&session_id, &ship_cells, &commitment_root, &zk_board_proof))?;
     if !board_ok { return Err(Error::ZkVerificationFailed); }
 
-    apply_board_commit(&mut game, player, cell_commitments, ship_cells)?;
+    if player == game.player1 {
+      game.player1_board_verifier = Some(verifier_addr);
+      game.player1_board_vk_version = Some(vk_version);
+    } else if player == game.player2 {
+      game.player2_board_verifier = Some(verifier_addr);
+      game.player2_board_vk_version = Some(vk_version);
+    }
+    warn_weak_commitments(&env, session_id, &player, &cell_commitments);
+    apply_board_commit(&env, &mut game, player.clone(), cell_commitments, ship_cells)?;
+    BoardCommitted { session_id, player, board: 0 }.publish(&env);
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
+    store_game(&env, session_id, &game);
     Ok(())
   }
 
   pub fn attack(env: Env, session_id: u32, attacker: Address, x: u32, y: u32) -> Result<(), Error> {
     attacker.require_auth();
     let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
 
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
-    if is_wager_game(&game) && !(game.player1_deposited && game.player2_deposited) {
-      return Err(Error::StakesNotFunded);
-    }
+    require_phase(&game, &[GamePhase::InProgress])?;
     if x >= game.board_size || y >= game.board_size { return Err(Error::InvalidCoordinate); }
+    let (x, y) = canonical_target(&game, &attacker, x, y);
     if game.player1_board.is_none() || game.player2_board.is_none() { return Err(Error::BoardsNotReady); }
     if game.pending_attacker.is_some() { return Err(Error::PendingAttackResolution); }
 
@@ -273,16 +1330,76 @@ impl BattleshipContract {
 
     let target_index = y.saturating_mul(game.board_size).saturating_add(x);
     let attacked = if attacker == game.player1 { &game.player1_attacks } else if attacker == game.player2 { &game.player2_attacks } else { return Err(Error::NotPlayer); };
-    if contains_u32(attacked, target_index) { return Err(Error::AlreadyAttacked); }
+    if has_bit(attacked, target_index) { return Err(Error::AlreadyAttacked); }
+
+    charge_shot_tax(&env, session_id, &mut game, &attacker)?;
+
+    // Firing directly abandons the attacker's own queued shot.
+    if game.queued_attacker == Some(attacker.clone()) {
+      game.queued_attacker = None;
+      game.queued_commitment = None;
+    }
+    let defender = if attacker == game.player1 { game.player2.clone() } else { game.player1.clone() };
+    game.pending_attacker = Some(attacker);
+    game.phase = GamePhase::AwaitingResolve;
+    game.pending_defender = Some(defender);
+    game.pending_x = Some(x);
+    game.pending_y = Some(y);
+    start_turn_clock(&env, &mut game);
+    announce_attack(&env, session_id, &game);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // `commitment` is `queued_shot_commitment(x, y, salt)`, so the defender resolving the pending
+  // shot cannot see where the next one lands.
+  pub fn queue_attack(env: Env, session_id: u32, attacker: Address, commitment: BytesN<32>) -> Result<(), Error> {
+    attacker.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+    require_capability(&game, CAP_SALVO)?;
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if game.shot_tax > 0 { return Err(Error::ShotTaxRequiresPlayer); }
+    if game.pending_attacker != Some(attacker.clone()) { return Err(Error::NotYourTurn); }
+
+    game.queued_attacker = Some(attacker);
+    game.queued_commitment = Some(commitment);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Reveals the queued shot once the turn is back with its attacker. The commitment already
+  // carries the attacker's authorization, so anyone holding the preimage may submit it.
+  pub fn register_queued_attack(env: Env, session_id: u32, x: u32, y: u32, salt: BytesN<32>) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+
+    require_phase(&game, &[GamePhase::InProgress])?;
+    let attacker = game.queued_attacker.clone().ok_or(Error::NoQueuedAttack)?;
+    if game.turn != Some(attacker.clone()) { return Err(Error::NotYourTurn); }
+    if game.queued_commitment != Some(queued_shot_commitment(&env, x, y, &salt)) { return Err(Error::InvalidReveal); }
+    if x >= game.board_size || y >= game.board_size { return Err(Error::InvalidCoordinate); }
+    let (x, y) = canonical_target(&game, &attacker, x, y);
+    let target_index = y.saturating_mul(game.board_size).saturating_add(x);
+    let attacked = if attacker == game.player1 { &game.player1_attacks } else { &game.player2_attacks };
+    if has_bit(attacked, target_index) { return Err(Error::AlreadyAttacked); }
 
     let defender = if attacker == game.player1 { game.player2.clone() } else { game.player1.clone() };
+    game.queued_attacker = None;
+    game.queued_commitment = None;
     game.pending_attacker = Some(attacker);
+    game.phase = GamePhase::AwaitingResolve;
     game.pending_defender = Some(defender);
     game.pending_x = Some(x);
     game.pending_y = Some(y);
+    start_turn_clock(&env, &mut game);
+    announce_attack(&env, session_id, &game);
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
+    store_game(&env, session_id, &game);
     Ok(())
   }
 
@@ -297,16 +1414,16 @@ impl BattleshipContract {
   ) -> Result<(), Error> {
     defender.require_auth();
     let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
 
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
 
     let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
     let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
     let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
     if pending_defender != defender { return Err(Error::NotPendingDefender); }
 
-    if env.storage().instance().has(&DataKey::ZkVerifierContract) {
+    if env.storage().instance().has(&DataKey::ZkVerifierContract) || game.pinned_verifier.is_some() {
       return Err(Error::ZkProofRequired);
     }
 
@@ -314,30 +1431,67 @@ impl BattleshipContract {
     let board = if defender == game.player1 { game.player1_board.clone().ok_or(Error::BoardsNotReady)? } else if defender == game.player2 { game.player2_board.clone().ok_or(Error::BoardsNotReady)? } else { return Err(Error::NotPlayer); };
     let expected = board.get(target_index).ok_or(Error::InvalidCoordinate)?;
 
-    let mut payload = Bytes::new(&env);
-    payload.push_back(if is_ship { 1 } else { 0 });
-    payload.append(&salt);
-    let computed = env.crypto().keccak256(&payload).to_array();
-    if expected != computed { return Err(Error::InvalidCellReveal); }
+    if expected != game_cell_commitment(&env, &game, target_index, is_ship, &salt) { return Err(Error::InvalidCellReveal); }
+    if zk_proof_hash != attack_proof_hash(&env, game.hash_algorithm, is_ship, &salt, pending_x, pending_y) { return Err(Error::InvalidProofHash); }
+
+    if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
+      let proof_signature = zk_proof_signature.ok_or(Error::MissingProofSignature)?;
+      let message = build_attack_proof_message(&env, game.hash_algorithm, &env.current_contract_address(), session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
+      env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
+    }
+
+    record_transcript(&env, &mut game, 0, pending_x, pending_y, is_ship, &salt);
+    record_reveal(&env, session_id, &game, 0, pending_x, pending_y, is_ship);
+    apply_resolved_attack(&env, session_id, &mut game, target_index, is_ship)?;
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn resolve_attack_merkle(
+    env: Env,
+    session_id: u32,
+    defender: Address,
+    is_ship: bool,
+    salt: Bytes,
+    merkle_proof: Vec<BytesN<32>>,
+    zk_proof_hash: BytesN<32>,
+    zk_proof_signature: Option<BytesN<64>>,
+  ) -> Result<(), Error> {
+    defender.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+
+    let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
+    let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
+    let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
+    if pending_defender != defender { return Err(Error::NotPendingDefender); }
+
+    if env.storage().instance().has(&DataKey::ZkVerifierContract) || game.pinned_verifier.is_some() {
+      return Err(Error::ZkProofRequired);
+    }
 
-    let mut proof_payload = Bytes::new(&env);
-    proof_payload.push_back(if is_ship { 1 } else { 0 });
-    proof_payload.append(&salt);
-    append_u32_be(&mut proof_payload, pending_x);
-    append_u32_be(&mut proof_payload, pending_y);
-    let computed_proof_hash = env.crypto().keccak256(&proof_payload).to_array();
-    if zk_proof_hash != computed_proof_hash { return Err(Error::InvalidProofHash); }
+    let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
+    let root = if defender == game.player1 { game.player1_board_root.clone().ok_or(Error::BoardsNotReady)? } else if defender == game.player2 { game.player2_board_root.clone().ok_or(Error::BoardsNotReady)? } else { return Err(Error::NotPlayer); };
+    let board_cells = game.board_size.saturating_mul(game.board_size);
+    if merkle_proof.len() != merkle_depth(board_cells) { return Err(Error::InvalidMerkleProof); }
+    let leaf = game_cell_commitment(&env, &game, target_index, is_ship, &salt);
+    if !verify_merkle_proof(&env, game.hash_algorithm, &root, &leaf, target_index, &merkle_proof) { return Err(Error::InvalidMerkleProof); }
+    if zk_proof_hash != attack_proof_hash(&env, game.hash_algorithm, is_ship, &salt, pending_x, pending_y) { return Err(Error::InvalidProofHash); }
 
     if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
       let proof_signature = zk_proof_signature.ok_or(Error::MissingProofSignature)?;
-      let message = build_attack_proof_message(&env, session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
+      let message = build_attack_proof_message(&env, game.hash_algorithm, &env.current_contract_address(), session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
       env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
     }
 
+    record_transcript(&env, &mut game, 0, pending_x, pending_y, is_ship, &salt);
+    record_reveal(&env, session_id, &game, 0, pending_x, pending_y, is_ship);
     apply_resolved_attack(&env, session_id, &mut game, target_index, is_ship)?;
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
+    store_game(&env, session_id, &game);
     Ok(())
   }
 
@@ -350,8 +1504,9 @@ impl BattleshipContract {
     defender.require_auth();
 
     let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+    require_capability(&game, CAP_ZK)?;
 
     let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
     let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
@@ -375,12 +1530,14 @@ impl BattleshipContract {
     let expected = board.get(target_index).ok_or(Error::InvalidCoordinate)?;
 
     let verifier = ZkVerifierClient::new(&env, &verifier_addr);
-    let is_ship = verifier.verify_attack(&session_id, &pending_x, &pending_y, &expected, &zk_attack_proof);
+    let is_ship = zk_verdict(verifier.try_verify_attack(&session_id, &pending_x, &pending_y, &expected, &zk_attack_proof))?;
 
+    count_zk_proof(&mut game, &defender);
+    record_transcript(&env, &mut game, 0, pending_x, pending_y, is_ship, &Bytes::new(&env));
+    record_reveal(&env, session_id, &game, 0, pending_x, pending_y, is_ship);
     apply_resolved_attack(&env, session_id, &mut game, target_index, is_ship)?;
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
+    store_game(&env, session_id, &game);
     Ok(())
   }
 
@@ -393,34 +1550,7 @@ impl BattleshipContract {
     y: u32,
   ) -> Result<(), Error> {
     consume_session_authorization(&env, session_id, &attacker, &delegate)?;
-
-    let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
-    if is_wager_game(&game) && !(game.player1_deposited && game.player2_deposited) {
-      return Err(Error::StakesNotFunded);
-    }
-    if x >= game.board_size || y >= game.board_size { return Err(Error::InvalidCoordinate); }
-    if game.player1_board.is_none() || game.player2_board.is_none() { return Err(Error::BoardsNotReady); }
-    if game.pending_attacker.is_some() { return Err(Error::PendingAttackResolution); }
-
-    let turn = game.turn.clone().ok_or(Error::BoardsNotReady)?;
-    if attacker != turn { return Err(Error::NotYourTurn); }
-
-    let target_index = y.saturating_mul(game.board_size).saturating_add(x);
-    let attacked = if attacker == game.player1 { &game.player1_attacks } else if attacker == game.player2 { &game.player2_attacks } else { return Err(Error::NotPlayer); };
-    if contains_u32(attacked, target_index) { return Err(Error::AlreadyAttacked); }
-
-    let defender = if attacker == game.player1 { game.player2.clone() } else { game.player1.clone() };
-    game.pending_attacker = Some(attacker);
-    game.pending_defender = Some(defender);
-    game.pending_x = Some(x);
-    game.pending_y = Some(y);
-
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
-    Ok(())
+    delegated_attack(&env, session_id, attacker, x, y)
   }
 
   pub fn resolve_attack_by_session(
@@ -434,51 +1564,8 @@ impl BattleshipContract {
     zk_proof_signature: Option<BytesN<64>>,
   ) -> Result<(), Error> {
     consume_session_authorization(&env, session_id, &defender, &delegate)?;
-
-    let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
-
-    let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
-    let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
-    let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
-    if pending_defender != defender { return Err(Error::NotPendingDefender); }
-
-    if env.storage().instance().has(&DataKey::ZkVerifierContract) {
-      return Err(Error::ZkProofRequired);
-    }
-
-    let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
-    let board = if defender == game.player1 { game.player1_board.clone().ok_or(Error::BoardsNotReady)? } else if defender == game.player2 { game.player2_board.clone().ok_or(Error::BoardsNotReady)? } else { return Err(Error::NotPlayer); };
-    let expected = board.get(target_index).ok_or(Error::InvalidCoordinate)?;
-
-    let mut payload = Bytes::new(&env);
-    payload.push_back(if is_ship { 1 } else { 0 });
-    payload.append(&salt);
-    let computed = env.crypto().keccak256(&payload).to_array();
-    if expected != computed { return Err(Error::InvalidCellReveal); }
-
-    let mut proof_payload = Bytes::new(&env);
-    proof_payload.push_back(if is_ship { 1 } else { 0 });
-    proof_payload.append(&salt);
-    append_u32_be(&mut proof_payload, pending_x);
-    append_u32_be(&mut proof_payload, pending_y);
-    let computed_proof_hash = env.crypto().keccak256(&proof_payload).to_array();
-    if zk_proof_hash != computed_proof_hash { return Err(Error::InvalidProofHash); }
-
-    if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
-      let proof_signature = zk_proof_signature.ok_or(Error::MissingProofSignature)?;
-      let message = build_attack_proof_message(&env, session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
-      env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
-    }
-
-    apply_resolved_attack(&env, session_id, &mut game, target_index, is_ship)?;
-
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
-    Ok(())
-  }
+    delegated_resolve(&env, session_id, defender, is_ship, salt, zk_proof_hash, zk_proof_signature)
+  }
 
   pub fn resolve_attack_zk_by_session(
     env: Env,
@@ -490,8 +1577,9 @@ impl BattleshipContract {
     consume_session_authorization(&env, session_id, &defender, &delegate)?;
 
     let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+    require_capability(&game, CAP_ZK)?;
 
     let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
     let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
@@ -515,12 +1603,15 @@ impl BattleshipContract {
     let expected = board.get(target_index).ok_or(Error::InvalidCoordinate)?;
 
     let verifier = ZkVerifierClient::new(&env, &verifier_addr);
-    let is_ship = verifier.verify_attack(&session_id, &pending_x, &pending_y, &expected, &zk_attack_proof);
+    let is_ship = zk_verdict(verifier.try_verify_attack(&session_id, &pending_x, &pending_y, &expected, &zk_attack_proof))?;
 
+    prover::pay_prover(&env, session_id, &defender, &delegate);
+    count_zk_proof(&mut game, &defender);
+    record_transcript(&env, &mut game, 0, pending_x, pending_y, is_ship, &Bytes::new(&env));
+    record_reveal(&env, session_id, &game, 0, pending_x, pending_y, is_ship);
     apply_resolved_attack(&env, session_id, &mut game, target_index, is_ship)?;
 
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
+    store_game(&env, session_id, &game);
     Ok(())
   }
 
@@ -539,16 +1630,18 @@ impl BattleshipContract {
     }
 
     let game_key = DataKey::Game(session_id);
-    let game: Game = env.storage().temporary().get(&game_key).ok_or(Error::GameNotFound)?;
+    let game: Game = env.storage().temporary().get(&game_key).ok_or_else(|| missing_game(&env, session_id))?;
     if player != game.player1 && player != game.player2 {
       return Err(Error::NotPlayer);
     }
+    consume_rate_limit(&env, &player)?;
 
     let expires_ledger = env.ledger().sequence().saturating_add(ttl_ledgers);
     let session_key = DataKey::Session(player, delegate, session_id);
     let grant = SessionGrant {
       expires_ledger,
       uses_left,
+      transferable: false,
     };
 
     env.storage().persistent().set(&session_key, &grant);
@@ -556,6 +1649,39 @@ impl BattleshipContract {
     Ok(())
   }
 
+  // Pre-approves `delegate` to hand its grant to another address, e.g. a standby proving
+  // service, so failover does not need the player online.
+  pub fn allow_session_transfer(env: Env, session_id: u32, player: Address, delegate: Address) -> Result<(), Error> {
+    player.require_auth();
+    let session_key = DataKey::Session(player, delegate, session_id);
+    let mut grant: SessionGrant = env.storage().persistent().get(&session_key).ok_or(Error::InvalidSession)?;
+    grant.transferable = true;
+    env.storage().persistent().set(&session_key, &grant);
+    extend_session_ttl(&env, &session_key);
+    Ok(())
+  }
+
+  // Moves a transferable grant as-is (expiry, remaining uses, transferability) from `delegate`
+  // to `new_delegate`. The old delegate loses it; the player can still revoke the new one.
+  pub fn transfer_session(env: Env, session_id: u32, player: Address, delegate: Address, new_delegate: Address) -> Result<(), Error> {
+    delegate.require_auth();
+    if new_delegate == player || new_delegate == delegate { return Err(Error::InvalidSessionConfig); }
+    let session_key = DataKey::Session(player.clone(), delegate, session_id);
+    let grant: SessionGrant = env.storage().persistent().get(&session_key).ok_or(Error::InvalidSession)?;
+    if env.ledger().sequence() > grant.expires_ledger {
+      env.storage().persistent().remove(&session_key);
+      return Err(Error::SessionExpired);
+    }
+    if !grant.transferable { return Err(Error::GrantNotTransferable); }
+    let new_key = DataKey::Session(player, new_delegate, session_id);
+    if env.storage().persistent().has(&new_key) { return Err(Error::InvalidSessionConfig); }
+
+    env.storage().persistent().remove(&session_key);
+    env.storage().persistent().set(&new_key, &grant);
+    extend_session_ttl(&env, &new_key);
+    Ok(())
+  }
+
   pub fn revoke_session(env: Env, session_id: u32, player: Address, delegate: Address) -> Result<(), Error> {
     player.require_auth();
 
@@ -578,9 +1704,111 @@ impl BattleshipContract {
     env.storage().persistent().get(&session_key)
   }
 
+  pub fn get_event_schema_version(_env: Env) -> u32 {
+    EVENT_SCHEMA_VERSION
+  }
+
   pub fn get_game(env: Env, session_id: u32) -> Result<Game, Error> {
     let key = DataKey::Game(session_id);
-    env.storage().temporary().get(&key).ok_or(Error::GameNotFound)
+    env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))
+  }
+
+  pub fn get_game_summary(env: Env, session_id: u32) -> Result<GameSummary, Error> {
+    let game = Self::get_game(env.clone(), session_id)?;
+    Ok(GameSummary {
+      status: game_status(&game),
+      phase: game.phase,
+      player1_ready: has_committed(&game, &game.player1),
+      player2_ready: has_committed(&game, &game.player2),
+      turn: game.turn,
+      pending_defender: game.pending_defender,
+      player1_hits: game.player1_hits,
+      player2_hits: game.player2_hits,
+      winner: game.winner,
+      drawn: game.drawn,
+      deadlines: Self::get_deadlines(env, session_id)?,
+      player1: game.player1,
+      player2: game.player2,
+    })
+  }
+
+  pub fn get_game_phase(env: Env, session_id: u32) -> Result<GamePhase, Error> {
+    Ok(Self::get_game(env, session_id)?.phase)
+  }
+
+  pub fn get_game_tombstone(env: Env, session_id: u32) -> Option<GameTombstone> {
+    env.storage().persistent().get(&DataKey::Tombstone(session_id))
+  }
+
+  pub fn get_escrow_record(env: Env, session_id: u32) -> Option<EscrowRecord> {
+    env.storage().persistent().get(&EscrowKey::Held(session_id))
+  }
+
+  pub fn recover_expired(env: Env, session_id: u32, keeper: Address) -> Result<i128, Error> {
+    if env.storage().temporary().has(&DataKey::Game(session_id)) { return Err(Error::GameNotExpired); }
+    let tombstone_key = DataKey::Tombstone(session_id);
+    let mut tombstone: GameTombstone = env.storage().persistent().get(&tombstone_key).ok_or(Error::GameNotFound)?;
+    let escrow_key = EscrowKey::Held(session_id);
+    let record: EscrowRecord = env.storage().persistent().get(&escrow_key).ok_or(Error::NothingToRecover)?;
+
+    // Refunds go back to whoever paid in, so the caller needs no authorization.
+    let token_client = token::Client::new(&env, &record.token);
+    let escrow = env.current_contract_address();
+    let mut total: i128 = 0;
+    for share in record.shares.iter() {
+      token_client.transfer(&escrow, &share.owner, &share.amount);
+      Payout { session_id, to: share.owner, token: record.token.clone(), amount: share.amount }.publish(&env);
+      total = total.saturating_add(share.amount);
+    }
+    adjust_liabilities(&env, &record.token, total.saturating_neg());
+    env.storage().persistent().remove(&escrow_key);
+    channel::discard_channel(&env, session_id);
+
+    if tombstone.escrowed { count_escrowed_game(&env, session_id, false); }
+    tombstone.escrowed = false;
+    env.storage().persistent().set(&tombstone_key, &tombstone);
+    env.storage().persistent().extend_ttl(&tombstone_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    EscrowRecovered { session_id, token: record.token, amount: total }.publish(&env);
+    pay_keeper_bounty(&env, &keeper, 1);
+    Ok(total)
+  }
+
+  pub fn get_withdrawable(env: Env, owner: Address, token: Address) -> i128 {
+    env.storage().persistent().get(&EscrowKey::Withdrawable(owner, token)).unwrap_or(0)
+  }
+
+  pub fn withdraw(env: Env, owner: Address, token: Address) -> Result<i128, Error> {
+    owner.require_auth();
+    let amount = withdraw_balance(&env, &owner, &token);
+    if amount <= 0 { return Err(Error::NothingToRecover); }
+    Ok(amount)
+  }
+
+  // Claims every listed token in one call; an empty list sweeps all of `list_balances`. Tokens
+  // with nothing to claim are skipped. Returns what was paid out per token.
+  pub fn withdraw_all(env: Env, owner: Address, tokens: Vec<Address>) -> Result<Map<Address, i128>, Error> {
+    owner.require_auth();
+    let tokens = if tokens.is_empty() { Self::list_balances(env.clone(), owner.clone()).keys() } else { tokens };
+    let mut paid = Map::new(&env);
+    for token in tokens.iter() {
+      if paid.contains_key(token.clone()) { continue; }
+      let amount = withdraw_balance(&env, &owner, &token);
+      if amount > 0 { paid.set(token, amount); }
+    }
+    if paid.is_empty() { return Err(Error::NothingToRecover); }
+    Ok(paid)
+  }
+
+  // Every token `owner` has a withdrawable balance in: winnings held for a rematch, refunds,
+  // released skip bonds and slashed bonds alike.
+  pub fn list_balances(env: Env, owner: Address) -> Map<Address, i128> {
+    let tokens: Vec<Address> = env.storage().persistent().get(&EscrowKey::OwnerTokens(owner.clone())).unwrap_or(Vec::new(&env));
+    let mut balances = Map::new(&env);
+    for token in tokens.iter() {
+      let amount: i128 = env.storage().persistent().get(&EscrowKey::Withdrawable(owner.clone(), token.clone())).unwrap_or(0);
+      if amount > 0 { balances.set(token, amount); }
+    }
+    balances
   }
 
   pub fn get_admin(env: Env) -> Address {
@@ -588,9 +1816,10 @@ impl BattleshipContract {
   }
 
   pub fn set_admin(env: Env, new_admin: Address) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
+    require_admin(&env);
+    new_admin.require_auth();
     env.storage().instance().set(&DataKey::Admin, &new_admin);
+    record_setting_change(&env, "admin", new_admin);
   }
 
   pub fn get_hub(env: Env) -> Address {
@@ -602,15 +1831,15 @@ impl BattleshipContract {
   }
 
   pub fn set_bet_token(env: Env, token_contract: Address) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
+    require_admin(&env);
     env.storage().instance().set(&ConfigKey::BetToken, &token_contract);
+    record_config_change(&env, ConfigBundle { bet_token: Some(token_contract), ..unchanged_config() });
   }
 
   pub fn clear_bet_token(env: Env) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
+    require_admin(&env);
     env.storage().instance().remove(&ConfigKey::BetToken);
+    record_config_change(&env, ConfigBundle { clear_bet_token: true, ..unchanged_config() });
   }
 
   pub fn get_fee_bps(env: Env) -> u32 {
@@ -622,162 +1851,2314 @@ impl BattleshipContract {
   }
 
   pub fn set_fee_bps(env: Env, fee_bps: u32) -> Result<(), Error> {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    if fee_bps > 2_000 { return Err(Error::InvalidFeeBps); }
+    require_admin(&env);
+    if fee_bps > MAX_FEE_BPS { return Err(Error::InvalidFeeBps); }
     env.storage().instance().set(&ConfigKey::FeeBps, &fee_bps);
+    record_config_change(&env, ConfigBundle { fee_bps: Some(fee_bps), ..unchanged_config() });
     Ok(())
   }
 
   pub fn set_fee_recipient(env: Env, recipient: Address) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
+    require_admin(&env);
     env.storage().instance().set(&ConfigKey::FeeRecipient, &recipient);
+    record_config_change(&env, ConfigBundle { fee_recipient: Some(recipient), ..unchanged_config() });
+  }
+
+  pub fn configure(env: Env, config: ConfigBundle) -> Result<(), Error> {
+    require_admin(&env);
+    apply_config(&env, &config)?;
+    record_config_change(&env, config.clone());
+    ConfigChanged { config }.publish(&env);
+    Ok(())
+  }
+
+  pub fn get_config_history_len(env: Env) -> u32 {
+    env.storage().persistent().get(&ConfigHistoryKey::Len).unwrap_or(0)
+  }
+
+  pub fn get_config_history(env: Env, cursor: u32, limit: u32) -> Vec<ConfigRecord> {
+    let len: u32 = env.storage().persistent().get(&ConfigHistoryKey::Len).unwrap_or(0);
+    let end = cursor.saturating_add(limit.min(MAX_CONFIG_HISTORY_PAGE)).min(len);
+    let mut records = Vec::new(&env);
+    for index in cursor..end {
+      if let Some(record) = env.storage().persistent().get(&ConfigHistoryKey::Entry(index)) { records.push_back(record); }
+    }
+    records
+  }
+
+  pub fn get_annotation_fee(env: Env) -> i128 {
+    env.storage().instance().get(&ConfigKey::AnnotationFee).unwrap_or(0)
+  }
+
+  pub fn set_annotation_fee(env: Env, fee: i128) -> Result<(), Error> {
+    require_admin(&env);
+    if fee < 0 { return Err(Error::InvalidStakeAmount); }
+    env.storage().instance().set(&ConfigKey::AnnotationFee, &fee);
+    record_setting_change(&env, "annotation_fee", fee);
+    Ok(())
+  }
+
+  pub fn get_zk_rebate(env: Env) -> i128 {
+    env.storage().instance().get(&ConfigKey::ZkRebate).unwrap_or(0)
+  }
+
+  // `board_count` is 1 for a classic game or 2 for parallel boards; `stake` is per player.
+  pub fn quote_game_cost(env: Env, board_size: u32, board_count: u32, zk: bool, stake: i128) -> Result<CostQuote, Error> {
+    if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&board_size) { return Err(Error::InvalidBoardSize); }
+    if board_count == 0 || board_count > MAX_BOARDS_PER_PLAYER { return Err(Error::InvalidBoardIndex); }
+    if zk && board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if stake < 0 { return Err(Error::InvalidStakeAmount); }
+
+    let cells = board_size * board_size;
+    let boards = 2 * board_count;
+    let max_moves = boards * cells;
+    // Each board stores its commitments plus a root; each move appends one u32 target.
+    let bytes_written = boards * (cells + 1) * 32 + max_moves * 4;
+    // start_game, any set_board_size/set_board_count, deposits, one commit per board, then
+    // an attack and a reveal per move.
+    let mut setup = 1 + boards;
+    if board_size != DEFAULT_BOARD_SIZE { setup += 1; }
+    if board_count > 1 { setup += 1; }
+    if stake > 0 { setup += 2; }
+    let transactions = setup + max_moves * 2;
+
+    let fee_bps: u32 = env.storage().instance().get(&ConfigKey::FeeBps).unwrap_or(DEFAULT_FEE_BPS);
+    let protocol_fee = stake.saturating_mul(fee_bps as i128) / BPS_DENOMINATOR * 2;
+    let per_proof: i128 = env.storage().instance().get(&ConfigKey::ZkRebate).unwrap_or(0);
+    let max_zk_rebate = if zk { per_proof.saturating_mul(max_moves as i128).min(protocol_fee) } else { 0 };
+    Ok(CostQuote { bytes_written, transactions, max_moves, fee_bps, protocol_fee, max_zk_rebate })
+  }
+
+  pub fn set_zk_rebate(env: Env, per_proof: i128) -> Result<(), Error> {
+    require_admin(&env);
+    if per_proof < 0 { return Err(Error::InvalidStakeAmount); }
+    env.storage().instance().set(&ConfigKey::ZkRebate, &per_proof);
+    record_setting_change(&env, "zk_rebate", per_proof);
+    Ok(())
+  }
+
+  pub fn annotate(env: Env, session_id: u32, author: Address, move_index: u32, note: String) -> Result<(), Error> {
+    author.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &ENDED_PHASES)?;
+    if note.is_empty() || note.len() > MAX_ANNOTATION_BYTES { return Err(Error::InvalidAnnotation); }
+    if move_index >= recorded_moves(&game) { return Err(Error::InvalidAnnotation); }
+
+    let key = AnnotationKey::Game(session_id);
+    let mut annotations: Vec<Annotation> = env.storage().persistent().get(&key).unwrap_or(Vec::new(&env));
+    if annotations.len() >= MAX_ANNOTATIONS_PER_GAME { return Err(Error::InvalidAnnotation); }
+
+    let fee: i128 = env.storage().instance().get(&ConfigKey::AnnotationFee).unwrap_or(0);
+    if fee > 0 && author != game.player1 && author != game.player2 {
+      let token_contract: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+      let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
+      token::Client::new(&env, &token_contract).transfer(&author, &fee_recipient, &fee);
+    }
+
+    annotations.push_back(Annotation { author: author.clone(), move_index, note, ledger: env.ledger().sequence() });
+    env.storage().persistent().set(&key, &annotations);
+    env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    AnnotationAdded { session_id, author, move_index }.publish(&env);
+    Ok(())
+  }
+
+  pub fn get_annotations(env: Env, session_id: u32) -> Vec<Annotation> {
+    env.storage().persistent().get(&AnnotationKey::Game(session_id)).unwrap_or(Vec::new(&env))
+  }
+
+  pub fn attach_replay(env: Env, session_id: u32, player: Address, content_hash: Bytes) -> Result<(), Error> {
+    player.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &ENDED_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    if content_hash.is_empty() || content_hash.len() > MAX_CONTENT_HASH_BYTES { return Err(Error::InvalidContentHash); }
+
+    // One replay per player; attaching again replaces the earlier link.
+    let key = AnnotationKey::Replays(session_id);
+    let mut replays: Vec<ReplayAttachment> = env.storage().persistent().get(&key).unwrap_or(Vec::new(&env));
+    if let Some(index) = replays.iter().position(|replay| replay.player == player) { replays.remove(index as u32); }
+    replays.push_back(ReplayAttachment { player: player.clone(), content_hash: content_hash.clone(), ledger: env.ledger().sequence() });
+    env.storage().persistent().set(&key, &replays);
+    env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    ReplayAttached { session_id, player, content_hash }.publish(&env);
+    Ok(())
+  }
+
+  pub fn get_replays(env: Env, session_id: u32) -> Vec<ReplayAttachment> {
+    env.storage().persistent().get(&AnnotationKey::Replays(session_id)).unwrap_or(Vec::new(&env))
   }
 
   pub fn deposit_stake(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
     player.require_auth();
 
     let key = DataKey::Game(session_id);
-    let mut game: Game = env.storage().temporary().get(&key).ok_or(Error::GameNotFound)?;
-    if game.winner.is_some() { return Err(Error::GameAlreadyEnded); }
-    if !is_wager_game(&game) { return Ok(()); }
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    if !is_wager_game(&game) { return require_phase(&game, &LIVE_PHASES); }
+    require_phase(&game, &[GamePhase::AwaitingDeposits])?;
 
-    let amount = if player == game.player1 {
+    // Backers have already escrowed part of the side; the player deposits the rest.
+    let is_player1 = player == game.player1;
+    let amount = if is_player1 {
       if game.player1_deposited { return Err(Error::AlreadyDeposited); }
-      game.player1_points
+      game.player1_stake.saturating_sub(game.player1_backed)
     } else if player == game.player2 {
       if game.player2_deposited { return Err(Error::AlreadyDeposited); }
-      game.player2_points
+      game.player2_stake.saturating_sub(game.player2_backed)
     } else {
       return Err(Error::NotPlayer);
     };
 
-    if amount <= 0 {
-      if player == game.player1 {
-        game.player1_deposited = true;
-      } else {
-        game.player2_deposited = true;
-      }
-      env.storage().temporary().set(&key, &game);
-      extend_game_ttl(&env, &key);
-      return Ok(());
-    }
+    if amount <= 0 {
+      mark_deposited(&mut game, is_player1);
+      store_game(&env, session_id, &game);
+      return Ok(());
+    }
+
+    let token_contract = escrow_token(&env, session_id)?;
+    let token_client = token::Client::new(&env, &token_contract);
+    let escrow = env.current_contract_address();
+    token_client.transfer(&player, &escrow, &amount);
+    adjust_liabilities(&env, &token_contract, amount);
+    StakeDeposited { session_id, player: player.clone(), from: player.clone(), token: token_contract, amount }.publish(&env);
+
+    mark_deposited(&mut game, is_player1);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn back_player(env: Env, session_id: u32, backer: Address, player: Address, amount: i128) -> Result<(), Error> {
+    backer.require_auth();
+
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if amount <= 0 { return Err(Error::InvalidStakeAmount); }
+
+    let backs_player1 = if player == game.player1 { true } else if player == game.player2 { false } else { return Err(Error::NotPlayer); };
+    let (deposited, stake, backed, mut backers) = if backs_player1 {
+      (game.player1_deposited, game.player1_stake, game.player1_backed, game.player1_backers.clone())
+    } else {
+      (game.player2_deposited, game.player2_stake, game.player2_backed, game.player2_backers.clone())
+    };
+    if deposited { return Err(Error::BackingClosed); }
+    if amount > stake.saturating_sub(backed) { return Err(Error::InvalidStakeAmount); }
+
+    match backers.iter().position(|backing| backing.backer == backer) {
+      Some(index) => {
+        let mut backing = backers.get(index as u32).unwrap();
+        backing.amount = backing.amount.saturating_add(amount);
+        backers.set(index as u32, backing);
+      }
+      None => {
+        if backers.len() >= MAX_BACKERS_PER_SIDE { return Err(Error::TooManyBackers); }
+        backers.push_back(Backing { backer: backer.clone(), amount });
+      }
+    }
+
+    let token_contract = escrow_token(&env, session_id)?;
+    let escrow = env.current_contract_address();
+    token::Client::new(&env, &token_contract).transfer(&backer, &escrow, &amount);
+    adjust_liabilities(&env, &token_contract, amount);
+    StakeDeposited { session_id, player, from: backer, token: token_contract, amount }.publish(&env);
+
+    if backs_player1 {
+      game.player1_backed = backed.saturating_add(amount);
+      game.player1_backers = backers;
+    } else {
+      game.player2_backed = backed.saturating_add(amount);
+      game.player2_backers = backers;
+    }
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn tip_player(env: Env, session_id: u32, from: Address, player: Address, amount: i128) -> Result<(), Error> {
+    from.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    if amount <= 0 { return Err(Error::InvalidStakeAmount); }
+
+    let mut jar = Self::get_tip_jar(env.clone(), session_id, player.clone());
+    let token_contract = match jar.token.clone() { Some(token_contract) => token_contract, None => escrow_token(&env, session_id)? };
+    let escrow = env.current_contract_address();
+    token::Client::new(&env, &token_contract).transfer(&from, &escrow, &amount);
+    adjust_liabilities(&env, &token_contract, amount);
+
+    let held = jar.hold_until_end;
+    if held {
+      jar.token = Some(token_contract);
+      jar.held = jar.held.saturating_add(amount);
+      store_tip_jar(&env, session_id, &player, &jar);
+    } else {
+      credit_withdrawable(&env, &player, &token_contract, amount);
+    }
+    TipReceived { session_id, player, from, amount, held }.publish(&env);
+    Ok(())
+  }
+
+  // Later tips are held until the game ends; tips already credited stay withdrawable.
+  pub fn set_tip_hold(env: Env, session_id: u32, player: Address, hold_until_end: bool) -> Result<(), Error> {
+    player.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+
+    let mut jar = Self::get_tip_jar(env.clone(), session_id, player.clone());
+    jar.hold_until_end = hold_until_end;
+    store_tip_jar(&env, session_id, &player, &jar);
+    Ok(())
+  }
+
+  // Anyone may release a player's held tips once the game has ended or expired from storage.
+  pub fn release_tips(env: Env, session_id: u32, player: Address, keeper: Address) -> Result<i128, Error> {
+    if let Some(game) = env.storage().temporary().get::<DataKey, Game>(&DataKey::Game(session_id)) {
+      require_phase(&game, &ENDED_PHASES)?;
+    }
+    let mut jar = Self::get_tip_jar(env.clone(), session_id, player.clone());
+    let token_contract = jar.token.clone().ok_or(Error::NothingToRecover)?;
+    if jar.held <= 0 { return Err(Error::NothingToRecover); }
+
+    let amount = jar.held;
+    credit_withdrawable(&env, &player, &token_contract, amount);
+    jar.held = 0;
+    store_tip_jar(&env, session_id, &player, &jar);
+    pay_keeper_bounty(&env, &keeper, 1);
+    Ok(amount)
+  }
+
+  pub fn get_tip_jar(env: Env, session_id: u32, player: Address) -> TipJar {
+    env.storage().persistent().get(&EscrowKey::Tips(session_id, player)).unwrap_or(TipJar { token: None, hold_until_end: false, held: 0 })
+  }
+
+  pub fn set_shot_tax(env: Env, session_id: u32, shot_tax: i128) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.player1_board.is_some() || game.player2_board.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    if shot_tax < 0 || (shot_tax > 0 && !is_wager_game(&game)) { return Err(Error::InvalidStakeAmount); }
+    if !env.storage().instance().has(&ConfigKey::BetToken) { return Err(Error::BetTokenNotConfigured); }
+
+    game.shot_tax = shot_tax;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn set_organizer_cut(env: Env, session_id: u32, organizer: Address, organizer_bps: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.turn.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    if organizer_bps as i128 > BPS_DENOMINATOR { return Err(Error::InvalidFeeBps); }
+
+    game.organizer = Some(organizer);
+    game.organizer_bps = organizer_bps;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Casual games only: a player who lets `turn_ledgers` pass without acting can have their
+  // turn skipped by the opponent, who is paid `penalty` out of the staller's bond.
+  pub fn set_skip_policy(env: Env, session_id: u32, turn_ledgers: u32, bond: i128, penalty: i128) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if has_committed(&game, &game.player1) || has_committed(&game, &game.player2) { return Err(Error::BoardAlreadyCommitted); }
+    if is_wager_game(&game) || game.exhibition { return Err(Error::CasualGameOnly); }
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    if game.skip_turn_ledgers > 0 { return Err(Error::AlreadyDeposited); }
+    if turn_ledgers == 0 { return Err(Error::InvalidConfig); }
+    if bond < 0 || penalty < 0 || penalty > bond || (bond > 0 && penalty == 0) { return Err(Error::InvalidStakeAmount); }
+
+    if bond > 0 {
+      let token_contract = escrow_token(&env, session_id)?;
+      let token_client = token::Client::new(&env, &token_contract);
+      let escrow = env.current_contract_address();
+      token_client.transfer(&game.player1, &escrow, &bond);
+      token_client.transfer(&game.player2, &escrow, &bond);
+      adjust_liabilities(&env, &token_contract, bond.saturating_mul(2));
+    }
+    game.skip_turn_ledgers = turn_ledgers;
+    game.skip_penalty = penalty;
+    game.player1_skip_bond = bond;
+    game.player2_skip_bond = bond;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // A stalled attacker loses the turn; a stalled defender loses the pending shot, which the
+  // attacker fires again. Returns the amount slashed to the claimant's withdrawable balance.
+  pub fn skip_turn(env: Env, session_id: u32, claimant: Address) -> Result<i128, Error> {
+    claimant.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    if game.skip_turn_ledgers == 0 { return Err(Error::SkipPolicyNotSet); }
+    require_phase(&game, &PLAY_PHASES)?;
+    if claimant != game.player1 && claimant != game.player2 { return Err(Error::NotPlayer); }
+
+    let staller = game.pending_defender.clone().or(game.turn.clone()).ok_or(Error::BoardsNotReady)?;
+    if staller == claimant { return Err(Error::NotYourTurn); }
+    if env.ledger().sequence() <= game.turn_started_ledger.saturating_add(dev_window(&env, game.skip_turn_ledgers)) { return Err(Error::DeadlineNotReached); }
+
+    let bond = if staller == game.player1 { &mut game.player1_skip_bond } else { &mut game.player2_skip_bond };
+    let slashed = game.skip_penalty.min(*bond);
+    *bond = bond.saturating_sub(slashed);
+    if slashed > 0 {
+      let token_contract = escrow_token(&env, session_id)?;
+      credit_withdrawable(&env, &claimant, &token_contract, slashed);
+    }
+
+    game.pending_attacker = None;
+    game.phase = GamePhase::InProgress;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.turn = Some(claimant);
+    drop_queued_attack_if_decided(&mut game);
+    start_turn_clock(&env, &mut game);
+    store_game(&env, session_id, &game);
+    Ok(slashed)
+  }
+
+  // What is left of both bonds becomes withdrawable once the game is over.
+  pub fn release_skip_bonds(env: Env, session_id: u32, keeper: Address) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &ENDED_PHASES)?;
+    if game.player1_skip_bond <= 0 && game.player2_skip_bond <= 0 { return Err(Error::NothingToRecover); }
+
+    let token_contract = escrow_token(&env, session_id)?;
+    if game.player1_skip_bond > 0 { credit_withdrawable(&env, &game.player1, &token_contract, game.player1_skip_bond); }
+    if game.player2_skip_bond > 0 { credit_withdrawable(&env, &game.player2, &token_contract, game.player2_skip_bond); }
+    game.player1_skip_bond = 0;
+    game.player2_skip_bond = 0;
+    store_game(&env, session_id, &game);
+    pay_keeper_bounty(&env, &keeper, 1);
+    Ok(())
+  }
+
+  // Any game, staked or not: a player who lets `ledgers` pass without attacking or resolving
+  // forfeits to the opponent through `claim_timeout_win`.
+  pub fn set_move_timeout(env: Env, session_id: u32, ledgers: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    require_capability(&game, CAP_TIMEOUTS)?;
+    if has_committed(&game, &game.player1) || has_committed(&game, &game.player2) { return Err(Error::BoardAlreadyCommitted); }
+    if ledgers == 0 { return Err(Error::InvalidConfig); }
+
+    game.move_timeout_ledgers = ledgers;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn claim_timeout_win(env: Env, session_id: u32, claimant: Address) -> Result<(), Error> {
+    claimant.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    if game.move_timeout_ledgers == 0 { return Err(Error::MoveTimeoutNotSet); }
+    require_phase(&game, &PLAY_PHASES)?;
+    if claimant != game.player1 && claimant != game.player2 { return Err(Error::NotPlayer); }
+
+    let staller = game.pending_defender.clone().or(game.turn.clone()).ok_or(Error::BoardsNotReady)?;
+    if staller == claimant { return Err(Error::NotYourTurn); }
+    if env.ledger().sequence() <= game.move_deadline_ledger { return Err(Error::DeadlineNotReached); }
+
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+    game.turn = None;
+    game.winner = Some(claimant.clone());
+    game.phase = GamePhase::Finished;
+    settle_wager(&env, session_id, &mut game)?;
+    apply_reward_multiplier(&env, session_id, &mut game);
+    if !game.exhibition { record_abandon(&env, &staller); }
+    TimeoutWin { session_id, winner: claimant.clone(), staller }.publish(&env);
+    end_game_hub(&env, session_id, &game, claimant == game.player1);
+
+    store_game(&env, session_id, &game);
+    pay_keeper_bounty(&env, &claimant, 1);
+    Ok(())
+  }
+
+  // For the pending defender of a ZK game while the verifier is down: the proof is tried again
+  // and, if the verifier still cannot be reached, the defender's clock restarts so no skip or
+  // timeout claim can be made off the outage. Returns the new move deadline.
+  pub fn report_verifier_outage(env: Env, session_id: u32, defender: Address, zk_attack_proof: Bytes) -> Result<u32, Error> {
+    defender.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+    if game.pending_defender != Some(defender.clone()) { return Err(Error::NotPendingDefender); }
+    let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
+    let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
+    let verifier_addr: Address = env.storage().instance().get(&DataKey::ZkVerifierContract).ok_or(Error::ZkVerifierNotConfigured)?;
+
+    let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
+    let board = if defender == game.player1 { game.player1_board.clone() } else { game.player2_board.clone() };
+    let expected = board.ok_or(Error::BoardsNotReady)?.get(target_index).ok_or(Error::InvalidCoordinate)?;
+    let verifier = ZkVerifierClient::new(&env, &verifier_addr);
+    match zk_verdict(verifier.try_verify_attack(&session_id, &pending_x, &pending_y, &expected, &zk_attack_proof)) {
+      Err(Error::VerifierUnavailable) => {}
+      _ => return Err(Error::VerifierAvailable),
+    }
+
+    start_turn_clock(&env, &mut game);
+    VerifierOutage { session_id, defender, deadline_ledger: game.move_deadline_ledger }.publish(&env);
+    store_game(&env, session_id, &game);
+    Ok(game.move_deadline_ledger)
+  }
+
+  pub fn get_deadlines(env: Env, session_id: u32) -> Result<Vec<Deadline>, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    let mut deadlines = Vec::new(&env);
+    if is_over(&game) { return Ok(deadlines); }
+
+    // Only obligations backed by an enforced timeout are listed.
+    if let Some(expires_ledger) = commit_deadline(&env, &game) {
+      let player = if has_committed(&game, &game.player1) { game.player2.clone() } else { game.player1.clone() };
+      if !has_committed(&game, &player) { deadlines.push_back(Deadline { obligation: Obligation::Commit, player, expires_ledger }); }
+    }
+    let skip_deadline = (game.skip_turn_ledgers > 0).then(|| game.turn_started_ledger.saturating_add(dev_window(&env, game.skip_turn_ledgers)));
+    let move_deadline = (game.move_timeout_ledgers > 0).then_some(game.move_deadline_ledger);
+    if let Some(expires_ledger) = match (skip_deadline, move_deadline) { (Some(skip), Some(timeout)) => Some(skip.min(timeout)), (deadline, None) | (None, deadline) => deadline } {
+      if let Some(player) = game.pending_defender.clone() {
+        deadlines.push_back(Deadline { obligation: Obligation::Resolve, player, expires_ledger });
+      } else if let Some(player) = game.turn.clone() {
+        deadlines.push_back(Deadline { obligation: Obligation::Move, player, expires_ledger });
+      }
+    }
+    Ok(deadlines)
+  }
+
+  pub fn get_legal_actions(env: Env, session_id: u32, player: Address) -> Result<LegalActions, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    let mut actions = LegalActions { deposit: false, commit: false, attack: false, attack_targets: 0, queue_attack: false, resolve: false };
+    if is_over(&game) { return Ok(actions); }
+
+    let is_player1 = player == game.player1;
+    let funded = !is_wager_game(&game) || (game.player1_deposited && game.player2_deposited);
+    actions.deposit = is_wager_game(&game) && !(if is_player1 { game.player1_deposited } else { game.player2_deposited });
+    actions.commit = funded && !has_committed(&game, &player);
+
+    let cells = game.board_size.saturating_mul(game.board_size);
+    actions.attack_targets = if game.board_count > 1 {
+      let boards = if is_player1 { &game.player2_boards } else { &game.player1_boards };
+      boards.iter().fold(0u32, |open, board| open.saturating_add(cells.saturating_sub(bit_count(&board.attacked))))
+    } else {
+      cells.saturating_sub(bit_count(if is_player1 { &game.player1_attacks } else { &game.player2_attacks }))
+    };
+    actions.attack = funded && boards_ready(&game) && game.pending_attacker.is_none() && game.turn == Some(player.clone()) && actions.attack_targets > 0;
+    actions.queue_attack = game.pending_attacker == Some(player.clone())
+      && require_capability(&game, CAP_SALVO).is_ok()
+      && game.board_count <= 1
+      && game.shot_tax == 0
+      && actions.attack_targets > 1;
+    actions.resolve = game.pending_defender == Some(player);
+    Ok(actions)
+  }
+
+  pub fn get_boards_view(env: Env, session_id: u32) -> Result<BoardsView, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+    let pending = match (game.pending_x, game.pending_y) {
+      (Some(x), Some(y)) => Some(y.saturating_mul(game.board_size).saturating_add(x)),
+      _ => None,
+    };
+    let pending_for = |attacker: &Address| if game.pending_attacker.as_ref() == Some(attacker) { pending } else { None };
+    Ok(BoardsView {
+      board_size: game.board_size,
+      player1: targeting_grid(&env, game.board_size, &game.player1_attacks, &game.player1_hit_attacks, pending_for(&game.player1)),
+      player2: targeting_grid(&env, game.board_size, &game.player2_attacks, &game.player2_hit_attacks, pending_for(&game.player2)),
+    })
+  }
+
+  // Classic fleet on the default board; the same seed always gives the same placement.
+  pub fn suggest_board(env: Env, seed: BytesN<32>) -> Vec<u32> {
+    placement::suggest_board(&env, &seed, DEFAULT_BOARD_SIZE, &CLASSIC_FLEET).unwrap_or(Vec::new(&env))
+  }
+
+  pub fn get_commit_window(env: Env) -> u32 {
+    dev_window(&env, env.storage().instance().get(&ConfigKey::CommitWindow).unwrap_or(DEFAULT_COMMIT_WINDOW_LEDGERS))
+  }
+
+  pub fn set_commit_window(env: Env, ledgers: u32) -> Result<(), Error> {
+    require_admin(&env);
+    if ledgers == 0 { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::CommitWindow, &ledgers);
+    record_setting_change(&env, "commit_window", ledgers);
+    Ok(())
+  }
+
+  // For local devnets: caps every contract-defined window (commit, turn and move timeouts, game
+  // duration, abandon cooldowns, upgrade delay, channel challenges) at `timeout_ledgers`, so
+  // integration suites reach forfeits and disputes within a few ledgers. Storage TTLs are the
+  // network's and are left alone. 0 turns it off; refused on the public network.
+  pub fn set_dev_mode(env: Env, timeout_ledgers: u32) -> Result<(), Error> {
+    require_admin(&env);
+    let change = ConfigBundle { dev_mode: Some(timeout_ledgers), ..unchanged_config() };
+    apply_config(&env, &change)?;
+    record_config_change(&env, change);
+    Ok(())
+  }
+
+  pub fn get_dev_mode(env: Env) -> u32 {
+    env.storage().instance().get(&ConfigKey::DevMode).unwrap_or(0)
+  }
+
+  // 0 means games may run until their storage TTL lapses.
+  pub fn get_max_game_duration(env: Env) -> u32 {
+    env.storage().instance().get(&ConfigKey::MaxGameDuration).unwrap_or(0)
+  }
+
+  pub fn set_max_game_duration(env: Env, ledgers: u32) -> Result<(), Error> {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::MaxGameDuration, &ledgers);
+    record_setting_change(&env, "max_game_duration", ledgers);
+    Ok(())
+  }
+
+  pub fn adjudicate(env: Env, session_id: u32, keeper: Address) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+
+    // An uncapped game never reaches its duration deadline.
+    let deadline = duration_deadline(&env, &game).ok_or(Error::DeadlineNotReached)?;
+    if env.ledger().sequence() <= deadline { return Err(Error::DeadlineNotReached); }
+
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.pending_board = None;
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+    game.turn = None;
+
+    // A player sitting on unproven buffered resolutions could be hiding hits, so they forfeit.
+    let player1_unproven = !game.player1_buffer.is_empty();
+    let player2_unproven = !game.player2_buffer.is_empty();
+    let player1_won = if player1_unproven != player2_unproven {
+      Some(player2_unproven)
+    } else if game.player1_hits == game.player2_hits {
+      None
+    } else {
+      Some(game.player1_hits > game.player2_hits)
+    };
+
+    match player1_won {
+      Some(player1_won) => {
+        let winner = if player1_won { game.player1.clone() } else { game.player2.clone() };
+        game.winner = Some(winner.clone());
+        game.phase = GamePhase::Finished;
+        settle_wager(&env, session_id, &mut game)?;
+        apply_reward_multiplier(&env, session_id, &mut game);
+        GameAdjudicated { session_id, winner: Some(winner), player1_hits: game.player1_hits, player2_hits: game.player2_hits }.publish(&env);
+        end_game_hub(&env, session_id, &game, player1_won);
+      }
+      None => {
+        // A tie is a draw; hubs without `end_game_draw` leave the session to the hub operator.
+        refund_stakes(&env, session_id, &mut game)?;
+        game.voided = true;
+        game.drawn = true;
+        game.phase = GamePhase::Finished;
+        GameAdjudicated { session_id, winner: None, player1_hits: game.player1_hits, player2_hits: game.player2_hits }.publish(&env);
+        end_game_hub_draw(&env, session_id, &game);
+      }
+    }
+
+    store_game(&env, session_id, &game);
+    pay_keeper_bounty(&env, &keeper, 1);
+    Ok(())
+  }
+
+  pub fn void_uncommitted_game(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingBoards])?;
+    require_capability(&game, CAP_TIMEOUTS)?;
+
+    let opponent = if player == game.player1 { game.player2.clone() } else if player == game.player2 { game.player1.clone() } else { return Err(Error::NotPlayer); };
+    if !has_committed(&game, &player) { return Err(Error::BoardsNotReady); }
+    if has_committed(&game, &opponent) { return Err(Error::BoardAlreadyCommitted); }
+
+    let deadline = commit_deadline(&env, &game).ok_or(Error::BoardsNotReady)?;
+    if env.ledger().sequence() <= deadline { return Err(Error::CommitWindowOpen); }
+
+    refund_stakes(&env, session_id, &mut game)?;
+    game.voided = true;
+    game.winner = Some(player.clone());
+    game.phase = GamePhase::Cancelled;
+    GameVoided { session_id, by: player.clone() }.publish(&env);
+    if !game.exhibition { record_abandon(&env, &opponent); }
+    end_game_hub(&env, session_id, &game, player == game.player1);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // A concession is not an abandon. Before both stakes are in, the game is voided with any
+  // deposit refunded, as `void_uncommitted_game` does; otherwise the opponent takes the pot.
+  pub fn resign(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    let winner = if player == game.player1 { game.player2.clone() } else if player == game.player2 { game.player1.clone() } else { return Err(Error::NotPlayer); };
+
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+    game.turn = None;
+    game.winner = Some(winner.clone());
+    game.phase = GamePhase::Finished;
+    if is_wager_game(&game) && !(game.player1_deposited && game.player2_deposited) {
+      refund_stakes(&env, session_id, &mut game)?;
+      game.voided = true;
+      game.phase = GamePhase::Cancelled;
+    } else {
+      settle_wager(&env, session_id, &mut game)?;
+      apply_reward_multiplier(&env, session_id, &mut game);
+    }
+    Resigned { session_id, player, winner: winner.clone() }.publish(&env);
+    end_game_hub(&env, session_id, &game, winner == game.player1);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // The offer stands until the opponent accepts it or either player declines it.
+  pub fn offer_draw(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+
+    game.draw_offered_by = Some(player);
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn decline_draw(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    require_phase(&game, &LIVE_PHASES)?;
+    if game.draw_offered_by.is_none() { return Err(Error::NoDrawOffer); }
+
+    game.draw_offered_by = None;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  // Ends the game with no winner. Stakes go back to their depositors less `get_draw_fee_bps`.
+  pub fn accept_draw(env: Env, session_id: u32, player: Address) -> Result<i128, Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    let offered_by = game.draw_offered_by.clone().ok_or(Error::NoDrawOffer)?;
+    if offered_by == player { return Err(Error::NoDrawOffer); }
+
+    let fee = refund_draw(&env, session_id, &mut game)?;
+    game.voided = true;
+    game.drawn = true;
+    game.phase = GamePhase::Finished;
+    game.draw_offered_by = None;
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.pending_board = None;
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+    game.turn = None;
+    GameDrawn { session_id, offered_by, fee }.publish(&env);
+    end_game_hub_draw(&env, session_id, &game);
+
+    store_game(&env, session_id, &game);
+    Ok(fee)
+  }
+
+  pub fn get_draw_fee_bps(env: Env) -> u32 {
+    env.storage().instance().get(&ConfigKey::DrawFeeBps).unwrap_or(0)
+  }
+
+  pub fn set_draw_fee_bps(env: Env, fee_bps: u32) -> Result<(), Error> {
+    require_admin(&env);
+    if fee_bps > MAX_FEE_BPS { return Err(Error::InvalidFeeBps); }
+    env.storage().instance().set(&ConfigKey::DrawFeeBps, &fee_bps);
+    record_setting_change(&env, "draw_fee_bps", fee_bps);
+    Ok(())
+  }
+
+  // Walks away from a game the opponent has not committed to, with no deadline to wait out. The
+  // game entry and any staged board chunks are removed and only the tombstone, marked voided,
+  // remains.
+  pub fn cancel_game(env: Env, session_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    let opponent = if player == game.player1 { game.player2.clone() } else if player == game.player2 { game.player1.clone() } else { return Err(Error::NotPlayer); };
+    if has_committed(&game, &opponent) { return Err(Error::BoardAlreadyCommitted); }
+
+    refund_stakes(&env, session_id, &mut game)?;
+    if game.player1_skip_bond > 0 || game.player2_skip_bond > 0 {
+      let token_contract = escrow_token(&env, session_id)?;
+      if game.player1_skip_bond > 0 { credit_withdrawable(&env, &game.player1, &token_contract, game.player1_skip_bond); }
+      if game.player2_skip_bond > 0 { credit_withdrawable(&env, &game.player2, &token_contract, game.player2_skip_bond); }
+      game.player1_skip_bond = 0;
+      game.player2_skip_bond = 0;
+    }
+    game.voided = true;
+    game.phase = GamePhase::Cancelled;
+    update_tombstone(&env, session_id, &game);
+    env.storage().temporary().remove(&key);
+    env.storage().temporary().remove(&DataKey::BoardChunks(session_id, game.player1.clone()));
+    env.storage().temporary().remove(&DataKey::BoardChunks(session_id, game.player2.clone()));
+    GameVoided { session_id, by: player }.publish(&env);
+    // The hub has no cancelled outcome, so the session is closed there as a draw.
+    end_game_hub_draw(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn void_session(env: Env, session_id: u32, reason: String) -> Result<(), Error> {
+    require_admin(&env);
+    if reason.is_empty() || reason.len() > MAX_VOID_REASON_BYTES { return Err(Error::InvalidConfig); }
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+
+    // No winner is recorded, so the hub session is left for the hub operator to close.
+    refund_stakes(&env, session_id, &mut game)?;
+    game.voided = true;
+    game.phase = GamePhase::Cancelled;
+    game.pending_attacker = None;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.pending_board = None;
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+    game.turn = None;
+    store_game(&env, session_id, &game);
+
+    let blacklist_key = DataKey::VoidedSession(session_id);
+    env.storage().persistent().set(&blacklist_key, &reason);
+    env.storage().persistent().extend_ttl(&blacklist_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    SessionVoided { session_id, reason }.publish(&env);
+    announce_game_ended(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn get_void_reason(env: Env, session_id: u32) -> Option<String> {
+    env.storage().persistent().get(&DataKey::VoidedSession(session_id))
+  }
+
+  pub fn set_board_count(env: Env, session_id: u32, board_count: u32) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    require_capability(&game, CAP_SALVO)?;
+    if game.pinned_verifier.is_some() { return Err(Error::ZkProofRequired); }
+    if game.player1_board.is_some() || game.player2_board.is_some() || !game.player1_boards.is_empty() || !game.player2_boards.is_empty() {
+      return Err(Error::BoardAlreadyCommitted);
+    }
+    if board_count == 0 || board_count > MAX_BOARDS_PER_PLAYER { return Err(Error::InvalidBoardIndex); }
+
+    game.board_count = board_count;
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn commit_parallel_board(
+    env: Env,
+    session_id: u32,
+    player: Address,
+    board_index: u32,
+    cell_commitments: Vec<BytesN<32>>,
+    ship_cells: u32,
+    board_proof_hash: Option<BytesN<32>>,
+    board_proof_signature: Option<BytesN<64>>,
+  ) -> Result<(), Error> {
+    player.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &[GamePhase::AwaitingBoards])?;
+    if game.board_count < 2 { return Err(Error::BoardModeMismatch); }
+
+    let board_cells = game.board_size.saturating_mul(game.board_size);
+    if cell_commitments.len() != board_cells { return Err(Error::InvalidBoardCommitmentLength); }
+    if ship_cells == 0 || ship_cells > board_cells { return Err(Error::InvalidShipCount); }
+    if game.fleet.total_cells > 0 && ship_cells != game.fleet.total_cells { return Err(Error::FleetMismatch); }
+    if env.storage().instance().has(&DataKey::ZkVerifierContract) || game.pinned_verifier.is_some() {
+      return Err(Error::ZkProofRequired);
+    }
+
+    let boards = if player == game.player1 { &game.player1_boards } else if player == game.player2 { &game.player2_boards } else { return Err(Error::NotPlayer); };
+    if board_index >= game.board_count { return Err(Error::InvalidBoardIndex); }
+    if board_index < boards.len() { return Err(Error::BoardAlreadyCommitted); }
+    if board_index > boards.len() { return Err(Error::InvalidBoardIndex); }
+
+    let root = compute_merkle_root(&env, game.hash_algorithm, &cell_commitments);
+    if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
+      let proof_hash = board_proof_hash.ok_or(Error::MissingProofSignature)?;
+      let proof_signature = board_proof_signature.ok_or(Error::MissingProofSignature)?;
+      let commitment_root = compute_commitment_root(&env, game.hash_algorithm, &cell_commitments);
+      let message = build_board_proof_message(&env, game.hash_algorithm, &env.current_contract_address(), session_id, ship_cells, &commitment_root, &proof_hash);
+      env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
+    }
+
+    warn_weak_commitments(&env, session_id, &player, &cell_commitments);
+    let board = ParallelBoard { cells: cell_commitments, root, ship_cells, hits: 0, attacked: empty_bitmap(&env) };
+    if player == game.player1 { game.player1_boards.push_back(board); } else { game.player2_boards.push_back(board); }
+    BoardCommitted { session_id, player: player.clone(), board: board_index }.publish(&env);
+    if game.first_commit_ledger.is_none() { game.first_commit_ledger = Some(env.ledger().sequence()); }
+    if boards_ready(&game) && game.turn.is_none() && !awaiting_randomness(&game) {
+      begin_play(&env, &mut game);
+    }
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn attack_board(env: Env, session_id: u32, attacker: Address, board_index: u32, x: u32, y: u32) -> Result<(), Error> {
+    attacker.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+
+    require_phase(&game, &[GamePhase::InProgress])?;
+    if game.board_count < 2 { return Err(Error::BoardModeMismatch); }
+    if x >= game.board_size || y >= game.board_size { return Err(Error::InvalidCoordinate); }
+    let (x, y) = canonical_target(&game, &attacker, x, y);
+    if game.pending_attacker.is_some() { return Err(Error::PendingAttackResolution); }
+
+    let turn = game.turn.clone().ok_or(Error::BoardsNotReady)?;
+    if attacker != turn { return Err(Error::NotYourTurn); }
+
+    let target_index = y.saturating_mul(game.board_size).saturating_add(x);
+    let target_boards = if attacker == game.player1 { &game.player2_boards } else { &game.player1_boards };
+    let target = target_boards.get(board_index).ok_or(Error::InvalidBoardIndex)?;
+    if has_bit(&target.attacked, target_index) { return Err(Error::AlreadyAttacked); }
+
+    charge_shot_tax(&env, session_id, &mut game, &attacker)?;
+
+    let defender = if attacker == game.player1 { game.player2.clone() } else { game.player1.clone() };
+    game.pending_attacker = Some(attacker);
+    game.phase = GamePhase::AwaitingResolve;
+    game.pending_defender = Some(defender);
+    game.pending_x = Some(x);
+    game.pending_y = Some(y);
+    game.pending_board = Some(board_index);
+    start_turn_clock(&env, &mut game);
+    announce_attack(&env, session_id, &game);
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn resolve_parallel_attack(
+    env: Env,
+    session_id: u32,
+    defender: Address,
+    is_ship: bool,
+    salt: Bytes,
+    zk_proof_hash: BytesN<32>,
+    zk_proof_signature: Option<BytesN<64>>,
+  ) -> Result<(), Error> {
+    defender.require_auth();
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+
+    require_phase(&game, &[GamePhase::AwaitingResolve])?;
+
+    let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
+    let board_index = game.pending_board.ok_or(Error::NoPendingAttack)?;
+    let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
+    let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
+    if pending_defender != defender { return Err(Error::NotPendingDefender); }
+
+    let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
+    let boards = if defender == game.player1 { &game.player1_boards } else { &game.player2_boards };
+    let mut board = boards.get(board_index).ok_or(Error::InvalidBoardIndex)?;
+    let expected = board.cells.get(target_index).ok_or(Error::InvalidCoordinate)?;
+
+    if expected != game_cell_commitment(&env, &game, target_index, is_ship, &salt) { return Err(Error::InvalidCellReveal); }
+    if zk_proof_hash != attack_proof_hash(&env, game.hash_algorithm, is_ship, &salt, pending_x, pending_y) { return Err(Error::InvalidProofHash); }
+
+    if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
+      let proof_signature = zk_proof_signature.ok_or(Error::MissingProofSignature)?;
+      let message = build_attack_proof_message(&env, game.hash_algorithm, &env.current_contract_address(), session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
+      env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
+    }
+
+    record_transcript(&env, &mut game, board_index, pending_x, pending_y, is_ship, &salt);
+    record_reveal(&env, session_id, &game, board_index, pending_x, pending_y, is_ship);
+    set_bit(&mut board.attacked, target_index);
+    if is_ship { board.hits = board.hits.saturating_add(1); }
+    let player1_attacked = defender == game.player2;
+    if player1_attacked {
+      if is_ship { game.player1_hits = game.player1_hits.saturating_add(1); }
+      game.player2_boards.set(board_index, board);
+      game.turn = Some(game.player2.clone());
+    } else {
+      if is_ship { game.player2_hits = game.player2_hits.saturating_add(1); }
+      game.player1_boards.set(board_index, board);
+      game.turn = Some(game.player1.clone());
+    }
+
+    game.pending_attacker = None;
+    game.phase = GamePhase::InProgress;
+    game.pending_defender = None;
+    game.pending_x = None;
+    game.pending_y = None;
+    game.pending_board = None;
+    start_turn_clock(&env, &mut game);
+
+    let sunk = if player1_attacked { all_boards_sunk(&game.player2_boards) } else { all_boards_sunk(&game.player1_boards) };
+    if sunk {
+      game.winner = Some(if player1_attacked { game.player1.clone() } else { game.player2.clone() });
+      game.phase = GamePhase::Finished;
+      settle_wager(&env, session_id, &mut game)?;
+      apply_reward_multiplier(&env, session_id, &mut game);
+      end_game_hub(&env, session_id, &game, player1_attacked);
+    }
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn get_verifier(env: Env) -> Option<BytesN<32>> {
+    env.storage().instance().get(&DataKey::VerifierPubKey)
+  }
+
+  pub fn get_zk_verifier(env: Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::ZkVerifierContract)
+  }
+
+  pub fn get_zk_vk_version(env: Env) -> u32 {
+    env.storage().instance().get(&ConfigKey::ZkVkVersion).unwrap_or(0)
+  }
+
+  pub fn set_zk_vk_version(env: Env, vk_version: u32) {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::ZkVkVersion, &vk_version);
+    record_config_change(&env, ConfigBundle { zk_vk_version: Some(vk_version), ..unchanged_config() });
+  }
+
+  pub fn set_verifier(env: Env, verifier_pub_key: BytesN<32>) {
+    require_admin(&env);
+    env.storage().instance().set(&DataKey::VerifierPubKey, &verifier_pub_key);
+    record_config_change(&env, ConfigBundle { verifier_pub_key: Some(verifier_pub_key), ..unchanged_config() });
+  }
+
+  pub fn clear_verifier(env: Env) {
+    require_admin(&env);
+    env.storage().instance().remove(&DataKey::VerifierPubKey);
+    record_config_change(&env, ConfigBundle { clear_verifier: true, ..unchanged_config() });
+  }
+
+  pub fn set_zk_verifier(env: Env, verifier_contract: Address) {
+    require_admin(&env);
+    env.storage().instance().set(&DataKey::ZkVerifierContract, &verifier_contract);
+    record_config_change(&env, ConfigBundle { zk_verifier: Some(verifier_contract), ..unchanged_config() });
+  }
+
+  pub fn clear_zk_verifier(env: Env) {
+    require_admin(&env);
+    env.storage().instance().remove(&DataKey::ZkVerifierContract);
+    record_config_change(&env, ConfigBundle { clear_zk_verifier: true, ..unchanged_config() });
+  }
+
+  pub fn get_eligibility_checker(env: Env) -> Option<Address> {
+    env.storage().instance().get(&ConfigKey::EligibilityChecker)
+  }
+
+  pub fn set_eligibility_checker(env: Env, checker_contract: Address) {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::EligibilityChecker, &checker_contract);
+    record_setting_change(&env, "eligibility_checker", checker_contract);
+  }
+
+  pub fn clear_eligibility_checker(env: Env) {
+    require_admin(&env);
+    env.storage().instance().remove(&ConfigKey::EligibilityChecker);
+    record_setting_change(&env, "eligibility_checker", ());
+  }
+
+  pub fn get_randomness_beacon(env: Env) -> Option<Address> {
+    env.storage().instance().get(&ConfigKey::RandomnessBeacon)
+  }
+
+  pub fn set_randomness_beacon(env: Env, beacon: Address) {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::RandomnessBeacon, &beacon);
+    record_setting_change(&env, "randomness_beacon", beacon);
+  }
+
+  pub fn clear_randomness_beacon(env: Env) {
+    require_admin(&env);
+    env.storage().instance().remove(&ConfigKey::RandomnessBeacon);
+    record_setting_change(&env, "randomness_beacon", ());
+  }
+
+  pub fn set_randomness_source(env: Env, session_id: u32, source: RandomnessSource) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+
+    require_phase(&game, &SETUP_PHASES)?;
+    if game.first_commit_ledger.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    if game.randomness_beacon.is_some() || game.randomness.is_some() { return Err(Error::RandomnessAlreadyFulfilled); }
+
+    if source == RandomnessSource::Beacon {
+      // The beacon is pinned at selection so a later admin change cannot redirect a live request.
+      let beacon: Address = env.storage().instance().get(&ConfigKey::RandomnessBeacon).ok_or(Error::BeaconNotConfigured)?;
+      RandomnessBeaconClient::new(&env, &beacon).request_randomness(&env.current_contract_address(), &session_id);
+      game.randomness_beacon = Some(beacon);
+    }
+
+    store_game(&env, session_id, &game);
+    Ok(())
+  }
+
+  pub fn fulfill_randomness(env: Env, session_id: u32, randomness: BytesN<32>) -> Result<(), Error> {
+    let key = DataKey::Game(session_id);
+    let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, session_id))?;
+    let beacon = game.randomness_beacon.clone().ok_or(Error::BeaconNotConfigured)?;
+    beacon.require_auth();
+    if game.randomness.is_some() { return Err(Error::RandomnessAlreadyFulfilled); }
+    require_phase(&game, &SETUP_PHASES)?;
+
+    game.randomness = Some(randomness.clone());
+    if boards_ready(&game) && game.turn.is_none() { begin_play(&env, &mut game); }
+
+    store_game(&env, session_id, &game);
+    RandomnessFulfilled { session_id, beacon, randomness }.publish(&env);
+    Ok(())
+  }
+
+  pub fn set_hub(env: Env, new_hub: Address) {
+    require_admin(&env);
+    env.storage().instance().set(&DataKey::GameHubAddress, &new_hub);
+    record_config_change(&env, ConfigBundle { hub: Some(new_hub), ..unchanged_config() });
+  }
+
+  // None until the current hub has been sent a result.
+  pub fn get_hub_supports_v2(env: Env) -> Option<bool> {
+    let game_hub_addr: Address = env.storage().instance().get(&DataKey::GameHubAddress)?;
+    env.storage().instance().get(&ConfigKey::HubV2(game_hub_addr))
+  }
+
+  pub fn retry_hub_notifications(env: Env, keeper: Address, limit: u32) -> u32 {
+    let mut queue = load_hub_queue(&env);
+    let now = env.ledger().sequence();
+
+    let mut remaining = Vec::new(&env);
+    let mut attempted = 0u32;
+    let mut delivered = 0u32;
+    for mut notification in queue.iter() {
+      if attempted >= limit || notification.next_retry_ledger > now {
+        remaining.push_back(notification);
+        continue;
+      }
+      attempted += 1;
+      let game: Option<Game> = env.storage().temporary().get(&DataKey::Game(notification.session_id));
+      let game_hub = GameHubClient::new(&env, &notification.hub);
+      let ended = if notification.drawn {
+        notify_hub_draw(&game_hub, notification.session_id)
+      } else {
+        notify_hub_end(&env, &game_hub, notification.session_id, notification.player1_won, game.as_ref())
+      };
+      if ended {
+        delivered += 1;
+        continue;
+      }
+      notification.attempts = notification.attempts.saturating_add(1);
+      notification.next_retry_ledger = next_hub_retry_ledger(now, notification.attempts);
+      HubNotificationQueued {
+        session_id: notification.session_id,
+        attempts: notification.attempts,
+        next_retry_ledger: notification.next_retry_ledger,
+      }
+      .publish(&env);
+      remaining.push_back(notification);
+    }
+
+    queue = remaining;
+    store_hub_queue(&env, &queue);
+    pay_keeper_bounty(&env, &keeper, delivered);
+    delivered
+  }
+
+  pub fn get_liabilities(env: Env, token_contract: Address) -> i128 {
+    env.storage().persistent().get(&EscrowKey::Liabilities(token_contract)).unwrap_or(0)
+  }
+
+  pub fn check_solvency(env: Env, token_contract: Address) -> SolvencyReport {
+    let balance = token::Client::new(&env, &token_contract).balance(&env.current_contract_address());
+    let liabilities: i128 = env.storage().persistent().get(&EscrowKey::Liabilities(token_contract.clone())).unwrap_or(0);
+    let keeper_pool: i128 = env.storage().persistent().get(&KeeperKey::Pool(token_contract.clone())).unwrap_or(0);
+    let required = liabilities.saturating_add(keeper_pool);
+    if balance != required {
+      SolvencyAlert { token: token_contract.clone(), balance, required }.publish(&env);
+    }
+    SolvencyReport {
+      token: token_contract,
+      balance,
+      liabilities,
+      keeper_pool,
+      solvent: balance >= required,
+      balanced: balance == required,
+    }
+  }
+
+  pub fn get_rate_limit(env: Env) -> Option<RateLimit> {
+    env.storage().instance().get(&ConfigKey::RateLimit)
+  }
+
+  pub fn set_rate_limit(env: Env, limit: RateLimit) -> Result<(), Error> {
+    require_admin(&env);
+    if limit.max_actions == 0 || limit.window_ledgers == 0 { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::RateLimit, &limit);
+    record_setting_change(&env, "rate_limit", limit);
+    Ok(())
+  }
+
+  pub fn clear_rate_limit(env: Env) {
+    require_admin(&env);
+    env.storage().instance().remove(&ConfigKey::RateLimit);
+    record_setting_change(&env, "rate_limit", ());
+  }
+
+  pub fn get_hub_stats_enabled(env: Env) -> bool {
+    env.storage().instance().get(&ConfigKey::HubStats).unwrap_or(false)
+  }
+
+  pub fn set_hub_stats_enabled(env: Env, enabled: bool) {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::HubStats, &enabled);
+    record_config_change(&env, ConfigBundle { hub_stats: Some(enabled), ..unchanged_config() });
+  }
+
+  pub fn get_ttl_policy(env: Env) -> TtlPolicy {
+    ttl_policy(&env)
+  }
+
+  pub fn get_game_ttl(env: Env, finished: bool) -> u32 {
+    let policy = ttl_policy(&env);
+    if finished { policy.finished_game_ledgers } else { policy.active_game_ledgers }
+  }
+
+  pub fn get_max_game_ttl(env: Env) -> u32 {
+    max_game_ttl(&env)
+  }
+
+  pub fn set_max_game_ttl(env: Env, ledgers: u32) -> Result<(), Error> {
+    require_admin(&env);
+    if !(MIN_GAME_TTL_LEDGERS..=MAX_POLICY_TTL_LEDGERS).contains(&ledgers) { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::MaxGameTtl, &ledgers);
+    record_setting_change(&env, "max_game_ttl", ledgers);
+    Ok(())
+  }
+
+  pub fn get_session_grant_ttl(env: Env) -> u32 {
+    ttl_policy(&env).session_grant_ledgers
+  }
+
+  // Applies to entries as they are next written; existing TTLs are not shortened.
+  pub fn set_ttl_policy(env: Env, policy: TtlPolicy) -> Result<(), Error> {
+    require_admin(&env);
+    for ledgers in [policy.active_game_ledgers, policy.finished_game_ledgers, policy.session_grant_ledgers] {
+      if !(MIN_POLICY_TTL_LEDGERS..=MAX_POLICY_TTL_LEDGERS).contains(&ledgers) { return Err(Error::InvalidConfig); }
+    }
+    env.storage().instance().set(&ConfigKey::TtlPolicy, &policy);
+    record_setting_change(&env, "ttl_policy", policy);
+    Ok(())
+  }
+
+  pub fn get_keeper_config(env: Env) -> Option<KeeperConfig> {
+    env.storage().instance().get(&ConfigKey::Keeper)
+  }
+
+  pub fn set_keeper_config(env: Env, config: KeeperConfig) -> Result<(), Error> {
+    require_admin(&env);
+    if config.bounty_per_unit < 0 || config.max_per_call < 0 || config.pool_bps > BPS_DENOMINATOR as u32 {
+      return Err(Error::InvalidConfig);
+    }
+    env.storage().instance().set(&ConfigKey::Keeper, &config);
+    record_setting_change(&env, "keeper", config);
+    Ok(())
+  }
+
+  pub fn get_pass_config(env: Env) -> Option<PassConfig> {
+    env.storage().instance().get(&ConfigKey::Pass)
+  }
+
+  pub fn set_pass_config(env: Env, config: PassConfig) -> Result<(), Error> {
+    require_admin(&env);
+    if config.price <= 0 || config.duration_ledgers == 0 || config.fee_discount_bps > BPS_DENOMINATOR as u32 {
+      return Err(Error::InvalidConfig);
+    }
+    env.storage().instance().set(&ConfigKey::Pass, &config);
+    record_setting_change(&env, "pass", config);
+    Ok(())
+  }
+
+  pub fn get_abandon_policy(env: Env) -> AbandonPolicy {
+    abandon_policy(&env)
+  }
+
+  pub fn set_abandon_policy(env: Env, policy: AbandonPolicy) -> Result<(), Error> {
+    require_admin(&env);
+    if policy.base_cooldown_ledgers > policy.max_cooldown_ledgers { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::AbandonPolicy, &policy);
+    record_setting_change(&env, "abandon_policy", policy);
+    Ok(())
+  }
+
+  pub fn get_reputation(env: Env, player: Address) -> Reputation {
+    load_reputation(&env, &player)
+  }
+
+  pub fn get_reward_windows(env: Env) -> Vec<RewardWindow> {
+    env.storage().instance().get(&ConfigKey::RewardWindows).unwrap_or(Vec::new(&env))
+  }
+
+  pub fn set_reward_windows(env: Env, windows: Vec<RewardWindow>) -> Result<(), Error> {
+    require_admin(&env);
+    if windows.len() > MAX_REWARD_WINDOWS { return Err(Error::InvalidConfig); }
+    for window in windows.iter() {
+      if window.start_ledger >= window.end_ledger { return Err(Error::InvalidConfig); }
+      if !(BASE_REWARD_MULTIPLIER_BPS..=MAX_REWARD_MULTIPLIER_BPS).contains(&window.multiplier_bps) { return Err(Error::InvalidConfig); }
+    }
+    env.storage().instance().set(&ConfigKey::RewardWindows, &windows);
+    record_setting_change(&env, "reward_windows", windows);
+    Ok(())
+  }
+
+  pub fn get_reward_multiplier(env: Env) -> u32 {
+    active_reward_multiplier(&env)
+  }
+
+  pub fn get_pass_expiry(env: Env, player: Address) -> Option<u32> {
+    env.storage().persistent().get(&DataKey::PassExpiry(player))
+  }
+
+  pub fn buy_pass(env: Env, player: Address) -> Result<u32, Error> {
+    player.require_auth();
+    let config: PassConfig = env.storage().instance().get(&ConfigKey::Pass).ok_or(Error::PassNotAvailable)?;
+    let token_contract: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+    let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
+    token::Client::new(&env, &token_contract).transfer(&player, &fee_recipient, &config.price);
+
+    let key = DataKey::PassExpiry(player);
+    let now = env.ledger().sequence();
+    let current: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    let expiry = current.max(now).saturating_add(config.duration_ledgers);
+    env.storage().persistent().set(&key, &expiry);
+    let ttl = expiry.saturating_sub(now).max(PERSISTENT_STATE_TTL_LEDGERS);
+    env.storage().persistent().extend_ttl(&key, ttl, ttl);
+    Ok(expiry)
+  }
+
+  pub fn get_keeper_pool(env: Env, token_contract: Address) -> i128 {
+    env.storage().persistent().get(&KeeperKey::Pool(token_contract)).unwrap_or(0)
+  }
+
+  pub fn fund_keeper_pool(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+    from.require_auth();
+    if amount <= 0 { return Err(Error::InvalidStakeAmount); }
+    let token_contract: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+    let escrow = env.current_contract_address();
+    token::Client::new(&env, &token_contract).transfer(&from, &escrow, &amount);
+    credit_keeper_pool(&env, &token_contract, amount);
+    Ok(())
+  }
+
+  pub fn get_pending_hub_notifications(env: Env) -> Vec<HubNotification> {
+    load_hub_queue(&env)
+  }
+
+  pub fn get_upgrade_delay(env: Env) -> u32 {
+    dev_window(&env, env.storage().instance().get(&ConfigKey::UpgradeDelay).unwrap_or(DEFAULT_UPGRADE_DELAY_LEDGERS))
+  }
+
+  // Applies to proposals made afterwards; one already pending keeps its announced ledger.
+  pub fn set_upgrade_delay(env: Env, ledgers: u32) -> Result<(), Error> {
+    require_admin(&env);
+    if !(MIN_UPGRADE_DELAY_LEDGERS..=MAX_POLICY_TTL_LEDGERS).contains(&ledgers) { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::UpgradeDelay, &ledgers);
+    record_setting_change(&env, "upgrade_delay", ledgers);
+    Ok(())
+  }
+
+  pub fn get_upgrade_freeze(env: Env) -> UpgradeFreeze {
+    upgrade_freeze(&env)
+  }
+
+  pub fn set_upgrade_freeze(env: Env, freeze: UpgradeFreeze) {
+    require_admin(&env);
+    env.storage().instance().set(&ConfigKey::UpgradeFreeze, &freeze);
+    record_setting_change(&env, "upgrade_freeze", freeze);
+  }
+
+  // Sessions whose tombstone says funds are held in escrow: unsettled wagers and live bonds.
+  pub fn get_escrowed_games(env: Env) -> u32 {
+    escrowed_games(&env)
+  }
+
+  // Positions in the index of escrowed games; `get_unsettled_games` pages over the same range.
+  pub fn get_escrow_index_len(env: Env) -> u32 {
+    env.storage().persistent().get(&EscrowKey::IndexLen).unwrap_or(0)
+  }
+
+  // Scans index positions `cursor..cursor + limit` (at most 50) and returns the games there that
+  // need a keeper. Games still in play are skipped, so a page can come back short or empty.
+  // Removals swap the last entry into the freed slot, so a full pass may miss one moved game.
+  pub fn get_unsettled_games(env: Env, cursor: u32, limit: u32) -> Vec<UnsettledGame> {
+    let len = Self::get_escrow_index_len(env.clone());
+    let end = cursor.saturating_add(limit.min(MAX_CONFIG_HISTORY_PAGE)).min(len);
+    let hub_queue = load_hub_queue(&env);
+    let mut games = Vec::new(&env);
+    for position in cursor..end {
+      let Some(session_id) = env.storage().persistent().get::<EscrowKey, u32>(&EscrowKey::IndexEntry(position)) else { continue; };
+      let Some(tombstone) = env.storage().persistent().get::<DataKey, GameTombstone>(&DataKey::Tombstone(session_id)) else { continue; };
+      let action = match env.storage().temporary().get::<DataKey, Game>(&DataKey::Game(session_id)) {
+        None => KeeperAction::RecoverExpired,
+        Some(game) if is_over(&game) => KeeperAction::ReleaseBonds,
+        Some(_) => continue,
+      };
+      let hub_pending = hub_queue.iter().any(|notification| notification.session_id == session_id);
+      games.push_back(UnsettledGame { session_id, status: tombstone.status, action, hub_pending });
+    }
+    games
+  }
+
+  pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+    env.storage().instance().get(&ConfigKey::PendingUpgrade)
+  }
+
+  // Code changes are announced before they happen, so players with funds in escrow can finish
+  // or leave their games first. A new proposal replaces the pending one and restarts the delay.
+  pub fn propose_upgrade(env: Env, new_wasm_hash: BytesN<32>) -> u32 {
+    require_admin(&env);
+    let now = env.ledger().sequence();
+    let executable_ledger = now.saturating_add(Self::get_upgrade_delay(env.clone()));
+    let pending = PendingUpgrade { wasm_hash: new_wasm_hash.clone(), proposed_ledger: now, executable_ledger };
+    env.storage().instance().set(&ConfigKey::PendingUpgrade, &pending);
+    UpgradeProposed { wasm_hash: new_wasm_hash, executable_ledger }.publish(&env);
+    executable_ledger
+  }
+
+  pub fn execute_upgrade(env: Env) -> Result<(), Error> {
+    require_admin(&env);
+    let pending: PendingUpgrade = env.storage().instance().get(&ConfigKey::PendingUpgrade).ok_or(Error::NoPendingUpgrade)?;
+    if env.ledger().sequence() < pending.executable_ledger { return Err(Error::DeadlineNotReached); }
+    let freeze = upgrade_freeze(&env);
+    if freeze.enabled && freeze.max_escrowed_games.is_some_and(|max| escrowed_games(&env) > max) { return Err(Error::UpgradeDeferred); }
+    env.storage().instance().remove(&ConfigKey::PendingUpgrade);
+    UpgradeExecuted { wasm_hash: pending.wasm_hash.clone() }.publish(&env);
+    env.deployer().update_current_contract_wasm(pending.wasm_hash);
+    Ok(())
+  }
+}
+
+fn require_admin(env: &Env) {
+  let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
+  admin.require_auth();
+}
+
+fn upgrade_freeze(env: &Env) -> UpgradeFreeze {
+  env.storage().instance().get(&ConfigKey::UpgradeFreeze).unwrap_or(UpgradeFreeze { enabled: false, max_escrowed_games: None })
+}
+
+fn require_creation_open(env: &Env) -> Result<(), Error> {
+  if upgrade_freeze(env).enabled && env.storage().instance().has(&ConfigKey::PendingUpgrade) { return Err(Error::CreationFrozen); }
+  Ok(())
+}
+
+fn escrowed_games(env: &Env) -> u32 {
+  env.storage().instance().get(&ConfigKey::EscrowedGames).unwrap_or(0)
+}
+
+fn count_escrowed_game(env: &Env, session_id: u32, escrowed: bool) {
+  let count = if escrowed { escrowed_games(env).saturating_add(1) } else { escrowed_games(env).saturating_sub(1) };
+  env.storage().instance().set(&ConfigKey::EscrowedGames, &count);
+  if escrowed { index_escrowed_game(env, session_id); } else { unindex_escrowed_game(env, session_id); }
+}
+
+fn index_escrowed_game(env: &Env, session_id: u32) {
+  let persistent = env.storage().persistent();
+  if persistent.has(&EscrowKey::IndexSlot(session_id)) { return; }
+  let len: u32 = persistent.get(&EscrowKey::IndexLen).unwrap_or(0);
+  store_index_entry(env, len, session_id);
+  persistent.set(&EscrowKey::IndexLen, &len.saturating_add(1));
+  persistent.extend_ttl(&EscrowKey::IndexLen, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+// Swap-remove: the last entry moves into the freed position. Games escrowed before the index
+// existed have no slot and are left alone.
+fn unindex_escrowed_game(env: &Env, session_id: u32) {
+  let persistent = env.storage().persistent();
+  let Some(position) = persistent.get::<EscrowKey, u32>(&EscrowKey::IndexSlot(session_id)) else { return; };
+  let last = persistent.get::<EscrowKey, u32>(&EscrowKey::IndexLen).unwrap_or(1).saturating_sub(1);
+  if position != last {
+    if let Some(moved) = persistent.get::<EscrowKey, u32>(&EscrowKey::IndexEntry(last)) { store_index_entry(env, position, moved); }
+  }
+  persistent.remove(&EscrowKey::IndexEntry(last));
+  persistent.remove(&EscrowKey::IndexSlot(session_id));
+  persistent.set(&EscrowKey::IndexLen, &last);
+  persistent.extend_ttl(&EscrowKey::IndexLen, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn store_index_entry(env: &Env, position: u32, session_id: u32) {
+  let persistent = env.storage().persistent();
+  persistent.set(&EscrowKey::IndexEntry(position), &session_id);
+  persistent.extend_ttl(&EscrowKey::IndexEntry(position), PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+  persistent.set(&EscrowKey::IndexSlot(session_id), &position);
+  persistent.extend_ttl(&EscrowKey::IndexSlot(session_id), PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn create_game(
+  env: &Env,
+  session_id: u32,
+  player1: Address,
+  player2: Address,
+  player1_points: i128,
+  player2_points: i128,
+  player1_stake: i128,
+  player2_stake: i128,
+  capabilities: u32,
+  ttl_ledgers: u32,
+) -> Result<(), Error> {
+  if player1_points < 0 || player2_points < 0 || player1_stake < 0 || player2_stake < 0 { return Err(Error::InvalidStakeAmount); }
+  if env.storage().persistent().has(&DataKey::VoidedSession(session_id)) { return Err(Error::SessionBlacklisted); }
+  // An expired session keeps its escrow record until `recover_expired`, so its id stays taken.
+  if env.storage().temporary().has(&DataKey::Game(session_id)) || env.storage().persistent().has(&DataKey::Tombstone(session_id)) {
+    return Err(Error::SessionInUse);
+  }
+  require_creation_open(env)?;
+  consume_rate_limit(env, &player1)?;
+  consume_rate_limit(env, &player2)?;
+
+  let is_wager = player1_stake > 0 || player2_stake > 0;
+  require_eligible(env, &player1)?;
+  require_eligible(env, &player2)?;
+  if is_wager && (in_abandon_cooldown(env, &player1) || in_abandon_cooldown(env, &player2)) { return Err(Error::AbandonCooldown); }
+
+  let game_hub_addr: Address = env.storage().instance().get(&DataKey::GameHubAddress).expect("GameHub address not set");
+  let game_hub = GameHubClient::new(env, &game_hub_addr);
+  game_hub.start_game(&env.current_contract_address(), &session_id, &player1, &player2, &player1_points, &player2_points);
+
+  let mut game = new_game(env, player1, player2, player1_points, player2_points, player1_stake, player2_stake);
+  game.capabilities = capabilities;
+  game.hub = Some(game_hub_addr);
+  game.ttl_ledgers = ttl_ledgers;
+  store_game(env, session_id, &game);
+  GameStarted { session_id, player1: game.player1, player2: game.player2, player1_stake, player2_stake }.publish(env);
+  Ok(())
+}
+
+fn new_game(
+  env: &Env,
+  player1: Address,
+  player2: Address,
+  player1_points: i128,
+  player2_points: i128,
+  player1_stake: i128,
+  player2_stake: i128,
+) -> Game {
+  let is_wager = player1_stake > 0 || player2_stake > 0;
+  Game {
+    player1, player2, player1_points, player2_points, player1_stake, player2_stake,
+    board_size: DEFAULT_BOARD_SIZE,
+    player1_board: None, player2_board: None,
+    player1_ship_cells: None, player2_ship_cells: None,
+    player1_hits: 0, player2_hits: 0,
+    player1_attacks: empty_bitmap(env), player2_attacks: empty_bitmap(env),
+    player1_hit_attacks: empty_bitmap(env), player2_hit_attacks: empty_bitmap(env),
+    turn: None, pending_attacker: None, pending_defender: None, pending_x: None, pending_y: None,
+    winner: None,
+    player1_deposited: !is_wager || player1_stake == 0,
+    player2_deposited: !is_wager || player2_stake == 0,
+    payout_processed: !is_wager,
+    player1_board_verifier: None, player1_board_vk_version: None,
+    player2_board_verifier: None, player2_board_vk_version: None,
+    player1_board_root: None, player2_board_root: None,
+    shot_tax: 0, player1_shot_tax_paid: 0, player2_shot_tax_paid: 0,
+    board_count: 1, player1_boards: Vec::new(env), player2_boards: Vec::new(env), pending_board: None,
+    queued_attacker: None, queued_commitment: None,
+    organizer: None, organizer_bps: 0,
+    first_commit_ledger: None, voided: false, reward_multiplier_bps: 0,
+    randomness_beacon: None, randomness: None,
+    board_transforms: false, player1_transform: 0, player2_transform: 0,
+    player1_backers: Vec::new(env), player2_backers: Vec::new(env), player1_backed: 0, player2_backed: 0,
+    hash_algorithm: HashAlgorithm::Keccak256,
+    exhibition: false,
+    transcript_hash: None,
+    start_ledger: env.ledger().sequence(),
+    player1_zk_proofs: 0,
+    player2_zk_proofs: 0,
+    aggregated_proofs: false,
+    player1_buffer: Vec::new(env),
+    player2_buffer: Vec::new(env),
+    player1_proven_moves: 0,
+    player2_proven_moves: 0,
+    capabilities: CAP_ALL,
+    turn_started_ledger: 0,
+    skip_turn_ledgers: 0,
+    skip_penalty: 0,
+    player1_skip_bond: 0,
+    player2_skip_bond: 0,
+    hub: None,
+    ttl_ledgers: 0,
+    commitment_version: COMMITMENT_V2,
+    time_control: None,
+    max_duration_ledgers: 0,
+    pinned_verifier: None,
+    pinned_vk_version: None,
+    move_timeout_ledgers: 0,
+    move_deadline_ledger: 0,
+    draw_offered_by: None,
+    drawn: false,
+    fleet: FleetConfig { ship_lengths: Vec::new(env), total_cells: 0 },
+    operator: None,
+    phase: if is_wager { GamePhase::AwaitingDeposits } else { GamePhase::AwaitingBoards },
+  }
+}
+
+fn unchanged_config() -> ConfigBundle {
+  ConfigBundle {
+    hub: None,
+    bet_token: None,
+    fee_bps: None,
+    fee_recipient: None,
+    verifier_pub_key: None,
+    zk_verifier: None,
+    zk_vk_version: None,
+    dev_mode: None,
+    hub_stats: None,
+    clear_bet_token: false,
+    clear_verifier: false,
+    clear_zk_verifier: false,
+  }
+}
+
+// Append-only: entries are never rewritten, so a game's settlement ledger can be matched to
+// the policy in force at the time.
+fn record_config_change(env: &Env, change: ConfigBundle) {
+  append_config_record(env, ConfigRecord { ledger: env.ledger().sequence(), change, setting: None, value: Bytes::new(env) });
+}
+
+fn record_setting_change<V: IntoVal<Env, Val>>(env: &Env, setting: &str, value: V) {
+  let value = value.into_val(env).to_xdr(env);
+  append_config_record(env, ConfigRecord { ledger: env.ledger().sequence(), change: unchanged_config(), setting: Some(Symbol::new(env, setting)), value });
+}
+
+fn append_config_record(env: &Env, record: ConfigRecord) {
+  let storage = env.storage().persistent();
+  let index: u32 = storage.get(&ConfigHistoryKey::Len).unwrap_or(0);
+  let entry_key = ConfigHistoryKey::Entry(index);
+  storage.set(&entry_key, &record);
+  storage.extend_ttl(&entry_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+  storage.set(&ConfigHistoryKey::Len, &index.saturating_add(1));
+  storage.extend_ttl(&ConfigHistoryKey::Len, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn apply_config(env: &Env, config: &ConfigBundle) -> Result<(), Error> {
+  if let Some(fee_bps) = config.fee_bps {
+    if fee_bps > MAX_FEE_BPS { return Err(Error::InvalidFeeBps); }
+  }
+  if (config.clear_bet_token && config.bet_token.is_some())
+    || (config.clear_verifier && config.verifier_pub_key.is_some())
+    || (config.clear_zk_verifier && config.zk_verifier.is_some())
+  {
+    return Err(Error::InvalidConfig);
+  }
+  if let Some(timeout_ledgers) = config.dev_mode {
+    let public: BytesN<32> = env.crypto().sha256(&Bytes::from_slice(env, PUBLIC_NETWORK_PASSPHRASE)).into();
+    if timeout_ledgers > 0 && env.ledger().network_id() == public { return Err(Error::InvalidConfig); }
+  }
+
+  let storage = env.storage().instance();
+  if let Some(hub) = &config.hub { storage.set(&DataKey::GameHubAddress, hub); }
+  if let Some(bet_token) = &config.bet_token { storage.set(&ConfigKey::BetToken, bet_token); }
+  if let Some(fee_bps) = &config.fee_bps { storage.set(&ConfigKey::FeeBps, fee_bps); }
+  if let Some(fee_recipient) = &config.fee_recipient { storage.set(&ConfigKey::FeeRecipient, fee_recipient); }
+  if let Some(verifier_pub_key) = &config.verifier_pub_key { storage.set(&DataKey::VerifierPubKey, verifier_pub_key); }
+  if let Some(zk_verifier) = &config.zk_verifier { storage.set(&DataKey::ZkVerifierContract, zk_verifier); }
+  if let Some(zk_vk_version) = &config.zk_vk_version { storage.set(&ConfigKey::ZkVkVersion, zk_vk_version); }
+  if let Some(dev_mode) = &config.dev_mode { storage.set(&ConfigKey::DevMode, dev_mode); }
+  if let Some(hub_stats) = &config.hub_stats { storage.set(&ConfigKey::HubStats, hub_stats); }
+  if config.clear_bet_token { storage.remove(&ConfigKey::BetToken); }
+  if config.clear_verifier { storage.remove(&DataKey::VerifierPubKey); }
+  if config.clear_zk_verifier { storage.remove(&DataKey::ZkVerifierContract); }
+  Ok(())
+}
+
+fn end_game_hub(env: &Env, session_id: u32, game: &Game, player1_won: bool) {
+  announce_game_ended(env, session_id, game);
+  if game.exhibition { return; }
+  let game_hub_addr = starting_hub(env, session_id, game);
+  let game_hub = GameHubClient::new(env, &game_hub_addr);
+  let ended = notify_hub_end(env, &game_hub, session_id, player1_won, Some(game));
+  report_hub_stats(env, &game_hub, session_id, game);
+  if !ended { queue_hub_notification(env, session_id, game_hub_addr, player1_won, false); }
+}
+
+// The session id is only known to the hub that started the game, so a rotated hub is not told.
+fn starting_hub(env: &Env, session_id: u32, game: &Game) -> Address {
+  let current_hub: Address = env.storage().instance().get(&DataKey::GameHubAddress).expect("GameHub address not set");
+  let game_hub_addr = game.hub.clone().unwrap_or(current_hub.clone());
+  if game_hub_addr != current_hub {
+    HubRotated { session_id, original_hub: game_hub_addr.clone(), current_hub }.publish(env);
+  }
+  game_hub_addr
+}
+
+fn queue_hub_notification(env: &Env, session_id: u32, hub: Address, player1_won: bool, drawn: bool) {
+  let now = env.ledger().sequence();
+  let notification = HubNotification {
+    session_id,
+    hub,
+    player1_won,
+    drawn,
+    attempts: 1,
+    next_retry_ledger: next_hub_retry_ledger(now, 1),
+  };
+  HubNotificationQueued {
+    session_id,
+    attempts: notification.attempts,
+    next_retry_ledger: notification.next_retry_ledger,
+  }
+  .publish(env);
+
+  let mut queue = load_hub_queue(env);
+  queue.push_back(notification);
+  store_hub_queue(env, &queue);
+}
+
+// Whether the hub takes `end_game_v2` is probed on first use and cached per hub address, so a
+// rotated hub is probed again. Without the game (expired before a retry) only `end_game` is sent.
+fn notify_hub_end(env: &Env, game_hub: &GameHubClient, session_id: u32, player1_won: bool, game: Option<&Game>) -> bool {
+  let key = ConfigKey::HubV2(game_hub.address.clone());
+  let supports_v2: Option<bool> = env.storage().instance().get(&key);
+  if let (Some(game), true) = (game, supports_v2 != Some(false)) {
+    let result = GameResult { player1_won, player1_hits: game.player1_hits, player2_hits: game.player2_hits, moves: recorded_moves(game) };
+    if matches!(game_hub.try_end_game_v2(&session_id, &result), Ok(Ok(()))) {
+      if supports_v2.is_none() { env.storage().instance().set(&key, &true); }
+      return true;
+    }
+  }
+  let ended = matches!(game_hub.try_end_game(&session_id, &player1_won), Ok(Ok(())));
+  // Only a hub that accepted the legacy call right after refusing v2 is marked legacy.
+  if ended && game.is_some() && supports_v2.is_none() { env.storage().instance().set(&key, &false); }
+  ended
+}
+
+fn end_game_hub_draw(env: &Env, session_id: u32, game: &Game) {
+  announce_game_ended(env, session_id, game);
+  if game.exhibition { return; }
+  let game_hub_addr = starting_hub(env, session_id, game);
+  let game_hub = GameHubClient::new(env, &game_hub_addr);
+  let ended = notify_hub_draw(&game_hub, session_id);
+  report_hub_stats(env, &game_hub, session_id, game);
+  if !ended { queue_hub_notification(env, session_id, game_hub_addr, false, true); }
+}
+
+fn notify_hub_draw(game_hub: &GameHubClient, session_id: u32) -> bool {
+  matches!(game_hub.try_end_game_draw(&session_id), Ok(Ok(())))
+}
+
+fn announce_game_ended(env: &Env, session_id: u32, game: &Game) {
+  GameEnded { session_id, winner: game.winner.clone(), drawn: game.drawn, voided: game.voided }.publish(env);
+}
+
+// A hub that rejects or lacks `report_stats` must never block settlement, so failures are dropped.
+fn report_hub_stats(env: &Env, game_hub: &GameHubClient, session_id: u32, game: &Game) {
+  if !env.storage().instance().get(&ConfigKey::HubStats).unwrap_or(false) { return; }
+  let duration = env.ledger().sequence().saturating_sub(game.start_ledger);
+  let _ = game_hub.try_report_stats(&session_id, &game.player1_hits, &game.player2_hits, &recorded_moves(game), &duration);
+}
+
+fn abandon_policy(env: &Env) -> AbandonPolicy {
+  env.storage().instance().get(&ConfigKey::AbandonPolicy).unwrap_or(AbandonPolicy {
+    free_abandons: DEFAULT_FREE_ABANDONS,
+    base_cooldown_ledgers: DEFAULT_ABANDON_COOLDOWN_LEDGERS,
+    max_cooldown_ledgers: DEFAULT_MAX_ABANDON_COOLDOWN_LEDGERS,
+  })
+}
+
+fn load_reputation(env: &Env, player: &Address) -> Reputation {
+  env.storage().persistent().get(&DataKey::Reputation(player.clone())).unwrap_or(Reputation {
+    abandons: 0,
+    last_abandon_ledger: 0,
+    cooldown_until: 0,
+    flagged: false,
+  })
+}
+
+fn in_abandon_cooldown(env: &Env, player: &Address) -> bool {
+  env.ledger().sequence() < load_reputation(env, player).cooldown_until
+}
+
+// Each abandon past the free allowance doubles the cooldown, up to the policy maximum.
+fn record_abandon(env: &Env, player: &Address) {
+  let policy = abandon_policy(env);
+  let mut reputation = load_reputation(env, player);
+  let now = env.ledger().sequence();
+  reputation.abandons = reputation.abandons.saturating_add(1);
+  reputation.last_abandon_ledger = now;
+  if reputation.abandons > policy.free_abandons {
+    let shift = (reputation.abandons - policy.free_abandons - 1).min(16);
+    let cooldown = dev_window(env, policy.base_cooldown_ledgers.saturating_mul(1 << shift).min(policy.max_cooldown_ledgers));
+    reputation.cooldown_until = reputation.cooldown_until.max(now.saturating_add(cooldown));
+    reputation.flagged = true;
+  }
+
+  let key = DataKey::Reputation(player.clone());
+  env.storage().persistent().set(&key, &reputation);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+// Fails closed: a checker that errors or traps is treated as declining the player.
+fn require_eligible(env: &Env, player: &Address) -> Result<(), Error> {
+  let Some(checker_contract) = env.storage().instance().get::<_, Address>(&ConfigKey::EligibilityChecker) else { return Ok(()); };
+  match EligibilityCheckerClient::new(env, &checker_contract).try_is_eligible(player) {
+    Ok(Ok(true)) => Ok(()),
+    _ => Err(Error::PlayerNotEligible),
+  }
+}
+
+// A token or collection that fails the call counts as the gate not being met.
+fn require_token_gates(env: &Env, gates: &Vec<TokenGate>, player: &Address) -> Result<(), Error> {
+  for gate in gates.iter() {
+    let met = match gate {
+      TokenGate::MinBalance(token_contract, min_balance) => {
+        matches!(token::Client::new(env, &token_contract).try_balance(player), Ok(Ok(balance)) if balance >= min_balance)
+      }
+      TokenGate::HoldsItem(collection, token_id) => {
+        matches!(NftCollectionClient::new(env, &collection).try_owner_of(&token_id), Ok(Ok(owner)) if owner == *player)
+      }
+    };
+    if !met { return Err(Error::TokenGateNotMet); }
+  }
+  Ok(())
+}
+
+fn validate_token_gates(gates: &Vec<TokenGate>) -> Result<(), Error> {
+  if gates.len() > MAX_TOKEN_GATES { return Err(Error::InvalidConfig); }
+  if gates.iter().any(|gate| matches!(gate, TokenGate::MinBalance(_, min_balance) if min_balance <= 0)) { return Err(Error::InvalidConfig); }
+  Ok(())
+}
+
+fn zk_verdict(
+  result: Result<Result<bool, soroban_sdk::ConversionError>, Result<soroban_sdk::Error, soroban_sdk::InvokeError>>,
+) -> Result<bool, Error> {
+  match result {
+    Ok(Ok(verdict)) => Ok(verdict),
+    // Contract errors and failed signature checks are the verifier rejecting the proof.
+    Err(Ok(err)) if err.is_type(ScErrorType::Contract) || err.is_type(ScErrorType::Crypto) => Err(Error::ZkVerificationFailed),
+    _ => Err(Error::VerifierUnavailable),
+  }
+}
+
+fn adjust_liabilities(env: &Env, token_contract: &Address, delta: i128) {
+  let key = EscrowKey::Liabilities(token_contract.clone());
+  let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+  env.storage().persistent().set(&key, &current.saturating_add(delta));
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+// Moves funds the contract already holds (and counts as liabilities) to an owner's balance.
+fn withdraw_balance(env: &Env, owner: &Address, token_contract: &Address) -> i128 {
+  let key = EscrowKey::Withdrawable(owner.clone(), token_contract.clone());
+  let amount: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+  if amount <= 0 { return 0; }
+
+  env.storage().persistent().remove(&key);
+  token::Client::new(env, token_contract).transfer(&env.current_contract_address(), owner, &amount);
+  adjust_liabilities(env, token_contract, amount.saturating_neg());
+  amount
+}
+
+fn credit_withdrawable(env: &Env, owner: &Address, token_contract: &Address, amount: i128) {
+  let key = EscrowKey::Withdrawable(owner.clone(), token_contract.clone());
+  let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+  env.storage().persistent().set(&key, &balance.saturating_add(amount));
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+
+  // Balances are keyed by token, so each owner keeps the list of tokens they were credited in.
+  let tokens_key = EscrowKey::OwnerTokens(owner.clone());
+  let mut tokens: Vec<Address> = env.storage().persistent().get(&tokens_key).unwrap_or(Vec::new(env));
+  if !tokens.contains(token_contract) {
+    tokens.push_back(token_contract.clone());
+    env.storage().persistent().set(&tokens_key, &tokens);
+  }
+  env.storage().persistent().extend_ttl(&tokens_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn store_tip_jar(env: &Env, session_id: u32, player: &Address, jar: &TipJar) {
+  let key = EscrowKey::Tips(session_id, player.clone());
+  env.storage().persistent().set(&key, jar);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn credit_keeper_pool(env: &Env, token_contract: &Address, amount: i128) {
+  let key = KeeperKey::Pool(token_contract.clone());
+  let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+  env.storage().persistent().set(&key, &balance.saturating_add(amount));
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn pay_keeper_bounty(env: &Env, keeper: &Address, units: u32) {
+  if units == 0 { return; }
+  let config: KeeperConfig = match env.storage().instance().get(&ConfigKey::Keeper) {
+    Some(config) => config,
+    None => return,
+  };
+  let token_contract: Address = match env.storage().instance().get(&ConfigKey::BetToken) {
+    Some(token_contract) => token_contract,
+    None => return,
+  };
+
+  let now = env.ledger().sequence();
+  let last_paid_key = KeeperKey::LastPaid(keeper.clone());
+  if let Some(last_paid) = env.storage().temporary().get::<KeeperKey, u32>(&last_paid_key) {
+    if now < last_paid.saturating_add(config.min_interval_ledgers) { return; }
+  }
+
+  let pool_key = KeeperKey::Pool(token_contract.clone());
+  let pool: i128 = env.storage().persistent().get(&pool_key).unwrap_or(0);
+  let amount = config.bounty_per_unit.saturating_mul(units as i128).min(config.max_per_call).min(pool);
+  if amount <= 0 { return; }
+
+  env.storage().persistent().set(&pool_key, &pool.saturating_sub(amount));
+  env.storage().temporary().set(&last_paid_key, &now);
+  env.storage().temporary().extend_ttl(&last_paid_key, config.min_interval_ledgers.max(1), config.min_interval_ledgers.max(1));
+  let escrow = env.current_contract_address();
+  token::Client::new(env, &token_contract).transfer(&escrow, keeper, &amount);
+  KeeperPaid { keeper: keeper.clone(), token: token_contract, amount }.publish(env);
+}
+
+fn next_hub_retry_ledger(now: u32, attempts: u32) -> u32 {
+  let shift = attempts.saturating_sub(1).min(HUB_RETRY_MAX_BACKOFF_SHIFT);
+  now.saturating_add(HUB_RETRY_BASE_LEDGERS << shift)
+}
+
+fn load_hub_queue(env: &Env) -> Vec<HubNotification> {
+  env.storage().persistent().get(&DataKey::HubRetryQueue).unwrap_or(Vec::new(env))
+}
+
+fn store_hub_queue(env: &Env, queue: &Vec<HubNotification>) {
+  let key = DataKey::HubRetryQueue;
+  if queue.is_empty() {
+    env.storage().persistent().remove(&key);
+    return;
+  }
+  env.storage().persistent().set(&key, queue);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn is_wager_game(game: &Game) -> bool {
+  game.player1_stake > 0 || game.player2_stake > 0
+}
+
+fn settle_wager(env: &Env, session_id: u32, game: &mut Game) -> Result<(), Error> {
+  if game.payout_processed { return Ok(()); }
+  if !is_wager_game(game) {
+    game.payout_processed = true;
+    return Ok(());
+  }
+  if !game.player1_deposited || !game.player2_deposited { return Err(Error::StakesNotFunded); }
+
+  let winner = game.winner.clone().ok_or(Error::GameAlreadyEnded)?;
+  let token_contract = escrow_token(env, session_id)?;
+  let fee_bps: u32 = env.storage().instance().get(&ConfigKey::FeeBps).unwrap_or(DEFAULT_FEE_BPS);
+  let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
 
-    let token_contract: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
-    let token_client = token::Client::new(&env, &token_contract);
-    let escrow = env.current_contract_address();
-    token_client.transfer(&player, &escrow, &amount);
+  let total_pot = game.player1_stake
+    .saturating_add(game.player2_stake)
+    .saturating_add(game.player1_shot_tax_paid)
+    .saturating_add(game.player2_shot_tax_paid);
+  let player1_stake = game.player1_stake.saturating_add(game.player1_shot_tax_paid);
+  let player2_stake = game.player2_stake.saturating_add(game.player2_shot_tax_paid);
+  let fee_amount = player_fee(env, &game.player1, player1_stake, fee_bps).saturating_add(player_fee(env, &game.player2, player2_stake, fee_bps));
+  let winner_amount = total_pot.saturating_sub(fee_amount);
+  let organizer_share = fee_amount.saturating_mul(game.organizer_bps as i128) / BPS_DENOMINATOR;
+  let protocol_fee = fee_amount.saturating_sub(organizer_share);
+  // An operator is paid out of the protocol's part of the fee, never the organizer's.
+  let mut operator = game.operator.as_ref().and_then(|operator_id| operator::load_operator(env, operator_id).ok());
+  let operator_share = operator.as_ref().map_or(0, |operator| protocol_fee.saturating_mul(i128::from(operator.share_bps)) / BPS_DENOMINATOR);
+  let protocol_fee = protocol_fee.saturating_sub(operator_share);
+  let keeper_share = match env.storage().instance().get::<ConfigKey, KeeperConfig>(&ConfigKey::Keeper) {
+    Some(keeper) => protocol_fee.saturating_mul(keeper.pool_bps as i128) / BPS_DENOMINATOR,
+    None => 0,
+  };
+  let recipient_amount = protocol_fee.saturating_sub(keeper_share);
+  let (player1_rebate, player2_rebate) = zk_rebates(env, game, recipient_amount);
+  let recipient_amount = recipient_amount.saturating_sub(player1_rebate).saturating_sub(player2_rebate);
 
-    if player == game.player1 {
-      game.player1_deposited = true;
+  let token_client = token::Client::new(env, &token_contract);
+  let escrow = env.current_contract_address();
+
+  // With a rematch on offer, whatever goes to the players stays here as withdrawable.
+  let hold = rematch::holds_winnings(env, session_id);
+  let mut held: i128 = 0;
+  let mut pay_player = |to: &Address, amount: i128| {
+    if hold && (*to == game.player1 || *to == game.player2) {
+      credit_withdrawable(env, to, &token_contract, amount);
+      held = held.saturating_add(amount);
     } else {
-      game.player2_deposited = true;
+      token_client.transfer(&escrow, to, &amount);
     }
-
-    env.storage().temporary().set(&key, &game);
-    extend_game_ttl(&env, &key);
-    Ok(())
+    Payout { session_id, to: to.clone(), token: token_contract.clone(), amount }.publish(env);
+  };
+
+  // The winning side's backers share its payout in proportion to what they put into that side.
+  let (winning_backers, winning_stake) = if winner == game.player1 { (&game.player1_backers, player1_stake) } else { (&game.player2_backers, player2_stake) };
+  let mut player_amount = winner_amount;
+  for backing in winning_backers.iter() {
+    let share = winner_amount.saturating_mul(backing.amount) / winning_stake;
+    if share > 0 { pay_player(&backing.backer, share); }
+    player_amount = player_amount.saturating_sub(share);
   }
-
-  pub fn get_verifier(env: Env) -> Option<BytesN<32>> {
-    env.storage().instance().get(&DataKey::VerifierPubKey)
+  if player_amount > 0 {
+    pay_player(&winner, player_amount);
+  }
+  if player1_rebate > 0 {
+    pay_player(&game.player1, player1_rebate);
+  }
+  if player2_rebate > 0 {
+    pay_player(&game.player2, player2_rebate);
+  }
+  if recipient_amount > 0 {
+    token_client.transfer(&escrow, &fee_recipient, &recipient_amount);
+  }
+  if organizer_share > 0 {
+    if let Some(organizer) = &game.organizer {
+      token_client.transfer(&escrow, organizer, &organizer_share);
+    }
+  }
+  if operator_share > 0 {
+    if let (Some(operator), Some(operator_id)) = (operator.as_mut(), &game.operator) {
+      token_client.transfer(&escrow, &operator.owner, &operator_share);
+      operator.fees_earned = operator.fees_earned.saturating_add(operator_share);
+      operator::store_operator(env, operator_id, operator);
+    }
   }
+  if keeper_share > 0 {
+    credit_keeper_pool(env, &token_contract, keeper_share);
+  }
+  adjust_liabilities(env, &token_contract, total_pot.saturating_sub(held).saturating_neg());
+
+  Settlement {
+    session_id,
+    winner,
+    token: token_contract,
+    pot: total_pot,
+    fee_bps,
+    fee_amount,
+    organizer_share,
+    keeper_share,
+    rebates: player1_rebate.saturating_add(player2_rebate),
+    fee_recipient_amount: recipient_amount,
+    winner_amount,
+    operator: game.operator.clone(),
+    operator_share,
+  }
+  .publish(env);
+  game.payout_processed = true;
+  Ok(())
+}
 
-  pub fn get_zk_verifier(env: Env) -> Option<Address> {
-    env.storage().instance().get(&DataKey::ZkVerifierContract)
+fn count_zk_proof(game: &mut Game, defender: &Address) {
+  if *defender == game.player1 {
+    game.player1_zk_proofs = game.player1_zk_proofs.saturating_add(1);
+  } else {
+    game.player2_zk_proofs = game.player2_zk_proofs.saturating_add(1);
   }
+}
 
-  pub fn set_verifier(env: Env, verifier_pub_key: BytesN<32>) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.storage().instance().set(&DataKey::VerifierPubKey, &verifier_pub_key);
+// Proving-cost rebates are paid only out of the fee recipient's share. When that share cannot
+// cover every proof, it is split in proportion to each player's proof count.
+fn zk_rebates(env: &Env, game: &Game, available: i128) -> (i128, i128) {
+  let per_proof: i128 = env.storage().instance().get(&ConfigKey::ZkRebate).unwrap_or(0);
+  let proofs = game.player1_zk_proofs.saturating_add(game.player2_zk_proofs) as i128;
+  if per_proof <= 0 || proofs == 0 || available <= 0 { return (0, 0); }
+  let owed = per_proof.saturating_mul(proofs);
+  if owed <= available {
+    return (per_proof.saturating_mul(game.player1_zk_proofs as i128), per_proof.saturating_mul(game.player2_zk_proofs as i128));
   }
+  let player1_rebate = available.saturating_mul(game.player1_zk_proofs as i128) / proofs;
+  let player2_rebate = available.saturating_mul(game.player2_zk_proofs as i128) / proofs;
+  (player1_rebate, player2_rebate)
+}
 
-  pub fn clear_verifier(env: Env) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.storage().instance().remove(&DataKey::VerifierPubKey);
+fn recorded_moves(game: &Game) -> u32 {
+  let mut moves = bit_count(&game.player1_attacks).saturating_add(bit_count(&game.player2_attacks));
+  for board in game.player1_boards.iter().chain(game.player2_boards.iter()) {
+    moves = moves.saturating_add(bit_count(&board.attacked));
   }
+  moves
+}
 
-  pub fn set_zk_verifier(env: Env, verifier_contract: Address) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.storage().instance().set(&DataKey::ZkVerifierContract, &verifier_contract);
+fn all_boards_sunk(boards: &Vec<ParallelBoard>) -> bool {
+  for board in boards.iter() {
+    if board.hits < board.ship_cells { return false; }
   }
+  true
+}
 
-  pub fn clear_zk_verifier(env: Env) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.storage().instance().remove(&DataKey::ZkVerifierContract);
+fn player_fee(env: &Env, player: &Address, stake: i128, fee_bps: u32) -> i128 {
+  let fee = stake.saturating_mul(fee_bps as i128) / BPS_DENOMINATOR;
+  let Some(config) = env.storage().instance().get::<ConfigKey, PassConfig>(&ConfigKey::Pass) else { return fee; };
+  let expiry: u32 = env.storage().persistent().get(&DataKey::PassExpiry(player.clone())).unwrap_or(0);
+  if expiry <= env.ledger().sequence() { return fee; }
+  fee.saturating_sub(fee.saturating_mul(config.fee_discount_bps as i128) / BPS_DENOMINATOR)
+}
+
+fn charge_shot_tax(env: &Env, session_id: u32, game: &mut Game, attacker: &Address) -> Result<(), Error> {
+  if game.shot_tax <= 0 { return Ok(()); }
+  let token_contract = escrow_token(env, session_id)?;
+  let escrow = env.current_contract_address();
+  token::Client::new(env, &token_contract).transfer(attacker, &escrow, &game.shot_tax);
+  adjust_liabilities(env, &token_contract, game.shot_tax);
+
+  if *attacker == game.player1 {
+    game.player1_shot_tax_paid = game.player1_shot_tax_paid.saturating_add(game.shot_tax);
+  } else {
+    game.player2_shot_tax_paid = game.player2_shot_tax_paid.saturating_add(game.shot_tax);
   }
+  Ok(())
+}
 
-  pub fn set_hub(env: Env, new_hub: Address) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.storage().instance().set(&DataKey::GameHubAddress, &new_hub);
+// Counts storage-creating calls per address in fixed ledger windows. Counters are temporary
+// entries that lapse with their window, so the limiter itself adds no lasting footprint.
+fn consume_rate_limit(env: &Env, address: &Address) -> Result<(), Error> {
+  let Some(limit) = env.storage().instance().get::<ConfigKey, RateLimit>(&ConfigKey::RateLimit) else { return Ok(()); };
+  let now = env.ledger().sequence();
+  let key = DataKey::RateCounter(address.clone());
+  let mut counter: RateCounter = env.storage().temporary().get(&key).unwrap_or(RateCounter { window_start: now, count: 0 });
+  if now >= counter.window_start.saturating_add(limit.window_ledgers) {
+    counter = RateCounter { window_start: now, count: 0 };
   }
+  if counter.count >= limit.max_actions { return Err(Error::RateLimited); }
+  counter.count += 1;
+  env.storage().temporary().set(&key, &counter);
+  let ledgers = limit.window_ledgers.min(env.storage().max_ttl());
+  env.storage().temporary().extend_ttl(&key, ledgers, ledgers);
+  Ok(())
+}
+
+fn require_capability(game: &Game, capability: u32) -> Result<(), Error> {
+  if game.capabilities & capability != capability { return Err(Error::CapabilityNotNegotiated); }
+  Ok(())
+}
+
+// The second stake in moves the game on to board commits.
+fn mark_deposited(game: &mut Game, player1: bool) {
+  if player1 { game.player1_deposited = true; } else { game.player2_deposited = true; }
+  if game.player1_deposited && game.player2_deposited { game.phase = GamePhase::AwaitingBoards; }
+}
 
-  pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
-    let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("Admin not set");
-    admin.require_auth();
-    env.deployer().update_current_contract_wasm(new_wasm_hash);
+// Voided games may have no winner but accept no further play.
+fn is_over(game: &Game) -> bool {
+  ENDED_PHASES.contains(&game.phase)
+}
+
+// A contract-defined window of `ledgers`, shortened to the dev-mode timeout when one is set. A
+// zero window stays zero, since it means the timeout is off.
+fn dev_window(env: &Env, ledgers: u32) -> u32 {
+  match env.storage().instance().get::<ConfigKey, u32>(&ConfigKey::DevMode) {
+    Some(timeout) if timeout > 0 => ledgers.min(timeout),
+    _ => ledgers,
   }
 }
 
-fn end_game_hub(env: &Env, session_id: u32, player1_won: bool) {
-  let game_hub_addr: Address = env.storage().instance().get(&DataKey::GameHubAddress).expect("GameHub address not set");
-  let game_hub = GameHubClient::new(env, &game_hub_addr);
-  game_hub.end_game(&session_id, &player1_won);
+// Last ledger on which the second player can still commit; afterwards the game can be voided.
+fn commit_deadline(env: &Env, game: &Game) -> Option<u32> {
+  let window = BattleshipContract::get_commit_window(env.clone());
+  game.first_commit_ledger.map(|first_commit| first_commit.saturating_add(window))
 }
 
-fn is_wager_game(game: &Game) -> bool {
-  game.player1_points > 0 || game.player2_points > 0
+// Last ledger a game may still be played on before anyone can force adjudication.
+fn duration_deadline(env: &Env, game: &Game) -> Option<u32> {
+  let max_duration: u32 = match game.max_duration_ledgers {
+    0 => env.storage().instance().get(&ConfigKey::MaxGameDuration).unwrap_or(0),
+    ledgers => ledgers,
+  };
+  if max_duration == 0 { return None; }
+  Some(game.start_ledger.saturating_add(dev_window(env, max_duration)))
 }
 
-fn settle_wager(env: &Env, game: &mut Game) -> Result<(), Error> {
-  if game.payout_processed { return Ok(()); }
-  if !is_wager_game(game) {
-    game.payout_processed = true;
-    return Ok(());
+fn has_committed(game: &Game, player: &Address) -> bool {
+  if game.board_count > 1 {
+    let boards = if *player == game.player1 { &game.player1_boards } else { &game.player2_boards };
+    return boards.len() == game.board_count;
   }
-  if !game.player1_deposited || !game.player2_deposited { return Err(Error::StakesNotFunded); }
+  if *player == game.player1 { game.player1_board.is_some() } else { game.player2_board.is_some() }
+}
 
-  let winner = game.winner.clone().ok_or(Error::GameAlreadyEnded)?;
-  let token_contract: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
-  let fee_bps: u32 = env.storage().instance().get(&ConfigKey::FeeBps).unwrap_or(DEFAULT_FEE_BPS);
-  let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
+// Refunds follow the escrow ledger: every depositor, backer and shot-tax payer gets back
+// exactly what they paid, in the token the game's escrow was opened in, even when the two
+// sides staked different amounts, only one side has deposited, or the bet token has changed.
+fn refund_stakes(env: &Env, session_id: u32, game: &mut Game) -> Result<(), Error> {
+  if game.payout_processed { return Ok(()); }
+  let shares = escrow_shares(env, game);
+  if !shares.is_empty() {
+    let token_contract = escrow_token(env, session_id)?;
+    let token_client = token::Client::new(env, &token_contract);
+    let escrow = env.current_contract_address();
+    let mut total: i128 = 0;
+    for share in shares.iter() {
+      token_client.transfer(&escrow, &share.owner, &share.amount);
+      Payout { session_id, to: share.owner, token: token_contract.clone(), amount: share.amount }.publish(env);
+      total = total.saturating_add(share.amount);
+    }
+    adjust_liabilities(env, &token_contract, total.saturating_neg());
+  }
+  game.payout_processed = true;
+  Ok(())
+}
 
-  let total_pot = game.player1_points.saturating_add(game.player2_points);
-  let fee_amount = total_pot.saturating_mul(fee_bps as i128) / BPS_DENOMINATOR;
-  let winner_amount = total_pot.saturating_sub(fee_amount);
+// As `refund_stakes`, keeping the draw fee from each share for the fee recipient.
+fn refund_draw(env: &Env, session_id: u32, game: &mut Game) -> Result<i128, Error> {
+  if game.payout_processed { return Ok(0); }
+  let shares = escrow_shares(env, game);
+  let mut fees: i128 = 0;
+  if !shares.is_empty() {
+    let fee_bps: u32 = env.storage().instance().get(&ConfigKey::DrawFeeBps).unwrap_or(0);
+    let token_contract = escrow_token(env, session_id)?;
+    let token_client = token::Client::new(env, &token_contract);
+    let escrow = env.current_contract_address();
+    let mut total: i128 = 0;
+    for share in shares.iter() {
+      let fee = share.amount.saturating_mul(fee_bps as i128) / BPS_DENOMINATOR;
+      token_client.transfer(&escrow, &share.owner, &share.amount.saturating_sub(fee));
+      Payout { session_id, to: share.owner, token: token_contract.clone(), amount: share.amount.saturating_sub(fee) }.publish(env);
+      fees = fees.saturating_add(fee);
+      total = total.saturating_add(share.amount);
+    }
+    if fees > 0 {
+      let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
+      token_client.transfer(&escrow, &fee_recipient, &fees);
+    }
+    adjust_liabilities(env, &token_contract, total.saturating_neg());
+  }
+  game.payout_processed = true;
+  Ok(fees)
+}
 
-  let token_client = token::Client::new(env, &token_contract);
-  let escrow = env.current_contract_address();
+// The first deposit into a game fixes its token in the escrow record; later deposits, shot
+// tax, refunds and the payout use that token even if the admin has since switched bet tokens.
+fn escrow_token(env: &Env, session_id: u32) -> Result<Address, Error> {
+  if let Some(record) = env.storage().persistent().get::<EscrowKey, EscrowRecord>(&EscrowKey::Held(session_id)) { return Ok(record.token); }
+  env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)
+}
 
-  if winner_amount > 0 {
-    token_client.transfer(&escrow, &winner, &winner_amount);
+fn commit_single_board(
+  env: &Env,
+  session_id: u32,
+  player: Address,
+  cell_commitments: Vec<BytesN<32>>,
+  ship_cells: u32,
+  board_proof_hash: Option<BytesN<32>>,
+  board_proof_signature: Option<BytesN<64>>,
+) -> Result<(), Error> {
+  let key = DataKey::Game(session_id);
+  let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(env, session_id))?;
+  require_phase(&game, &[GamePhase::AwaitingBoards])?;
+
+  if game.board_count > 1 { return Err(Error::BoardModeMismatch); }
+  let board_cells = game.board_size.saturating_mul(game.board_size);
+  if cell_commitments.len() != board_cells { return Err(Error::InvalidBoardCommitmentLength); }
+  if ship_cells == 0 || ship_cells > board_cells { return Err(Error::InvalidShipCount); }
+  if game.fleet.total_cells > 0 && ship_cells != game.fleet.total_cells { return Err(Error::FleetMismatch); }
+
+  if env.storage().instance().has(&DataKey::ZkVerifierContract) || game.pinned_verifier.is_some() {
+    return Err(Error::ZkProofRequired);
   }
-  if fee_amount > 0 {
-    token_client.transfer(&escrow, &fee_recipient, &fee_amount);
+  if game.player1_board_verifier.is_some() || game.player2_board_verifier.is_some() {
+    return Err(Error::VerifierChanged);
   }
 
-  game.payout_processed = true;
+  if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
+    let proof_hash = board_proof_hash.ok_or(Error::MissingProofSignature)?;
+    let proof_signature = board_proof_signature.ok_or(Error::MissingProofSignature)?;
+    let commitment_root = compute_commitment_root(env, game.hash_algorithm, &cell_commitments);
+    let message = build_board_proof_message(env, game.hash_algorithm, &env.current_contract_address(), session_id, ship_cells, &commitment_root, &proof_hash);
+    env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
+  }
+
+  warn_weak_commitments(env, session_id, &player, &cell_commitments);
+  apply_board_commit(env, &mut game, player.clone(), cell_commitments, ship_cells)?;
+  BoardCommitted { session_id, player, board: 0 }.publish(env);
+
+  store_game(env, session_id, &game);
   Ok(())
 }
 
+// Boards built with per-cell random salts never repeat a commitment, so a board made of only a
+// handful of distinct values means the client reused salts. Such boards are still accepted
+// but flagged; the scan stops as soon as the board has more distinct values than the limit.
+fn warn_weak_commitments(env: &Env, session_id: u32, player: &Address, cell_commitments: &Vec<BytesN<32>>) {
+  let mut distinct: Vec<BytesN<32>> = Vec::new(env);
+  for commitment in cell_commitments.iter() {
+    if distinct.contains(&commitment) { continue; }
+    if distinct.len() >= WEAK_BOARD_DISTINCT_COMMITMENTS { return; }
+    distinct.push_back(commitment);
+  }
+  WeakBoardCommitment { session_id, player: player.clone(), distinct_commitments: distinct.len() }.publish(env);
+}
+
 fn apply_board_commit(
+  env: &Env,
   game: &mut Game,
   player: Address,
   cell_commitments: Vec<BytesN<32>>,
   ship_cells: u32,
 ) -> Result<(), Error> {
+  if game.first_commit_ledger.is_none() { game.first_commit_ledger = Some(env.ledger().sequence()); }
   if player == game.player1 {
     if game.player1_board.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    game.player1_board_root = Some(compute_merkle_root(env, game.hash_algorithm, &cell_commitments));
     game.player1_board = Some(cell_commitments);
     game.player1_ship_cells = Some(ship_cells);
   } else if player == game.player2 {
     if game.player2_board.is_some() { return Err(Error::BoardAlreadyCommitted); }
+    game.player2_board_root = Some(compute_merkle_root(env, game.hash_algorithm, &cell_commitments));
     game.player2_board = Some(cell_commitments);
     game.player2_ship_cells = Some(ship_cells);
   } else {
@@ -785,7 +4166,7 @@ fn apply_board_commit(
   }
 
   if game.player1_board.is_some() && game.player2_board.is_some() && game.turn.is_none() {
-    game.turn = Some(game.player1.clone());
+    if !awaiting_randomness(game) { begin_play(env, game); }
     if game.player1_ship_cells.is_none() { game.player1_ship_cells = Some(DEFAULT_SHIP_CELLS); }
     if game.player2_ship_cells.is_none() { game.player2_ship_cells = Some(DEFAULT_SHIP_CELLS); }
   }
@@ -793,51 +4174,407 @@ fn apply_board_commit(
   Ok(())
 }
 
+fn boards_ready(game: &Game) -> bool {
+  if game.board_count > 1 {
+    game.player1_boards.len() == game.board_count && game.player2_boards.len() == game.board_count
+  } else {
+    game.player1_board.is_some() && game.player2_board.is_some()
+  }
+}
+
+fn awaiting_randomness(game: &Game) -> bool {
+  game.randomness_beacon.is_some() && game.randomness.is_none()
+}
+
+// Beacon games let the low bit of the beacon output pick who shoots first; every other
+// game keeps the classic player1-first order.
+fn first_mover(game: &Game) -> Address {
+  match &game.randomness {
+    Some(randomness) if randomness.get(31).unwrap_or(0) & 1 == 1 => game.player2.clone(),
+    _ => game.player1.clone(),
+  }
+}
+
+// Boards are committed in canonical orientation; transforms are drawn only once both are
+// locked in, so neither player can place against the orientation the opponent will see.
+fn begin_play(env: &Env, game: &mut Game) {
+  game.turn = Some(first_mover(game));
+  game.phase = GamePhase::InProgress;
+  start_turn_clock(env, game);
+  if !game.board_transforms { return; }
+  match &game.randomness {
+    Some(randomness) => {
+      game.player1_transform = u32::from(randomness.get(0).unwrap_or(0)) % BOARD_TRANSFORMS;
+      game.player2_transform = u32::from(randomness.get(1).unwrap_or(0)) % BOARD_TRANSFORMS;
+    }
+    None => {
+      game.player1_transform = env.prng().gen_range::<u64>(0..u64::from(BOARD_TRANSFORMS)) as u32;
+      game.player2_transform = env.prng().gen_range::<u64>(0..u64::from(BOARD_TRANSFORMS)) as u32;
+    }
+  }
+}
+
+fn canonical_target(game: &Game, attacker: &Address, x: u32, y: u32) -> (u32, u32) {
+  let transform = if *attacker == game.player1 { game.player2_transform } else { game.player1_transform };
+  transform_cell(transform, game.board_size, x, y)
+}
+
+// Maps a cell as displayed under `transform` back to the committed orientation. Bit 2 mirrors
+// the columns, then the low two bits rotate by that many quarter turns.
+pub fn transform_cell(transform: u32, board_size: u32, x: u32, y: u32) -> (u32, u32) {
+  let edge = board_size.saturating_sub(1);
+  let x = if transform & 4 != 0 { edge - x } else { x };
+  match transform & 3 {
+    0 => (x, y),
+    1 => (y, edge - x),
+    2 => (edge - x, edge - y),
+    _ => (edge - y, x),
+  }
+}
+
+// Folds each accepted reveal into the game's rolling transcript, so a full move log can later
+// be checked against a single stored hash.
+fn record_transcript(env: &Env, game: &mut Game, board: u32, x: u32, y: u32, is_ship: bool, salt: &Bytes) {
+  let Some(previous) = game.transcript_hash.clone() else { return; };
+  let reveal = MoveReveal { board, x, y, is_ship, salt: salt.clone() };
+  game.transcript_hash = Some(transcript_step(env, game.hash_algorithm, &previous, &reveal));
+}
+
 fn apply_resolved_attack(env: &Env, session_id: u32, game: &mut Game, target_index: u32, is_ship: bool) -> Result<(), Error> {
+  record_attack(game, target_index, is_ship)?;
+  settle_if_decided(env, session_id, game)?;
+  drop_queued_attack_if_decided(game);
+  start_turn_clock(env, game);
+  Ok(())
+}
+
+fn record_attack(game: &mut Game, target_index: u32, is_ship: bool) -> Result<(), Error> {
   let pending_attacker = game.pending_attacker.clone().ok_or(Error::NoPendingAttack)?;
+  let attacker_is_player1 = pending_attacker == game.player1;
 
-  if pending_attacker == game.player1 {
-    game.player1_attacks.push_back(target_index);
-    if is_ship {
-      game.player1_hits = game.player1_hits.saturating_add(1);
-      game.player1_hit_attacks.push_back(target_index);
-    }
+  if attacker_is_player1 {
+    set_bit(&mut game.player1_attacks, target_index);
     game.turn = Some(game.player2.clone());
   } else {
-    game.player2_attacks.push_back(target_index);
-    if is_ship {
-      game.player2_hits = game.player2_hits.saturating_add(1);
-      game.player2_hit_attacks.push_back(target_index);
-    }
+    set_bit(&mut game.player2_attacks, target_index);
     game.turn = Some(game.player1.clone());
   }
+  if is_ship { credit_hit(game, attacker_is_player1, target_index); }
 
   game.pending_attacker = None;
+  game.phase = GamePhase::InProgress;
   game.pending_defender = None;
   game.pending_x = None;
   game.pending_y = None;
+  Ok(())
+}
+
+fn credit_hit(game: &mut Game, attacker_is_player1: bool, target_index: u32) {
+  if attacker_is_player1 {
+    game.player1_hits = game.player1_hits.saturating_add(1);
+    set_bit(&mut game.player1_hit_attacks, target_index);
+  } else {
+    game.player2_hits = game.player2_hits.saturating_add(1);
+    set_bit(&mut game.player2_hit_attacks, target_index);
+  }
+}
+
+fn targeting_grid(env: &Env, board_size: u32, attacks: &BytesN<32>, hits: &BytesN<32>, pending: Option<u32>) -> Bytes {
+  let cells = board_size.saturating_mul(board_size).min(MAX_BOARD_SIZE * MAX_BOARD_SIZE);
+  let mut packed = [0u8; (MAX_BOARD_SIZE * MAX_BOARD_SIZE / 4) as usize];
+  let mut mark = |index: u32, value: u8| {
+    if index < cells { packed[(index / 4) as usize] |= value << ((index % 4) * 2); }
+  };
+  for index in 0..cells {
+    if has_bit(attacks, index) { mark(index, if has_bit(hits, index) { GRID_HIT } else { GRID_MISS }); }
+  }
+  if let Some(index) = pending { mark(index, GRID_PENDING); }
+  Bytes::from_slice(env, &packed[..cells.div_ceil(4) as usize])
+}
 
+// A player whose own board still has unproven buffered resolutions cannot be declared the
+// winner yet: proving them could credit the opponent with hits that landed earlier.
+fn settle_if_decided(env: &Env, session_id: u32, game: &mut Game) -> Result<(), Error> {
   let player1_ship_cells = game.player1_ship_cells.unwrap_or(DEFAULT_SHIP_CELLS);
   let player2_ship_cells = game.player2_ship_cells.unwrap_or(DEFAULT_SHIP_CELLS);
-  if game.player1_hits >= player2_ship_cells {
+  let board_cells = game.board_size.saturating_mul(game.board_size);
+  // Unproven buffered claims are not yet known to be misses.
+  let player1_settled_attacks = bit_count(&game.player1_attacks).saturating_sub(game.player2_buffer.len());
+  let player2_settled_attacks = bit_count(&game.player2_attacks).saturating_sub(game.player1_buffer.len());
+  if game.player1_hits >= player2_ship_cells && game.player1_buffer.is_empty() {
     game.winner = Some(game.player1.clone());
-    settle_wager(env, game)?;
-    end_game_hub(env, session_id, true);
-  } else if game.player2_hits >= player1_ship_cells {
+    game.phase = GamePhase::Finished;
+    settle_wager(env, session_id, game)?;
+    apply_reward_multiplier(env, session_id, game);
+    end_game_hub(env, session_id, game, true);
+  } else if game.player2_hits >= player1_ship_cells && game.player2_buffer.is_empty() {
     game.winner = Some(game.player2.clone());
-    settle_wager(env, game)?;
-    end_game_hub(env, session_id, false);
+    game.phase = GamePhase::Finished;
+    settle_wager(env, session_id, game)?;
+    apply_reward_multiplier(env, session_id, game);
+    end_game_hub(env, session_id, game, false);
+  } else if let Some(shortfall) = unreachable_hits(game.player1_hits, player1_settled_attacks, player2_ship_cells, board_cells) {
+    // Player 2 declared more ship cells than the unrevealed cells can hold.
+    end_game_early(env, session_id, game, true, shortfall)?;
+  } else if let Some(shortfall) = unreachable_hits(game.player2_hits, player2_settled_attacks, player1_ship_cells, board_cells) {
+    end_game_early(env, session_id, game, false, shortfall)?;
+  }
+  Ok(())
+}
+
+// A queued shot waits for `register_queued_attack` until the game is decided.
+fn drop_queued_attack_if_decided(game: &mut Game) {
+  if game.winner.is_some() {
+    game.queued_attacker = None;
+    game.queued_commitment = None;
+  }
+}
+
+// Published whenever a shot becomes pending, whether fired directly or promoted from the queue.
+fn announce_attack(env: &Env, session_id: u32, game: &Game) {
+  if let (Some(attacker), Some(x), Some(y)) = (game.pending_attacker.clone(), game.pending_x, game.pending_y) {
+    AttackMade { session_id, attacker, board: game.pending_board.unwrap_or(0), x, y }.publish(env);
+  }
+}
+
+// Every accepted reveal is logged and announced before the shot is recorded on the game.
+fn record_reveal(env: &Env, session_id: u32, game: &Game, board: u32, x: u32, y: u32, is_ship: bool) {
+  movelog::append_move(env, session_id, game, board, x, y, is_ship);
+  if let Some(defender) = game.pending_defender.clone() {
+    AttackResolved { session_id, defender, board, x, y, is_ship }.publish(env);
+  }
+}
+
+fn active_reward_multiplier(env: &Env) -> u32 {
+  let now = env.ledger().sequence();
+  let windows: Vec<RewardWindow> = env.storage().instance().get(&ConfigKey::RewardWindows).unwrap_or(Vec::new(env));
+  // Overlapping windows do not stack; the largest multiplier applies.
+  windows.iter()
+    .filter(|window| window.start_ledger <= now && now < window.end_ledger)
+    .map(|window| window.multiplier_bps)
+    .fold(BASE_REWARD_MULTIPLIER_BPS, u32::max)
+}
+
+// The hub only learns the winner, so boosted points are published for hub-side point and ELO
+// accounting to pick up.
+fn apply_reward_multiplier(env: &Env, session_id: u32, game: &mut Game) {
+  game.reward_multiplier_bps = active_reward_multiplier(env);
+  if game.reward_multiplier_bps == BASE_REWARD_MULTIPLIER_BPS || game.exhibition { return; }
+  let multiplier = i128::from(game.reward_multiplier_bps);
+  RewardMultiplied {
+    session_id,
+    multiplier_bps: game.reward_multiplier_bps,
+    player1_points: game.player1_points.saturating_mul(multiplier) / BPS_DENOMINATOR,
+    player2_points: game.player2_points.saturating_mul(multiplier) / BPS_DENOMINATOR,
   }
+  .publish(env);
+}
+
+fn unreachable_hits(hits: u32, attacks: u32, ship_cells: u32, board_cells: u32) -> Option<u32> {
+  let reachable = hits.saturating_add(board_cells.saturating_sub(attacks));
+  if reachable >= ship_cells { return None; }
+  Some(ship_cells - reachable)
+}
 
+fn end_game_early(env: &Env, session_id: u32, game: &mut Game, player1_won: bool, shortfall: u32) -> Result<(), Error> {
+  let winner = if player1_won { game.player1.clone() } else { game.player2.clone() };
+  game.winner = Some(winner.clone());
+  game.phase = GamePhase::Finished;
+  settle_wager(env, session_id, game)?;
+  apply_reward_multiplier(env, session_id, game);
+  EarlyTermination { session_id, winner, unreachable_hits: shortfall }.publish(env);
+  end_game_hub(env, session_id, game, player1_won);
   Ok(())
 }
 
-fn extend_game_ttl(env: &Env, key: &DataKey) {
-  env.storage().temporary().extend_ttl(key, GAME_TTL_LEDGERS, GAME_TTL_LEDGERS);
+fn ttl_policy(env: &Env) -> TtlPolicy {
+  env.storage().instance().get(&ConfigKey::TtlPolicy).unwrap_or(TtlPolicy {
+    active_game_ledgers: DEFAULT_GAME_TTL_LEDGERS,
+    finished_game_ledgers: DEFAULT_GAME_TTL_LEDGERS,
+    session_grant_ledgers: DEFAULT_SESSION_GRANT_TTL_LEDGERS,
+  })
+}
+
+fn time_controls(env: &Env) -> Map<Symbol, TimeControl> {
+  env.storage().instance().get(&ConfigKey::TimeControls).unwrap_or(Map::new(env))
+}
+
+fn max_game_ttl(env: &Env) -> u32 {
+  env.storage().instance().get(&ConfigKey::MaxGameTtl).unwrap_or(MAX_POLICY_TTL_LEDGERS)
+}
+
+// Policies are bounded when set, but the network's maximum entry TTL can still be lower.
+fn extend_game_ttl(env: &Env, key: &DataKey, ledgers: u32) {
+  let ledgers = ledgers.min(env.storage().max_ttl());
+  env.storage().temporary().extend_ttl(key, ledgers, ledgers);
+}
+
+fn store_game(env: &Env, session_id: u32, game: &Game) {
+  let key = DataKey::Game(session_id);
+  env.storage().temporary().set(&key, game);
+  let policy = ttl_policy(env);
+  let active_ledgers = if game.ttl_ledgers > 0 { game.ttl_ledgers } else { policy.active_game_ledgers };
+  extend_game_ttl(env, &key, if is_over(game) { policy.finished_game_ledgers } else { active_ledgers });
+  update_tombstone(env, session_id, game);
+}
+
+// Reports a phase mismatch with the error for whatever is actually in the way.
+fn require_phase(game: &Game, allowed: &[GamePhase]) -> Result<(), Error> {
+  if allowed.contains(&game.phase) { return Ok(()); }
+  if is_over(game) { return Err(Error::GameAlreadyEnded); }
+  if allowed.contains(&GamePhase::Finished) { return Err(Error::GameNotFinished); }
+  if game.phase == GamePhase::AwaitingDeposits { return Err(Error::StakesNotFunded); }
+  if allowed.contains(&GamePhase::AwaitingResolve) && !allowed.contains(&GamePhase::InProgress) { return Err(Error::NoPendingAttack); }
+  if allowed.contains(&GamePhase::AwaitingBoards) { return Err(Error::BoardAlreadyCommitted); }
+  if allowed.contains(&GamePhase::AwaitingDeposits) { return Err(Error::AlreadyDeposited); }
+  if game.phase == GamePhase::AwaitingBoards { return Err(Error::BoardsNotReady); }
+  Err(Error::PendingAttackResolution)
+}
+
+fn game_status(game: &Game) -> GameStatus {
+  if game.voided { GameStatus::Voided } else if game.winner.is_some() { GameStatus::Finished } else { GameStatus::Active }
+}
+
+// The tombstone is only rewritten when the status or escrow flag changes, not on every move.
+fn update_tombstone(env: &Env, session_id: u32, game: &Game) {
+  let status = game_status(game);
+  let mut shares = if game.payout_processed { Vec::new(env) } else { escrow_shares(env, game) };
+  // Skip bonds outlive settlement until they are released.
+  if game.player1_skip_bond > 0 { shares.push_back(EscrowShare { owner: game.player1.clone(), amount: game.player1_skip_bond }); }
+  if game.player2_skip_bond > 0 { shares.push_back(EscrowShare { owner: game.player2.clone(), amount: game.player2_skip_bond }); }
+  let escrowed = !shares.is_empty();
+  if is_wager_game(game) || game.skip_turn_ledgers > 0 { sync_escrow_record(env, session_id, shares); }
+
+  let key = DataKey::Tombstone(session_id);
+  let previous: Option<GameTombstone> = env.storage().persistent().get(&key);
+  if let Some(previous) = &previous {
+    if previous.status == status && previous.escrowed == escrowed { return; }
+  }
+  if status != GameStatus::Active { channel::discard_channel(env, session_id); }
+  if previous.as_ref().map(|tombstone| tombstone.escrowed).unwrap_or(false) != escrowed { count_escrowed_game(env, session_id, escrowed); }
+  let created_ledger = previous.map(|tombstone| tombstone.created_ledger).unwrap_or(env.ledger().sequence());
+  env.storage().persistent().set(&key, &GameTombstone { status, escrowed, created_ledger });
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn escrow_shares(env: &Env, game: &Game) -> Vec<EscrowShare> {
+  let mut shares = Vec::new(env);
+  let player1 = if game.player1_deposited { game.player1_stake.saturating_sub(game.player1_backed) } else { 0 };
+  let player2 = if game.player2_deposited { game.player2_stake.saturating_sub(game.player2_backed) } else { 0 };
+  let player1 = player1.saturating_add(game.player1_shot_tax_paid);
+  let player2 = player2.saturating_add(game.player2_shot_tax_paid);
+  if player1 > 0 { shares.push_back(EscrowShare { owner: game.player1.clone(), amount: player1 }); }
+  if player2 > 0 { shares.push_back(EscrowShare { owner: game.player2.clone(), amount: player2 }); }
+  for backing in game.player1_backers.iter().chain(game.player2_backers.iter()) {
+    shares.push_back(EscrowShare { owner: backing.backer, amount: backing.amount });
+  }
+  shares
+}
+
+// Mirrors what the game holds in escrow into persistent storage, so the funds can still be
+// returned by `recover_expired` after the temporary game entry is gone.
+fn sync_escrow_record(env: &Env, session_id: u32, shares: Vec<EscrowShare>) {
+  let key = EscrowKey::Held(session_id);
+  let previous: Option<EscrowRecord> = env.storage().persistent().get(&key);
+  if shares.is_empty() {
+    if previous.is_some() { env.storage().persistent().remove(&key); }
+    return;
+  }
+  if previous.as_ref().is_some_and(|record| record.shares == shares) { return; }
+  let token = match previous {
+    Some(record) => record.token,
+    None => match env.storage().instance().get::<_, Address>(&ConfigKey::BetToken) { Some(token) => token, None => return },
+  };
+  env.storage().persistent().set(&key, &EscrowRecord { token, shares });
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn missing_game(env: &Env, session_id: u32) -> Error {
+  if env.storage().persistent().has(&DataKey::Tombstone(session_id)) { Error::GameExpired } else { Error::GameNotFound }
 }
 
 fn extend_session_ttl(env: &Env, key: &DataKey) {
-  env.storage().persistent().extend_ttl(key, SESSION_GRANT_TTL_LEDGERS, SESSION_GRANT_TTL_LEDGERS);
+  let ledgers = ttl_policy(env).session_grant_ledgers.min(env.storage().max_ttl());
+  env.storage().persistent().extend_ttl(key, ledgers, ledgers);
+}
+
+// Everything `attack` does after authorization, for callers that authorized the move some
+// other way. Shot taxes need the attacker's own signature, so they are refused here.
+fn delegated_attack(env: &Env, session_id: u32, attacker: Address, x: u32, y: u32) -> Result<(), Error> {
+  let key = DataKey::Game(session_id);
+  let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(env, session_id))?;
+
+  require_phase(&game, &[GamePhase::InProgress])?;
+  if x >= game.board_size || y >= game.board_size { return Err(Error::InvalidCoordinate); }
+  let (x, y) = canonical_target(&game, &attacker, x, y);
+  if game.player1_board.is_none() || game.player2_board.is_none() { return Err(Error::BoardsNotReady); }
+  if game.pending_attacker.is_some() { return Err(Error::PendingAttackResolution); }
+
+  let turn = game.turn.clone().ok_or(Error::BoardsNotReady)?;
+  if attacker != turn { return Err(Error::NotYourTurn); }
+
+  let target_index = y.saturating_mul(game.board_size).saturating_add(x);
+  let attacked = if attacker == game.player1 { &game.player1_attacks } else if attacker == game.player2 { &game.player2_attacks } else { return Err(Error::NotPlayer); };
+  if has_bit(attacked, target_index) { return Err(Error::AlreadyAttacked); }
+
+  if game.shot_tax > 0 { return Err(Error::ShotTaxRequiresPlayer); }
+
+  let defender = if attacker == game.player1 { game.player2.clone() } else { game.player1.clone() };
+  game.pending_attacker = Some(attacker);
+  game.phase = GamePhase::AwaitingResolve;
+  game.pending_defender = Some(defender);
+  game.pending_x = Some(x);
+  game.pending_y = Some(y);
+  start_turn_clock(env, &mut game);
+  announce_attack(env, session_id, &game);
+
+  store_game(env, session_id, &game);
+  Ok(())
+}
+
+// `resolve_attack` after authorization, shared the same way.
+fn delegated_resolve(
+  env: &Env,
+  session_id: u32,
+  defender: Address,
+  is_ship: bool,
+  salt: Bytes,
+  zk_proof_hash: BytesN<32>,
+  zk_proof_signature: Option<BytesN<64>>,
+) -> Result<(), Error> {
+  let key = DataKey::Game(session_id);
+  let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(env, session_id))?;
+
+  require_phase(&game, &[GamePhase::AwaitingResolve])?;
+
+  let pending_defender = game.pending_defender.clone().ok_or(Error::NoPendingAttack)?;
+  let pending_x = game.pending_x.ok_or(Error::NoPendingAttack)?;
+  let pending_y = game.pending_y.ok_or(Error::NoPendingAttack)?;
+  if pending_defender != defender { return Err(Error::NotPendingDefender); }
+
+  if env.storage().instance().has(&DataKey::ZkVerifierContract) || game.pinned_verifier.is_some() {
+    return Err(Error::ZkProofRequired);
+  }
+
+  let target_index = pending_y.saturating_mul(game.board_size).saturating_add(pending_x);
+  let board = if defender == game.player1 { game.player1_board.clone().ok_or(Error::BoardsNotReady)? } else if defender == game.player2 { game.player2_board.clone().ok_or(Error::BoardsNotReady)? } else { return Err(Error::NotPlayer); };
+  let expected = board.get(target_index).ok_or(Error::InvalidCoordinate)?;
+
+  if expected != game_cell_commitment(env, &game, target_index, is_ship, &salt) { return Err(Error::InvalidCellReveal); }
+  if zk_proof_hash != attack_proof_hash(env, game.hash_algorithm, is_ship, &salt, pending_x, pending_y) { return Err(Error::InvalidProofHash); }
+
+  if let Some(verifier_key) = env.storage().instance().get::<DataKey, BytesN<32>>(&DataKey::VerifierPubKey) {
+    let proof_signature = zk_proof_signature.ok_or(Error::MissingProofSignature)?;
+    let message = build_attack_proof_message(env, game.hash_algorithm, &env.current_contract_address(), session_id, pending_x, pending_y, is_ship, &zk_proof_hash);
+    env.crypto().ed25519_verify(&verifier_key, &message, &proof_signature);
+  }
+
+  record_transcript(env, &mut game, 0, pending_x, pending_y, is_ship, &salt);
+  record_reveal(env, session_id, &game, 0, pending_x, pending_y, is_ship);
+  apply_resolved_attack(env, session_id, &mut game, target_index, is_ship)?;
+
+  store_game(env, session_id, &game);
+  Ok(())
 }
 
 fn consume_session_authorization(env: &Env, session_id: u32, player: &Address, delegate: &Address) -> Result<(), Error> {
@@ -864,13 +4601,29 @@ fn consume_session_authorization(env: &Env, session_id: u32, player: &Address, d
   Ok(())
 }
 
-fn contains_u32(list: &Vec<u32>, value: u32) -> bool {
-  let mut index = 0;
-  while index < list.len() {
-    if list.get(index).unwrap_or_default() == value { return true; }
-    index += 1;
-  }
-  false
+// Restarts the clock of whoever has to act next. Without a move timeout there is no move
+// deadline, and `move_deadline_ledger` stays 0.
+fn start_turn_clock(env: &Env, game: &mut Game) {
+  game.turn_started_ledger = env.ledger().sequence();
+  game.move_deadline_ledger = if game.move_timeout_ledgers == 0 { 0 } else { game.turn_started_ledger.saturating_add(dev_window(env, game.move_timeout_ledgers)) };
+}
+
+// Attack bitmaps keep one bit per cell: cell `i` is bit `i % 8` of byte `i / 8`. 32 bytes
+// cover the largest (16x16) board.
+fn empty_bitmap(env: &Env) -> BytesN<32> {
+  BytesN::from_array(env, &[0u8; 32])
+}
+
+fn has_bit(bitmap: &BytesN<32>, index: u32) -> bool {
+  bitmap.get(index / 8).is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+fn set_bit(bitmap: &mut BytesN<32>, index: u32) {
+  if let Some(byte) = bitmap.get(index / 8) { bitmap.set(index / 8, byte | (1 << (index % 8))); }
+}
+
+fn bit_count(bitmap: &BytesN<32>) -> u32 {
+  bitmap.to_array().iter().map(|byte| byte.count_ones()).sum()
 }
 
 fn append_u32_be(bytes: &mut Bytes, value: u32) {
@@ -880,18 +4633,149 @@ fn append_u32_be(bytes: &mut Bytes, value: u32) {
   bytes.push_back((value & 0xff) as u8);
 }
 
-fn compute_commitment_root(env: &Env, commitments: &Vec<BytesN<32>>) -> BytesN<32> {
+pub fn cell_commitment(env: &Env, algorithm: HashAlgorithm, is_ship: bool, salt: &Bytes) -> BytesN<32> {
+  let mut payload = Bytes::new(env);
+  payload.push_back(if is_ship { 1 } else { 0 });
+  payload.append(salt);
+  algorithm.digest(env, &payload)
+}
+
+// Version 2 preimage: the cell index is hashed in, so equal cells no longer share a commitment.
+pub fn indexed_cell_commitment(env: &Env, algorithm: HashAlgorithm, index: u32, is_ship: bool, salt: &Bytes) -> BytesN<32> {
+  let mut payload = Bytes::new(env);
+  payload.push_back(if is_ship { 1 } else { 0 });
+  append_u32_be(&mut payload, index);
+  payload.append(salt);
+  algorithm.digest(env, &payload)
+}
+
+fn game_cell_commitment(env: &Env, game: &Game, index: u32, is_ship: bool, salt: &Bytes) -> BytesN<32> {
+  if game.commitment_version >= COMMITMENT_V2 {
+    indexed_cell_commitment(env, game.hash_algorithm, index, is_ship, salt)
+  } else {
+    cell_commitment(env, game.hash_algorithm, is_ship, salt)
+  }
+}
+
+pub fn attack_proof_hash(env: &Env, algorithm: HashAlgorithm, is_ship: bool, salt: &Bytes, x: u32, y: u32) -> BytesN<32> {
+  let mut payload = Bytes::new(env);
+  payload.push_back(if is_ship { 1 } else { 0 });
+  payload.append(salt);
+  append_u32_be(&mut payload, x);
+  append_u32_be(&mut payload, y);
+  algorithm.digest(env, &payload)
+}
+
+pub fn compute_commitment_root(env: &Env, algorithm: HashAlgorithm, commitments: &Vec<BytesN<32>>) -> BytesN<32> {
   let mut packed = Bytes::new(env);
   let mut index = 0;
   while index < commitments.len() {
     packed.append(&Bytes::from_array(env, &commitments.get(index).unwrap().to_array()));
     index += 1;
   }
-  BytesN::from_array(env, &env.crypto().keccak256(&packed).to_array())
+  algorithm.digest(env, &packed)
+}
+
+pub fn merkle_depth(leaf_count: u32) -> u32 {
+  let mut depth = 0;
+  while (1u64 << depth) < leaf_count as u64 { depth += 1; }
+  depth
+}
+
+pub fn compute_merkle_root(env: &Env, algorithm: HashAlgorithm, leaves: &Vec<BytesN<32>>) -> BytesN<32> {
+  let width = 1u32 << merkle_depth(leaves.len());
+  let mut level = leaves.clone();
+  while level.len() < width {
+    level.push_back(BytesN::from_array(env, &[0u8; 32]));
+  }
+  while level.len() > 1 {
+    let mut next = Vec::new(env);
+    let mut index = 0;
+    while index < level.len() {
+      next.push_back(hash_merkle_node(env, algorithm, &level.get(index).unwrap(), &level.get(index + 1).unwrap()));
+      index += 2;
+    }
+    level = next;
+  }
+  level.get(0).unwrap_or(BytesN::from_array(env, &[0u8; 32]))
+}
+
+pub fn build_merkle_proof(env: &Env, algorithm: HashAlgorithm, leaves: &Vec<BytesN<32>>, index: u32) -> Vec<BytesN<32>> {
+  let width = 1u32 << merkle_depth(leaves.len());
+  let mut level = leaves.clone();
+  while level.len() < width {
+    level.push_back(BytesN::from_array(env, &[0u8; 32]));
+  }
+  let mut proof = Vec::new(env);
+  let mut position = index;
+  while level.len() > 1 {
+    proof.push_back(level.get(position ^ 1).unwrap());
+    let mut next = Vec::new(env);
+    let mut cursor = 0;
+    while cursor < level.len() {
+      next.push_back(hash_merkle_node(env, algorithm, &level.get(cursor).unwrap(), &level.get(cursor + 1).unwrap()));
+      cursor += 2;
+    }
+    level = next;
+    position /= 2;
+  }
+  proof
+}
+
+pub fn verify_merkle_proof(env: &Env, algorithm: HashAlgorithm, root: &BytesN<32>, leaf: &BytesN<32>, index: u32, proof: &Vec<BytesN<32>>) -> bool {
+  let mut node = leaf.clone();
+  let mut position = index;
+  for sibling in proof.iter() {
+    node = if position.is_multiple_of(2) { hash_merkle_node(env, algorithm, &node, &sibling) } else { hash_merkle_node(env, algorithm, &sibling, &node) };
+    position /= 2;
+  }
+  position == 0 && node == *root
+}
+
+fn hash_merkle_node(env: &Env, algorithm: HashAlgorithm, left: &BytesN<32>, right: &BytesN<32>) -> BytesN<32> {
+  let mut payload = Bytes::new(env);
+  payload.push_back(1u8);
+  payload.append(&Bytes::from_array(env, &left.to_array()));
+  payload.append(&Bytes::from_array(env, &right.to_array()));
+  algorithm.digest(env, &payload)
+}
+
+pub fn compute_transcript_root(env: &Env, algorithm: HashAlgorithm, moves: &Vec<BufferedMove>) -> BytesN<32> {
+  let mut leaves = Vec::new(env);
+  for buffered in moves.iter() {
+    let mut payload = Bytes::new(env);
+    append_u32_be(&mut payload, buffered.x);
+    append_u32_be(&mut payload, buffered.y);
+    payload.append(&Bytes::from_array(env, &buffered.expected_commitment.to_array()));
+    payload.push_back(if buffered.is_ship { 1 } else { 0 });
+    leaves.push_back(algorithm.digest(env, &payload));
+  }
+  compute_merkle_root(env, algorithm, &leaves)
+}
+
+// Coordinates are as the attacker sees the board; the reveal maps them like a direct attack.
+pub fn queued_shot_commitment(env: &Env, x: u32, y: u32, salt: &BytesN<32>) -> BytesN<32> {
+  let mut payload = Bytes::new(env);
+  append_u32_be(&mut payload, x);
+  append_u32_be(&mut payload, y);
+  payload.append(&Bytes::from_array(env, &salt.to_array()));
+  env.crypto().sha256(&payload).into()
+}
+
+pub fn transcript_step(env: &Env, algorithm: HashAlgorithm, previous: &BytesN<32>, reveal: &MoveReveal) -> BytesN<32> {
+  let mut payload = Bytes::from_array(env, &previous.to_array());
+  append_u32_be(&mut payload, reveal.board);
+  append_u32_be(&mut payload, reveal.x);
+  append_u32_be(&mut payload, reveal.y);
+  payload.push_back(if reveal.is_ship { 1 } else { 0 });
+  payload.append(&reveal.salt);
+  algorithm.digest(env, &payload)
 }
 
-fn build_board_proof_message(
+pub fn build_board_proof_message(
   env: &Env,
+  algorithm: HashAlgorithm,
+  verifier: &Address,
   session_id: u32,
   ship_cells: u32,
   commitment_root: &BytesN<32>,
@@ -899,15 +4783,19 @@ fn build_board_proof_message(
 ) -> Bytes {
   let mut msg = Bytes::new(env);
   msg.push_back(1u8);
+  msg.append(&signing_domain(env, verifier));
   append_u32_be(&mut msg, session_id);
   append_u32_be(&mut msg, ship_cells);
   msg.append(&Bytes::from_array(env, &commitment_root.to_array()));
   msg.append(&Bytes::from_array(env, &proof_hash.to_array()));
+  algorithm.append_tag(&mut msg);
   msg
 }
 
-fn build_attack_proof_message(
+pub fn build_attack_proof_message(
   env: &Env,
+  algorithm: HashAlgorithm,
+  verifier: &Address,
   session_id: u32,
   x: u32,
   y: u32,
@@ -916,10 +4804,14 @@ fn build_attack_proof_message(
 ) -> Bytes {
   let mut msg = Bytes::new(env);
   msg.push_back(2u8);
+  msg.append(&signing_domain(env, verifier));
   append_u32_be(&mut msg, session_id);
   append_u32_be(&mut msg, x);
   append_u32_be(&mut msg, y);
   msg.push_back(if is_ship { 1 } else { 0 });
   msg.append(&Bytes::from_array(env, &proof_hash.to_array()));
+  algorithm.append_tag(&mut msg);
   msg
 }
+
+mod test;
@@ -0,0 +1,315 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, Vec};
+
+use crate::{
+  adjust_liabilities, consume_rate_limit, create_game, credit_withdrawable, mark_deposited, pay_keeper_bounty, record_setting_change, require_admin, require_phase, require_token_gates,
+  store_game, validate_token_gates, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, ConfigKey, DataKey, Error, Game, GamePhase, TokenGate, BPS_DENOMINATOR,
+  CAP_ALL, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+// A posted offer to play for `stake` on each side. Open challenges (`opponent: None`) can be
+// taken by anyone; the stake is escrowed when posting and becomes the challenger's deposit.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge {
+  pub challenger: Address,
+  pub opponent: Option<Address>,
+  pub stake: i128,
+  pub token: Option<Address>,
+  pub posted_ledger: u32,
+  pub expires_ledger: u32,
+  pub gates: Vec<TokenGate>,
+}
+
+// Stake band an open challenge accepts from `quick_match`: `base_band_bps` of the posted stake
+// either side, widened by `widen_bps` for every `step_ledgers` waited, up to `max_band_bps`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchPolicy {
+  pub base_band_bps: u32,
+  pub widen_bps: u32,
+  pub step_ledgers: u32,
+  pub max_band_bps: u32,
+}
+
+// `position` counts from the front of the open index (the longest wait). The estimate is the
+// average wait of past matches less the time already waited; `None` until a match has happened.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueueStatus {
+  pub position: u32,
+  pub waited_ledgers: u32,
+  pub band_bps: u32,
+  pub min_stake: i128,
+  pub max_stake: i128,
+  pub estimated_wait_ledgers: Option<u32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WaitStats {
+  pub matches: u32,
+  pub total_wait_ledgers: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LobbyKey { Challenge(u32), Open, NextId, SweepCursor, WaitStats }
+
+pub const MAX_OPEN_CHALLENGES: u32 = 128;
+pub const MAX_CHALLENGE_TTL_LEDGERS: u32 = 120_960;
+const MAX_SWEEP_PER_CALL: u32 = 32;
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn post_challenge(env: Env, challenger: Address, opponent: Option<Address>, stake: i128, ttl_ledgers: u32) -> Result<u32, Error> {
+    challenger.require_auth();
+    if stake < 0 { return Err(Error::InvalidStakeAmount); }
+    if opponent.as_ref() == Some(&challenger) { return Err(Error::NotPlayer); }
+    if ttl_ledgers == 0 || ttl_ledgers > MAX_CHALLENGE_TTL_LEDGERS { return Err(Error::InvalidConfig); }
+    let mut open = load_open(&env);
+    if open.len() >= MAX_OPEN_CHALLENGES { return Err(Error::LobbyFull); }
+    consume_rate_limit(&env, &challenger)?;
+
+    let token = if stake > 0 {
+      let token: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+      let escrow = env.current_contract_address();
+      token::Client::new(&env, &token).transfer(&challenger, &escrow, &stake);
+      adjust_liabilities(&env, &token, stake);
+      Some(token)
+    } else {
+      None
+    };
+
+    let challenge_id: u32 = env.storage().persistent().get(&LobbyKey::NextId).unwrap_or(0);
+    store_lobby(&env, &LobbyKey::NextId, &challenge_id.saturating_add(1));
+    let now = env.ledger().sequence();
+    let challenge = Challenge {
+      challenger,
+      opponent,
+      stake,
+      token,
+      posted_ledger: now,
+      expires_ledger: now.saturating_add(ttl_ledgers),
+      gates: Vec::new(&env),
+    };
+    store_lobby(&env, &LobbyKey::Challenge(challenge_id), &challenge);
+    open.push_back(challenge_id);
+    store_lobby(&env, &LobbyKey::Open, &open);
+    Ok(challenge_id)
+  }
+
+  // Starts `session_id` with the challenger as player1. The challenger consented by posting,
+  // so only the acceptor signs here and still owes their own `deposit_stake`.
+  pub fn accept_challenge(env: Env, challenge_id: u32, session_id: u32, acceptor: Address) -> Result<(), Error> {
+    acceptor.require_auth();
+    let challenge = load_challenge(&env, challenge_id)?;
+    if env.ledger().sequence() > challenge.expires_ledger { return Err(Error::ChallengeExpired); }
+    if acceptor == challenge.challenger { return Err(Error::NotPlayer); }
+    if challenge.opponent.as_ref().is_some_and(|opponent| *opponent != acceptor) { return Err(Error::NotPlayer); }
+    require_token_gates(&env, &challenge.gates, &acceptor)?;
+    let stake = challenge.stake;
+    take_challenge(&env, challenge_id, challenge, session_id, acceptor, stake)
+  }
+
+  // Accepts the longest-waiting open challenge whose current stake band covers `stake`, which
+  // becomes the player's own side of the game. Returns the challenge taken.
+  pub fn quick_match(env: Env, session_id: u32, player: Address, stake: i128) -> Result<u32, Error> {
+    player.require_auth();
+    if stake < 0 { return Err(Error::InvalidStakeAmount); }
+    let policy = match_policy(&env);
+    let now = env.ledger().sequence();
+    for challenge_id in load_open(&env).iter() {
+      let Some(challenge) = env.storage().persistent().get::<LobbyKey, Challenge>(&LobbyKey::Challenge(challenge_id)) else { continue; };
+      if challenge.opponent.is_some() || challenge.challenger == player || now > challenge.expires_ledger { continue; }
+      let (min_stake, max_stake) = stake_range(&challenge, band_bps(&policy, &challenge, now));
+      if stake < min_stake || stake > max_stake { continue; }
+      if require_token_gates(&env, &challenge.gates, &player).is_err() { continue; }
+      take_challenge(&env, challenge_id, challenge, session_id, player, stake)?;
+      return Ok(challenge_id);
+    }
+    Err(Error::NoMatchFound)
+  }
+
+  pub fn get_match_policy(env: Env) -> MatchPolicy {
+    match_policy(&env)
+  }
+
+  pub fn set_match_policy(env: Env, policy: MatchPolicy) -> Result<(), Error> {
+    require_admin(&env);
+    let max_bps = BPS_DENOMINATOR as u32;
+    if policy.max_band_bps > max_bps || policy.base_band_bps > policy.max_band_bps { return Err(Error::InvalidConfig); }
+    if policy.widen_bps > 0 && policy.step_ledgers == 0 { return Err(Error::InvalidConfig); }
+    env.storage().instance().set(&ConfigKey::MatchPolicy, &policy);
+    record_setting_change(&env, "match_policy", policy);
+    Ok(())
+  }
+
+  pub fn get_queue_status(env: Env, challenge_id: u32) -> Result<QueueStatus, Error> {
+    let challenge = load_challenge(&env, challenge_id)?;
+    let position = load_open(&env).first_index_of(challenge_id).ok_or(Error::ChallengeNotFound)?;
+    let now = env.ledger().sequence();
+    let waited_ledgers = now.saturating_sub(challenge.posted_ledger);
+    let band_bps = band_bps(&match_policy(&env), &challenge, now);
+    let (min_stake, max_stake) = stake_range(&challenge, band_bps);
+    let stats = wait_stats(&env);
+    let estimated_wait_ledgers = match stats.matches {
+      0 => None,
+      matches => Some(((stats.total_wait_ledgers / matches as u64) as u32).saturating_sub(waited_ledgers)),
+    };
+    Ok(QueueStatus { position, waited_ledgers, band_bps, min_stake, max_stake, estimated_wait_ledgers })
+  }
+
+  // Restricts who may take the challenge, by `accept_challenge` or `quick_match`.
+  pub fn set_challenge_gates(env: Env, challenge_id: u32, gates: Vec<TokenGate>) -> Result<(), Error> {
+    let mut challenge = load_challenge(&env, challenge_id)?;
+    challenge.challenger.require_auth();
+    validate_token_gates(&gates)?;
+
+    challenge.gates = gates;
+    store_lobby(&env, &LobbyKey::Challenge(challenge_id), &challenge);
+    Ok(())
+  }
+
+  pub fn cancel_challenge(env: Env, challenge_id: u32) -> Result<i128, Error> {
+    let challenge = load_challenge(&env, challenge_id)?;
+    challenge.challenger.require_auth();
+    remove_challenge(&env, challenge_id);
+    Ok(refund_challenge(&env, &challenge))
+  }
+
+  pub fn get_challenge(env: Env, challenge_id: u32) -> Option<Challenge> {
+    env.storage().persistent().get(&LobbyKey::Challenge(challenge_id))
+  }
+
+  pub fn get_open_challenges(env: Env) -> Vec<u32> {
+    load_open(&env)
+  }
+
+  // Walks the open index from where the last sweep stopped and drops expired challenges,
+  // refunding their stakes. Returns the number removed; keepers are paid per removal.
+  pub fn sweep_stale(env: Env, keeper: Address, limit: u32) -> u32 {
+    let open = load_open(&env);
+    let len = open.len();
+    if len == 0 { return 0; }
+    let now = env.ledger().sequence();
+    let mut index = env.storage().persistent().get::<LobbyKey, u32>(&LobbyKey::SweepCursor).unwrap_or(0);
+    if index >= len { index = 0; }
+
+    // The window wraps around the end of the index; the cursor is then moved to the first
+    // entry the window did not reach.
+    let visits = limit.min(MAX_SWEEP_PER_CALL).min(len);
+    let stop = (index + visits) % len;
+    let mut remaining = Vec::new(&env);
+    let mut next_cursor = 0u32;
+    let mut removed = 0u32;
+    for position in 0..len {
+      let challenge_id = open.get_unchecked(position);
+      if position == stop && visits < len { next_cursor = remaining.len(); }
+      let in_window = (position + len - index) % len < visits;
+      let stale = in_window && match env.storage().persistent().get::<LobbyKey, Challenge>(&LobbyKey::Challenge(challenge_id)) {
+        Some(challenge) if now > challenge.expires_ledger => {
+          env.storage().persistent().remove(&LobbyKey::Challenge(challenge_id));
+          refund_challenge(&env, &challenge);
+          true
+        }
+        Some(_) => false,
+        None => true,
+      };
+      if stale {
+        removed += 1;
+      } else {
+        remaining.push_back(challenge_id);
+      }
+    }
+
+    store_lobby(&env, &LobbyKey::SweepCursor, &next_cursor);
+    store_open(&env, &remaining);
+    pay_keeper_bounty(&env, &keeper, removed);
+    removed
+  }
+}
+
+fn take_challenge(env: &Env, challenge_id: u32, challenge: Challenge, session_id: u32, acceptor: Address, acceptor_stake: i128) -> Result<(), Error> {
+  if env.storage().temporary().has(&DataKey::Game(session_id)) || env.storage().persistent().has(&DataKey::Tombstone(session_id)) {
+    return Err(Error::SessionInUse);
+  }
+  remove_challenge(env, challenge_id);
+  record_wait(env, &challenge);
+
+  let stake = challenge.stake;
+  create_game(env, session_id, challenge.challenger.clone(), acceptor, stake, acceptor_stake, stake, acceptor_stake, CAP_ALL, 0)?;
+  let Some(token) = challenge.token else { return Ok(()); };
+  // A stake escrowed in a since-replaced bet token cannot fund this game; it is handed
+  // back and the challenger deposits again like any other player.
+  let bet_token: Option<Address> = env.storage().instance().get(&ConfigKey::BetToken);
+  if bet_token != Some(token.clone()) {
+    credit_withdrawable(env, &challenge.challenger, &token, stake);
+    return Ok(());
+  }
+  let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or(Error::GameNotFound)?;
+  require_phase(&game, &[GamePhase::AwaitingDeposits])?;
+  mark_deposited(&mut game, true);
+  store_game(env, session_id, &game);
+  Ok(())
+}
+
+fn match_policy(env: &Env) -> MatchPolicy {
+  env.storage().instance().get(&ConfigKey::MatchPolicy).unwrap_or(MatchPolicy { base_band_bps: 0, widen_bps: 0, step_ledgers: 0, max_band_bps: 0 })
+}
+
+fn band_bps(policy: &MatchPolicy, challenge: &Challenge, now: u32) -> u32 {
+  let steps = now.saturating_sub(challenge.posted_ledger).checked_div(policy.step_ledgers).unwrap_or(0);
+  policy.base_band_bps.saturating_add(steps.saturating_mul(policy.widen_bps)).min(policy.max_band_bps)
+}
+
+fn stake_range(challenge: &Challenge, band_bps: u32) -> (i128, i128) {
+  let band = challenge.stake.saturating_mul(band_bps as i128) / BPS_DENOMINATOR;
+  (challenge.stake.saturating_sub(band), challenge.stake.saturating_add(band))
+}
+
+fn wait_stats(env: &Env) -> WaitStats {
+  env.storage().persistent().get(&LobbyKey::WaitStats).unwrap_or(WaitStats { matches: 0, total_wait_ledgers: 0 })
+}
+
+fn record_wait(env: &Env, challenge: &Challenge) {
+  let mut stats = wait_stats(env);
+  stats.matches = stats.matches.saturating_add(1);
+  stats.total_wait_ledgers = stats.total_wait_ledgers.saturating_add(env.ledger().sequence().saturating_sub(challenge.posted_ledger) as u64);
+  store_lobby(env, &LobbyKey::WaitStats, &stats);
+}
+
+fn load_challenge(env: &Env, challenge_id: u32) -> Result<Challenge, Error> {
+  env.storage().persistent().get(&LobbyKey::Challenge(challenge_id)).ok_or(Error::ChallengeNotFound)
+}
+
+fn load_open(env: &Env) -> Vec<u32> {
+  env.storage().persistent().get(&LobbyKey::Open).unwrap_or(Vec::new(env))
+}
+
+fn store_open(env: &Env, open: &Vec<u32>) {
+  if open.is_empty() {
+    env.storage().persistent().remove(&LobbyKey::Open);
+    return;
+  }
+  store_lobby(env, &LobbyKey::Open, open);
+}
+
+fn remove_challenge(env: &Env, challenge_id: u32) {
+  env.storage().persistent().remove(&LobbyKey::Challenge(challenge_id));
+  let mut open = load_open(env);
+  if let Some(position) = open.first_index_of(challenge_id) { open.remove(position); }
+  store_open(env, &open);
+}
+
+fn refund_challenge(env: &Env, challenge: &Challenge) -> i128 {
+  let Some(token) = &challenge.token else { return 0; };
+  if challenge.stake <= 0 { return 0; }
+  token::Client::new(env, token).transfer(&env.current_contract_address(), &challenge.challenger, &challenge.stake);
+  adjust_liabilities(env, token, challenge.stake.saturating_neg());
+  challenge.stake
+}
+
+fn store_lobby<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &LobbyKey, value: &V) {
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
@@ -0,0 +1,103 @@
+use soroban_sdk::{contractimpl, contracttype, Bytes, Env, Vec};
+
+use crate::{BattleshipContract, BattleshipContractArgs, BattleshipContractClient, Game, PERSISTENT_STATE_TTL_LEDGERS};
+
+// One revealed shot, decoded from a log page. Buffered resolutions are logged as claimed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoggedMove {
+  pub by_player1: bool,
+  pub board: u32,
+  pub x: u32,
+  pub y: u32,
+  pub is_ship: bool,
+}
+
+// Kept with the pages so they decode after the game itself has expired. `run_offset` is where
+// the header of the last run on the current page sits.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MoveLogIndex {
+  pub board_size: u32,
+  pub moves: u32,
+  pub pages: u32,
+  pub run_offset: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MoveLogKey { Index(u32), Page(u32, u32) }
+
+pub const MOVES_PER_PAGE: u32 = 32;
+
+// A page is a sequence of runs. A run is a header byte (bit 7 set when player 2 fired, low
+// bits the run length), the board index, then two bytes per shot: the hit flag in the top bit
+// and the cell index below it. Consecutive shots by one player on one board share a header.
+const RUN_PLAYER2: u8 = 0x80;
+const RUN_LENGTH_MASK: u8 = 0x7f;
+const CELL_HIT: u16 = 0x8000;
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn get_move_log_index(env: Env, session_id: u32) -> MoveLogIndex {
+    load_index(&env, session_id)
+  }
+
+  // Pages hold `MOVES_PER_PAGE` moves each; a page past the end reads as empty.
+  pub fn get_move_log_page(env: Env, session_id: u32, page: u32) -> Vec<LoggedMove> {
+    let mut moves = Vec::new(&env);
+    let Some(bytes) = env.storage().persistent().get::<MoveLogKey, Bytes>(&MoveLogKey::Page(session_id, page)) else { return moves; };
+    let board_size = load_index(&env, session_id).board_size.max(1);
+    let mut offset = 0u32;
+    while offset + 2 <= bytes.len() {
+      let header = bytes.get_unchecked(offset);
+      let board = u32::from(bytes.get_unchecked(offset + 1));
+      offset += 2;
+      for _ in 0..(header & RUN_LENGTH_MASK) {
+        let cell = u16::from_be_bytes([bytes.get_unchecked(offset), bytes.get_unchecked(offset + 1)]);
+        offset += 2;
+        let index = u32::from(cell & !CELL_HIT);
+        moves.push_back(LoggedMove { by_player1: header & RUN_PLAYER2 == 0, board, x: index % board_size, y: index / board_size, is_ship: cell & CELL_HIT != 0 });
+      }
+    }
+    moves
+  }
+}
+
+// Called as each reveal is accepted, before the shot is recorded on the game, so the pending
+// attacker is still known. Only the current page is rewritten.
+pub(crate) fn append_move(env: &Env, session_id: u32, game: &Game, board: u32, x: u32, y: u32, is_ship: bool) {
+  let by_player2 = game.pending_attacker.as_ref() == Some(&game.player2);
+  let mut index = load_index(env, session_id);
+  let page = index.moves / MOVES_PER_PAGE;
+  let page_key = MoveLogKey::Page(session_id, page);
+  let mut bytes: Bytes = if index.moves.is_multiple_of(MOVES_PER_PAGE) { Bytes::new(env) } else { env.storage().persistent().get(&page_key).unwrap_or(Bytes::new(env)) };
+
+  let continues_run = !bytes.is_empty() && {
+    let header = bytes.get_unchecked(index.run_offset);
+    (header & RUN_PLAYER2 != 0) == by_player2 && u32::from(bytes.get_unchecked(index.run_offset + 1)) == board
+  };
+  if continues_run {
+    let header = bytes.get_unchecked(index.run_offset);
+    bytes.set(index.run_offset, header + 1);
+  } else {
+    index.run_offset = bytes.len();
+    bytes.push_back(if by_player2 { RUN_PLAYER2 | 1 } else { 1 });
+    bytes.push_back(board as u8);
+  }
+  let cell = (y.saturating_mul(game.board_size).saturating_add(x) as u16) | if is_ship { CELL_HIT } else { 0 };
+  bytes.extend_from_array(&cell.to_be_bytes());
+
+  index.board_size = game.board_size;
+  index.moves = index.moves.saturating_add(1);
+  index.pages = page.saturating_add(1);
+  env.storage().persistent().set(&page_key, &bytes);
+  env.storage().persistent().extend_ttl(&page_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+  let index_key = MoveLogKey::Index(session_id);
+  env.storage().persistent().set(&index_key, &index);
+  env.storage().persistent().extend_ttl(&index_key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
+
+fn load_index(env: &Env, session_id: u32) -> MoveLogIndex {
+  env.storage().persistent().get(&MoveLogKey::Index(session_id)).unwrap_or(MoveLogIndex { board_size: 0, moves: 0, pages: 0, run_offset: 0 })
+}
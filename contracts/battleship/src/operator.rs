@@ -0,0 +1,63 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, Symbol};
+
+use crate::{record_setting_change, require_admin, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, Error, BPS_DENOMINATOR, PERSISTENT_STATE_TTL_LEDGERS};
+
+// A front-end that games can be attributed to. `share_bps` of the protocol fee on its settled
+// wagers goes to `owner`; `games` and `fees_earned` are running totals for analytics.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Operator {
+  pub owner: Address,
+  pub share_bps: u32,
+  pub games: u32,
+  pub fees_earned: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OperatorKey { Operator(Symbol) }
+
+#[contractimpl]
+impl BattleshipContract {
+  // Claims `operator_id` for `owner`. Ids are first come, first served; the fee share starts at
+  // zero until the admin sets one.
+  pub fn register_operator(env: Env, operator_id: Symbol, owner: Address) -> Result<(), Error> {
+    owner.require_auth();
+    if env.storage().persistent().has(&OperatorKey::Operator(operator_id.clone())) { return Err(Error::OperatorTaken); }
+    store_operator(&env, &operator_id, &Operator { owner, share_bps: 0, games: 0, fees_earned: 0 });
+    Ok(())
+  }
+
+  pub fn set_operator_share(env: Env, operator_id: Symbol, share_bps: u32) -> Result<(), Error> {
+    require_admin(&env);
+    if i128::from(share_bps) > BPS_DENOMINATOR { return Err(Error::InvalidFeeBps); }
+    let mut operator = load_operator(&env, &operator_id)?;
+    operator.share_bps = share_bps;
+    store_operator(&env, &operator_id, &operator);
+    record_setting_change(&env, "operator_share", (operator_id, share_bps));
+    Ok(())
+  }
+
+  // Hands the id, and its future fee share, to `new_owner`.
+  pub fn transfer_operator(env: Env, operator_id: Symbol, new_owner: Address) -> Result<(), Error> {
+    let mut operator = load_operator(&env, &operator_id)?;
+    operator.owner.require_auth();
+    operator.owner = new_owner;
+    store_operator(&env, &operator_id, &operator);
+    Ok(())
+  }
+
+  pub fn get_operator(env: Env, operator_id: Symbol) -> Option<Operator> {
+    env.storage().persistent().get(&OperatorKey::Operator(operator_id))
+  }
+}
+
+pub(crate) fn load_operator(env: &Env, operator_id: &Symbol) -> Result<Operator, Error> {
+  env.storage().persistent().get(&OperatorKey::Operator(operator_id.clone())).ok_or(Error::UnknownOperator)
+}
+
+pub(crate) fn store_operator(env: &Env, operator_id: &Symbol, operator: &Operator) {
+  let key = OperatorKey::Operator(operator_id.clone());
+  env.storage().persistent().set(&key, operator);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
@@ -0,0 +1,200 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Env, Vec};
+
+use crate::tournament::load_tournament;
+use crate::{
+  adjust_liabilities, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, ConfigKey, Error, Tournament, TournamentFormat,
+  TournamentStatus, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PredictionContest {
+  pub token: Address,
+  pub entry_fee: i128,
+  pub first_round_matches: u32,
+  pub entrants: Vec<Address>,
+  pub pool: i128,
+  pub settled: bool,
+  pub top_score: u32,
+  pub top_scorers: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PredictionEntry {
+  pub picks: Vec<Address>,
+  pub claimed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PredictionKey { Contest(u32), Entry(u32, Address) }
+
+const MAX_PREDICTION_ENTRANTS: u32 = 128;
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn open_prediction_contest(env: Env, tournament_id: u32, entry_fee: i128) -> Result<(), Error> {
+    let tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.format != TournamentFormat::SingleElimination || predictions_locked(&tournament) { return Err(Error::PredictionsLocked); }
+    if env.storage().persistent().has(&PredictionKey::Contest(tournament_id)) { return Err(Error::InvalidTournamentState); }
+    if entry_fee <= 0 { return Err(Error::InvalidStakeAmount); }
+    let token: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+
+    let contest = PredictionContest {
+      token,
+      entry_fee,
+      first_round_matches: tournament.matches.len(),
+      entrants: Vec::new(&env),
+      pool: 0,
+      settled: false,
+      top_score: 0,
+      top_scorers: 0,
+    };
+    store_prediction(&env, &PredictionKey::Contest(tournament_id), &contest);
+    Ok(())
+  }
+
+  pub fn enter_prediction(env: Env, tournament_id: u32, spectator: Address, picks: Vec<Address>) -> Result<(), Error> {
+    spectator.require_auth();
+    let tournament = load_tournament(&env, tournament_id)?;
+    let mut contest = load_contest(&env, tournament_id)?;
+    if predictions_locked(&tournament) { return Err(Error::PredictionsLocked); }
+    if tournament.players.contains(&spectator) { return Err(Error::InvalidPrediction); }
+    let entry_key = PredictionKey::Entry(tournament_id, spectator.clone());
+    if env.storage().persistent().has(&entry_key) { return Err(Error::AlreadyJoined); }
+    if contest.entrants.len() >= MAX_PREDICTION_ENTRANTS { return Err(Error::TournamentFull); }
+    if !valid_bracket(&tournament, &picks) { return Err(Error::InvalidPrediction); }
+
+    let escrow = env.current_contract_address();
+    token::Client::new(&env, &contest.token).transfer(&spectator, &escrow, &contest.entry_fee);
+    adjust_liabilities(&env, &contest.token, contest.entry_fee);
+    contest.pool = contest.pool.saturating_add(contest.entry_fee);
+    contest.entrants.push_back(spectator);
+
+    store_prediction(&env, &entry_key, &PredictionEntry { picks, claimed: false });
+    store_prediction(&env, &PredictionKey::Contest(tournament_id), &contest);
+    Ok(())
+  }
+
+  pub fn get_prediction_contest(env: Env, tournament_id: u32) -> Result<PredictionContest, Error> {
+    load_contest(&env, tournament_id)
+  }
+
+  pub fn get_prediction_score(env: Env, tournament_id: u32, spectator: Address) -> Result<u32, Error> {
+    let tournament = load_tournament(&env, tournament_id)?;
+    let contest = load_contest(&env, tournament_id)?;
+    let entry = load_entry(&env, tournament_id, spectator)?;
+    Ok(score_picks(&entry.picks, &tournament.results, contest.first_round_matches))
+  }
+
+  pub fn settle_prediction_contest(env: Env, tournament_id: u32) -> Result<u32, Error> {
+    let tournament = load_tournament(&env, tournament_id)?;
+    let mut contest = load_contest(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Finished || contest.settled { return Err(Error::InvalidTournamentState); }
+
+    let (mut top_score, mut top_scorers) = (0, 0);
+    for spectator in contest.entrants.iter() {
+      let entry = load_entry(&env, tournament_id, spectator)?;
+      let score = score_picks(&entry.picks, &tournament.results, contest.first_round_matches);
+      if score > top_score || top_scorers == 0 {
+        top_score = score;
+        top_scorers = 1;
+      } else if score == top_score {
+        top_scorers += 1;
+      }
+    }
+    contest.settled = true;
+    contest.top_score = top_score;
+    contest.top_scorers = top_scorers;
+
+    // Whatever cannot be split evenly between the top scorers goes to the organizer.
+    let remainder = if top_scorers == 0 { contest.pool } else { contest.pool % i128::from(top_scorers) };
+    if remainder > 0 {
+      token::Client::new(&env, &contest.token).transfer(&env.current_contract_address(), &tournament.organizer, &remainder);
+      adjust_liabilities(&env, &contest.token, remainder.saturating_neg());
+      contest.pool = contest.pool.saturating_sub(remainder);
+    }
+    store_prediction(&env, &PredictionKey::Contest(tournament_id), &contest);
+    Ok(top_score)
+  }
+
+  pub fn claim_prediction_prize(env: Env, tournament_id: u32, spectator: Address) -> Result<i128, Error> {
+    spectator.require_auth();
+    let tournament = load_tournament(&env, tournament_id)?;
+    let contest = load_contest(&env, tournament_id)?;
+    if !contest.settled { return Err(Error::InvalidTournamentState); }
+    let entry_key = PredictionKey::Entry(tournament_id, spectator.clone());
+    let mut entry = load_entry(&env, tournament_id, spectator.clone())?;
+    if entry.claimed || score_picks(&entry.picks, &tournament.results, contest.first_round_matches) != contest.top_score {
+      return Err(Error::InvalidPrediction);
+    }
+
+    let prize = contest.pool / i128::from(contest.top_scorers);
+    token::Client::new(&env, &contest.token).transfer(&env.current_contract_address(), &spectator, &prize);
+    adjust_liabilities(&env, &contest.token, prize.saturating_neg());
+    entry.claimed = true;
+    store_prediction(&env, &entry_key, &entry);
+    Ok(prize)
+  }
+}
+
+// Scores a bracket prediction against the knockout results recorded so far. Both lists hold one winner
+// per match, round after round; a correct pick in round `r` is worth `2^(r-1)` points.
+pub fn score_picks(picks: &Vec<Address>, results: &Vec<Address>, first_round_matches: u32) -> u32 {
+  let (mut offset, mut round_matches, mut weight, mut score) = (0, first_round_matches, 1u32, 0u32);
+  while offset < results.len() && round_matches > 0 {
+    for index in offset..(offset + round_matches).min(results.len()) {
+      if picks.get(index) == results.get(index) { score = score.saturating_add(weight); }
+    }
+    if round_matches == 1 { break; }
+    offset += round_matches;
+    round_matches = round_matches.div_ceil(2);
+    weight = weight.saturating_mul(2);
+  }
+  score
+}
+
+// Predictions lock once any first-round match has been decided on the board; byes are known upfront.
+fn predictions_locked(tournament: &Tournament) -> bool {
+  if tournament.status != TournamentStatus::Running || tournament.round != 1 { return true; }
+  tournament.matches.iter().any(|entry| entry.player2.is_some() && entry.winner.is_some())
+}
+
+// A bracket picks a participant for every first-round match and, for each later match, the pick of
+// one of the two matches feeding into it.
+fn valid_bracket(tournament: &Tournament, picks: &Vec<Address>) -> bool {
+  let mut round_matches = tournament.matches.len();
+  for (index, entry) in tournament.matches.iter().enumerate() {
+    let Some(pick) = picks.get(index as u32) else { return false; };
+    if pick != entry.player1 && Some(pick) != entry.player2 { return false; }
+  }
+
+  let mut offset = 0;
+  while round_matches > 1 {
+    let next_offset = offset + round_matches;
+    for next_index in 0..round_matches.div_ceil(2) {
+      let Some(pick) = picks.get(next_offset + next_index) else { return false; };
+      let first_feeder = picks.get(offset + next_index * 2);
+      let second_feeder = if next_index * 2 + 1 < round_matches { picks.get(offset + next_index * 2 + 1) } else { None };
+      if Some(pick.clone()) != first_feeder && Some(pick) != second_feeder { return false; }
+    }
+    offset = next_offset;
+    round_matches = round_matches.div_ceil(2);
+  }
+  picks.len() == offset + 1
+}
+
+fn load_contest(env: &Env, tournament_id: u32) -> Result<PredictionContest, Error> {
+  env.storage().persistent().get(&PredictionKey::Contest(tournament_id)).ok_or(Error::TournamentNotFound)
+}
+
+fn load_entry(env: &Env, tournament_id: u32, spectator: Address) -> Result<PredictionEntry, Error> {
+  env.storage().persistent().get(&PredictionKey::Entry(tournament_id, spectator)).ok_or(Error::InvalidPrediction)
+}
+
+fn store_prediction<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &PredictionKey, value: &V) {
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
@@ -0,0 +1,119 @@
+use soroban_sdk::{contractimpl, contracttype, symbol_short, token, Address, Env, Symbol, Vec};
+
+use crate::{
+  adjust_liabilities, missing_game, require_phase, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, ConfigKey, DataKey, Error,
+  Game, ENDED_PHASES, LIVE_PHASES, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProverListing {
+  pub fee: i128,
+  pub circuits: Vec<Symbol>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProverEngagement {
+  pub service: Address,
+  pub token: Address,
+  pub fee: i128,
+  pub escrowed: i128,
+  pub proofs_paid: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProverKey { Listing(Address), Engagement(u32, Address) }
+
+pub const CIRCUIT_ATTACK: Symbol = symbol_short!("attack");
+
+const MAX_PROVER_CIRCUITS: u32 = 8;
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn register_prover(env: Env, service: Address, fee: i128, circuits: Vec<Symbol>) -> Result<(), Error> {
+    service.require_auth();
+    if fee < 0 { return Err(Error::InvalidStakeAmount); }
+    if circuits.is_empty() || circuits.len() > MAX_PROVER_CIRCUITS { return Err(Error::InvalidConfig); }
+    store_prover(&env, &ProverKey::Listing(service), &ProverListing { fee, circuits });
+    Ok(())
+  }
+
+  // Games that already engaged the service keep the fee they locked in.
+  pub fn unregister_prover(env: Env, service: Address) -> Result<(), Error> {
+    service.require_auth();
+    let key = ProverKey::Listing(service);
+    if !env.storage().persistent().has(&key) { return Err(Error::ProverNotRegistered); }
+    env.storage().persistent().remove(&key);
+    Ok(())
+  }
+
+  pub fn get_prover(env: Env, service: Address) -> Option<ProverListing> {
+    env.storage().persistent().get(&ProverKey::Listing(service))
+  }
+
+  pub fn select_prover(env: Env, session_id: u32, player: Address, service: Address, proofs: u32) -> Result<(), Error> {
+    player.require_auth();
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &LIVE_PHASES)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    if proofs == 0 { return Err(Error::InvalidStakeAmount); }
+
+    let listing: ProverListing = env.storage().persistent().get(&ProverKey::Listing(service.clone())).ok_or(Error::ProverNotRegistered)?;
+    if !listing.circuits.contains(CIRCUIT_ATTACK) { return Err(Error::UnsupportedCircuit); }
+    let key = ProverKey::Engagement(session_id, player.clone());
+    if env.storage().persistent().has(&key) { return Err(Error::AlreadyDeposited); }
+
+    let token: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+    let escrowed = listing.fee.saturating_mul(proofs as i128);
+    if escrowed > 0 {
+      let escrow = env.current_contract_address();
+      token::Client::new(&env, &token).transfer(&player, &escrow, &escrowed);
+      adjust_liabilities(&env, &token, escrowed);
+    }
+    store_prover(&env, &key, &ProverEngagement { service, token, fee: listing.fee, escrowed, proofs_paid: 0 });
+    Ok(())
+  }
+
+  pub fn get_prover_engagement(env: Env, session_id: u32, player: Address) -> Option<ProverEngagement> {
+    env.storage().persistent().get(&ProverKey::Engagement(session_id, player))
+  }
+
+  // Unspent proving escrow goes back to the player once the game is over or has expired.
+  pub fn withdraw_prover_escrow(env: Env, session_id: u32, player: Address) -> Result<i128, Error> {
+    player.require_auth();
+    if let Some(game) = env.storage().temporary().get::<DataKey, Game>(&DataKey::Game(session_id)) {
+      require_phase(&game, &ENDED_PHASES)?;
+    }
+    let key = ProverKey::Engagement(session_id, player.clone());
+    let mut engagement: ProverEngagement = env.storage().persistent().get(&key).ok_or(Error::NothingToRecover)?;
+    if engagement.escrowed <= 0 { return Err(Error::NothingToRecover); }
+
+    let refund = engagement.escrowed;
+    token::Client::new(&env, &engagement.token).transfer(&env.current_contract_address(), &player, &refund);
+    adjust_liabilities(&env, &engagement.token, refund.saturating_neg());
+    engagement.escrowed = 0;
+    store_prover(&env, &key, &engagement);
+    Ok(refund)
+  }
+}
+
+// Pays the engaged service for one verified proof it submitted as the defender's delegate.
+// Proofs submitted by anyone else, or after the escrow runs dry, release nothing.
+pub(crate) fn pay_prover(env: &Env, session_id: u32, defender: &Address, delegate: &Address) {
+  let key = ProverKey::Engagement(session_id, defender.clone());
+  let Some(mut engagement) = env.storage().persistent().get::<ProverKey, ProverEngagement>(&key) else { return; };
+  if engagement.service != *delegate || engagement.fee <= 0 || engagement.escrowed < engagement.fee { return; }
+
+  token::Client::new(env, &engagement.token).transfer(&env.current_contract_address(), delegate, &engagement.fee);
+  adjust_liabilities(env, &engagement.token, engagement.fee.saturating_neg());
+  engagement.escrowed = engagement.escrowed.saturating_sub(engagement.fee);
+  engagement.proofs_paid = engagement.proofs_paid.saturating_add(1);
+  store_prover(env, &key, &engagement);
+}
+
+fn store_prover<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &ProverKey, value: &V) {
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
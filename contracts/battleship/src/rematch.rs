@@ -0,0 +1,118 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env};
+
+use crate::{
+  create_game, escrow_token, mark_deposited, missing_game, require_phase, store_game, Backing, BattleshipContract, BattleshipContractArgs,
+  BattleshipContractClient, DataKey, Error, EscrowKey, Game, GamePhase, StakeDeposited, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+// A standing offer to play `new_session_id` as the next game between the same pair, with the
+// same stakes or, for double-or-nothing, twice them.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RematchOffer {
+  pub requested_by: Address,
+  pub new_session_id: u32,
+  pub double_or_nothing: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RematchKey { Offer(u32) }
+
+#[contractimpl]
+impl BattleshipContract {
+  // May be made while the game is still running. While an offer stands, settlement credits the
+  // players' winnings as withdrawable instead of sending them, so they can roll over.
+  pub fn request_rematch(env: Env, session_id: u32, player: Address, new_session_id: u32, double_or_nothing: bool) -> Result<(), Error> {
+    player.require_auth();
+    let game = load_game(&env, session_id)?;
+    if player != game.player1 && player != game.player2 { return Err(Error::NotPlayer); }
+    if game.phase == GamePhase::Cancelled || game.drawn { return Err(Error::GameAlreadyEnded); }
+    if new_session_id == session_id { return Err(Error::SessionInUse); }
+
+    let key = RematchKey::Offer(session_id);
+    env.storage().persistent().set(&key, &RematchOffer { requested_by: player, new_session_id, double_or_nothing });
+    env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    Ok(())
+  }
+
+  pub fn get_rematch_offer(env: Env, session_id: u32) -> Option<RematchOffer> {
+    env.storage().persistent().get(&RematchKey::Offer(session_id))
+  }
+
+  // The opponent accepts once the game is settled. Each side's stake is funded first from its
+  // withdrawable balance in the escrow token, recorded as the player backing their own side;
+  // whatever is left is deposited with `deposit_stake` as usual. Returns the new session id.
+  pub fn accept_rematch(env: Env, session_id: u32, player: Address) -> Result<u32, Error> {
+    player.require_auth();
+    let game = load_game(&env, session_id)?;
+    let key = RematchKey::Offer(session_id);
+    let offer: RematchOffer = env.storage().persistent().get(&key).ok_or(Error::NoRematchOffer)?;
+    if player == offer.requested_by || (player != game.player1 && player != game.player2) { return Err(Error::NotPlayer); }
+    require_phase(&game, &[GamePhase::Finished])?;
+    if game.drawn { return Err(Error::GameAlreadyEnded); }
+
+    let multiplier = if offer.double_or_nothing { 2 } else { 1 };
+    let player1_stake = game.player1_stake.saturating_mul(multiplier);
+    let player2_stake = game.player2_stake.saturating_mul(multiplier);
+    let new_session_id = offer.new_session_id;
+    create_game(
+      &env,
+      new_session_id,
+      game.player1.clone(),
+      game.player2.clone(),
+      game.player1_points,
+      game.player2_points,
+      player1_stake,
+      player2_stake,
+      game.capabilities,
+      game.ttl_ledgers,
+    )?;
+    env.storage().persistent().remove(&key);
+
+    let mut rematch: Game = env.storage().temporary().get(&DataKey::Game(new_session_id)).ok_or(Error::GameNotFound)?;
+    if player1_stake > 0 || player2_stake > 0 {
+      let token_contract = escrow_token(&env, new_session_id)?;
+      let rolled1 = roll_over(&env, &rematch.player1, &token_contract, player1_stake);
+      let rolled2 = roll_over(&env, &rematch.player2, &token_contract, player2_stake);
+      if rolled1 > 0 {
+        StakeDeposited { session_id: new_session_id, player: rematch.player1.clone(), from: rematch.player1.clone(), token: token_contract.clone(), amount: rolled1 }.publish(&env);
+        rematch.player1_backers.push_back(Backing { backer: rematch.player1.clone(), amount: rolled1 });
+        rematch.player1_backed = rolled1;
+        if rolled1 >= player1_stake { mark_deposited(&mut rematch, true); }
+      }
+      if rolled2 > 0 {
+        StakeDeposited { session_id: new_session_id, player: rematch.player2.clone(), from: rematch.player2.clone(), token: token_contract.clone(), amount: rolled2 }.publish(&env);
+        rematch.player2_backers.push_back(Backing { backer: rematch.player2.clone(), amount: rolled2 });
+        rematch.player2_backed = rolled2;
+        if rolled2 >= player2_stake { mark_deposited(&mut rematch, false); }
+      }
+      store_game(&env, new_session_id, &rematch);
+    }
+    Ok(new_session_id)
+  }
+}
+
+// True while an offer stands, so `settle_wager` holds the players' winnings for a rollover.
+pub(crate) fn holds_winnings(env: &Env, session_id: u32) -> bool {
+  env.storage().persistent().has(&RematchKey::Offer(session_id))
+}
+
+// Moves up to `stake` out of the player's withdrawable balance. Both sides of the move are
+// contract liabilities, so the totals are unchanged.
+fn roll_over(env: &Env, player: &Address, token_contract: &Address, stake: i128) -> i128 {
+  let key = EscrowKey::Withdrawable(player.clone(), token_contract.clone());
+  let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+  let rolled = balance.min(stake).max(0);
+  if rolled == 0 { return 0; }
+  if rolled == balance {
+    env.storage().persistent().remove(&key);
+  } else {
+    env.storage().persistent().set(&key, &balance.saturating_sub(rolled));
+  }
+  rolled
+}
+
+fn load_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+  env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(env, session_id))
+}
@@ -0,0 +1,112 @@
+use battlefield_common::digest::state_digest;
+use soroban_sdk::xdr::{FromXdr, ToXdr};
+use soroban_sdk::{contractimpl, contracttype, Address, Bytes, BytesN, Env, Map, String, Vec};
+
+use crate::channel::{store_channel, Channel, ChannelKey};
+use crate::prover::{ProverEngagement, ProverKey};
+use crate::{
+  adjust_liabilities, count_escrowed_game, missing_game, require_admin, store_game, Annotation, AnnotationKey, BattleshipContract, BattleshipContractArgs,
+  BattleshipContractClient, DataKey, Error, EscrowKey, EscrowRecord, Game, GameTombstone, ReplayAttachment, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+// Session grants are keyed by delegate and cannot be enumerated, so they are not part of a
+// snapshot; players re-authorize their delegates on the new deployment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameSnapshot {
+  pub version: u32,
+  pub session_id: u32,
+  pub game: Game,
+  pub tombstone: GameTombstone,
+  pub escrow: Vec<EscrowRecord>,
+  pub void_reason: Option<String>,
+  pub prover_engagements: Map<Address, ProverEngagement>,
+  pub annotations: Vec<Annotation>,
+  pub replays: Vec<ReplayAttachment>,
+  pub channel: Vec<Channel>,
+}
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn export_game(env: Env, session_id: u32) -> Result<Bytes, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    let persistent = env.storage().persistent();
+    let tombstone: GameTombstone = persistent.get(&DataKey::Tombstone(session_id)).ok_or(Error::GameNotFound)?;
+
+    let mut escrow = Vec::new(&env);
+    if let Some(record) = persistent.get::<EscrowKey, EscrowRecord>(&EscrowKey::Held(session_id)) { escrow.push_back(record); }
+    let mut channel = Vec::new(&env);
+    if let Some(open) = persistent.get::<ChannelKey, Channel>(&ChannelKey::Channel(session_id)) { channel.push_back(open); }
+    let mut prover_engagements = Map::new(&env);
+    for player in [game.player1.clone(), game.player2.clone()] {
+      if let Some(engagement) = persistent.get::<ProverKey, ProverEngagement>(&ProverKey::Engagement(session_id, player.clone())) {
+        prover_engagements.set(player, engagement);
+      }
+    }
+
+    let snapshot = GameSnapshot {
+      version: SNAPSHOT_VERSION,
+      session_id,
+      tombstone,
+      escrow,
+      void_reason: persistent.get(&DataKey::VoidedSession(session_id)),
+      prover_engagements,
+      annotations: persistent.get(&AnnotationKey::Game(session_id)).unwrap_or(Vec::new(&env)),
+      replays: persistent.get(&AnnotationKey::Replays(session_id)).unwrap_or(Vec::new(&env)),
+      channel,
+      game,
+    };
+    Ok(snapshot.to_xdr(&env))
+  }
+
+  // Hash of the live `Game` in the common crate's canonical encoding, which players co-sign
+  // off-chain as the state advances. Unlike `export_game` it covers the game record alone.
+  pub fn get_game_digest(env: Env, session_id: u32) -> Result<BytesN<32>, Error> {
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    Ok(state_digest(&env, &env.current_contract_address(), session_id, game))
+  }
+
+  // Tokens are not moved by an import: escrowed amounts are added to this deployment's
+  // liabilities, so `check_solvency` reports a shortfall until the funds are transferred over.
+  pub fn import_game(env: Env, snapshot: Bytes) -> Result<u32, Error> {
+    require_admin(&env);
+    let snapshot = GameSnapshot::from_xdr(&env, &snapshot).map_err(|_| Error::InvalidSnapshot)?;
+    if snapshot.version != SNAPSHOT_VERSION { return Err(Error::InvalidSnapshot); }
+    let session_id = snapshot.session_id;
+    if env.storage().persistent().has(&DataKey::Tombstone(session_id)) || env.storage().temporary().has(&DataKey::Game(session_id)) {
+      return Err(Error::SessionInUse);
+    }
+
+    // The tombstone and escrow record go in first so `store_game` keeps their original
+    // creation ledger and token instead of deriving fresh ones.
+    store_persistent(&env, &DataKey::Tombstone(session_id), &snapshot.tombstone);
+    if snapshot.tombstone.escrowed { count_escrowed_game(&env, session_id, true); }
+    if let Some(record) = snapshot.escrow.get(0) {
+      let held = record.shares.iter().fold(0i128, |total, share| total.saturating_add(share.amount));
+      adjust_liabilities(&env, &record.token, held);
+      store_persistent(&env, &EscrowKey::Held(session_id), &record);
+    }
+    store_game(&env, session_id, &snapshot.game);
+
+    if let Some(reason) = snapshot.void_reason { store_persistent(&env, &DataKey::VoidedSession(session_id), &reason); }
+    for (player, engagement) in snapshot.prover_engagements.iter() {
+      if engagement.escrowed > 0 { adjust_liabilities(&env, &engagement.token, engagement.escrowed); }
+      store_persistent(&env, &ProverKey::Engagement(session_id, player), &engagement);
+    }
+    if !snapshot.annotations.is_empty() { store_persistent(&env, &AnnotationKey::Game(session_id), &snapshot.annotations); }
+    if !snapshot.replays.is_empty() { store_persistent(&env, &AnnotationKey::Replays(session_id), &snapshot.replays); }
+    if let Some(channel) = snapshot.channel.get(0) { store_channel(&env, session_id, &channel); }
+    Ok(session_id)
+  }
+}
+
+fn store_persistent<K, V>(env: &Env, key: &K, value: &V)
+where
+  K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+  V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+{
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
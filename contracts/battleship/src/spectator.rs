@@ -0,0 +1,146 @@
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, Env, Vec};
+
+use crate::{
+  missing_game, require_phase, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, ConfigKey, DataKey, Deadline, Error, Game, BPS_DENOMINATOR,
+  LIVE_PHASES, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+// Premium matches charge `price` in `token` for a ticket. `player_share_bps` of each ticket is
+// split evenly between the two players; the rest, odd stroop included, goes to the fee recipient.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayPerView {
+  pub token: Address,
+  pub price: i128,
+  pub player_share_bps: u32,
+  pub tickets_sold: u32,
+  pub revenue: i128,
+}
+
+// The enriched live view. Grids are empty for multi-board games, as in `get_boards_view`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiveView {
+  pub board_size: u32,
+  pub player1_grid: Bytes,
+  pub player2_grid: Bytes,
+  pub player1_hits: u32,
+  pub player2_hits: u32,
+  pub turn: Option<Address>,
+  pub pending_defender: Option<Address>,
+  pub deadlines: Vec<Deadline>,
+  pub spectators: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SpectatorKey { PayPerView(u32), Registry(u32) }
+
+pub const MAX_SPECTATORS: u32 = 256;
+
+#[contractimpl]
+impl BattleshipContract {
+  // Both players opt the match into pay-per-view; a zero price makes it free again. The price
+  // is fixed once the first ticket sells.
+  pub fn set_pay_per_view(env: Env, session_id: u32, price: i128, player_share_bps: u32) -> Result<(), Error> {
+    let game = load_game(&env, session_id)?;
+    game.player1.require_auth();
+    game.player2.require_auth();
+    require_phase(&game, &LIVE_PHASES)?;
+    if price < 0 { return Err(Error::InvalidStakeAmount); }
+    if i128::from(player_share_bps) > BPS_DENOMINATOR { return Err(Error::InvalidConfig); }
+
+    let key = SpectatorKey::PayPerView(session_id);
+    if let Some(current) = env.storage().persistent().get::<SpectatorKey, PayPerView>(&key) {
+      if current.tickets_sold > 0 { return Err(Error::InvalidConfig); }
+    }
+    if price == 0 {
+      env.storage().persistent().remove(&key);
+      return Ok(());
+    }
+    let token: Address = env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?;
+    let view = PayPerView { token, price, player_share_bps, tickets_sold: 0, revenue: 0 };
+    store_spectator(&env, &key, &view);
+    Ok(())
+  }
+
+  pub fn get_pay_per_view(env: Env, session_id: u32) -> Option<PayPerView> {
+    env.storage().persistent().get(&SpectatorKey::PayPerView(session_id))
+  }
+
+  // Registers `viewer` as a spectator of a live game, buying a ticket if the match is premium.
+  // Returns the amount charged; registering twice is free.
+  pub fn join_spectators(env: Env, session_id: u32, viewer: Address) -> Result<i128, Error> {
+    viewer.require_auth();
+    let game = load_game(&env, session_id)?;
+    require_phase(&game, &LIVE_PHASES)?;
+
+    let registry_key = SpectatorKey::Registry(session_id);
+    let mut registry: Vec<Address> = env.storage().persistent().get(&registry_key).unwrap_or(Vec::new(&env));
+    if registry.contains(&viewer) { return Ok(0); }
+    if registry.len() >= MAX_SPECTATORS { return Err(Error::SpectatorsFull); }
+
+    let mut charged = 0;
+    let view_key = SpectatorKey::PayPerView(session_id);
+    if let Some(mut view) = env.storage().persistent().get::<SpectatorKey, PayPerView>(&view_key) {
+      let fee_recipient: Address = env.storage().instance().get(&ConfigKey::FeeRecipient).expect("Fee recipient not set");
+      let player_share = view.price.saturating_mul(i128::from(view.player_share_bps)) / BPS_DENOMINATOR / 2;
+      let protocol_share = view.price.saturating_sub(player_share.saturating_mul(2));
+      let token_client = token::Client::new(&env, &view.token);
+      if player_share > 0 {
+        token_client.transfer(&viewer, &game.player1, &player_share);
+        token_client.transfer(&viewer, &game.player2, &player_share);
+      }
+      if protocol_share > 0 { token_client.transfer(&viewer, &fee_recipient, &protocol_share); }
+
+      view.tickets_sold = view.tickets_sold.saturating_add(1);
+      view.revenue = view.revenue.saturating_add(view.price);
+      store_spectator(&env, &view_key, &view);
+      charged = view.price;
+    }
+
+    registry.push_back(viewer);
+    store_spectator(&env, &registry_key, &registry);
+    Ok(charged)
+  }
+
+  pub fn get_spectators(env: Env, session_id: u32) -> Vec<Address> {
+    env.storage().persistent().get(&SpectatorKey::Registry(session_id)).unwrap_or(Vec::new(&env))
+  }
+
+  // Free on ordinary matches. On premium ones the viewer signs and must be a player or hold a
+  // ticket; `get_game` and `get_boards_view` stay open to everyone either way.
+  pub fn get_live_view(env: Env, session_id: u32, viewer: Address) -> Result<LiveView, Error> {
+    let game = load_game(&env, session_id)?;
+    let spectators = Self::get_spectators(env.clone(), session_id);
+    if env.storage().persistent().has(&SpectatorKey::PayPerView(session_id)) {
+      viewer.require_auth();
+      if viewer != game.player1 && viewer != game.player2 && !spectators.contains(&viewer) { return Err(Error::TicketRequired); }
+    }
+
+    let (player1_grid, player2_grid) = match Self::get_boards_view(env.clone(), session_id) {
+      Ok(boards) => (boards.player1, boards.player2),
+      Err(_) => (Bytes::new(&env), Bytes::new(&env)),
+    };
+    Ok(LiveView {
+      board_size: game.board_size,
+      player1_grid,
+      player2_grid,
+      player1_hits: game.player1_hits,
+      player2_hits: game.player2_hits,
+      turn: game.turn.clone(),
+      pending_defender: game.pending_defender.clone(),
+      deadlines: Self::get_deadlines(env.clone(), session_id)?,
+      spectators: spectators.len(),
+    })
+  }
+}
+
+fn load_game(env: &Env, session_id: u32) -> Result<Game, Error> {
+  env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(env, session_id))
+}
+
+fn store_spectator<V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(env: &Env, key: &SpectatorKey, value: &V) {
+  env.storage().persistent().set(key, value);
+  env.storage().persistent().extend_ttl(key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
@@ -1,8 +1,29 @@
 #![cfg(test)]
 
-use crate::{BattleshipContract, BattleshipContractClient, Error};
-use soroban_sdk::testutils::{Address as _, Ledger as _};
-use soroban_sdk::{contract, contractimpl, Address, Bytes, BytesN, Env, Vec};
+use crate::{
+    attack_proof_hash, bit_count, build_board_proof_message, build_merkle_proof, cell_commitment, compute_commitment_root, compute_merkle_root,
+    credit_withdrawable, device_attack_message, device_resolve_message, has_bit, indexed_cell_commitment, nonce_commitment, queued_shot_commitment, set_bit, testutils,
+    transcript_step, transform_cell, AbandonPolicy, AttackMade, AttackResolved, BattleshipContract, BattleshipContractClient, BoardCommitted, Bracket,
+    ConfigBundle, ConfigChanged, ConfigRecord, CostQuote, DataKey, Deadline, DeviceKey, Error, FleetConfig, Game, GameDrawn, GameEnded, GameInit,
+    GamePhase, GameResult, GameSnapshot, GameStarted, GameStatus, HashAlgorithm, HubRotated, KeeperAction, KeeperConfig, LegalActions, LoggedMove,
+    MatchPolicy, MoveReveal, Obligation, PassConfig, Payout, PendingUpgrade, PrizeSplit, QueueStatus, RandomnessSource, RateLimit, Resigned,
+    RewardMultiplied, RewardWindow, SessionGrant, Settlement, StakeDeposited, TimeControl, TimeoutWin, TipReceived, TokenGate, TournamentFormat,
+    TournamentResultForced, TournamentSchedule, TournamentStatus, TtlPolicy, UpgradeFreeze, UpgradeProposed, WeakBoardCommitment, CAP_SALVO,
+    CAP_TIMEOUTS, CAP_ZK, CHANNEL_CHALLENGE_LEDGERS, CIRCUIT_ATTACK, COMMITMENT_V2, DEVICE_SCOPE_ALL, DEVICE_SCOPE_ATTACK, DEVICE_SCOPE_RESOLVE,
+    EVENT_SCHEMA_VERSION, GRID_HIT, GRID_MISS, GRID_OPEN, GRID_PENDING, MOVES_PER_PAGE, SNAPSHOT_VERSION,
+};
+use battlefield_common::digest::{canonical_state, checkpoint_message, state_digest};
+use battlefield_common::events::{TOPIC_CONFIG, TOPIC_WEAK_BOARD};
+use ed25519_dalek::{Signer, SigningKey};
+use multisig_admin::{MultisigAdmin, Signature as MultisigSignature};
+use scripted_bot::{ScriptedBot, ScriptedBotClient, Shot};
+use soroban_sdk::testutils::storage::{Persistent as _, Temporary as _};
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::xdr::{
+    ContractEventBody, FromXdr, HashIdPreimage, HashIdPreimageSorobanAuthorization, Limits, ScSymbol, ScVal, SorobanAuthorizationEntry,
+    SorobanCredentials, ToXdr, WriteXdr,
+};
+use soroban_sdk::{contract, contractimpl, symbol_short, token, vec, Address, Bytes, BytesN, Env, Event, IntoVal, String, Symbol, Vec};
 
 #[contract]
 pub struct TestGameHub;
@@ -22,9 +43,191 @@ impl TestGameHub {
 
     pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {}
 
+    pub fn end_game_draw(_env: Env, _session_id: u32) {}
+
     pub fn add_game(_env: Env, _game_address: Address) {}
 }
 
+#[contract]
+pub struct FlakyGameHub;
+
+#[contractimpl]
+impl FlakyGameHub {
+    pub fn start_game(
+        _env: Env,
+        _game_id: Address,
+        _session_id: u32,
+        _player1: Address,
+        _player2: Address,
+        _player1_points: i128,
+        _player2_points: i128,
+    ) {
+    }
+
+    pub fn end_game(env: Env, session_id: u32, _player1_won: bool) {
+        if env.storage().instance().get(&symbol_short!("failing")).unwrap_or(false) {
+            panic!("hub unavailable");
+        }
+        env.storage().instance().set(&session_id, &true);
+    }
+
+    pub fn end_game_draw(env: Env, session_id: u32) {
+        Self::end_game(env, session_id, false);
+    }
+
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage().instance().set(&symbol_short!("failing"), &failing);
+    }
+
+    pub fn was_ended(env: Env, session_id: u32) -> bool {
+        env.storage().instance().get(&session_id).unwrap_or(false)
+    }
+
+    pub fn report_stats(env: Env, session_id: u32, player1_hits: u32, player2_hits: u32, moves: u32, duration: u32) {
+        env.storage().instance().set(&(symbol_short!("stats"), session_id), &(player1_hits, player2_hits, moves, duration));
+    }
+
+    pub fn stats(env: Env, session_id: u32) -> Option<(u32, u32, u32, u32)> {
+        env.storage().instance().get(&(symbol_short!("stats"), session_id))
+    }
+}
+
+#[contract]
+pub struct TestZkVerifier;
+
+#[contractimpl]
+impl TestZkVerifier {
+    pub fn verify_board(_env: Env, _session_id: u32, _ship_cells: u32, _commitment_root: BytesN<32>, _proof: Bytes) -> bool {
+        true
+    }
+
+    pub fn verify_attack(
+        _env: Env,
+        _session_id: u32,
+        _x: u32,
+        _y: u32,
+        _expected_commitment: BytesN<32>,
+        proof: Bytes,
+    ) -> bool {
+        proof.get(0).unwrap_or(0) == 1
+    }
+
+    pub fn verify_transcript(
+        _env: Env,
+        _session_id: u32,
+        _from_move: u32,
+        _to_move: u32,
+        _transcript_root: BytesN<32>,
+        proof: Bytes,
+    ) -> bool {
+        proof.get(0).unwrap_or(0) == 1
+    }
+}
+
+#[contract]
+pub struct TrappingZkVerifier;
+
+#[contractimpl]
+impl TrappingZkVerifier {
+    pub fn verify_board(_env: Env, _session_id: u32, _ship_cells: u32, _commitment_root: BytesN<32>, proof: Bytes) -> bool {
+        if proof.is_empty() {
+            panic!("verifier out of service");
+        }
+        false
+    }
+
+    pub fn verify_attack(
+        _env: Env,
+        _session_id: u32,
+        _x: u32,
+        _y: u32,
+        _expected_commitment: BytesN<32>,
+        _proof: Bytes,
+    ) -> bool {
+        panic!("verifier out of service");
+    }
+}
+
+#[contract]
+pub struct AllowListChecker;
+
+#[contractimpl]
+impl AllowListChecker {
+    pub fn allow(env: Env, player: Address) {
+        env.storage().persistent().set(&player, &true);
+    }
+
+    pub fn is_eligible(env: Env, player: Address) -> bool {
+        env.storage().persistent().has(&player)
+    }
+}
+
+#[contract]
+pub struct TestCollection;
+
+#[contractimpl]
+impl TestCollection {
+    pub fn mint(env: Env, token_id: u32, owner: Address) {
+        env.storage().persistent().set(&token_id, &owner);
+    }
+
+    pub fn owner_of(env: Env, token_id: u32) -> Address {
+        env.storage().persistent().get(&token_id).unwrap()
+    }
+}
+
+#[contract]
+pub struct V2GameHub;
+
+#[contractimpl]
+impl V2GameHub {
+    pub fn start_game(
+        _env: Env,
+        _game_id: Address,
+        _session_id: u32,
+        _player1: Address,
+        _player2: Address,
+        _player1_points: i128,
+        _player2_points: i128,
+    ) {
+    }
+
+    pub fn end_game(_env: Env, _session_id: u32, _player1_won: bool) {
+        panic!("legacy end_game called on a v2 hub");
+    }
+
+    pub fn end_game_v2(env: Env, session_id: u32, result: GameResult) {
+        env.storage().instance().set(&session_id, &result);
+    }
+
+    pub fn result(env: Env, session_id: u32) -> Option<GameResult> {
+        env.storage().instance().get(&session_id)
+    }
+
+    pub fn end_game_draw(env: Env, session_id: u32) {
+        env.storage().persistent().set(&session_id, &true);
+    }
+
+    pub fn drawn(env: Env, session_id: u32) -> bool {
+        env.storage().persistent().has(&session_id)
+    }
+}
+
+#[contract]
+pub struct TestBeacon;
+
+#[contractimpl]
+impl TestBeacon {
+    pub fn request_randomness(env: Env, consumer: Address, request_id: u32) {
+        env.storage().instance().set(&request_id, &consumer);
+    }
+
+    pub fn deliver(env: Env, request_id: u32, randomness: BytesN<32>) {
+        let consumer: Address = env.storage().instance().get(&request_id).unwrap();
+        BattleshipContractClient::new(&env, &consumer).fulfill_randomness(&request_id, &randomness);
+    }
+}
+
 fn setup_test() -> (
     Env,
     BattleshipContractClient<'static>,
@@ -50,7 +253,7 @@ fn setup_test() -> (
     let game_hub = TestGameHubClient::new(&env, &hub_addr);
 
     let admin = Address::generate(&env);
-    let contract_id = env.register(BattleshipContract, (&admin, &hub_addr));
+    let contract_id = env.register(BattleshipContract, (&admin, &hub_addr, None::<ConfigBundle>));
     let client = BattleshipContractClient::new(&env, &contract_id);
 
     game_hub.add_game(&contract_id);
@@ -58,12 +261,25 @@ fn setup_test() -> (
     let player1 = Address::generate(&env);
     let player2 = Address::generate(&env);
 
+    let token_admin = Address::generate(&env);
+    let bet_token = env.register_stellar_asset_contract_v2(token_admin).address();
+    let token_admin_client = token::StellarAssetClient::new(&env, &bet_token);
+    token_admin_client.mint(&player1, &1000_0000000i128);
+    token_admin_client.mint(&player2, &1000_0000000i128);
+    client.set_bet_token(&bet_token);
+
     (env, client, player1, player2, hub_addr)
 }
 
-fn commit_for(env: &Env, is_ship: bool) -> [u8; 32] {
+fn fund_game(client: &BattleshipContractClient, session_id: u32, player1: &Address, player2: &Address) {
+    client.deposit_stake(&session_id, player1);
+    client.deposit_stake(&session_id, player2);
+}
+
+fn commit_for(env: &Env, index: u32, is_ship: bool) -> [u8; 32] {
     let mut payload = Bytes::new(env);
     payload.push_back(if is_ship { 1 } else { 0 });
+    payload.extend_from_array(&index.to_be_bytes());
     payload.append(&Bytes::from_array(env, &[9u8; 32]));
     env.crypto().keccak256(&payload).to_array()
 }
@@ -71,8 +287,6 @@ fn commit_for(env: &Env, is_ship: bool) -> [u8; 32] {
 fn build_board(env: &Env, board_size: u32, ship_indexes: &[u32]) -> Vec<BytesN<32>> {
     let mut board = Vec::new(env);
     let total = board_size * board_size;
-    let hit = commit_for(env, true);
-    let miss = commit_for(env, false);
 
     for i in 0..total {
         let mut is_ship = false;
@@ -84,11 +298,7 @@ fn build_board(env: &Env, board_size: u32, ship_indexes: &[u32]) -> Vec<BytesN<3
             }
             idx += 1;
         }
-        if is_ship {
-            board.push_back(BytesN::from_array(env, &hit));
-        } else {
-            board.push_back(BytesN::from_array(env, &miss));
-        }
+        board.push_back(BytesN::from_array(env, &commit_for(env, i, is_ship)));
     }
 
     board
@@ -109,6 +319,44 @@ fn proof_hash_for(env: &Env, is_ship: bool, x: u32, y: u32) -> [u8; 32] {
     env.crypto().keccak256(&payload).to_array()
 }
 
+fn resolve_cell(env: &Env, client: &BattleshipContractClient, session_id: u32, defender: &Address, is_ship: bool, x: u32, y: u32) {
+    let salt = Bytes::from_array(env, &[9u8; 32]);
+    client.resolve_attack(
+        &session_id,
+        defender,
+        &is_ship,
+        &salt,
+        &BytesN::from_array(env, &proof_hash_for(env, is_ship, x, y)),
+        &None,
+    );
+}
+
+fn start_committed_game(env: &Env, client: &BattleshipContractClient, session_id: u32, player1: &Address, player2: &Address) {
+    client.start_game(&session_id, player1, player2, &0, &0);
+    let p1_board = build_board(env, 10, &[0, 1, 2]);
+    let p2_board = build_board(env, 10, &[0, 5, 10]);
+    client.commit_board(&session_id, player1, &p1_board, &3, &None, &None);
+    client.commit_board(&session_id, player2, &p2_board, &3, &None, &None);
+}
+
+/// Player 1 sinks player 2's fleet at (0,0), (5,0), (0,1) while player 2 misses.
+fn play_until_player1_wins(env: &Env, client: &BattleshipContractClient, session_id: u32, player1: &Address, player2: &Address) {
+    let p1_targets = [(0u32, 0u32), (5, 0), (0, 1)];
+    let p2_targets = [(9u32, 9u32), (8, 9)];
+    let mut round = 0usize;
+    while round < p1_targets.len() {
+        let (x, y) = p1_targets[round];
+        client.attack(&session_id, player1, &x, &y);
+        resolve_cell(env, client, session_id, player2, true, x, y);
+        if round < p2_targets.len() {
+            let (x, y) = p2_targets[round];
+            client.attack(&session_id, player2, &x, &y);
+            resolve_cell(env, client, session_id, player1, false, x, y);
+        }
+        round += 1;
+    }
+}
+
 fn assert_contract_error<T, E>(
     result: &Result<Result<T, E>, Result<Error, soroban_sdk::InvokeError>>,
     expected_error: Error,
@@ -129,6 +377,7 @@ fn test_start_commit_attack_resolve() {
     let points = 100_0000000i128;
 
     client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
 
     let board_size = 10;
     let p1_board = build_board(&env, board_size, &[0, 1, 2]);
@@ -167,6 +416,7 @@ fn test_reject_duplicate_attack() {
     let points = 100_0000000i128;
 
     client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
 
     let board_size = 10;
     let p1_board = build_board(&env, board_size, &[0, 1, 2]);
@@ -209,6 +459,7 @@ fn test_invalid_reveal_fails() {
     let points = 100_0000000i128;
 
     client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
 
     let board_size = 10;
     let p1_board = build_board(&env, board_size, &[0, 1, 2]);
@@ -239,6 +490,7 @@ fn test_invalid_proof_hash_fails() {
     let points = 100_0000000i128;
 
     client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
 
     let board_size = 10;
     let p1_board = build_board(&env, board_size, &[0, 1, 2]);
@@ -274,3 +526,3533 @@ fn test_zk_verifier_admin_config() {
     client.clear_zk_verifier();
     assert!(client.get_zk_verifier().is_none());
 }
+
+fn empty_config() -> ConfigBundle {
+    ConfigBundle {
+        hub: None,
+        bet_token: None,
+        fee_bps: None,
+        fee_recipient: None,
+        verifier_pub_key: None,
+        zk_verifier: None,
+        zk_vk_version: None,
+        dev_mode: None,
+        hub_stats: None,
+        clear_bet_token: false,
+        clear_verifier: false,
+        clear_zk_verifier: false,
+    }
+}
+
+#[test]
+fn test_configure_applies_bundle_atomically() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+
+    let new_hub = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let zk_contract = Address::generate(&env);
+    let mut config = empty_config();
+    config.hub = Some(new_hub.clone());
+    config.fee_bps = Some(250);
+    config.fee_recipient = Some(recipient.clone());
+    config.verifier_pub_key = Some(BytesN::from_array(&env, &[3u8; 32]));
+    config.zk_verifier = Some(zk_contract.clone());
+    config.clear_bet_token = true;
+    client.configure(&config);
+
+    assert_eq!(client.get_hub(), new_hub);
+    assert_eq!(client.get_fee_bps(), 250);
+    assert_eq!(client.get_fee_recipient(), recipient);
+    assert_eq!(client.get_verifier().unwrap(), BytesN::from_array(&env, &[3u8; 32]));
+    assert_eq!(client.get_zk_verifier().unwrap(), zk_contract);
+    assert!(client.get_bet_token().is_none());
+
+    let mut bad_fee = empty_config();
+    bad_fee.fee_bps = Some(5_000);
+    bad_fee.hub = Some(Address::generate(&env));
+    let err = client.try_configure(&bad_fee);
+    assert_contract_error(&err, Error::InvalidFeeBps);
+    assert_eq!(client.get_hub(), new_hub);
+}
+
+#[test]
+fn test_constructor_applies_initial_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let hub_addr = env.register(TestGameHub, ());
+    let admin = Address::generate(&env);
+    let bet_token = Address::generate(&env);
+    let zk_contract = Address::generate(&env);
+    let mut config = empty_config();
+    config.bet_token = Some(bet_token.clone());
+    config.fee_bps = Some(100);
+    config.zk_verifier = Some(zk_contract.clone());
+
+    let contract_id = env.register(BattleshipContract, (&admin, &hub_addr, Some(config)));
+    let client = BattleshipContractClient::new(&env, &contract_id);
+
+    assert_eq!(client.get_bet_token().unwrap(), bet_token);
+    assert_eq!(client.get_fee_bps(), 100);
+    assert_eq!(client.get_fee_recipient(), admin);
+    assert_eq!(client.get_zk_verifier().unwrap(), zk_contract);
+    assert!(client.get_verifier().is_none());
+}
+
+#[test]
+fn test_hub_failure_queues_notification_for_retry() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let keeper = Address::generate(&env);
+
+    let flaky_addr = env.register(FlakyGameHub, ());
+    let flaky_hub = FlakyGameHubClient::new(&env, &flaky_addr);
+    client.set_hub(&flaky_addr);
+    flaky_hub.set_failing(&true);
+
+    let session_id = 303u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    assert_eq!(client.get_game(&session_id).winner.unwrap(), player1);
+    let pending = client.get_pending_hub_notifications();
+    assert_eq!(pending.len(), 1);
+    let queued = pending.get(0).unwrap();
+    assert_eq!(queued.session_id, session_id);
+    assert!(queued.player1_won);
+    assert_eq!(queued.attempts, 1);
+
+    assert_eq!(client.retry_hub_notifications(&keeper, &10), 0);
+
+    env.ledger().set_sequence_number(queued.next_retry_ledger);
+    assert_eq!(client.retry_hub_notifications(&keeper, &10), 0);
+    let retried = client.get_pending_hub_notifications().get(0).unwrap();
+    assert_eq!(retried.attempts, 2);
+    let first_delay = queued.next_retry_ledger - 100;
+    assert_eq!(retried.next_retry_ledger - queued.next_retry_ledger, first_delay * 2);
+
+    flaky_hub.set_failing(&false);
+    env.ledger().set_sequence_number(retried.next_retry_ledger);
+    assert_eq!(client.retry_hub_notifications(&keeper, &10), 1);
+    assert!(client.get_pending_hub_notifications().is_empty());
+    assert!(flaky_hub.was_ended(&session_id));
+}
+
+#[test]
+fn test_keeper_bounty_paid_from_pool_with_rate_limit() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let keeper = Address::generate(&env);
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+
+    client.set_keeper_config(&KeeperConfig {
+        bounty_per_unit: 10,
+        max_per_call: 15,
+        min_interval_ledgers: 50,
+        pool_bps: 0,
+    });
+    client.fund_keeper_pool(&player1, &100);
+    assert_eq!(client.get_keeper_pool(&bet_token), 100);
+
+    let flaky_addr = env.register(FlakyGameHub, ());
+    let flaky_hub = FlakyGameHubClient::new(&env, &flaky_addr);
+    client.set_hub(&flaky_addr);
+    flaky_hub.set_failing(&true);
+
+    start_committed_game(&env, &client, 401, &player1, &player2);
+    play_until_player1_wins(&env, &client, 401, &player1, &player2);
+    start_committed_game(&env, &client, 402, &player1, &player2);
+    play_until_player1_wins(&env, &client, 402, &player1, &player2);
+    assert_eq!(client.get_pending_hub_notifications().len(), 2);
+
+    flaky_hub.set_failing(&false);
+    env.ledger().set_sequence_number(1_000);
+    assert_eq!(client.retry_hub_notifications(&keeper, &1), 1);
+    assert_eq!(token_client.balance(&keeper), 10);
+    assert_eq!(client.get_keeper_pool(&bet_token), 90);
+
+    assert_eq!(client.retry_hub_notifications(&keeper, &1), 1);
+    assert_eq!(token_client.balance(&keeper), 10);
+    assert_eq!(client.get_keeper_pool(&bet_token), 90);
+}
+
+#[test]
+fn test_keeper_bounty_paid_for_timeouts_adjudication_recovery_and_bond_release() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let keeper = Address::generate(&env);
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_keeper_config(&KeeperConfig { bounty_per_unit: 5, max_per_call: 5, min_interval_ledgers: 0, pool_bps: 0 });
+    client.fund_keeper_pool(&player1, &100);
+    env.ledger().set_min_temp_entry_ttl(10);
+
+    client.start_game(&411, &player1, &player2, &0, &0);
+    client.set_move_timeout(&411, &20);
+    client.set_skip_policy(&411, &10, &30, &10);
+    client.commit_board(&411, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&411, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.start_game_with_ttl(&412, &player1, &player2, &0, &0, &720);
+    client.set_skip_policy(&412, &10, &30, &10);
+    client.set_max_game_duration(&500);
+    start_committed_game(&env, &client, 413, &player1, &player2);
+
+    env.ledger().set_sequence_number(121);
+    let p2_before = token_client.balance(&player2);
+    client.claim_timeout_win(&411, &player2);
+    assert_eq!(token_client.balance(&player2), p2_before + 5);
+    client.release_skip_bonds(&411, &keeper);
+    assert_eq!(token_client.balance(&keeper), 5);
+
+    env.ledger().set_sequence_number(1_000);
+    client.adjudicate(&413, &keeper);
+    client.recover_expired(&412, &keeper);
+    assert_eq!(token_client.balance(&keeper), 15);
+    assert_eq!(client.get_keeper_pool(&bet_token), 80);
+}
+
+fn multisig_keys() -> [SigningKey; 3] {
+    let mut keys = [
+        SigningKey::from_bytes(&[1u8; 32]),
+        SigningKey::from_bytes(&[2u8; 32]),
+        SigningKey::from_bytes(&[3u8; 32]),
+    ];
+    keys.sort_by_key(|key| key.verifying_key().to_bytes());
+    keys
+}
+
+fn multisig_sign(env: &Env, keys: &[&SigningKey], payload: &BytesN<32>) -> Vec<MultisigSignature> {
+    let mut signatures = Vec::new(env);
+    for key in keys {
+        signatures.push_back(MultisigSignature {
+            public_key: BytesN::from_array(env, &key.verifying_key().to_bytes()),
+            signature: BytesN::from_array(env, &key.sign(&payload.to_array()).to_bytes()),
+        });
+    }
+    signatures
+}
+
+#[test]
+fn test_multisig_admin_requires_two_of_three() {
+    let env = Env::default();
+    let keys = multisig_keys();
+    let mut signers = Vec::new(&env);
+    for key in &keys {
+        signers.push_back(BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+    }
+    let multisig = env.register(MultisigAdmin, (signers, 2u32));
+    let payload = BytesN::from_array(&env, &[42u8; 32]);
+
+    let two = multisig_sign(&env, &[&keys[0], &keys[2]], &payload);
+    assert!(env
+        .try_invoke_contract_check_auth::<multisig_admin::Error>(&multisig, &payload, two.into_val(&env), &vec![&env])
+        .is_ok());
+
+    let one = multisig_sign(&env, &[&keys[1]], &payload);
+    assert_eq!(
+        env.try_invoke_contract_check_auth::<multisig_admin::Error>(&multisig, &payload, one.into_val(&env), &vec![&env]),
+        Err(Ok(multisig_admin::Error::NotEnoughSignatures))
+    );
+
+    let outsider = SigningKey::from_bytes(&[9u8; 32]);
+    let mut forged = multisig_sign(&env, &[&keys[0]], &payload);
+    forged.append(&multisig_sign(&env, &[&outsider], &payload));
+    assert!(env
+        .try_invoke_contract_check_auth::<multisig_admin::Error>(&multisig, &payload, forged.into_val(&env), &vec![&env])
+        .is_err());
+}
+
+#[test]
+fn test_contract_admin_authorizes_admin_entrypoints() {
+    let env = Env::default();
+    let keys = multisig_keys();
+    let mut signers = Vec::new(&env);
+    for key in &keys {
+        signers.push_back(BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+    }
+    let multisig = env.register(MultisigAdmin, (signers, 2u32));
+    let hub_addr = env.register(TestGameHub, ());
+    let contract_id = env.register(BattleshipContract, (&multisig, &hub_addr, None::<ConfigBundle>));
+    let client = BattleshipContractClient::new(&env, &contract_id);
+
+    let outsider = Address::generate(&env);
+    assert!(client
+        .mock_auths(&[MockAuth {
+            address: &outsider,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "set_fee_bps",
+                args: (300u32,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_set_fee_bps(&300)
+        .is_err());
+
+    client
+        .mock_auths(&[MockAuth {
+            address: &multisig,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "set_fee_bps",
+                args: (300u32,).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .set_fee_bps(&300);
+    assert_eq!(client.get_fee_bps(), 300);
+}
+
+#[test]
+fn test_liabilities_track_escrow_and_detect_discrepancy() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+
+    let session_id = 505u32;
+    let points = 10_0000000i128;
+    client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
+    assert_eq!(client.get_liabilities(&bet_token), 2 * points);
+
+    let report = client.check_solvency(&bet_token);
+    assert!(report.solvent && report.balanced);
+
+    token::StellarAssetClient::new(&env, &bet_token).mint(&client.address, &5);
+    let report = client.check_solvency(&bet_token);
+    assert!(report.solvent);
+    assert!(!report.balanced);
+    assert_eq!(report.balance - report.liabilities, 5);
+
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    client.commit_board(&session_id, &player1, &p1_board, &3, &None, &None);
+    client.commit_board(&session_id, &player2, &p2_board, &3, &None, &None);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_zk_board_commits_pinned_to_same_verifier_version() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let zk_addr = env.register(TestZkVerifier, ());
+    client.set_zk_verifier(&zk_addr);
+    client.set_zk_vk_version(&1);
+
+    let session_id = 606u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    let proof = Bytes::from_array(&env, &[1u8; 8]);
+    client.commit_board_zk(&session_id, &player1, &p1_board, &3, &proof);
+
+    let game = client.get_game(&session_id);
+    assert_eq!(game.player1_board_verifier.unwrap(), zk_addr);
+    assert_eq!(game.player1_board_vk_version.unwrap(), 1);
+
+    client.set_zk_vk_version(&2);
+    let err = client.try_commit_board_zk(&session_id, &player2, &p2_board, &3, &proof);
+    assert_contract_error(&err, Error::VerifierChanged);
+
+    client.set_zk_vk_version(&1);
+    client.commit_board_zk(&session_id, &player2, &p2_board, &3, &proof);
+    let game = client.get_game(&session_id);
+    assert_eq!(game.player2_board_verifier.unwrap(), zk_addr);
+    assert_eq!(game.player2_board_vk_version.unwrap(), 1);
+}
+
+#[test]
+fn test_merkle_root_stored_and_proof_resolution() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let session_id = 707u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    let game = client.get_game(&session_id);
+    assert_eq!(game.player2_board_root.unwrap(), compute_merkle_root(&env, HashAlgorithm::Keccak256, &p2_board));
+
+    client.attack(&session_id, &player1, &5, &0);
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    let proof_hash = BytesN::from_array(&env, &proof_hash_for(&env, true, 5, 0));
+
+    let wrong_proof = build_merkle_proof(&env, HashAlgorithm::Keccak256, &p2_board, 6);
+    let err = client.try_resolve_attack_merkle(&session_id, &player2, &true, &salt, &wrong_proof, &proof_hash, &None);
+    assert_contract_error(&err, Error::InvalidMerkleProof);
+
+    let proof = build_merkle_proof(&env, HashAlgorithm::Keccak256, &p2_board, 5);
+    assert_eq!(proof.len(), 7);
+    let lie = BytesN::from_array(&env, &proof_hash_for(&env, false, 5, 0));
+    let err = client.try_resolve_attack_merkle(&session_id, &player2, &false, &salt, &proof, &lie, &None);
+    assert_contract_error(&err, Error::InvalidMerkleProof);
+
+    client.resolve_attack_merkle(&session_id, &player2, &true, &salt, &proof, &proof_hash, &None);
+    assert_eq!(client.get_game(&session_id).player1_hits, 1);
+}
+
+#[test]
+fn test_overdeclared_fleet_ends_game_early() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let session_id = 808u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    // Player 2 claims every cell is a ship but only places one.
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0]), &100, &None, &None);
+
+    client.attack(&session_id, &player1, &0, &0);
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    assert!(client.get_game(&session_id).winner.is_none());
+
+    client.attack(&session_id, &player2, &9, &9);
+    resolve_cell(&env, &client, session_id, &player1, false, 9, 9);
+
+    client.attack(&session_id, &player1, &1, &0);
+    resolve_cell(&env, &client, session_id, &player2, false, 1, 0);
+
+    let game = client.get_game(&session_id);
+    assert_eq!(game.winner, Some(player1.clone()));
+    let err = client.try_attack(&session_id, &player2, &8, &9);
+    assert_contract_error(&err, Error::GameAlreadyEnded);
+}
+
+#[test]
+fn test_shot_tax_grows_pot_and_pays_winner() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+
+    let session_id = 909u32;
+    let points = 10_0000000i128;
+    let tax = 1_0000000i128;
+    client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
+
+    let err = client.try_set_shot_tax(&session_id, &-1);
+    assert_contract_error(&err, Error::InvalidStakeAmount);
+    client.set_shot_tax(&session_id, &tax);
+
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    client.commit_board(&session_id, &player1, &p1_board, &3, &None, &None);
+    client.commit_board(&session_id, &player2, &p2_board, &3, &None, &None);
+
+    let delegate = Address::generate(&env);
+    client.authorize_session(&session_id, &player1, &delegate, &100, &10);
+    let err = client.try_attack_by_session(&session_id, &player1, &delegate, &0, &0);
+    assert_contract_error(&err, Error::ShotTaxRequiresPlayer);
+
+    let p1_before = token_client.balance(&player1);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    let game = client.get_game(&session_id);
+    assert_eq!(game.player1_shot_tax_paid, 3 * tax);
+    assert_eq!(game.player2_shot_tax_paid, 2 * tax);
+    assert_eq!(token_client.balance(&player1) - p1_before, 2 * points + 2 * tax);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+fn resolve_parallel_cell(env: &Env, client: &BattleshipContractClient, session_id: u32, defender: &Address, is_ship: bool, x: u32, y: u32) {
+    let salt = Bytes::from_array(env, &[9u8; 32]);
+    client.resolve_parallel_attack(
+        &session_id,
+        defender,
+        &is_ship,
+        &salt,
+        &BytesN::from_array(env, &proof_hash_for(env, is_ship, x, y)),
+        &None,
+    );
+}
+
+#[test]
+fn test_parallel_boards_require_sinking_both_fleets() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let session_id = 1001u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    client.set_board_count(&session_id, &2);
+
+    let board = build_board(&env, 10, &[0]);
+    let err = client.try_commit_board(&session_id, &player1, &board, &1, &None, &None);
+    assert_contract_error(&err, Error::BoardModeMismatch);
+    let err = client.try_commit_parallel_board(&session_id, &player1, &1, &board, &1, &None, &None);
+    assert_contract_error(&err, Error::InvalidBoardIndex);
+
+    for index in 0..2u32 {
+        client.commit_parallel_board(&session_id, &player1, &index, &board, &1, &None, &None);
+        client.commit_parallel_board(&session_id, &player2, &index, &board, &1, &None, &None);
+    }
+
+    client.attack_board(&session_id, &player1, &0, &0, &0);
+    resolve_parallel_cell(&env, &client, session_id, &player2, true, 0, 0);
+    assert!(client.get_game(&session_id).winner.is_none());
+
+    client.attack_board(&session_id, &player2, &1, &9, &9);
+    resolve_parallel_cell(&env, &client, session_id, &player1, false, 9, 9);
+
+    let err = client.try_attack_board(&session_id, &player1, &0, &0, &0);
+    assert_contract_error(&err, Error::AlreadyAttacked);
+    client.attack_board(&session_id, &player1, &1, &0, &0);
+    resolve_parallel_cell(&env, &client, session_id, &player2, true, 0, 0);
+
+    let game = client.get_game(&session_id);
+    assert_eq!(game.winner, Some(player1.clone()));
+    assert_eq!(game.player1_hits, 2);
+    assert_eq!(game.player2_boards.get(1).unwrap().hits, 1);
+    let board0 = game.player2_boards.get(0).unwrap();
+    assert!(has_bit(&board0.attacked, 0) && bit_count(&board0.attacked) == 1);
+    assert!(has_bit(&game.player1_boards.get(1).unwrap().attacked, 99));
+}
+
+#[test]
+fn test_annotations_on_finished_games() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+
+    let session_id = 1101u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    let note = String::from_str(&env, "Opening probe on the left edge");
+    let err = client.try_annotate(&session_id, &player1, &0, &note);
+    assert_contract_error(&err, Error::GameNotFinished);
+
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+    client.annotate(&session_id, &player2, &1, &note);
+    let err = client.try_annotate(&session_id, &player2, &5, &note);
+    assert_contract_error(&err, Error::InvalidAnnotation);
+
+    let coach = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&coach, &100);
+    client.set_annotation_fee(&40);
+    let fee_recipient = client.get_fee_recipient();
+    let recipient_before = token_client.balance(&fee_recipient);
+    client.annotate(&session_id, &coach, &4, &String::from_str(&env, "Should have switched rows"));
+    assert_eq!(token_client.balance(&coach), 60);
+    assert_eq!(token_client.balance(&fee_recipient) - recipient_before, 40);
+
+    let annotations = client.get_annotations(&session_id);
+    assert_eq!(annotations.len(), 2);
+    assert_eq!(annotations.get(1).unwrap().author, coach);
+    assert_eq!(annotations.get(1).unwrap().move_index, 4);
+}
+
+#[test]
+fn test_event_schema_version_and_stable_topics() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+    assert_eq!(client.get_event_schema_version(), EVENT_SCHEMA_VERSION);
+
+    let mut config = empty_config();
+    config.fee_bps = Some(100);
+    client.configure(&config);
+
+    let events = env.events().all().filter_by_contract(&client.address);
+    let event = events.events().last().unwrap().clone();
+    assert_eq!(event, ConfigChanged { config }.to_xdr(&env, &client.address));
+    let ContractEventBody::V0(body) = event.body;
+    let expected_topic = ScVal::Symbol(ScSymbol(TOPIC_CONFIG.try_into().unwrap()));
+    assert_eq!(body.topics.first(), Some(&expected_topic));
+}
+
+fn sign_message(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+    let mut raw = [0u8; 256];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut raw[..len]);
+    BytesN::from_array(env, &key.sign(&raw[..len]).to_bytes())
+}
+
+#[test]
+fn test_verifier_signatures_bound_to_network_and_deployment() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let verifier_key = SigningKey::from_bytes(&[5u8; 32]);
+    client.set_verifier(&BytesN::from_array(&env, &verifier_key.verifying_key().to_bytes()));
+
+    let session_id = 1201u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    let board = build_board(&env, 10, &[0, 1, 2]);
+    let root = compute_commitment_root(&env, HashAlgorithm::Keccak256, &board);
+    let proof_hash = BytesN::from_array(&env, &[4u8; 32]);
+
+    let other_deployment = Address::generate(&env);
+    let message = build_board_proof_message(&env, HashAlgorithm::Keccak256, &other_deployment, session_id, 3, &root, &proof_hash);
+    let foreign = sign_message(&env, &verifier_key, &message);
+    assert!(client.try_commit_board(&session_id, &player1, &board, &3, &Some(proof_hash.clone()), &Some(foreign)).is_err());
+
+    env.ledger().set_network_id([8u8; 32]);
+    let message = build_board_proof_message(&env, HashAlgorithm::Keccak256, &client.address, session_id, 3, &root, &proof_hash);
+    let other_network = sign_message(&env, &verifier_key, &message);
+    env.ledger().set_network_id([0u8; 32]);
+    assert!(client.try_commit_board(&session_id, &player1, &board, &3, &Some(proof_hash.clone()), &Some(other_network)).is_err());
+
+    let message = build_board_proof_message(&env, HashAlgorithm::Keccak256, &client.address, session_id, 3, &root, &proof_hash);
+    let signature = sign_message(&env, &verifier_key, &message);
+    client.commit_board(&session_id, &player1, &board, &3, &Some(proof_hash), &Some(signature));
+    assert!(client.get_game(&session_id).player1_board.is_some());
+}
+
+#[test]
+fn test_zk_verifier_trap_is_unavailable_not_rejection() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let zk_addr = env.register(TrappingZkVerifier, ());
+    client.set_zk_verifier(&zk_addr);
+
+    let session_id = 1301u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    let board = build_board(&env, 10, &[0, 1, 2]);
+
+    let err = client.try_commit_board_zk(&session_id, &player1, &board, &3, &Bytes::new(&env));
+    assert_contract_error(&err, Error::VerifierUnavailable);
+    let err = client.try_commit_board_zk(&session_id, &player1, &board, &3, &Bytes::from_array(&env, &[1u8; 8]));
+    assert_contract_error(&err, Error::ZkVerificationFailed);
+
+    client.set_zk_verifier(&Address::generate(&env));
+    let err = client.try_commit_board_zk(&session_id, &player1, &board, &3, &Bytes::from_array(&env, &[1u8; 8]));
+    assert_contract_error(&err, Error::VerifierUnavailable);
+}
+
+#[test]
+fn test_queued_attack_registers_when_turn_returns() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+
+    let session_id = 1401u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let commitment = queued_shot_commitment(&env, 5, 0, &salt);
+
+    client.attack(&session_id, &player1, &0, &0);
+    let err = client.try_queue_attack(&session_id, &player2, &commitment);
+    assert_contract_error(&err, Error::NotYourTurn);
+    client.queue_attack(&session_id, &player1, &commitment);
+    let game = client.get_game(&session_id);
+    assert_eq!((game.queued_attacker, game.queued_commitment), (Some(player1.clone()), Some(commitment)));
+
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    let err = client.try_register_queued_attack(&session_id, &5, &0, &salt);
+    assert_contract_error(&err, Error::NotYourTurn);
+    client.attack(&session_id, &player2, &9, &9);
+    resolve_cell(&env, &client, session_id, &player1, false, 9, 9);
+    assert!(client.get_game(&session_id).pending_attacker.is_none());
+
+    // The reveal must match the commitment; anyone holding the preimage can then submit it.
+    let err = client.try_register_queued_attack(&session_id, &5, &1, &salt);
+    assert_contract_error(&err, Error::InvalidReveal);
+    client.register_queued_attack(&session_id, &5, &0, &salt);
+    let game = client.get_game(&session_id);
+    assert_eq!(game.pending_attacker, Some(player1.clone()));
+    assert_eq!((game.pending_x, game.pending_y), (Some(5), Some(0)));
+    assert!(game.queued_attacker.is_none() && game.queued_commitment.is_none());
+
+    resolve_cell(&env, &client, session_id, &player2, true, 5, 0);
+    assert_eq!(client.get_game(&session_id).player1_hits, 2);
+    let err = client.try_register_queued_attack(&session_id, &5, &0, &salt);
+    assert_contract_error(&err, Error::NoQueuedAttack);
+
+    // A shot at an already-hit cell is refused on reveal.
+    let stale_salt = BytesN::from_array(&env, &[8; 32]);
+    client.attack(&session_id, &player2, &8, &9);
+    client.queue_attack(&session_id, &player2, &queued_shot_commitment(&env, 9, 9, &stale_salt));
+    resolve_cell(&env, &client, session_id, &player1, false, 8, 9);
+    client.attack(&session_id, &player1, &3, &3);
+    resolve_cell(&env, &client, session_id, &player2, false, 3, 3);
+    let err = client.try_register_queued_attack(&session_id, &9, &9, &stale_salt);
+    assert_contract_error(&err, Error::AlreadyAttacked);
+}
+
+#[test]
+fn test_organizer_cut_carved_from_protocol_fee() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let fee_recipient = Address::generate(&env);
+    let organizer = Address::generate(&env);
+    client.set_fee_recipient(&fee_recipient);
+    client.set_fee_bps(&1_000);
+
+    let session_id = 1501u32;
+    let points = 10_0000000i128;
+    client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
+    let err = client.try_set_organizer_cut(&session_id, &organizer, &10_001);
+    assert_contract_error(&err, Error::InvalidFeeBps);
+    client.set_organizer_cut(&session_id, &organizer, &2_500);
+
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    client.commit_board(&session_id, &player1, &p1_board, &3, &None, &None);
+    client.commit_board(&session_id, &player2, &p2_board, &3, &None, &None);
+    let err = client.try_set_organizer_cut(&session_id, &organizer, &5_000);
+    assert_contract_error(&err, Error::BoardAlreadyCommitted);
+
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    let fee = 2 * points / 10;
+    assert_eq!(token_client.balance(&organizer), fee / 4);
+    assert_eq!(token_client.balance(&fee_recipient), fee - fee / 4);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_session_pass_waives_holder_share_of_fee() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let fee_recipient = Address::generate(&env);
+    client.set_fee_recipient(&fee_recipient);
+    client.set_fee_bps(&1_000);
+
+    let err = client.try_buy_pass(&player1);
+    assert_contract_error(&err, Error::PassNotAvailable);
+    let price = 5_0000000i128;
+    client.set_pass_config(&PassConfig { price, duration_ledgers: 1_000, fee_discount_bps: 10_000 });
+    let expiry = client.buy_pass(&player1);
+    assert_eq!(expiry, 1_100);
+    assert_eq!(client.buy_pass(&player1), 2_100);
+    assert_eq!(token_client.balance(&fee_recipient), 2 * price);
+
+    let session_id = 1601u32;
+    let points = 10_0000000i128;
+    client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    client.commit_board(&session_id, &player1, &p1_board, &3, &None, &None);
+    client.commit_board(&session_id, &player2, &p2_board, &3, &None, &None);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    assert_eq!(token_client.balance(&fee_recipient) - 2 * price, points / 10);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_void_game_after_commit_window_refunds_stakes() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_commit_window(&50);
+
+    let session_id = 1701u32;
+    let points = 10_0000000i128;
+    let p1_start = token_client.balance(&player1);
+    let p2_start = token_client.balance(&player2);
+    client.start_game(&session_id, &player1, &player2, &points, &points);
+    fund_game(&client, session_id, &player1, &player2);
+
+    let err = client.try_void_uncommitted_game(&session_id, &player1);
+    assert_contract_error(&err, Error::BoardsNotReady);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    let err = client.try_void_uncommitted_game(&session_id, &player1);
+    assert_contract_error(&err, Error::CommitWindowOpen);
+    let err = client.try_void_uncommitted_game(&session_id, &player2);
+    assert_contract_error(&err, Error::BoardsNotReady);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 51);
+    client.void_uncommitted_game(&session_id, &player1);
+
+    let game = client.get_game(&session_id);
+    assert!(game.voided);
+    assert_eq!(token_client.balance(&player1), p1_start);
+    assert_eq!(token_client.balance(&player2), p2_start);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    let err = client.try_commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_contract_error(&err, Error::GameAlreadyEnded);
+}
+
+fn play_tournament_match(env: &Env, client: &BattleshipContractClient, tournament_id: u32, match_index: u32, session_id: u32) -> Address {
+    let entry = client.get_tournament(&tournament_id).matches.get(match_index).unwrap();
+    let (winner, loser) = (entry.player1.clone(), entry.player2.clone().unwrap());
+    start_committed_game(env, client, session_id, &winner, &loser);
+    client.link_tournament_match(&tournament_id, &match_index, &session_id);
+    play_until_player1_wins(env, client, session_id, &winner, &loser);
+    client.report_tournament_match(&tournament_id, &match_index);
+    winner
+}
+
+#[test]
+fn test_tournament_pairings_from_committed_nonces_are_verifiable() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[42u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &organizer_nonce));
+
+    let mut players = Vec::new(&env);
+    for seed in 0..4u8 {
+        let player = Address::generate(&env);
+        let nonce = BytesN::from_array(&env, &[seed + 1; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        if seed < 3 {
+            client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+        }
+        players.push_back(player);
+    }
+    let err = client.try_join_tournament(&tournament_id, &Address::generate(&env), &organizer_nonce);
+    assert_contract_error(&err, Error::TournamentFull);
+    let err = client.try_reveal_tournament_nonce(&tournament_id, &players.get(3).unwrap(), &organizer_nonce);
+    assert_contract_error(&err, Error::InvalidReveal);
+    let err = client.try_start_tournament(&tournament_id, &BytesN::from_array(&env, &[0u8; 32]));
+    assert_contract_error(&err, Error::InvalidReveal);
+
+    let matches = client.start_tournament(&tournament_id, &organizer_nonce);
+    assert_eq!(matches.len(), 2);
+    assert!(matches.get(1).unwrap().winner.is_some());
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!(tournament.seeding.len(), 3);
+    assert!(!tournament.seeding.contains(players.get(3).unwrap()));
+    assert!(client.verify_pairings(&tournament_id));
+
+    play_tournament_match(&env, &client, tournament_id, 0, 2001);
+    assert_eq!(client.get_tournament(&tournament_id).round, 2);
+    let champion = play_tournament_match(&env, &client, tournament_id, 0, 2002);
+
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!(tournament.status, TournamentStatus::Finished);
+    assert_eq!(tournament.winner, Some(champion));
+}
+
+#[test]
+fn test_round_robin_pool_standings_qualify_into_knockout() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[7u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &3, &nonce_commitment(&env, &organizer_nonce));
+    let err = client.try_set_round_robin(&tournament_id, &3, &4);
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_round_robin(&tournament_id, &3, &2);
+
+    for seed in 0..3u8 {
+        let player = Address::generate(&env);
+        let nonce = BytesN::from_array(&env, &[seed + 1; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+    }
+
+    let matches = client.start_tournament(&tournament_id, &organizer_nonce);
+    assert_eq!(matches.len(), 3);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!(tournament.format, TournamentFormat::RoundRobin);
+    assert!(tournament.pool_stage);
+
+    for (match_index, session_id) in [(0u32, 3001u32), (1, 3002), (2, 3003)] {
+        play_tournament_match(&env, &client, tournament_id, match_index, session_id);
+    }
+
+    // Player 1 of each pairing wins, so the top seed finishes 2-0 and the second seed 1-1.
+    let seeding = tournament.seeding;
+    let standings = client.get_standings(&tournament_id);
+    assert_eq!(standings.get(0).unwrap().player, seeding.get(0).unwrap());
+    assert_eq!(standings.get(0).unwrap().points, 6);
+    assert_eq!(standings.get(1).unwrap().player, seeding.get(1).unwrap());
+    assert_eq!(standings.get(1).unwrap().points, 3);
+    assert_eq!(standings.get(2).unwrap().losses, 2);
+    assert!(standings.get(0).unwrap().hit_difference > 0);
+
+    let tournament = client.get_tournament(&tournament_id);
+    assert!(!tournament.pool_stage);
+    assert_eq!(tournament.round, 2);
+    assert_eq!(tournament.matches.len(), 1);
+    let champion = play_tournament_match(&env, &client, tournament_id, 0, 3004);
+    assert_eq!(champion, seeding.get(0).unwrap());
+    assert_eq!(client.get_tournament(&tournament_id).status, TournamentStatus::Finished);
+}
+
+#[test]
+fn test_scheduled_tournament_refunds_no_shows_and_forfeits_stalled_matches() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let no_show = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&no_show, &1000);
+
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[9u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &organizer_nonce));
+    let schedule = TournamentSchedule {
+        registration_open: 110,
+        registration_close: 200,
+        check_in_close: 300,
+        round_ledgers: 1000,
+        entry_fee: 1000,
+        no_show_penalty_bps: 1000,
+    };
+    client.set_tournament_schedule(&tournament_id, &schedule);
+
+    let entrants = [player1.clone(), player2.clone(), no_show.clone()];
+    let err = client.try_join_tournament(&tournament_id, &player1, &nonce_commitment(&env, &organizer_nonce));
+    assert_contract_error(&err, Error::RegistrationClosed);
+    env.ledger().with_mut(|l| l.sequence_number = 150);
+    for (seed, player) in entrants.iter().enumerate() {
+        let nonce = BytesN::from_array(&env, &[seed as u8 + 1; 32]);
+        client.join_tournament(&tournament_id, player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, player, &nonce);
+    }
+    assert_eq!(client.get_liabilities(&bet_token), 3000);
+
+    env.ledger().with_mut(|l| l.sequence_number = 250);
+    client.check_in_tournament(&tournament_id, &player1);
+    client.check_in_tournament(&tournament_id, &player2);
+    let err = client.try_start_tournament(&tournament_id, &organizer_nonce);
+    assert_contract_error(&err, Error::DeadlineNotReached);
+
+    env.ledger().with_mut(|l| l.sequence_number = 300);
+    let matches = client.start_tournament(&tournament_id, &organizer_nonce);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(token_client.balance(&no_show), 900);
+    assert_eq!(token_client.balance(&organizer), 100);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!(tournament.prize_pool, 2000);
+    assert_eq!(tournament.round_deadline, Some(1300));
+
+    // Match player 2 starts the game but never commits, so they forfeit at the deadline.
+    let entry = matches.get(0).unwrap();
+    let (first, second) = (entry.player1.clone(), entry.player2.clone().unwrap());
+    client.start_game(&4001, &first, &second, &0, &0);
+    client.commit_board(&4001, &first, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.link_tournament_match(&tournament_id, &0, &4001);
+    let err = client.try_enforce_round_deadline(&tournament_id);
+    assert_contract_error(&err, Error::DeadlineNotReached);
+
+    env.ledger().with_mut(|l| l.sequence_number = 1301);
+    let balance_before = token_client.balance(&first);
+    assert_eq!(client.enforce_round_deadline(&tournament_id), 1);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!(tournament.status, TournamentStatus::Finished);
+    assert_eq!(tournament.winner, Some(first.clone()));
+    assert_eq!(token_client.balance(&first), balance_before + 2000);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_prediction_contest_pays_the_best_bracket() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[3u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &organizer_nonce));
+    for seed in 0..4u8 {
+        let player = Address::generate(&env);
+        let nonce = BytesN::from_array(&env, &[seed + 1; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+    }
+    let matches = client.start_tournament(&tournament_id, &organizer_nonce);
+    client.open_prediction_contest(&tournament_id, &100);
+
+    let (first, second) = (matches.get(0).unwrap(), matches.get(1).unwrap());
+    let oracle = Address::generate(&env);
+    let contrarian = Address::generate(&env);
+    for spectator in [&oracle, &contrarian] {
+        token::StellarAssetClient::new(&env, &bet_token).mint(spectator, &100);
+    }
+    let chalk = Vec::from_array(&env, [first.player1.clone(), second.player1.clone(), first.player1.clone()]);
+    let upset = Vec::from_array(&env, [first.player1.clone(), second.player2.clone().unwrap(), second.player2.clone().unwrap()]);
+    let inconsistent = Vec::from_array(&env, [first.player1.clone(), second.player1.clone(), second.player2.clone().unwrap()]);
+    let err = client.try_enter_prediction(&tournament_id, &oracle, &inconsistent);
+    assert_contract_error(&err, Error::InvalidPrediction);
+    client.enter_prediction(&tournament_id, &oracle, &chalk);
+    client.enter_prediction(&tournament_id, &contrarian, &upset);
+
+    play_tournament_match(&env, &client, tournament_id, 0, 5001);
+    let late = Address::generate(&env);
+    let err = client.try_enter_prediction(&tournament_id, &late, &chalk);
+    assert_contract_error(&err, Error::PredictionsLocked);
+    play_tournament_match(&env, &client, tournament_id, 1, 5002);
+    play_tournament_match(&env, &client, tournament_id, 0, 5003);
+
+    assert_eq!(client.get_prediction_score(&tournament_id, &oracle), 4);
+    assert_eq!(client.get_prediction_score(&tournament_id, &contrarian), 1);
+    assert_eq!(client.settle_prediction_contest(&tournament_id), 4);
+    let err = client.try_claim_prediction_prize(&tournament_id, &contrarian);
+    assert_contract_error(&err, Error::InvalidPrediction);
+    assert_eq!(client.claim_prediction_prize(&tournament_id, &oracle), 200);
+    assert_eq!(token_client.balance(&oracle), 200);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_reward_window_multiplies_points_of_games_finishing_inside_it() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    assert_eq!(client.get_reward_multiplier(), 10_000);
+
+    let too_big = vec![&env, RewardWindow { start_ledger: 100, end_ledger: 200, multiplier_bps: 60_000 }];
+    assert_contract_error(&client.try_set_reward_windows(&too_big), Error::InvalidConfig);
+    client.set_reward_windows(&vec![
+        &env,
+        RewardWindow { start_ledger: 500, end_ledger: 1_000, multiplier_bps: 20_000 },
+        RewardWindow { start_ledger: 800, end_ledger: 1_200, multiplier_bps: 15_000 },
+    ]);
+    assert_eq!(client.get_reward_multiplier(), 10_000);
+
+    let session_id = 6001u32;
+    client.start_game(&session_id, &player1, &player2, &100, &40);
+    fund_game(&client, session_id, &player1, &player2);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+
+    env.ledger().with_mut(|l| l.sequence_number = 900);
+    assert_eq!(client.get_reward_multiplier(), 20_000);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    let expected = RewardMultiplied { session_id, multiplier_bps: 20_000, player1_points: 200, player2_points: 80 };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().iter().any(|event| *event == expected.to_xdr(&env, &client.address)));
+    assert_eq!(client.get_game(&session_id).reward_multiplier_bps, 20_000);
+
+    env.ledger().with_mut(|l| l.sequence_number = 1_200);
+    assert_eq!(client.get_reward_multiplier(), 10_000);
+}
+
+#[test]
+fn test_drawn_games_accept_annotations_and_replays() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let session_id = 1102u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    client.attack(&session_id, &player1, &0, &0);
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    client.offer_draw(&session_id, &player2);
+    client.accept_draw(&session_id, &player1);
+    assert!(client.get_game(&session_id).winner.is_none());
+
+    client.annotate(&session_id, &player2, &0, &String::from_str(&env, "Agreed after one hit"));
+    client.attach_replay(&session_id, &player1, &Bytes::from_array(&env, &[0x12; 34]));
+    assert_eq!(client.get_annotations(&session_id).len(), 1);
+    assert_eq!(client.get_replays(&session_id).len(), 1);
+}
+
+#[test]
+fn test_players_attach_replay_content_hashes_to_finished_games() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let session_id = 7001u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+
+    let cid = Bytes::from_array(&env, &[0x12; 34]);
+    assert_contract_error(&client.try_attach_replay(&session_id, &player1, &cid), Error::GameNotFinished);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+
+    let outsider = Address::generate(&env);
+    assert_contract_error(&client.try_attach_replay(&session_id, &outsider, &cid), Error::NotPlayer);
+    let oversized = Bytes::from_array(&env, &[0u8; 65]);
+    assert_contract_error(&client.try_attach_replay(&session_id, &player2, &oversized), Error::InvalidContentHash);
+
+    client.attach_replay(&session_id, &player1, &cid);
+    client.attach_replay(&session_id, &player2, &cid);
+    let arweave_id = Bytes::from_array(&env, &[0xab; 32]);
+    client.attach_replay(&session_id, &player1, &arweave_id);
+
+    let replays = client.get_replays(&session_id);
+    assert_eq!(replays.len(), 2);
+    assert_eq!(replays.get(0).unwrap().player, player2);
+    assert_eq!(replays.get(1).unwrap().content_hash, arweave_id);
+}
+
+#[test]
+fn test_eligibility_checker_gates_new_games_and_tournament_entry() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let checker_addr = env.register(AllowListChecker, ());
+    let checker = AllowListCheckerClient::new(&env, &checker_addr);
+    client.set_eligibility_checker(&checker_addr);
+    assert_eq!(client.get_eligibility_checker(), Some(checker_addr));
+
+    checker.allow(&player1);
+    let err = client.try_start_game(&8001, &player1, &player2, &0, &0);
+    assert_contract_error(&err, Error::PlayerNotEligible);
+    checker.allow(&player2);
+    client.start_game(&8001, &player1, &player2, &0, &0);
+
+    let organizer_nonce = BytesN::from_array(&env, &[5u8; 32]);
+    let tournament_id = client.create_tournament(&Address::generate(&env), &4, &nonce_commitment(&env, &organizer_nonce));
+    let err = client.try_join_tournament(&tournament_id, &Address::generate(&env), &organizer_nonce);
+    assert_contract_error(&err, Error::PlayerNotEligible);
+    client.join_tournament(&tournament_id, &player1, &organizer_nonce);
+
+    client.clear_eligibility_checker();
+    client.start_game(&8002, &player1, &Address::generate(&env), &0, &0);
+}
+
+#[test]
+fn test_repeat_abandoners_are_locked_out_of_staked_games_for_escalating_cooldowns() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.set_commit_window(&10);
+    client.set_abandon_policy(&AbandonPolicy { free_abandons: 1, base_cooldown_ledgers: 100, max_cooldown_ledgers: 150 });
+    let stake = 1_0000000i128;
+
+    let abandon = |session_id: u32| {
+        client.start_game(&session_id, &player1, &player2, &stake, &stake);
+        fund_game(&client, session_id, &player1, &player2);
+        client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+        env.ledger().with_mut(|ledger| ledger.sequence_number += 11);
+        client.void_uncommitted_game(&session_id, &player1);
+    };
+
+    abandon(9001);
+    let reputation = client.get_reputation(&player2);
+    assert_eq!(reputation.abandons, 1);
+    assert!(!reputation.flagged);
+
+    abandon(9002);
+    let reputation = client.get_reputation(&player2);
+    assert!(reputation.flagged);
+    assert_eq!(reputation.cooldown_until, env.ledger().sequence() + 100);
+    assert_eq!(client.get_reputation(&player1).abandons, 0);
+
+    let err = client.try_start_game(&9003, &player1, &player2, &stake, &stake);
+    assert_contract_error(&err, Error::AbandonCooldown);
+    client.start_game(&9004, &player1, &player2, &0, &0);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 100);
+    abandon(9005);
+    assert_eq!(client.get_reputation(&player2).cooldown_until, env.ledger().sequence() + 150);
+}
+
+#[test]
+fn test_get_deadlines_reports_the_pending_commit_deadline() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.set_commit_window(&50);
+    let session_id = 9101u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    assert!(client.get_deadlines(&session_id).is_empty());
+
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    let expires_ledger = env.ledger().sequence() + 50;
+    assert_eq!(
+        client.get_deadlines(&session_id),
+        vec![&env, Deadline { obligation: Obligation::Commit, player: player2.clone(), expires_ledger }]
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = expires_ledger);
+    assert_contract_error(&client.try_void_uncommitted_game(&session_id, &player1), Error::CommitWindowOpen);
+    env.ledger().with_mut(|ledger| ledger.sequence_number = expires_ledger + 1);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert!(client.get_deadlines(&session_id).is_empty());
+}
+
+#[test]
+fn test_admin_void_session_freezes_refunds_and_blacklists_the_session() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let session_id = 9201u32;
+    let stake = 5_0000000i128;
+    let p1_start = token_client.balance(&player1);
+
+    client.start_game(&session_id, &player1, &player2, &stake, &stake);
+    fund_game(&client, session_id, &player1, &player2);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.attack(&session_id, &player1, &0, &0);
+
+    let reason = String::from_str(&env, "resolver exploit, incident 12");
+    client.void_session(&session_id, &reason);
+    assert_eq!(client.get_void_reason(&session_id), Some(reason.clone()));
+    assert_eq!(token_client.balance(&player1), p1_start);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+
+    let game = client.get_game(&session_id);
+    assert!(game.voided);
+    assert_eq!(game.winner, None);
+    assert_eq!((game.pending_attacker, game.pending_x, game.pending_y, game.pending_board), (None, None, None, None));
+    assert_eq!((game.queued_attacker, game.queued_commitment), (None, None));
+    let err = client.try_attack(&session_id, &player2, &1, &1);
+    assert_contract_error(&err, Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_void_session(&session_id, &reason), Error::GameAlreadyEnded);
+    let err = client.try_start_game(&session_id, &player1, &player2, &0, &0);
+    assert_contract_error(&err, Error::SessionBlacklisted);
+}
+
+#[test]
+fn test_big_board_commitments_can_be_uploaded_in_chunks() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let session_id = 9301u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    assert_contract_error(&client.try_set_board_size(&session_id, &17), Error::InvalidBoardSize);
+    client.set_board_size(&session_id, &12);
+
+    let board = build_board(&env, 12, &[0, 1, 2]);
+    assert_eq!(client.commit_board_chunk(&session_id, &player1, &0, &board.slice(0..72)), 72);
+    let err = client.try_commit_board_chunk(&session_id, &player1, &100, &board.slice(72..144));
+    assert_contract_error(&err, Error::InvalidBoardCommitmentLength);
+    let err = client.try_finalize_board_commit(&session_id, &player1, &3, &None, &None);
+    assert_contract_error(&err, Error::InvalidBoardCommitmentLength);
+
+    // Resending from an earlier offset replaces whatever was staged after it.
+    client.commit_board_chunk(&session_id, &player1, &72, &board.slice(72..100));
+    assert_eq!(client.commit_board_chunk(&session_id, &player1, &72, &board.slice(72..144)), 144);
+    let root = client.finalize_board_commit(&session_id, &player1, &3, &None, &None);
+    assert_eq!(root, compute_merkle_root(&env, HashAlgorithm::Keccak256, &board));
+    let err = client.try_commit_board_chunk(&session_id, &player1, &0, &board.slice(0..1));
+    assert_contract_error(&err, Error::BoardAlreadyCommitted);
+
+    client.commit_board(&session_id, &player2, &build_board(&env, 12, &[143]), &1, &None, &None);
+    client.attack(&session_id, &player1, &11, &11);
+    resolve_cell(&env, &client, session_id, &player2, true, 11, 11);
+    assert_eq!(client.get_game(&session_id).winner, Some(player1));
+}
+
+#[test]
+fn test_beacon_randomness_picks_first_mover_after_both_commits() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let session_id = 9501u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    let err = client.try_set_randomness_source(&session_id, &RandomnessSource::Beacon);
+    assert_contract_error(&err, Error::BeaconNotConfigured);
+
+    let beacon_addr = env.register(TestBeacon, ());
+    let beacon = TestBeaconClient::new(&env, &beacon_addr);
+    client.set_randomness_beacon(&beacon_addr);
+    client.set_randomness_source(&session_id, &RandomnessSource::Beacon);
+    assert_eq!(client.get_game(&session_id).randomness_beacon, Some(beacon_addr.clone()));
+
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_eq!(client.get_game(&session_id).turn, None);
+    let err = client.try_attack(&session_id, &player1, &0, &0);
+    assert_contract_error(&err, Error::BoardsNotReady);
+
+    let mut output = [7u8; 32];
+    output[31] = 1;
+    beacon.deliver(&session_id, &BytesN::from_array(&env, &output));
+    let game = client.get_game(&session_id);
+    assert_eq!(game.turn, Some(player2.clone()));
+    assert_eq!(game.randomness, Some(BytesN::from_array(&env, &output)));
+    client.attack(&session_id, &player2, &0, &0);
+
+    let err = client.try_fulfill_randomness(&session_id, &BytesN::from_array(&env, &[0u8; 32]));
+    assert_contract_error(&err, Error::RandomnessAlreadyFulfilled);
+
+    start_committed_game(&env, &client, 9502, &player1, &player2);
+    assert_eq!(client.get_game(&9502).turn, Some(player1));
+}
+
+#[test]
+fn test_board_transforms_map_displayed_shots_to_committed_cells() {
+    for transform in 0..8u32 {
+        let mut seen = Vec::<(u32, u32)>::new(&Env::default());
+        for y in 0..4u32 {
+            for x in 0..4u32 {
+                let cell = transform_cell(transform, 4, x, y);
+                assert!(cell.0 < 4 && cell.1 < 4 && !seen.contains(cell));
+                seen.push_back(cell);
+            }
+        }
+    }
+
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let session_id = 9601u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    let beacon_addr = env.register(TestBeacon, ());
+    client.set_randomness_beacon(&beacon_addr);
+    client.set_randomness_source(&session_id, &RandomnessSource::Beacon);
+    client.enable_board_transforms(&session_id);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    let err = client.try_enable_board_transforms(&session_id);
+    assert_contract_error(&err, Error::BoardAlreadyCommitted);
+
+    let mut output = [0u8; 32];
+    output[1] = 2;
+    TestBeaconClient::new(&env, &beacon_addr).deliver(&session_id, &BytesN::from_array(&env, &output));
+    let game = client.get_game(&session_id);
+    assert_eq!((game.player1_transform, game.player2_transform), (0, 2));
+
+    // Player 2's board is shown rotated half a turn, so the displayed corner (9,9) is committed cell (0,0).
+    client.attack(&session_id, &player1, &9, &9);
+    let game = client.get_game(&session_id);
+    assert_eq!((game.pending_x, game.pending_y), (Some(0), Some(0)));
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    assert_eq!(client.get_game(&session_id).player1_hits, 1);
+
+    client.attack(&session_id, &player2, &9, &9);
+    let game = client.get_game(&session_id);
+    assert_eq!((game.pending_x, game.pending_y), (Some(9), Some(9)));
+}
+
+#[test]
+fn test_backers_fund_part_of_a_side_and_share_its_winnings() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_fee_recipient(&Address::generate(&env));
+    let coach = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&coach, &200);
+    let session_id = 9701u32;
+
+    client.start_game(&session_id, &player1, &player2, &100, &100);
+    client.back_player(&session_id, &coach, &player1, &20);
+    client.back_player(&session_id, &coach, &player1, &40);
+    let err = client.try_back_player(&session_id, &coach, &player1, &41);
+    assert_contract_error(&err, Error::InvalidStakeAmount);
+    let game = client.get_game(&session_id);
+    assert_eq!(game.player1_backed, 60);
+    assert_eq!(game.player1_backers.len(), 1);
+
+    let player1_before = token_client.balance(&player1);
+    fund_game(&client, session_id, &player1, &player2);
+    assert_eq!(player1_before - token_client.balance(&player1), 40);
+    let err = client.try_back_player(&session_id, &coach, &player1, &1);
+    assert_contract_error(&err, Error::BackingClosed);
+
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    play_until_player1_wins(&env, &client, session_id, &player1, &player2);
+    // The 200 pot is split 60:40 between the coach and player 1.
+    assert_eq!(token_client.balance(&coach), 200 - 60 + 120);
+    assert_eq!(token_client.balance(&player1), player1_before - 40 + 80);
+
+    client.start_game(&9702, &player1, &player2, &100, &100);
+    client.back_player(&9702, &coach, &player2, &50);
+    client.void_session(&9702, &String::from_str(&env, "incident"));
+    assert_eq!(token_client.balance(&coach), 260);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_config_history_records_every_admin_change_in_order() {
+    let (env, client, _player1, _player2, hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    assert_eq!(client.get_config_history_len(), 2);
+
+    env.ledger().set_sequence_number(500);
+    client.set_fee_bps(&250);
+    let err = client.try_set_fee_bps(&5_000);
+    assert_contract_error(&err, Error::InvalidFeeBps);
+    client.configure(&ConfigBundle { clear_bet_token: true, ..empty_config() });
+    assert_eq!(client.get_config_history_len(), 4);
+
+    let history = client.get_config_history(&0, &10);
+    assert_eq!(history.len(), 4);
+    assert_eq!(history.get(0).unwrap().change.hub, Some(hub_addr));
+    assert_eq!(history.get(1).unwrap().change, ConfigBundle { bet_token: Some(bet_token), ..empty_config() });
+    assert_eq!(
+        history.get(2).unwrap(),
+        ConfigRecord { ledger: 500, change: ConfigBundle { fee_bps: Some(250), ..empty_config() }, setting: None, value: Bytes::new(&env) }
+    );
+    assert!(history.get(3).unwrap().change.clear_bet_token);
+
+    let page = client.get_config_history(&2, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().change.fee_bps, Some(250));
+    assert!(client.get_config_history(&4, &10).is_empty());
+}
+
+#[test]
+fn test_config_history_records_single_setting_changes() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+    let start = client.get_config_history_len();
+
+    env.ledger().set_sequence_number(600);
+    client.set_draw_fee_bps(&75);
+    assert_contract_error(&client.try_set_draw_fee_bps(&5_000), Error::InvalidFeeBps);
+    client.set_rate_limit(&RateLimit { max_actions: 3, window_ledgers: 10 });
+    client.clear_rate_limit();
+    assert_eq!(client.get_config_history_len(), start + 3);
+
+    let history = client.get_config_history(&start, &10);
+    let draw_fee = history.get(0).unwrap();
+    assert_eq!(draw_fee.ledger, 600);
+    assert_eq!(draw_fee.change, empty_config());
+    assert_eq!(draw_fee.setting, Some(Symbol::new(&env, "draw_fee_bps")));
+    assert_eq!(u32::from_xdr(&env, &draw_fee.value).unwrap(), 75);
+
+    let limit = history.get(1).unwrap();
+    assert_eq!(limit.setting, Some(Symbol::new(&env, "rate_limit")));
+    assert_eq!(RateLimit::from_xdr(&env, &limit.value).unwrap(), RateLimit { max_actions: 3, window_ledgers: 10 });
+    let cleared = history.get(2).unwrap();
+    assert_eq!(cleared.setting, Some(Symbol::new(&env, "rate_limit")));
+    assert_eq!(cleared.value, ().to_xdr(&env));
+}
+
+#[test]
+fn test_configure_sets_feature_flags() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+    let flags = ConfigBundle { fee_bps: Some(300), dev_mode: Some(5), hub_stats: Some(true), ..empty_config() };
+    client.configure(&flags);
+    assert_eq!((client.get_fee_bps(), client.get_dev_mode(), client.get_hub_stats_enabled()), (300, 5, true));
+    let len = client.get_config_history_len();
+    assert_eq!(client.get_config_history(&(len - 1), &1).get(0).unwrap().change, flags);
+
+    client.set_hub_stats_enabled(&false);
+    let record = client.get_config_history(&len, &1).get(0).unwrap();
+    assert_eq!((record.change, record.setting), (ConfigBundle { hub_stats: Some(false), ..empty_config() }, None));
+
+    // The whole bundle is refused, so the fee is not half-applied.
+    env.ledger().set_network_id(env.crypto().sha256(&Bytes::from_slice(&env, b"Public Global Stellar Network ; September 2015")).to_array());
+    let err = client.try_configure(&ConfigBundle { fee_bps: Some(100), dev_mode: Some(5), ..empty_config() });
+    assert_contract_error(&err, Error::InvalidConfig);
+    assert_eq!(client.get_fee_bps(), 300);
+    client.configure(&ConfigBundle { dev_mode: Some(0), ..empty_config() });
+    assert_eq!(client.get_dev_mode(), 0);
+}
+
+#[test]
+fn test_expired_games_are_reported_separately_from_unknown_ids() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    assert_contract_error(&client.try_get_game(&9801), Error::GameNotFound);
+    assert_eq!(client.get_game_tombstone(&9801), None);
+
+    client.start_game(&9801, &player1, &player2, &100, &100);
+    let tombstone = client.get_game_tombstone(&9801).unwrap();
+    assert_eq!((tombstone.status, tombstone.escrowed, tombstone.created_ledger), (GameStatus::Active, false, 100));
+    client.deposit_stake(&9801, &player1);
+    assert!(client.get_game_tombstone(&9801).unwrap().escrowed);
+
+    // Dropping the temporary entry stands in for its TTL lapsing.
+    env.as_contract(&client.address, || env.storage().temporary().remove(&DataKey::Game(9801)));
+    assert_contract_error(&client.try_get_game(&9801), Error::GameExpired);
+    assert_contract_error(&client.try_deposit_stake(&9801, &player2), Error::GameExpired);
+
+    client.set_fee_recipient(&Address::generate(&env));
+    start_committed_game(&env, &client, 9802, &player1, &player2);
+    play_until_player1_wins(&env, &client, 9802, &player1, &player2);
+    let tombstone = client.get_game_tombstone(&9802).unwrap();
+    assert_eq!((tombstone.status, tombstone.escrowed), (GameStatus::Finished, false));
+}
+
+#[test]
+fn test_recover_expired_refunds_stakes_stranded_by_an_expired_game() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let coach = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&coach, &30);
+    let player1_before = token_client.balance(&player1);
+    let player2_before = token_client.balance(&player2);
+
+    client.start_game(&9901, &player1, &player2, &100, &100);
+    client.back_player(&9901, &coach, &player2, &30);
+    fund_game(&client, 9901, &player1, &player2);
+    assert_eq!(client.get_escrow_record(&9901).unwrap().shares.len(), 3);
+    assert_contract_error(&client.try_recover_expired(&9901, &Address::generate(&env)), Error::GameNotExpired);
+
+    env.as_contract(&client.address, || env.storage().temporary().remove(&DataKey::Game(9901)));
+    assert_eq!(client.recover_expired(&9901, &Address::generate(&env)), 200);
+    assert_eq!(token_client.balance(&player1), player1_before);
+    assert_eq!(token_client.balance(&player2), player2_before);
+    assert_eq!(token_client.balance(&coach), 30);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    assert!(!client.get_game_tombstone(&9901).unwrap().escrowed);
+    assert_contract_error(&client.try_recover_expired(&9901, &Address::generate(&env)), Error::NothingToRecover);
+    assert_contract_error(&client.try_recover_expired(&9902, &Address::generate(&env)), Error::GameNotFound);
+}
+
+#[test]
+fn test_expired_funded_session_cannot_be_restarted_before_recovery() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let player1_before = token_client.balance(&player1);
+
+    client.start_game(&9903, &player1, &player2, &100, &100);
+    fund_game(&client, 9903, &player1, &player2);
+    assert_contract_error(&client.try_start_game(&9903, &player1, &player2, &0, &0), Error::SessionInUse);
+
+    env.as_contract(&client.address, || env.storage().temporary().remove(&DataKey::Game(9903)));
+    assert_contract_error(&client.try_start_game(&9903, &player1, &player2, &0, &0), Error::SessionInUse);
+    assert_eq!(client.get_liabilities(&bet_token), 200);
+    assert_eq!(client.recover_expired(&9903, &Address::generate(&env)), 200);
+    assert_eq!(token_client.balance(&player1), player1_before);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_staked_game_escrows_stakes_independently_of_hub_points() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_fee_recipient(&Address::generate(&env));
+    let player1_before = token_client.balance(&player1);
+
+    client.start_staked_game(&9951, &player1, &player2, &10, &10, &50, &50);
+    let game = client.get_game(&9951);
+    assert_eq!((game.player1_points, game.player1_stake), (10, 50));
+    assert!(!game.player1_deposited);
+    fund_game(&client, 9951, &player1, &player2);
+    assert_eq!(client.get_liabilities(&bet_token), 100);
+
+    client.commit_board(&9951, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9951, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    play_until_player1_wins(&env, &client, 9951, &player1, &player2);
+    assert_eq!(token_client.balance(&player1), player1_before + 50);
+
+    // Points without a stake report to the hub but escrow nothing.
+    client.start_staked_game(&9952, &player1, &player2, &10, &10, &0, &0);
+    let game = client.get_game(&9952);
+    assert!(game.player1_deposited && game.player2_deposited && game.payout_processed);
+    let err = client.try_start_staked_game(&9953, &player1, &player2, &10, &10, &-1, &0);
+    assert_contract_error(&err, Error::InvalidStakeAmount);
+}
+
+#[test]
+fn test_sha256_games_hash_commitments_and_reveals_with_sha256() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    let sha_board = |ships: &[u32]| {
+        let mut board = Vec::new(&env);
+        for index in 0..100u32 {
+            board.push_back(indexed_cell_commitment(&env, HashAlgorithm::Sha256, index, ships.contains(&index), &salt));
+        }
+        board
+    };
+
+    client.start_game(&9961, &player1, &player2, &0, &0);
+    client.set_hash_algorithm(&9961, &HashAlgorithm::Sha256);
+    client.commit_board(&9961, &player1, &sha_board(&[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9961, &player2, &sha_board(&[0, 5, 10]), &3, &None, &None);
+    let err = client.try_set_hash_algorithm(&9961, &HashAlgorithm::Keccak256);
+    assert_contract_error(&err, Error::BoardAlreadyCommitted);
+
+    client.attack(&9961, &player1, &0, &0);
+    let keccak_proof = BytesN::from_array(&env, &proof_hash_for(&env, true, 0, 0));
+    let err = client.try_resolve_attack(&9961, &player2, &true, &salt, &keccak_proof, &None);
+    assert_contract_error(&err, Error::InvalidProofHash);
+    let sha_proof = attack_proof_hash(&env, HashAlgorithm::Sha256, true, &salt, 0, 0);
+    client.resolve_attack(&9961, &player2, &true, &salt, &sha_proof, &None);
+    assert_eq!(client.get_game(&9961).player1_hits, 1);
+
+    let root = BytesN::from_array(&env, &[1u8; 32]);
+    let keccak_msg = build_board_proof_message(&env, HashAlgorithm::Keccak256, &client.address, 9961, 3, &root, &root);
+    let sha_msg = build_board_proof_message(&env, HashAlgorithm::Sha256, &client.address, 9961, 3, &root, &root);
+    assert_eq!(sha_msg.len(), keccak_msg.len() + 1);
+}
+
+#[test]
+fn test_exhibition_controller_plays_both_bot_seats_through_session_grants() {
+    let (env, client, _player1, _player2, _hub_addr) = setup_test();
+    let flaky_addr = env.register(FlakyGameHub, ());
+    client.set_hub(&flaky_addr);
+    let controller = Address::generate(&env);
+    let bot1 = Address::generate(&env);
+    let bot2 = Address::generate(&env);
+
+    let err = client.try_start_exhibition(&9971, &controller, &bot1, &bot1, &1_000);
+    assert_contract_error(&err, Error::NotPlayer);
+    client.start_exhibition(&9971, &controller, &bot1, &bot2, &1_000);
+    assert!(client.get_game(&9971).exhibition);
+    assert_eq!(client.get_session(&9971, &bot2, &controller).unwrap().uses_left, 0);
+    let err = client.try_start_exhibition(&9971, &controller, &bot1, &bot2, &1_000);
+    assert_contract_error(&err, Error::SessionInUse);
+
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    client.commit_board_by_session(&9971, &bot1, &controller, &build_board(&env, 10, &[0]), &1, &None, &None);
+    client.commit_board_by_session(&9971, &bot2, &controller, &build_board(&env, 10, &[0]), &1, &None, &None);
+    client.attack_by_session(&9971, &bot1, &controller, &0, &0);
+    let proof_hash = BytesN::from_array(&env, &proof_hash_for(&env, true, 0, 0));
+    client.resolve_attack_by_session(&9971, &bot2, &controller, &true, &salt, &proof_hash, &None);
+
+    assert_eq!(client.get_game(&9971).winner, Some(bot1));
+    assert!(!FlakyGameHubClient::new(&env, &flaky_addr).was_ended(&9971));
+}
+
+#[test]
+fn test_transcript_mode_checks_the_full_move_log_at_game_end() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.start_game(&9981, &player1, &player2, &0, &0);
+    let err = client.try_check_transcript(&9981, &Vec::new(&env));
+    assert_contract_error(&err, Error::TranscriptDisabled);
+    client.enable_transcript(&9981);
+    client.commit_board(&9981, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9981, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    let err = client.try_enable_transcript(&9981);
+    assert_contract_error(&err, Error::BoardAlreadyCommitted);
+
+    play_until_player1_wins(&env, &client, 9981, &player1, &player2);
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    let reveal = |x: u32, y: u32, is_ship: bool| MoveReveal { board: 0, x, y, is_ship, salt: salt.clone() };
+    let mut log = vec![
+        &env,
+        reveal(0, 0, true),
+        reveal(9, 9, false),
+        reveal(5, 0, true),
+        reveal(8, 9, false),
+        reveal(0, 1, true),
+    ];
+    assert!(client.check_transcript(&9981, &log));
+
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    let first = transcript_step(&env, HashAlgorithm::Keccak256, &zero, &log.get(0).unwrap());
+    assert_ne!(first, zero);
+    log.set(1, reveal(9, 9, true));
+    assert!(!client.check_transcript(&9981, &log));
+}
+
+#[test]
+fn test_adjudicate_ends_games_past_the_max_duration_by_hit_count() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    start_committed_game(&env, &client, 9991, &player1, &player2);
+    start_committed_game(&env, &client, 9992, &player1, &player2);
+    let err = client.try_adjudicate(&9991, &Address::generate(&env));
+    assert_contract_error(&err, Error::DeadlineNotReached);
+
+    client.set_max_game_duration(&500);
+    client.attack(&9991, &player1, &0, &0);
+    resolve_cell(&env, &client, 9991, &player2, true, 0, 0);
+    env.ledger().set_sequence_number(600);
+    let err = client.try_adjudicate(&9991, &Address::generate(&env));
+    assert_contract_error(&err, Error::DeadlineNotReached);
+
+    env.ledger().set_sequence_number(601);
+    client.adjudicate(&9991, &Address::generate(&env));
+    let game = client.get_game(&9991);
+    assert_eq!(game.winner, Some(player1.clone()));
+    assert!(game.turn.is_none());
+    let err = client.try_adjudicate(&9991, &Address::generate(&env));
+    assert_contract_error(&err, Error::GameAlreadyEnded);
+
+    client.adjudicate(&9992, &Address::generate(&env));
+    let game = client.get_game(&9992);
+    assert!(game.voided);
+    assert!(game.winner.is_none());
+}
+
+#[test]
+fn test_zk_resolutions_earn_a_rebate_from_the_protocol_fee() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let token_client = token::Client::new(&env, &client.get_bet_token().unwrap());
+    let fee_recipient = Address::generate(&env);
+    client.set_fee_recipient(&fee_recipient);
+    client.set_fee_bps(&1_000);
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    let err = client.try_set_zk_rebate(&-1);
+    assert_contract_error(&err, Error::InvalidStakeAmount);
+    client.set_zk_rebate(&1_000_000);
+
+    let points = 10_0000000i128;
+    client.start_game(&9993, &player1, &player2, &points, &points);
+    fund_game(&client, 9993, &player1, &player2);
+    let hit_proof = Bytes::from_array(&env, &[1u8; 8]);
+    let miss_proof = Bytes::from_array(&env, &[0u8; 8]);
+    client.commit_board_zk(&9993, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &hit_proof);
+    client.commit_board_zk(&9993, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &hit_proof);
+
+    for (round, (x, y)) in [(0u32, 0u32), (5, 0), (0, 1)].into_iter().enumerate() {
+        client.attack(&9993, &player1, &x, &y);
+        client.resolve_attack_zk(&9993, &player2, &hit_proof);
+        if round < 2 {
+            client.attack(&9993, &player2, &(9 - round as u32), &9);
+            client.resolve_attack_zk(&9993, &player1, &miss_proof);
+        }
+    }
+
+    let game = client.get_game(&9993);
+    assert_eq!(game.winner, Some(player1.clone()));
+    assert_eq!((game.player1_zk_proofs, game.player2_zk_proofs), (2, 3));
+    let fee = 2 * points / 10;
+    assert_eq!(token_client.balance(&fee_recipient), fee - 5_000_000);
+    assert_eq!(token_client.balance(&player2), 1000_0000000 - points + 3_000_000);
+    assert_eq!(token_client.balance(&player1), 1000_0000000 + points - fee + 2_000_000);
+}
+
+#[test]
+fn test_aggregated_proof_settles_buffered_resolutions_at_once() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.start_game(&9994, &player1, &player2, &0, &0);
+    let err = client.try_enable_aggregated_proofs(&9994);
+    assert_contract_error(&err, Error::ZkVerifierNotConfigured);
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    client.enable_aggregated_proofs(&9994);
+
+    let valid = Bytes::from_array(&env, &[1u8; 8]);
+    let invalid = Bytes::from_array(&env, &[0u8; 8]);
+    client.commit_board_zk(&9994, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &valid);
+    client.commit_board_zk(&9994, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &valid);
+
+    for (round, (x, y)) in [(0u32, 0u32), (5, 0), (0, 1)].into_iter().enumerate() {
+        client.attack(&9994, &player1, &x, &y);
+        client.resolve_attack_buffered(&9994, &player2, &true);
+        if round < 2 {
+            client.attack(&9994, &player2, &(9 - round as u32), &9);
+            client.resolve_attack_zk(&9994, &player1, &invalid);
+        }
+    }
+    let game = client.get_game(&9994);
+    assert_eq!(game.player1_hits, 0);
+    assert_eq!(game.player2_buffer.len(), 3);
+    assert!(game.winner.is_none());
+
+    let err = client.try_resolve_transcript(&9994, &player2, &invalid);
+    assert_contract_error(&err, Error::ZkVerificationFailed);
+    client.resolve_transcript(&9994, &player2, &valid);
+    let game = client.get_game(&9994);
+    assert_eq!(game.player1_hits, 3);
+    assert_eq!(game.player2_proven_moves, 3);
+    assert!(game.player2_buffer.is_empty());
+    assert_eq!(game.winner, Some(player1));
+}
+
+#[test]
+fn test_selected_prover_is_paid_per_verified_proof_from_escrow() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    let service = Address::generate(&env);
+    client.register_prover(&service, &100, &vec![&env, symbol_short!("board")]);
+
+    client.start_game(&9995, &player1, &player2, &0, &0);
+    let err = client.try_select_prover(&9995, &player2, &service, &2);
+    assert_contract_error(&err, Error::UnsupportedCircuit);
+    client.register_prover(&service, &100, &vec![&env, CIRCUIT_ATTACK]);
+    client.select_prover(&9995, &player2, &service, &2);
+    assert_eq!(client.get_liabilities(&bet_token), 200);
+    client.authorize_session(&9995, &player2, &service, &1_000, &0);
+
+    let proof = Bytes::from_array(&env, &[1u8; 8]);
+    client.commit_board_zk(&9995, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &proof);
+    client.commit_board_zk(&9995, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &proof);
+    client.attack(&9995, &player1, &0, &0);
+    client.resolve_attack_zk_by_session(&9995, &player2, &service, &proof);
+    assert_eq!(token_client.balance(&service), 100);
+    assert_eq!(client.get_prover_engagement(&9995, &player2).unwrap().proofs_paid, 1);
+
+    let err = client.try_withdraw_prover_escrow(&9995, &player2);
+    assert_contract_error(&err, Error::GameNotFinished);
+    client.void_session(&9995, &String::from_str(&env, "test"));
+    assert_eq!(client.withdraw_prover_escrow(&9995, &player2), 100);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_ttl_policy_sets_separate_lifetimes_for_active_and_finished_games() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    env.ledger().set_min_temp_entry_ttl(10);
+    env.ledger().set_min_persistent_entry_ttl(10);
+    let policy = TtlPolicy { active_game_ledgers: 20_000, finished_game_ledgers: 100_000, session_grant_ledgers: 50_000 };
+    let err = client.try_set_ttl_policy(&TtlPolicy { finished_game_ledgers: 10, ..policy.clone() });
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_ttl_policy(&policy);
+    assert_eq!(client.get_game_ttl(&true), 100_000);
+    assert_eq!(client.get_session_grant_ttl(), 50_000);
+
+    start_committed_game(&env, &client, 9996, &player1, &player2);
+    let delegate = Address::generate(&env);
+    client.authorize_session(&9996, &player1, &delegate, &1_000, &0);
+    let game_ttl = || env.as_contract(&client.address, || env.storage().temporary().get_ttl(&DataKey::Game(9996)));
+    assert_eq!(game_ttl(), 20_000);
+    let session_ttl = env.as_contract(&client.address, || env.storage().persistent().get_ttl(&DataKey::Session(player1.clone(), delegate.clone(), 9996)));
+    assert_eq!(session_ttl, 50_000);
+
+    play_until_player1_wins(&env, &client, 9996, &player1, &player2);
+    assert_eq!(game_ttl(), 100_000);
+}
+
+#[test]
+fn test_settlement_event_itemizes_the_payout() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let fee_recipient = Address::generate(&env);
+    let organizer = Address::generate(&env);
+    client.set_fee_recipient(&fee_recipient);
+    client.set_fee_bps(&1_000);
+
+    let points = 10_0000000i128;
+    client.start_game(&9997, &player1, &player2, &points, &points);
+    fund_game(&client, 9997, &player1, &player2);
+    client.set_organizer_cut(&9997, &organizer, &2_500);
+    client.commit_board(&9997, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9997, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    play_until_player1_wins(&env, &client, 9997, &player1, &player2);
+
+    let fee = 2 * points / 10;
+    let expected = Settlement {
+        session_id: 9997,
+        winner: player1,
+        token: bet_token,
+        pot: 2 * points,
+        fee_bps: 1_000,
+        fee_amount: fee,
+        organizer_share: fee / 4,
+        keeper_share: 0,
+        rebates: 0,
+        fee_recipient_amount: fee - fee / 4,
+        winner_amount: 2 * points - fee,
+        operator: None,
+        operator_share: 0,
+    };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().iter().any(|event| *event == expected.to_xdr(&env, &client.address)));
+}
+
+#[test]
+fn test_hub_receives_end_of_game_stats_when_enabled() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    start_committed_game(&env, &client, 9998, &player1, &player2);
+    client.set_hub_stats_enabled(&true);
+    play_until_player1_wins(&env, &client, 9998, &player1, &player2);
+    assert_eq!(client.get_game(&9998).winner, Some(player1.clone()));
+
+    let flaky_addr = env.register(FlakyGameHub, ());
+    let flaky_hub = FlakyGameHubClient::new(&env, &flaky_addr);
+    client.set_hub(&flaky_addr);
+    start_committed_game(&env, &client, 9999, &player1, &player2);
+    env.ledger().set_sequence_number(250);
+    play_until_player1_wins(&env, &client, 9999, &player1, &player2);
+    assert_eq!(flaky_hub.stats(&9999), Some((3, 0, 5, 150)));
+
+    client.set_hub_stats_enabled(&false);
+    start_committed_game(&env, &client, 10_000, &player1, &player2);
+    play_until_player1_wins(&env, &client, 10_000, &player1, &player2);
+    assert!(flaky_hub.was_ended(&10_000));
+    assert_eq!(flaky_hub.stats(&10_000), None);
+}
+
+#[test]
+fn test_capabilities_negotiated_at_start_gate_optional_entrypoints() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    client.start_game_with_capabilities(&10_001, &player1, &player2, &0, &0, &(CAP_ZK | CAP_SALVO), &(CAP_SALVO | CAP_TIMEOUTS));
+    assert_eq!(client.get_capabilities(&10_001), CAP_SALVO);
+
+    let proof = Bytes::from_array(&env, &[1u8; 8]);
+    let err = client.try_commit_board_zk(&10_001, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &proof);
+    assert_contract_error(&err, Error::CapabilityNotNegotiated);
+    client.set_board_count(&10_001, &2);
+
+    client.start_game(&10_002, &player1, &player2, &0, &0);
+    assert_eq!(client.get_capabilities(&10_002), CAP_ZK | CAP_SALVO | CAP_TIMEOUTS);
+    client.commit_board_zk(&10_002, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &proof);
+}
+
+#[test]
+fn test_rate_limit_caps_storage_creating_calls_per_address_window() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let err = client.try_set_rate_limit(&RateLimit { max_actions: 0, window_ledgers: 100 });
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_rate_limit(&RateLimit { max_actions: 2, window_ledgers: 100 });
+
+    client.start_game(&10_011, &player1, &player2, &0, &0);
+    client.authorize_session(&10_011, &player1, &Address::generate(&env), &1_000, &0);
+    let err = client.try_authorize_session(&10_011, &player1, &Address::generate(&env), &1_000, &0);
+    assert_contract_error(&err, Error::RateLimited);
+    let err = client.try_start_game(&10_012, &player1, &Address::generate(&env), &0, &0);
+    assert_contract_error(&err, Error::RateLimited);
+    client.authorize_session(&10_011, &player2, &Address::generate(&env), &1_000, &0);
+
+    env.ledger().set_sequence_number(200);
+    client.start_game(&10_012, &player1, &player2, &0, &0);
+    client.clear_rate_limit();
+    client.start_game(&10_013, &player1, &player2, &0, &0);
+    client.start_game(&10_014, &player1, &player2, &0, &0);
+}
+
+#[test]
+fn test_exported_game_imports_into_a_replacement_deployment() {
+    let (env, client, player1, player2, hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let points = 5_0000000i128;
+    client.start_game(&10_021, &player1, &player2, &points, &points);
+    fund_game(&client, 10_021, &player1, &player2);
+    client.commit_board(&10_021, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    let snapshot = client.export_game(&10_021);
+
+    let replacement_id = env.register(BattleshipContract, (&Address::generate(&env), &hub_addr, None::<ConfigBundle>));
+    let replacement = BattleshipContractClient::new(&env, &replacement_id);
+    assert!(replacement.try_import_game(&Bytes::from_array(&env, &[1, 2, 3])).is_err());
+    let mut future = GameSnapshot::from_xdr(&env, &snapshot).unwrap();
+    future.version = SNAPSHOT_VERSION + 1;
+    let err = replacement.try_import_game(&future.to_xdr(&env));
+    assert_contract_error(&err, Error::InvalidSnapshot);
+    assert_eq!(replacement.import_game(&snapshot), 10_021);
+
+    assert_eq!(replacement.get_game(&10_021), client.get_game(&10_021));
+    assert_eq!(replacement.get_game_tombstone(&10_021), client.get_game_tombstone(&10_021));
+    assert_eq!(replacement.get_escrow_record(&10_021), client.get_escrow_record(&10_021));
+    assert_eq!(replacement.get_liabilities(&bet_token), 2 * points);
+    let err = replacement.try_import_game(&snapshot);
+    assert_contract_error(&err, Error::SessionInUse);
+}
+
+/// Signs `invoke` on behalf of a scripted bot the way a wallet would, so the bot's
+/// `__check_auth` runs for real instead of being mocked.
+fn bot_auth(env: &Env, operator: &SigningKey, bot: &Address, invoke: &MockAuthInvoke) -> SorobanAuthorizationEntry {
+    let mut entry: SorobanAuthorizationEntry = (&MockAuth { address: bot, invoke }).into();
+    let invocation = entry.root_invocation.clone();
+    let SorobanCredentials::Address(credentials) = &mut entry.credentials else { unreachable!() };
+    credentials.signature_expiration_ledger = env.ledger().sequence() + 1_000;
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: env.ledger().network_id().to_array().into(),
+        nonce: credentials.nonce,
+        signature_expiration_ledger: credentials.signature_expiration_ledger,
+        invocation,
+    });
+    let payload = env.crypto().sha256(&Bytes::from_slice(env, &WriteXdr::to_xdr(&preimage, Limits::none()).unwrap()));
+    credentials.signature = BytesN::from_array(env, &operator.sign(&payload.to_array()).to_bytes()).into();
+    entry
+}
+
+fn bot_script(env: &Env, shots: &[(u32, u32)]) -> Vec<Shot> {
+    let mut script = Vec::new(env);
+    for &(x, y) in shots {
+        script.push_back(Shot { x, y });
+    }
+    script
+}
+
+#[test]
+fn test_contract_players_play_through_check_auth() {
+    let (env, client, _player1, _player2, _hub) = setup_test();
+    let session_id = 10_030u32;
+    let operator_a = SigningKey::from_bytes(&[11u8; 32]);
+    let operator_b = SigningKey::from_bytes(&[12u8; 32]);
+    let bot_a = env.register(
+        ScriptedBot,
+        (BytesN::from_array(&env, &operator_a.verifying_key().to_bytes()), &client.address, bot_script(&env, &[(0, 0), (5, 0), (0, 1)])),
+    );
+    let bot_b = env.register(
+        ScriptedBot,
+        (BytesN::from_array(&env, &operator_b.verifying_key().to_bytes()), &client.address, bot_script(&env, &[(9, 9), (8, 9)])),
+    );
+    let bot_a_client = ScriptedBotClient::new(&env, &bot_a);
+    let bot_b_client = ScriptedBotClient::new(&env, &bot_b);
+
+    let start_args = (session_id, 0i128).into_val(&env);
+    let start = MockAuthInvoke { contract: &client.address, fn_name: "start_game", args: start_args, sub_invokes: &[] };
+    env.set_auths(&[bot_auth(&env, &operator_a, &bot_a, &start), bot_auth(&env, &operator_b, &bot_b, &start)]);
+    client.start_game(&session_id, &bot_a, &bot_b, &0, &0);
+
+    for (bot, operator, ships) in [(&bot_a, &operator_a, [0u32, 1, 2]), (&bot_b, &operator_b, [0, 5, 10])] {
+        let board = build_board(&env, 10, &ships);
+        let args = (session_id, bot.clone(), board.clone(), 3u32, None::<BytesN<32>>, None::<BytesN<64>>).into_val(&env);
+        let commit = MockAuthInvoke { contract: &client.address, fn_name: "commit_board", args, sub_invokes: &[] };
+        env.set_auths(&[bot_auth(&env, operator, bot, &commit)]);
+        client.commit_board(&session_id, bot, &board, &3, &None, &None);
+    }
+
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    let reveal = |defender: &Address, operator: &SigningKey, is_ship: bool, shot: &Shot| {
+        let proof_hash = BytesN::from_array(&env, &proof_hash_for(&env, is_ship, shot.x, shot.y));
+        let args = (session_id, defender.clone(), is_ship, salt.clone(), proof_hash.clone(), None::<BytesN<64>>).into_val(&env);
+        let resolve = MockAuthInvoke { contract: &client.address, fn_name: "resolve_attack", args, sub_invokes: &[] };
+        env.set_auths(&[bot_auth(&env, operator, defender, &resolve)]);
+        client.resolve_attack(&session_id, defender, &is_ship, &salt, &proof_hash, &None);
+    };
+
+    for round in 0..3 {
+        env.set_auths(&[]);
+        let shot = bot_a_client.play(&session_id);
+        if round == 0 {
+            // A reveal signed by the wrong operator is rejected by the bot's `__check_auth`.
+            let proof_hash = BytesN::from_array(&env, &proof_hash_for(&env, true, shot.x, shot.y));
+            let args = (session_id, bot_b.clone(), true, salt.clone(), proof_hash.clone(), None::<BytesN<64>>).into_val(&env);
+            let resolve = MockAuthInvoke { contract: &client.address, fn_name: "resolve_attack", args, sub_invokes: &[] };
+            env.set_auths(&[bot_auth(&env, &operator_a, &bot_b, &resolve)]);
+            assert!(client.try_resolve_attack(&session_id, &bot_b, &true, &salt, &proof_hash, &None).is_err());
+        }
+        reveal(&bot_b, &operator_b, true, &shot);
+        if round < 2 {
+            env.set_auths(&[]);
+            let shot = bot_b_client.play(&session_id);
+            reveal(&bot_a, &operator_a, false, &shot);
+        }
+    }
+
+    assert_eq!(client.get_game(&session_id).winner, Some(bot_a.clone()));
+    assert_eq!(bot_a_client.get_next_shot(&session_id), 3);
+    assert_eq!(bot_b_client.get_next_shot(&session_id), 2);
+}
+
+#[test]
+fn test_legal_actions_follow_the_game_phase() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let session_id = 10_031u32;
+    let idle = LegalActions { deposit: false, commit: false, attack: false, attack_targets: 100, queue_attack: false, resolve: false };
+
+    client.start_staked_game(&session_id, &player1, &player2, &0, &0, &50, &50);
+    assert_eq!(client.get_legal_actions(&session_id, &player1), LegalActions { deposit: true, ..idle.clone() });
+    fund_game(&client, session_id, &player1, &player2);
+    assert_eq!(client.get_legal_actions(&session_id, &player2), LegalActions { commit: true, ..idle.clone() });
+
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_eq!(client.get_legal_actions(&session_id, &player1), LegalActions { attack: true, ..idle.clone() });
+    assert_eq!(client.get_legal_actions(&session_id, &player2), idle);
+
+    client.attack(&session_id, &player1, &0, &0);
+    assert_eq!(client.get_legal_actions(&session_id, &player1), LegalActions { queue_attack: true, ..idle.clone() });
+    assert_eq!(client.get_legal_actions(&session_id, &player2), LegalActions { resolve: true, ..idle.clone() });
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    assert_eq!(client.get_legal_actions(&session_id, &player1), LegalActions { attack_targets: 99, ..idle.clone() });
+    assert_eq!(client.get_legal_actions(&session_id, &player2), LegalActions { attack: true, ..idle.clone() });
+
+    let outsider = Address::generate(&env);
+    assert_contract_error(&client.try_get_legal_actions(&session_id, &outsider), Error::NotPlayer);
+}
+
+#[test]
+fn test_suggest_board_places_a_deterministic_classic_fleet() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let seed = BytesN::from_array(&env, &[7u8; 32]);
+    let ships = client.suggest_board(&seed);
+    assert_eq!(ships.len(), 17);
+    assert_eq!(client.suggest_board(&seed), ships);
+    assert_ne!(client.suggest_board(&BytesN::from_array(&env, &[8u8; 32])), ships);
+    for pair in 1..ships.len() {
+        assert!(ships.get(pair - 1).unwrap() < ships.get(pair).unwrap());
+    }
+    assert!(ships.iter().all(|index| index < 100));
+
+    let mut indexes = [0u32; 17];
+    for (slot, index) in indexes.iter_mut().zip(ships.iter()) {
+        *slot = index;
+    }
+    client.start_game(&10_032, &player1, &player2, &0, &0);
+    client.commit_board(&10_032, &player1, &build_board(&env, 10, &indexes), &17, &None, &None);
+}
+
+#[test]
+fn test_skip_turn_slashes_the_stalled_players_bond() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let session_id = 10_033u32;
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let starting_balance = token_client.balance(&player1);
+
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    client.set_skip_policy(&session_id, &10, &30, &10);
+    assert_eq!(token_client.balance(&player1), starting_balance - 30);
+    assert_eq!(client.get_escrow_record(&session_id).unwrap().shares.len(), 2);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+
+    // Player 1 never fires; player 2 takes the turn and 10 of the bond.
+    assert_contract_error(&client.try_skip_turn(&session_id, &player2), Error::DeadlineNotReached);
+    assert_contract_error(&client.try_skip_turn(&session_id, &player1), Error::NotYourTurn);
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.skip_turn(&session_id, &player2), 10);
+    assert_eq!(client.get_game(&session_id).turn, Some(player2.clone()));
+
+    // Player 1 then stalls the reveal; the pending shot is dropped and player 2 fires again.
+    client.attack(&session_id, &player2, &0, &0);
+    assert_eq!(client.get_deadlines(&session_id).get(0).unwrap().expires_ledger, 121);
+    env.ledger().set_sequence_number(122);
+    assert_eq!(client.skip_turn(&session_id, &player2), 10);
+    let game = client.get_game(&session_id);
+    assert_eq!((game.pending_attacker, game.turn, game.player1_skip_bond), (None, Some(player2.clone()), 10));
+    assert_eq!(client.get_withdrawable(&player2, &bet_token), 20);
+
+    client.void_session(&session_id, &String::from_str(&env, "test"));
+    client.release_skip_bonds(&session_id, &Address::generate(&env));
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 10);
+    assert_eq!(client.get_withdrawable(&player2, &bet_token), 50);
+    assert_eq!(client.get_escrow_record(&session_id), None);
+    assert_eq!(client.withdraw(&player2, &bet_token), 50);
+    assert_eq!(client.withdraw(&player1, &bet_token), 10);
+    assert_eq!(token_client.balance(&player1), starting_balance - 20);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+
+    client.start_staked_game(&(session_id + 1), &player1, &player2, &0, &0, &50, &50);
+    assert_contract_error(&client.try_set_skip_policy(&(session_id + 1), &10, &30, &10), Error::CasualGameOnly);
+}
+
+#[test]
+fn test_skip_bonds_stay_in_the_token_they_were_posted_in() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let session_id = 10_133u32;
+    let bet_token = client.get_bet_token().unwrap();
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    client.set_skip_policy(&session_id, &10, &30, &10);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+
+    let other_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.set_bet_token(&other_token);
+    env.ledger().set_sequence_number(111);
+    assert_eq!(client.skip_turn(&session_id, &player2), 10);
+    client.void_session(&session_id, &String::from_str(&env, "test"));
+    client.release_skip_bonds(&session_id, &Address::generate(&env));
+
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 20);
+    assert_eq!(client.get_withdrawable(&player2, &bet_token), 40);
+    assert_eq!(client.get_withdrawable(&player2, &other_token), 0);
+    assert_eq!(client.get_liabilities(&bet_token), 60);
+    assert_eq!(client.get_liabilities(&other_token), 0);
+}
+
+#[test]
+fn test_hub_v2_support_is_probed_and_cached_per_hub() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    assert_eq!(client.get_hub_supports_v2(), None);
+    start_committed_game(&env, &client, 10_034, &player1, &player2);
+    play_until_player1_wins(&env, &client, 10_034, &player1, &player2);
+    assert_eq!(client.get_hub_supports_v2(), Some(false));
+    assert!(client.get_pending_hub_notifications().is_empty());
+
+    let v2_hub = env.register(V2GameHub, ());
+    client.set_hub(&v2_hub);
+    assert_eq!(client.get_hub_supports_v2(), None);
+    start_committed_game(&env, &client, 10_035, &player1, &player2);
+    play_until_player1_wins(&env, &client, 10_035, &player1, &player2);
+    assert_eq!(client.get_hub_supports_v2(), Some(true));
+    assert_eq!(
+        V2GameHubClient::new(&env, &v2_hub).result(&10_035),
+        Some(GameResult { player1_won: true, player1_hits: 3, player2_hits: 0, moves: 5 })
+    );
+}
+
+#[test]
+fn test_game_result_goes_to_the_hub_that_started_it() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let original_hub = env.register(FlakyGameHub, ());
+    let rotated_hub = env.register(FlakyGameHub, ());
+    client.set_hub(&original_hub);
+    start_committed_game(&env, &client, 10_036, &player1, &player2);
+    assert_eq!(client.get_game(&10_036).hub, Some(original_hub.clone()));
+
+    client.set_hub(&rotated_hub);
+    play_until_player1_wins(&env, &client, 10_036, &player1, &player2);
+    let expected = HubRotated { session_id: 10_036, original_hub: original_hub.clone(), current_hub: rotated_hub.clone() };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().iter().any(|event| *event == expected.to_xdr(&env, &client.address)));
+    assert!(FlakyGameHubClient::new(&env, &original_hub).was_ended(&10_036));
+    assert!(!FlakyGameHubClient::new(&env, &rotated_hub).was_ended(&10_036));
+
+    // A queued retry also stays with the original hub.
+    start_committed_game(&env, &client, 10_037, &player1, &player2);
+    let current = FlakyGameHubClient::new(&env, &rotated_hub);
+    current.set_failing(&true);
+    play_until_player1_wins(&env, &client, 10_037, &player1, &player2);
+    client.set_hub(&original_hub);
+    current.set_failing(&false);
+    env.ledger().set_sequence_number(1_000);
+    assert_eq!(client.retry_hub_notifications(&player1, &1), 1);
+    assert!(current.was_ended(&10_037));
+    assert!(!FlakyGameHubClient::new(&env, &original_hub).was_ended(&10_037));
+}
+
+#[test]
+fn test_organizer_starts_a_whole_round_in_one_batch() {
+    let (env, client, _player1, _player2, _hub) = setup_test();
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[5u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &8, &nonce_commitment(&env, &organizer_nonce));
+    for seed in 0..8u8 {
+        let player = Address::generate(&env);
+        let nonce = BytesN::from_array(&env, &[seed + 60; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+    }
+    client.start_tournament(&tournament_id, &organizer_nonce);
+
+    let mut games = Vec::new(&env);
+    for match_index in 0..4u32 {
+        games.push_back(GameInit { match_index, session_id: 10_040 + match_index });
+    }
+    assert_eq!(client.start_games_batch(&tournament_id, &games), 4);
+    // Only the organizer signs; the players' intent comes from joining.
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(env.auths()[0].0, organizer);
+
+    let tournament = client.get_tournament(&tournament_id);
+    for match_index in 0..4u32 {
+        let entry = tournament.matches.get(match_index).unwrap();
+        assert_eq!(entry.session_id, Some(10_040 + match_index));
+        let game = client.get_game(&(10_040 + match_index));
+        assert_eq!((game.player1, game.player2), (entry.player1, entry.player2.unwrap()));
+    }
+    let again = vec![&env, GameInit { match_index: 0, session_id: 10_050 }];
+    assert_contract_error(&client.try_start_games_batch(&tournament_id, &again), Error::InvalidMatch);
+}
+
+#[test]
+fn test_games_can_request_their_own_ttl_within_the_admin_max() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    env.ledger().set_min_temp_entry_ttl(10);
+    client.set_max_game_ttl(&200_000);
+    let err = client.try_start_game_with_ttl(&10_051, &player1, &player2, &0, &0, &200_001);
+    assert_contract_error(&err, Error::InvalidConfig);
+    let err = client.try_start_game_with_ttl(&10_051, &player1, &player2, &0, &0, &100);
+    assert_contract_error(&err, Error::InvalidConfig);
+
+    client.start_game_with_ttl(&10_051, &player1, &player2, &0, &0, &1_000);
+    client.start_game_with_ttl(&10_052, &player1, &player2, &0, &0, &200_000);
+    let game_ttl = |session_id: u32| env.as_contract(&client.address, || env.storage().temporary().get_ttl(&DataKey::Game(session_id)));
+    assert_eq!(game_ttl(10_051), 1_000);
+    assert_eq!(game_ttl(10_052), 200_000);
+    assert_eq!(client.get_game(&10_051).ttl_ledgers, 1_000);
+}
+
+#[test]
+fn test_sweep_stale_refunds_expired_challenges_and_pays_the_keeper() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let keeper = Address::generate(&env);
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.set_keeper_config(&KeeperConfig { bounty_per_unit: 5, max_per_call: 100, min_interval_ledgers: 0, pool_bps: 0 });
+    client.fund_keeper_pool(&player2, &100);
+
+    let before = token_client.balance(&player1);
+    let short = client.post_challenge(&player1, &None, &50, &10);
+    let long = client.post_challenge(&player1, &None, &70, &1_000);
+    let direct = client.post_challenge(&player1, &Some(player2.clone()), &0, &10);
+    assert_eq!(token_client.balance(&player1), before - 120);
+    assert_eq!(client.get_open_challenges(), vec![&env, short, long, direct]);
+    assert_eq!(client.get_liabilities(&bet_token), 120);
+
+    // Nothing has expired yet, so the sweep only advances its cursor.
+    assert_eq!(client.sweep_stale(&keeper, &1), 0);
+    env.ledger().set_sequence_number(200);
+    assert_contract_error(&client.try_accept_challenge(&short, &10_060, &player2), Error::ChallengeExpired);
+    assert_eq!(client.sweep_stale(&keeper, &10), 2);
+    assert_eq!(client.get_open_challenges(), vec![&env, long]);
+    assert_eq!(client.get_challenge(&short), None);
+    assert_eq!(token_client.balance(&player1), before - 70);
+    assert_eq!(token_client.balance(&keeper), 10);
+    assert_eq!(client.sweep_stale(&keeper, &10), 0);
+
+    // A live challenge becomes the challenger's deposit in the new game.
+    client.accept_challenge(&long, &10_060, &player2);
+    assert!(client.get_open_challenges().is_empty());
+    let game = client.get_game(&10_060);
+    assert_eq!((game.player1, game.player2.clone()), (player1.clone(), player2.clone()));
+    assert!(game.player1_deposited && !game.player2_deposited);
+    assert_eq!(client.get_liabilities(&bet_token), 70);
+    assert_contract_error(&client.try_cancel_challenge(&long), Error::ChallengeNotFound);
+}
+
+#[test]
+fn test_boards_view_packs_both_targeting_grids() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_061, &player1, &player2);
+    client.attack(&10_061, &player1, &0, &0);
+    resolve_cell(&env, &client, 10_061, &player2, true, 0, 0);
+    client.attack(&10_061, &player2, &9, &9);
+    resolve_cell(&env, &client, 10_061, &player1, false, 9, 9);
+    client.attack(&10_061, &player1, &3, &0);
+
+    let view = client.get_boards_view(&10_061);
+    assert_eq!(view.board_size, 10);
+    assert_eq!((view.player1.len(), view.player2.len()), (25, 25));
+    assert_eq!(view.player1.get(0), Some(GRID_HIT | (GRID_PENDING << 6)));
+    assert_eq!(view.player2.get(24), Some(GRID_MISS << 6));
+    let marked = |grid: &Bytes| grid.iter().filter(|byte| *byte != GRID_OPEN).count();
+    assert_eq!((marked(&view.player1), marked(&view.player2)), (1, 1));
+}
+
+#[test]
+fn test_boards_with_reused_salts_are_flagged_at_commit() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    client.start_game(&10_062, &player1, &player2, &0, &0);
+    let mut salted = Vec::new(&env);
+    for index in 0..100u32 {
+        let salt = Bytes::from_array(&env, &[index as u8; 32]);
+        salted.push_back(indexed_cell_commitment(&env, HashAlgorithm::Keccak256, index, index < 3, &salt));
+    }
+    client.commit_board(&10_062, &player1, &salted, &3, &None, &None);
+    let weak_topic = ScVal::Symbol(ScSymbol(TOPIC_WEAK_BOARD.try_into().unwrap()));
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().iter().all(|event| {
+        let ContractEventBody::V0(body) = &event.body;
+        body.topics.first() != Some(&weak_topic)
+    }));
+
+    // A client still hashing without the cell index and reusing one salt leaves just the ship
+    // and water commitments.
+    let shared_salt = Bytes::from_array(&env, &[9u8; 32]);
+    let mut unindexed = Vec::new(&env);
+    for index in 0..100u32 {
+        unindexed.push_back(cell_commitment(&env, HashAlgorithm::Keccak256, [0, 5, 10].contains(&index), &shared_salt));
+    }
+    client.commit_board(&10_062, &player2, &unindexed, &3, &None, &None);
+    let expected = WeakBoardCommitment { session_id: 10_062, player: player2.clone(), distinct_commitments: 2 };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().iter().any(|event| *event == expected.to_xdr(&env, &client.address)));
+    assert!(client.get_game(&10_062).player2_board.is_some());
+}
+
+#[test]
+fn test_new_games_bind_the_cell_index_into_commitments() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_063, &player1, &player2);
+    let game = client.get_game(&10_063);
+    assert_eq!(game.commitment_version, COMMITMENT_V2);
+    let board = game.player2_board.unwrap();
+    assert_ne!(board.get(1), board.get(2));
+
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    assert_eq!(board.get(5), Some(indexed_cell_commitment(&env, HashAlgorithm::Keccak256, 5, true, &salt)));
+    assert_ne!(board.get(5), Some(cell_commitment(&env, HashAlgorithm::Keccak256, true, &salt)));
+
+    // Revealing a cell against another cell's commitment no longer works, even with a shared salt.
+    client.attack(&10_063, &player1, &1, &0);
+    let proof = BytesN::from_array(&env, &proof_hash_for(&env, false, 1, 0));
+    client.resolve_attack(&10_063, &player2, &false, &salt, &proof, &None);
+    let attacks = client.get_game(&10_063).player1_attacks;
+    assert!(has_bit(&attacks, 1) && bit_count(&attacks) == 1);
+}
+
+#[test]
+fn test_refunds_follow_the_escrow_ledger_after_a_bet_token_switch() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let original_token = client.get_bet_token().unwrap();
+    let original = token::Client::new(&env, &original_token);
+    let replacement_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let replacement = token::StellarAssetClient::new(&env, &replacement_token);
+    replacement.mint(&player1, &1_000);
+    replacement.mint(&player2, &1_000);
+
+    // Split stakes with only one side funded when the admin switches tokens.
+    client.start_staked_game(&10_064, &player1, &player2, &10, &10, &30, &50);
+    let before = original.balance(&player1);
+    client.deposit_stake(&10_064, &player1);
+    client.set_bet_token(&replacement_token);
+    client.void_session(&10_064, &String::from_str(&env, "token migration"));
+    assert_eq!(original.balance(&player1), before);
+    assert_eq!(client.get_liabilities(&original_token), 0);
+    assert_eq!(client.get_liabilities(&replacement_token), 0);
+    assert_eq!(token::Client::new(&env, &replacement_token).balance(&player1), 1_000);
+
+    // The second side pays in the token the game's escrow was opened in.
+    client.set_bet_token(&original_token);
+    client.start_staked_game(&10_065, &player1, &player2, &10, &10, &30, &50);
+    client.deposit_stake(&10_065, &player1);
+    client.set_bet_token(&replacement_token);
+    let before = original.balance(&player2);
+    client.deposit_stake(&10_065, &player2);
+    assert_eq!(original.balance(&player2), before - 50);
+    assert_eq!(client.get_escrow_record(&10_065).unwrap().token, original_token);
+    assert_eq!(client.get_liabilities(&original_token), 80);
+}
+
+#[test]
+fn test_upgrades_are_announced_before_they_can_execute() {
+    let (env, client, _player1, _player2, _hub) = setup_test();
+    assert_contract_error(&client.try_execute_upgrade(), Error::NoPendingUpgrade);
+    assert_contract_error(&client.try_set_upgrade_delay(&10), Error::InvalidConfig);
+    client.set_upgrade_delay(&1_000);
+
+    let wasm_hash = BytesN::from_array(&env, &[4u8; 32]);
+    assert_eq!(client.propose_upgrade(&wasm_hash), 1_100);
+    let expected = UpgradeProposed { wasm_hash: wasm_hash.clone(), executable_ledger: 1_100 };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert_eq!(events.events().last().unwrap().clone(), expected.to_xdr(&env, &client.address));
+    assert_eq!(client.get_pending_upgrade(), Some(PendingUpgrade { wasm_hash, proposed_ledger: 100, executable_ledger: 1_100 }));
+
+    env.ledger().set_sequence_number(1_099);
+    assert_contract_error(&client.try_execute_upgrade(), Error::DeadlineNotReached);
+
+    // A replacement proposal restarts the notice period.
+    let replacement = BytesN::from_array(&env, &[5u8; 32]);
+    assert_eq!(client.propose_upgrade(&replacement), 2_099);
+    assert_eq!(client.get_pending_upgrade().unwrap().wasm_hash, replacement);
+}
+
+#[test]
+fn test_upgrade_freeze_pauses_creation_and_waits_for_escrowed_games() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    client.set_upgrade_delay(&1_000);
+    client.set_upgrade_freeze(&UpgradeFreeze { enabled: true, max_escrowed_games: Some(0) });
+
+    client.start_staked_game(&10_066, &player1, &player2, &10, &10, &30, &30);
+    assert_eq!(client.get_escrowed_games(), 0);
+    client.deposit_stake(&10_066, &player1);
+    assert_eq!(client.get_escrowed_games(), 1);
+
+    client.propose_upgrade(&BytesN::from_array(&env, &[6u8; 32]));
+    assert_contract_error(&client.try_start_game(&10_067, &player1, &player2, &0, &0), Error::CreationFrozen);
+
+    // Past the notice period the upgrade still waits for the wager to be released.
+    env.ledger().set_sequence_number(1_100);
+    assert_contract_error(&client.try_execute_upgrade(), Error::UpgradeDeferred);
+    client.void_session(&10_066, &String::from_str(&env, "upgrade"));
+    assert_eq!(client.get_escrowed_games(), 0);
+
+    // Without the freeze, creation continues while the upgrade is pending.
+    client.set_upgrade_freeze(&UpgradeFreeze { enabled: false, max_escrowed_games: None });
+    client.start_game(&10_067, &player1, &player2, &0, &0);
+}
+
+#[test]
+fn test_quote_game_cost_covers_storage_transactions_and_fees() {
+    let (_env, client, _player1, _player2, _hub) = setup_test();
+    client.set_fee_bps(&500);
+    client.set_zk_rebate(&1);
+
+    // Classic 10x10: two boards of 100 commitments plus roots, up to 200 moves.
+    let quote = client.quote_game_cost(&10, &1, &true, &1_000);
+    assert_eq!(quote, CostQuote { bytes_written: 2 * 101 * 32 + 200 * 4, transactions: 5 + 400, max_moves: 200, fee_bps: 500, protocol_fee: 100, max_zk_rebate: 100 });
+
+    // Parallel boards on a smaller grid add the board-size and board-count calls.
+    let quote = client.quote_game_cost(&6, &2, &false, &0);
+    assert_eq!((quote.max_moves, quote.transactions, quote.protocol_fee, quote.max_zk_rebate), (144, 7 + 288, 0, 0));
+
+    assert_contract_error(&client.try_quote_game_cost(&17, &1, &false, &0), Error::InvalidBoardSize);
+    assert_contract_error(&client.try_quote_game_cost(&10, &2, &true, &0), Error::BoardModeMismatch);
+    assert_contract_error(&client.try_quote_game_cost(&10, &1, &false, &-1), Error::InvalidStakeAmount);
+}
+
+#[test]
+fn test_transferable_session_grants_move_to_a_standby_delegate() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_068, &player1, &player2);
+    let primary = Address::generate(&env);
+    let standby = Address::generate(&env);
+    client.authorize_session(&10_068, &player1, &primary, &1_000, &5);
+    client.attack_by_session(&10_068, &player1, &primary, &0, &0);
+
+    let err = client.try_transfer_session(&10_068, &player1, &primary, &standby);
+    assert_contract_error(&err, Error::GrantNotTransferable);
+    client.allow_session_transfer(&10_068, &player1, &primary);
+    assert_contract_error(&client.try_transfer_session(&10_068, &player1, &primary, &player1), Error::InvalidSessionConfig);
+
+    // The standby inherits the remaining uses and expiry; the failed-over delegate is cut off.
+    client.transfer_session(&10_068, &player1, &primary, &standby);
+    assert_eq!(client.get_session(&10_068, &player1, &primary), None);
+    assert_eq!(client.get_session(&10_068, &player1, &standby), Some(SessionGrant { expires_ledger: 1_100, uses_left: 4, transferable: true }));
+    assert_contract_error(&client.try_attack_by_session(&10_068, &player1, &primary, &1, &0), Error::InvalidSession);
+    resolve_cell(&env, &client, 10_068, &player2, true, 0, 0);
+    client.attack(&10_068, &player2, &9, &9);
+    resolve_cell(&env, &client, 10_068, &player1, false, 9, 9);
+    client.attack_by_session(&10_068, &player1, &standby, &1, &0);
+}
+
+#[test]
+fn test_quick_match_widens_stake_bands_with_wait_time() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let player3 = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &client.get_bet_token().unwrap()).mint(&player3, &1_000);
+    let err = client.try_set_match_policy(&MatchPolicy { base_band_bps: 0, widen_bps: 1_000, step_ledgers: 0, max_band_bps: 3_000 });
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_match_policy(&MatchPolicy { base_band_bps: 0, widen_bps: 1_000, step_ledgers: 100, max_band_bps: 3_000 });
+
+    let oldest = client.post_challenge(&player1, &None, &100, &10_000);
+    let status = client.get_queue_status(&oldest);
+    assert_eq!(status, QueueStatus { position: 0, waited_ledgers: 0, band_bps: 0, min_stake: 100, max_stake: 100, estimated_wait_ledgers: None });
+    assert_contract_error(&client.try_quick_match(&10_069, &player2, &120), Error::NoMatchFound);
+
+    env.ledger().set_sequence_number(150);
+    let newer = client.post_challenge(&player3, &None, &100, &10_000);
+    assert_eq!(client.get_queue_status(&newer).position, 1);
+
+    // Both bands now cover 110; the longer wait is matched first, at the player's own stake.
+    env.ledger().set_sequence_number(300);
+    assert_eq!((client.get_queue_status(&oldest).min_stake, client.get_queue_status(&oldest).max_stake), (80, 120));
+    assert_eq!(client.quick_match(&10_069, &player2, &110), oldest);
+    let game = client.get_game(&10_069);
+    assert_eq!((game.player1.clone(), game.player1_stake, game.player2_stake), (player1.clone(), 100, 110));
+    assert!(game.player1_deposited);
+
+    // One 200-ledger match so far, and the remaining challenge has waited 150 of it.
+    let status = client.get_queue_status(&newer);
+    assert_eq!((status.position, status.band_bps, status.estimated_wait_ledgers), (0, 1_000, Some(50)));
+}
+
+#[test]
+fn test_time_control_presets_set_move_deadline_clock_and_ttl() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    env.ledger().set_min_temp_entry_ttl(10);
+    let blitz = Symbol::new(&env, "Blitz");
+    let err = client.try_set_time_control(&blitz, &TimeControl { move_ledgers: 60, total_ledgers: 1_000, ttl_ledgers: 720 });
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_time_control(&blitz, &TimeControl { move_ledgers: 60, total_ledgers: 500, ttl_ledgers: 720 });
+    let err = client.try_start_game_with_time_control(&10_071, &player1, &player2, &0, &0, &Symbol::new(&env, "Rapid"));
+    assert_contract_error(&err, Error::TimeControlNotFound);
+
+    client.start_game_with_time_control(&10_071, &player1, &player2, &0, &0, &blitz);
+    let game = client.get_game(&10_071);
+    assert_eq!((game.time_control, game.skip_turn_ledgers, game.max_duration_ledgers, game.ttl_ledgers), (Some(blitz.clone()), 60, 500, 720));
+    let game_ttl = env.as_contract(&client.address, || env.storage().temporary().get_ttl(&DataKey::Game(10_071)));
+    assert_eq!(game_ttl, 720);
+
+    // The move deadline is an unbonded skip; the total clock overrides the global uncapped default.
+    client.commit_board(&10_071, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&10_071, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    env.ledger().set_sequence_number(161);
+    assert_eq!(client.skip_turn(&10_071, &player2), 0);
+    assert_eq!(client.get_game(&10_071).turn, Some(player2.clone()));
+    env.ledger().set_sequence_number(601);
+    client.adjudicate(&10_071, &Address::generate(&env));
+    assert!(client.get_game(&10_071).voided);
+
+    client.remove_time_control(&blitz);
+    assert!(client.get_time_controls().is_empty());
+}
+
+fn play_tournament_upset(env: &Env, client: &BattleshipContractClient, tournament_id: u32, match_index: u32, session_id: u32) -> Address {
+    let entry = client.get_tournament(&tournament_id).matches.get(match_index).unwrap();
+    let (winner, loser) = (entry.player2.clone().unwrap(), entry.player1.clone());
+    start_committed_game(env, client, session_id, &winner, &loser);
+    client.link_tournament_match(&tournament_id, &match_index, &session_id);
+    play_until_player1_wins(env, client, session_id, &winner, &loser);
+    client.report_tournament_match(&tournament_id, &match_index);
+    winner
+}
+
+#[test]
+fn test_double_elimination_drops_losers_and_resets_the_grand_final() {
+    let (env, client, _player1, _player2, _hub) = setup_test();
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[11u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &organizer_nonce));
+    client.set_double_elimination(&tournament_id);
+    for seed in 0..4u8 {
+        let player = Address::generate(&env);
+        let nonce = BytesN::from_array(&env, &[seed + 20; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+    }
+    let opening = client.start_tournament(&tournament_id, &organizer_nonce);
+    let (a, b) = (opening.get(0).unwrap().player1, opening.get(0).unwrap().player2.unwrap());
+    let (c, d) = (opening.get(1).unwrap().player1, opening.get(1).unwrap().player2.unwrap());
+
+    // Round 1 losers are not out: round 2 draws both brackets at once.
+    play_tournament_match(&env, &client, tournament_id, 0, 10_072);
+    play_tournament_match(&env, &client, tournament_id, 1, 10_073);
+    let round = client.get_tournament(&tournament_id).matches;
+    assert_eq!(round.len(), 2);
+    assert_eq!((round.get(0).unwrap().bracket, round.get(0).unwrap().player1, round.get(0).unwrap().player2), (Bracket::Winners, a.clone(), Some(c.clone())));
+    assert_eq!((round.get(1).unwrap().bracket, round.get(1).unwrap().player1, round.get(1).unwrap().player2), (Bracket::Losers, b.clone(), Some(d.clone())));
+    let archived = client.get_tournament_round(&tournament_id, &1);
+    assert_eq!((archived.get(0).unwrap().winner, archived.get(1).unwrap().winner), (Some(a.clone()), Some(c.clone())));
+    assert_eq!(archived.get(1).unwrap().session_id, Some(10_073));
+
+    // d is eliminated; c drops in and meets b while the winners-bracket champion has a bye.
+    play_tournament_match(&env, &client, tournament_id, 0, 10_074);
+    play_tournament_match(&env, &client, tournament_id, 1, 10_075);
+    let round = client.get_tournament(&tournament_id).matches;
+    assert_eq!((round.get(0).unwrap().player2, round.get(0).unwrap().winner), (None, Some(a.clone())));
+    assert_eq!((round.get(1).unwrap().bracket, round.get(1).unwrap().player1, round.get(1).unwrap().player2), (Bracket::Losers, b.clone(), Some(c.clone())));
+    play_tournament_match(&env, &client, tournament_id, 1, 10_076);
+
+    // The losers-bracket finalist has to win the grand final twice.
+    let final_match = client.get_tournament(&tournament_id).matches.get(0).unwrap();
+    assert_eq!((final_match.bracket, final_match.player1, final_match.player2), (Bracket::GrandFinal, a.clone(), Some(b.clone())));
+    play_tournament_upset(&env, &client, tournament_id, 0, 10_077);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!((tournament.status, tournament.round), (TournamentStatus::Running, 5));
+    assert_eq!(tournament.matches.get(0).unwrap().bracket, Bracket::GrandFinalReset);
+    play_tournament_match(&env, &client, tournament_id, 0, 10_078);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!((tournament.status, tournament.winner), (TournamentStatus::Finished, Some(a)));
+    assert_contract_error(&client.try_get_tournament_round(&tournament_id, &6), Error::InvalidMatch);
+}
+
+#[test]
+fn test_prize_split_pays_places_and_shares_tied_eliminations() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[12u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &organizer_nonce));
+    let schedule = TournamentSchedule {
+        registration_open: 100,
+        registration_close: 200,
+        check_in_close: 300,
+        round_ledgers: 10_000,
+        entry_fee: 100,
+        no_show_penalty_bps: 0,
+    };
+    client.set_tournament_schedule(&tournament_id, &schedule);
+    let err = client.try_set_prize_split(&tournament_id, &PrizeSplit::Custom(vec![&env, 7_000, 2_000]));
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_prize_split(&tournament_id, &PrizeSplit::TopThree);
+    assert_eq!(client.get_tournament(&tournament_id).prize_split, vec![&env, 6_000, 3_000, 1_000]);
+
+    let mut players = vec![&env, player1, player2];
+    for _ in 0..2 {
+        let player = Address::generate(&env);
+        token::StellarAssetClient::new(&env, &bet_token).mint(&player, &100);
+        players.push_back(player);
+    }
+    for (seed, player) in players.iter().enumerate() {
+        let nonce = BytesN::from_array(&env, &[seed as u8 + 30; 32]);
+        client.join_tournament(&tournament_id, &player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, &player, &nonce);
+    }
+    env.ledger().set_sequence_number(250);
+    for player in players.iter() {
+        client.check_in_tournament(&tournament_id, &player);
+    }
+    env.ledger().set_sequence_number(300);
+    client.start_tournament(&tournament_id, &organizer_nonce);
+    let balances = players.iter().fold(Vec::new(&env), |mut all, player| {
+        all.push_back(token_client.balance(&player));
+        all
+    });
+
+    let semifinal_loser = client.get_tournament(&tournament_id).matches.get(0).unwrap().player2.unwrap();
+    play_tournament_match(&env, &client, tournament_id, 0, 10_079);
+    play_tournament_match(&env, &client, tournament_id, 1, 10_080);
+    let finalist = client.get_tournament(&tournament_id).matches.get(0).unwrap().player2.unwrap();
+    let champion = play_tournament_match(&env, &client, tournament_id, 0, 10_081);
+
+    // 60% and 30% to the finalists; both semifinal losers split third place's 10%.
+    let gain = |player: &Address| token_client.balance(player) - balances.get(players.first_index_of(player).unwrap()).unwrap();
+    assert_eq!((gain(&champion), gain(&finalist), gain(&semifinal_loser)), (240, 120, 20));
+    assert_eq!(client.get_tournament(&tournament_id).eliminations.len(), 2);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_organizer_forces_a_stuck_match_without_touching_game_escrow() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[13u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &2, &nonce_commitment(&env, &organizer_nonce));
+    let schedule = TournamentSchedule {
+        registration_open: 100,
+        registration_close: 200,
+        check_in_close: 300,
+        round_ledgers: 1_000,
+        entry_fee: 0,
+        no_show_penalty_bps: 0,
+    };
+    client.set_tournament_schedule(&tournament_id, &schedule);
+    for (seed, player) in [player1.clone(), player2.clone()].iter().enumerate() {
+        let nonce = BytesN::from_array(&env, &[seed as u8 + 40; 32]);
+        client.join_tournament(&tournament_id, player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, player, &nonce);
+    }
+    env.ledger().set_sequence_number(250);
+    client.check_in_tournament(&tournament_id, &player1);
+    client.check_in_tournament(&tournament_id, &player2);
+    env.ledger().set_sequence_number(300);
+    client.start_tournament(&tournament_id, &organizer_nonce);
+
+    // The players link a side-staked game and one deposit sits in escrow when play stalls.
+    client.start_staked_game(&10_082, &player1, &player2, &0, &0, &40, &40);
+    client.deposit_stake(&10_082, &player1);
+    client.link_tournament_match(&tournament_id, &0, &10_082);
+    let err = client.try_force_tournament_result(&tournament_id, &0, &player2);
+    assert_contract_error(&err, Error::DeadlineNotReached);
+
+    env.ledger().set_sequence_number(1_301);
+    let err = client.try_force_tournament_result(&tournament_id, &0, &organizer);
+    assert_contract_error(&err, Error::NotPlayer);
+    client.force_tournament_result(&tournament_id, &0, &player2);
+    let expected = TournamentResultForced { tournament_id, match_index: 0, winner: player2.clone(), session_id: Some(10_082) };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert_eq!(events.events().first().unwrap().clone(), expected.to_xdr(&env, &client.address));
+
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!((tournament.status, tournament.winner), (TournamentStatus::Finished, Some(player2.clone())));
+    let game = client.get_game(&10_082);
+    assert!(game.winner.is_none() && game.player1_deposited && !game.voided);
+    assert_eq!(client.get_liabilities(&bet_token), 40);
+    assert_eq!(client.get_reputation(&player1).abandons, 0);
+}
+
+#[test]
+fn test_zk_tournament_pins_verifier_and_rejects_other_boards() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let organizer = Address::generate(&env);
+    let organizer_nonce = BytesN::from_array(&env, &[17u8; 32]);
+    let tournament_id = client.create_tournament(&organizer, &2, &nonce_commitment(&env, &organizer_nonce));
+    let err = client.try_require_zk_boards(&tournament_id);
+    assert_contract_error(&err, Error::ZkVerifierNotConfigured);
+
+    let zk_addr = env.register(TestZkVerifier, ());
+    client.set_zk_verifier(&zk_addr);
+    client.set_zk_vk_version(&1);
+    client.require_zk_boards(&tournament_id);
+    let tournament = client.get_tournament(&tournament_id);
+    assert_eq!((tournament.zk_verifier, tournament.zk_vk_version), (Some(zk_addr.clone()), Some(1)));
+    for (seed, player) in [player1.clone(), player2.clone()].iter().enumerate() {
+        let nonce = BytesN::from_array(&env, &[seed as u8 + 60; 32]);
+        client.join_tournament(&tournament_id, player, &nonce_commitment(&env, &nonce));
+        client.reveal_tournament_nonce(&tournament_id, player, &nonce);
+    }
+    client.start_tournament(&tournament_id, &organizer_nonce);
+    let err = client.try_require_zk_boards(&tournament_id);
+    assert_contract_error(&err, Error::InvalidTournamentState);
+
+    // A board proven under another VK version keeps the game out of the tournament.
+    let proof = Bytes::from_array(&env, &[1u8; 8]);
+    let p1_board = build_board(&env, 10, &[0, 1, 2]);
+    let p2_board = build_board(&env, 10, &[0, 5, 10]);
+    client.start_game(&10_091, &player1, &player2, &0, &0);
+    client.set_zk_vk_version(&2);
+    client.commit_board_zk(&10_091, &player1, &p1_board, &3, &proof);
+    client.set_zk_vk_version(&1);
+    let err = client.try_link_tournament_match(&tournament_id, &0, &10_091);
+    assert_contract_error(&err, Error::InvalidMatch);
+
+    client.start_game(&10_092, &player1, &player2, &0, &0);
+    client.link_tournament_match(&tournament_id, &0, &10_092);
+    let game = client.get_game(&10_092);
+    assert_eq!((game.pinned_verifier, game.pinned_vk_version), (Some(zk_addr.clone()), Some(1)));
+    let err = client.try_set_board_count(&10_092, &2);
+    assert_contract_error(&err, Error::ZkProofRequired);
+
+    // Rotating the global verifier mid-event does not change what the tournament accepts.
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    let err = client.try_commit_board_zk(&10_092, &player1, &p1_board, &3, &proof);
+    assert_contract_error(&err, Error::VerifierChanged);
+    client.set_zk_verifier(&zk_addr);
+    client.set_zk_vk_version(&3);
+    let err = client.try_commit_board_zk(&10_092, &player1, &p1_board, &3, &proof);
+    assert_contract_error(&err, Error::VerifierChanged);
+
+    client.set_zk_vk_version(&1);
+    client.commit_board_zk(&10_092, &player1, &p1_board, &3, &proof);
+    client.commit_board_zk(&10_092, &player2, &p2_board, &3, &proof);
+    let game = client.get_game(&10_092);
+    assert_eq!((game.player2_board_verifier, game.player2_board_vk_version), (Some(zk_addr), Some(1)));
+}
+
+#[test]
+fn test_premium_live_view_requires_a_ticket_and_splits_revenue() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let fee_recipient = client.get_fee_recipient();
+    let viewer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&viewer, &1_000);
+
+    client.start_game(&10_101, &player1, &player2, &0, &0);
+    client.get_live_view(&10_101, &viewer);
+    let err = client.try_set_pay_per_view(&10_101, &100, &10_001);
+    assert_contract_error(&err, Error::InvalidConfig);
+    client.set_pay_per_view(&10_101, &100, &6_000);
+    let err = client.try_get_live_view(&10_101, &viewer);
+    assert_contract_error(&err, Error::TicketRequired);
+    client.get_live_view(&10_101, &player2);
+
+    let (p1_start, p2_start, fees_start) = (token_client.balance(&player1), token_client.balance(&player2), token_client.balance(&fee_recipient));
+    assert_eq!(client.join_spectators(&10_101, &viewer), 100);
+    assert_eq!(client.join_spectators(&10_101, &viewer), 0);
+    assert_eq!(token_client.balance(&viewer), 900);
+    assert_eq!(token_client.balance(&player1) - p1_start, 30);
+    assert_eq!(token_client.balance(&player2) - p2_start, 30);
+    assert_eq!(token_client.balance(&fee_recipient) - fees_start, 40);
+    let view = client.get_pay_per_view(&10_101).unwrap();
+    assert_eq!((view.tickets_sold, view.revenue), (1, 100));
+    let err = client.try_set_pay_per_view(&10_101, &0, &0);
+    assert_contract_error(&err, Error::InvalidConfig);
+
+    let live = client.get_live_view(&10_101, &viewer);
+    assert_eq!((live.board_size, live.spectators, live.player1_grid.len()), (10, 1, 25));
+    assert_eq!(client.get_spectators(&10_101), Vec::from_array(&env, [viewer]));
+}
+
+#[test]
+fn test_tips_credit_players_and_can_be_held_until_the_game_ends() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let fan = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &bet_token).mint(&fan, &500);
+    start_committed_game(&env, &client, 10_111, &player1, &player2);
+
+    let err = client.try_tip_player(&10_111, &fan, &fan, &10);
+    assert_contract_error(&err, Error::NotPlayer);
+    client.tip_player(&10_111, &fan, &player2, &50);
+    let expected = TipReceived { session_id: 10_111, player: player2.clone(), from: fan.clone(), amount: 50, held: false };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert_eq!(events.events().last().unwrap().clone(), expected.to_xdr(&env, &client.address));
+    assert_eq!(client.get_withdrawable(&player2, &bet_token), 50);
+
+    client.set_tip_hold(&10_111, &player1, &true);
+    client.tip_player(&10_111, &fan, &player1, &120);
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 0);
+    assert_eq!(client.get_tip_jar(&10_111, &player1).held, 120);
+    assert_eq!(client.get_liabilities(&bet_token), 170);
+    let err = client.try_release_tips(&10_111, &player1, &Address::generate(&env));
+    assert_contract_error(&err, Error::GameNotFinished);
+
+    play_until_player1_wins(&env, &client, 10_111, &player1, &player2);
+    let err = client.try_tip_player(&10_111, &fan, &player1, &10);
+    assert_contract_error(&err, Error::GameAlreadyEnded);
+    assert_eq!(client.release_tips(&10_111, &player1, &Address::generate(&env)), 120);
+    let err = client.try_release_tips(&10_111, &player1, &Address::generate(&env));
+    assert_contract_error(&err, Error::NothingToRecover);
+    assert_eq!(client.withdraw(&player1, &bet_token), 120);
+    assert_eq!(client.get_liabilities(&bet_token), 50);
+}
+
+#[test]
+fn test_stalled_defender_forfeits_a_staked_game_on_move_timeout() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let session_id = 10_121u32;
+    client.start_staked_game(&session_id, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, session_id, &player1, &player2);
+    assert_contract_error(&client.try_claim_timeout_win(&session_id, &player1), Error::MoveTimeoutNotSet);
+    client.set_move_timeout(&session_id, &20);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_contract_error(&client.try_set_move_timeout(&session_id, &5), Error::BoardAlreadyCommitted);
+
+    // Player 2 never reveals the shot at them.
+    client.attack(&session_id, &player1, &0, &0);
+    assert_eq!(client.get_deadlines(&session_id).get(0).unwrap().expires_ledger, 120);
+    assert_contract_error(&client.try_claim_timeout_win(&session_id, &player1), Error::DeadlineNotReached);
+    env.ledger().set_sequence_number(121);
+    assert_contract_error(&client.try_claim_timeout_win(&session_id, &player2), Error::NotYourTurn);
+    let p1_before = token_client.balance(&player1);
+    client.claim_timeout_win(&session_id, &player1);
+    let expected = TimeoutWin { session_id, winner: player1.clone(), staller: player2.clone() };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().contains(&expected.to_xdr(&env, &client.address)));
+
+    let game = client.get_game(&session_id);
+    assert_eq!((game.winner, game.pending_defender, game.payout_processed), (Some(player1.clone()), None, true));
+    assert!(token_client.balance(&player1) > p1_before);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    assert_eq!(client.get_reputation(&player2).abandons, 1);
+    assert_contract_error(&client.try_claim_timeout_win(&session_id, &player1), Error::GameAlreadyEnded);
+}
+
+#[test]
+fn test_verifier_outage_restarts_the_defender_clock_before_a_timeout_claim() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let session_id = 10_122u32;
+    client.start_game(&session_id, &player1, &player2, &0, &0);
+    client.set_move_timeout(&session_id, &20);
+    client.commit_board(&session_id, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&session_id, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.set_zk_verifier(&env.register(TrappingZkVerifier, ()));
+
+    client.attack(&session_id, &player1, &0, &0);
+    assert_eq!(client.get_game(&session_id).move_deadline_ledger, 120);
+    env.ledger().set_sequence_number(115);
+    let proof = Bytes::from_array(&env, &[1u8]);
+    assert_contract_error(&client.try_resolve_attack_zk(&session_id, &player2, &proof), Error::VerifierUnavailable);
+    assert_contract_error(&client.try_report_verifier_outage(&session_id, &player1, &proof), Error::NotPendingDefender);
+    assert_eq!(client.report_verifier_outage(&session_id, &player2, &proof), 135);
+    assert_eq!(client.get_deadlines(&session_id).get(0).unwrap().expires_ledger, 135);
+
+    env.ledger().set_sequence_number(121);
+    assert_contract_error(&client.try_claim_timeout_win(&session_id, &player1), Error::DeadlineNotReached);
+
+    // Once the verifier answers again the clock can no longer be restarted.
+    client.set_zk_verifier(&env.register(TestZkVerifier, ()));
+    assert_contract_error(&client.try_report_verifier_outage(&session_id, &player2, &proof), Error::VerifierAvailable);
+    env.ledger().set_sequence_number(136);
+    client.claim_timeout_win(&session_id, &player1);
+    assert_eq!(client.get_game(&session_id).winner, Some(player1));
+}
+
+#[test]
+fn test_resign_hands_the_pot_to_the_opponent() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let p1_start = token_client.balance(&player1);
+
+    client.start_staked_game(&10_131, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, 10_131, &player1, &player2);
+    client.commit_board(&10_131, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    assert_contract_error(&client.try_resign(&10_131, &Address::generate(&env)), Error::NotPlayer);
+    client.resign(&10_131, &player2);
+    let expected = Resigned { session_id: 10_131, player: player2.clone(), winner: player1.clone() };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().contains(&expected.to_xdr(&env, &client.address)));
+
+    let game = client.get_game(&10_131);
+    assert_eq!((game.winner, game.voided, game.payout_processed), (Some(player1.clone()), false, true));
+    assert!(token_client.balance(&player1) > p1_start);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    assert_eq!(client.get_reputation(&player2).abandons, 0);
+    assert_contract_error(&client.try_resign(&10_131, &player1), Error::GameAlreadyEnded);
+
+    // Conceding before the opponent has deposited just returns the stake.
+    client.start_staked_game(&10_132, &player1, &player2, &0, &0, &100, &100);
+    client.deposit_stake(&10_132, &player1);
+    let p1_before = token_client.balance(&player1);
+    client.resign(&10_132, &player1);
+    let game = client.get_game(&10_132);
+    assert_eq!((game.winner, game.voided), (Some(player2.clone()), true));
+    assert_eq!(token_client.balance(&player1), p1_before + 100);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+}
+
+#[test]
+fn test_token_gates_restrict_tournament_entry_and_challenges() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let collection = TestCollectionClient::new(&env, &env.register(TestCollection, ()));
+    collection.mint(&7, &player1);
+
+    let organizer = Address::generate(&env);
+    let tournament_id = client.create_tournament(&organizer, &4, &nonce_commitment(&env, &BytesN::from_array(&env, &[3u8; 32])));
+    let err = client.try_set_tournament_gates(&tournament_id, &Vec::from_array(&env, [TokenGate::MinBalance(bet_token.clone(), 0)]));
+    assert_contract_error(&err, Error::InvalidConfig);
+    let gates = Vec::from_array(&env, [TokenGate::MinBalance(bet_token.clone(), 500), TokenGate::HoldsItem(collection.address.clone(), 7)]);
+    client.set_tournament_gates(&tournament_id, &gates);
+    client.join_tournament(&tournament_id, &player1, &BytesN::from_array(&env, &[1u8; 32]));
+    let err = client.try_join_tournament(&tournament_id, &player2, &BytesN::from_array(&env, &[2u8; 32]));
+    assert_contract_error(&err, Error::TokenGateNotMet);
+    let err = client.try_join_tournament(&tournament_id, &Address::generate(&env), &BytesN::from_array(&env, &[4u8; 32]));
+    assert_contract_error(&err, Error::TokenGateNotMet);
+    assert_eq!(client.get_tournament(&tournament_id).entry_gates, gates);
+
+    // An item that was never minted fails the gate rather than the call.
+    let challenge_id = client.post_challenge(&player1, &None, &0, &100);
+    client.set_challenge_gates(&challenge_id, &Vec::from_array(&env, [TokenGate::HoldsItem(collection.address.clone(), 9)]));
+    assert_contract_error(&client.try_quick_match(&10_141, &player2, &0), Error::NoMatchFound);
+    assert_contract_error(&client.try_accept_challenge(&challenge_id, &10_141, &player2), Error::TokenGateNotMet);
+    collection.mint(&9, &player2);
+    assert_eq!(client.quick_match(&10_141, &player2, &0), challenge_id);
+    assert_eq!(client.get_game(&10_141).player2, player2);
+}
+
+#[test]
+fn test_move_log_is_paged_every_32_moves() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let session_id = 10_151u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+
+    // Seventeen rounds of misses fill the first page and spill two moves onto the second.
+    for round in 0..17u32 {
+        let (x, y) = (round % 10, 3 + round / 10);
+        client.attack(&session_id, &player1, &x, &y);
+        resolve_cell(&env, &client, session_id, &player2, false, x, y);
+        client.attack(&session_id, &player2, &x, &y);
+        resolve_cell(&env, &client, session_id, &player1, false, x, y);
+    }
+    let index = client.get_move_log_index(&session_id);
+    assert_eq!((index.board_size, index.moves, index.pages), (10, 34, 2));
+
+    let first = client.get_move_log_page(&session_id, &0);
+    assert_eq!(first.len(), MOVES_PER_PAGE);
+    assert_eq!(first.get(0).unwrap(), LoggedMove { by_player1: true, board: 0, x: 0, y: 3, is_ship: false });
+    assert_eq!(first.get(1).unwrap(), LoggedMove { by_player1: false, board: 0, x: 0, y: 3, is_ship: false });
+    client.attack(&session_id, &player1, &0, &0);
+    resolve_cell(&env, &client, session_id, &player2, true, 0, 0);
+    let second = client.get_move_log_page(&session_id, &1);
+    assert_eq!(second.len(), 3);
+    assert_eq!(second.get(1).unwrap(), LoggedMove { by_player1: false, board: 0, x: 6, y: 4, is_ship: false });
+    assert_eq!(second.get(2).unwrap(), LoggedMove { by_player1: true, board: 0, x: 0, y: 0, is_ship: true });
+    assert!(client.get_move_log_page(&session_id, &2).is_empty());
+}
+
+#[test]
+fn test_agreed_draw_refunds_stakes_less_the_draw_fee() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let fee_recipient = client.get_fee_recipient();
+    let v2_hub = env.register(V2GameHub, ());
+    client.set_hub(&v2_hub);
+    client.set_draw_fee_bps(&500);
+
+    let session_id = 10_161u32;
+    client.start_staked_game(&session_id, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, session_id, &player1, &player2);
+    let (p1_start, p2_start, fees_start) = (token_client.balance(&player1), token_client.balance(&player2), token_client.balance(&fee_recipient));
+    assert_contract_error(&client.try_accept_draw(&session_id, &player2), Error::NoDrawOffer);
+    client.offer_draw(&session_id, &player1);
+    assert_contract_error(&client.try_accept_draw(&session_id, &player1), Error::NoDrawOffer);
+    client.decline_draw(&session_id, &player2);
+    assert_contract_error(&client.try_accept_draw(&session_id, &player2), Error::NoDrawOffer);
+
+    client.offer_draw(&session_id, &player1);
+    assert_eq!(client.accept_draw(&session_id, &player2), 10);
+    let expected = GameDrawn { session_id, offered_by: player1.clone(), fee: 10 };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().contains(&expected.to_xdr(&env, &client.address)));
+
+    let game = client.get_game(&session_id);
+    assert!(game.drawn && game.voided && game.winner.is_none());
+    assert_eq!(token_client.balance(&player1) - p1_start, 95);
+    assert_eq!(token_client.balance(&player2) - p2_start, 95);
+    assert_eq!(token_client.balance(&fee_recipient) - fees_start, 10);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    assert!(V2GameHubClient::new(&env, &v2_hub).drawn(&session_id));
+    assert_contract_error(&client.try_offer_draw(&session_id, &player2), Error::GameAlreadyEnded);
+}
+
+#[test]
+fn test_draw_clears_the_pending_shot_and_queues_a_failed_hub_notification() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let keeper = Address::generate(&env);
+    let flaky_addr = env.register(FlakyGameHub, ());
+    let flaky_hub = FlakyGameHubClient::new(&env, &flaky_addr);
+    client.set_hub(&flaky_addr);
+    flaky_hub.set_failing(&true);
+
+    let session_id = 10_162u32;
+    start_committed_game(&env, &client, session_id, &player1, &player2);
+    client.attack(&session_id, &player1, &0, &0);
+    client.queue_attack(&session_id, &player1, &queued_shot_commitment(&env, 1, 1, &BytesN::from_array(&env, &[4; 32])));
+    let rotated_hub = Address::generate(&env);
+    client.set_hub(&rotated_hub);
+    client.offer_draw(&session_id, &player1);
+    client.accept_draw(&session_id, &player2);
+    let expected = HubRotated { session_id, original_hub: flaky_addr.clone(), current_hub: rotated_hub };
+    let events = env.events().all().filter_by_contract(&client.address);
+    assert!(events.events().contains(&expected.to_xdr(&env, &client.address)));
+
+    let game = client.get_game(&session_id);
+    assert_eq!((game.pending_attacker, game.pending_x, game.pending_y, game.pending_board), (None, None, None, None));
+    assert_eq!((game.queued_attacker, game.queued_commitment), (None, None));
+
+    let queued = client.get_pending_hub_notifications().get(0).unwrap();
+    assert!(queued.drawn && queued.hub == flaky_addr);
+    flaky_hub.set_failing(&false);
+    env.ledger().set_sequence_number(queued.next_retry_ledger);
+    assert_eq!(client.retry_hub_notifications(&keeper, &10), 1);
+    assert!(flaky_hub.was_ended(&session_id));
+}
+
+#[test]
+fn test_cancel_game_refunds_deposits_and_removes_the_game() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    let p1_start = token_client.balance(&player1);
+
+    client.start_staked_game(&10_171, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, 10_171, &player1, &player2);
+    client.commit_board(&10_171, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    assert_contract_error(&client.try_cancel_game(&10_171, &player2), Error::BoardAlreadyCommitted);
+    assert_contract_error(&client.try_cancel_game(&10_171, &Address::generate(&env)), Error::NotPlayer);
+    assert_eq!(client.get_escrowed_games(), 1);
+
+    client.cancel_game(&10_171, &player1);
+    assert_eq!(token_client.balance(&player1), p1_start);
+    assert_eq!(client.get_liabilities(&bet_token), 0);
+    assert_eq!(client.get_escrowed_games(), 0);
+    let tombstone = client.get_game_tombstone(&10_171).unwrap();
+    assert_eq!((tombstone.status, tombstone.escrowed), (GameStatus::Voided, false));
+    assert_contract_error(&client.try_get_game(&10_171), Error::GameExpired);
+}
+
+#[test]
+fn test_cancel_game_ends_the_hub_session_and_drops_staged_chunks() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let v2_hub = env.register(V2GameHub, ());
+    client.set_hub(&v2_hub);
+
+    client.start_game(&10_172, &player1, &player2, &0, &0);
+    let board = build_board(&env, 10, &[0, 1, 2]);
+    client.commit_board_chunk(&10_172, &player2, &0, &board.slice(0..50));
+    client.cancel_game(&10_172, &player1);
+
+    assert!(V2GameHubClient::new(&env, &v2_hub).drawn(&10_172));
+    assert!(client.get_pending_hub_notifications().is_empty());
+    let staged = env.as_contract(&client.address, || env.storage().temporary().has(&DataKey::BoardChunks(10_172, player2.clone())));
+    assert!(!staged);
+}
+
+#[test]
+fn test_cancel_game_refunds_skip_bonds_in_the_posted_token() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    client.start_game(&10_173, &player1, &player2, &0, &0);
+    client.set_skip_policy(&10_173, &10, &30, &10);
+
+    let other_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    client.set_bet_token(&other_token);
+    client.cancel_game(&10_173, &player1);
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 30);
+    assert_eq!(client.get_withdrawable(&player2, &bet_token), 30);
+    assert_eq!(client.get_withdrawable(&player1, &other_token), 0);
+    assert_eq!(client.get_liabilities(&bet_token), 60);
+}
+
+#[test]
+fn test_game_digest_matches_canonical_encoding_and_tracks_moves() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_181, &player1, &player2);
+
+    let digest = client.get_game_digest(&10_181);
+    assert_eq!(client.get_game_digest(&10_181), digest);
+    let encoding = canonical_state(&env, &client.address, 10_181, client.get_game(&10_181));
+    assert_eq!(BytesN::<32>::from(env.crypto().sha256(&encoding)), digest);
+    assert_eq!(encoding.slice(0..16), Bytes::from_slice(&env, b"battlefield:game"));
+
+    client.attack(&10_181, &player1, &0, &0);
+    let after = client.get_game_digest(&10_181);
+    assert_ne!(after, digest);
+    assert_eq!(after, state_digest(&env, &client.address, 10_181, client.get_game(&10_181)));
+    assert_contract_error(&client.try_get_game_digest(&10_182), Error::GameNotFound);
+}
+
+#[test]
+fn test_channel_checkpoint_and_force_onchain() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_191, &player1, &player2);
+    let (key1, key2) = (SigningKey::from_bytes(&[11u8; 32]), SigningKey::from_bytes(&[12u8; 32]));
+    let opening = client.get_game(&10_191);
+    let digest = client.open_channel(
+        &10_191,
+        &BytesN::from_array(&env, &key1.verifying_key().to_bytes()),
+        &BytesN::from_array(&env, &key2.verifying_key().to_bytes()),
+    );
+    assert_eq!(digest, state_digest(&env, &client.address, 10_191, opening.clone()));
+    assert_contract_error(&client.try_attack(&10_191, &player1, &0, &0), Error::BoardsNotReady);
+
+    // Player 1 hits cell 0 off-chain and both sign the resulting state.
+    let mut state = opening.clone();
+    set_bit(&mut state.player1_attacks, 0);
+    set_bit(&mut state.player1_hit_attacks, 0);
+    state.player1_hits = 1;
+    state.turn = Some(player2.clone());
+    let state_hash = state_digest(&env, &client.address, 10_191, state.clone());
+    let message = checkpoint_message(&env, &state_hash, 2);
+    client.checkpoint(&10_191, &state_hash, &sign_message(&env, &key1, &message), &sign_message(&env, &key2, &message), &2);
+    assert_contract_error(
+        &client.try_checkpoint(&10_191, &state_hash, &sign_message(&env, &key1, &message), &sign_message(&env, &key2, &message), &2),
+        Error::StaleCheckpoint,
+    );
+    assert_eq!(client.get_channel(&10_191).unwrap().move_count, 2);
+
+    assert_contract_error(&client.try_force_onchain(&10_191, &player1, &opening), Error::InvalidCheckpoint);
+    client.force_onchain(&10_191, &player1, &state);
+    let game = client.get_game(&10_191);
+    assert_eq!((game.player1_hits, game.turn.clone()), (1, Some(player2.clone())));
+    assert!(!client.get_channel(&10_191).unwrap().open);
+    client.attack(&10_191, &player2, &3, &3);
+    assert_contract_error(&client.try_force_onchain(&10_191, &player1, &state), Error::ChannelNotOpen);
+}
+
+fn open_test_channel(env: &Env, client: &BattleshipContractClient, session_id: u32) -> (SigningKey, SigningKey) {
+    let (key1, key2) = (SigningKey::from_bytes(&[11u8; 32]), SigningKey::from_bytes(&[12u8; 32]));
+    client.open_channel(
+        &session_id,
+        &BytesN::from_array(env, &key1.verifying_key().to_bytes()),
+        &BytesN::from_array(env, &key2.verifying_key().to_bytes()),
+    );
+    (key1, key2)
+}
+
+fn sign_checkpoint(env: &Env, client: &BattleshipContractClient, keys: &(SigningKey, SigningKey), session_id: u32, state: &Game, move_count: u32) -> (BytesN<32>, BytesN<64>, BytesN<64>) {
+    let digest = state_digest(env, &client.address, session_id, state.clone());
+    let message = checkpoint_message(env, &digest, move_count);
+    (digest, sign_message(env, &keys.0, &message), sign_message(env, &keys.1, &message))
+}
+
+#[test]
+fn test_channel_fraud_proofs_forfeit_the_cheater() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+
+    // Player 2 answers player 1's shot on a ship cell with a miss.
+    start_committed_game(&env, &client, 10_201, &player1, &player2);
+    let mut lie = client.get_game(&10_201);
+    let keys = open_test_channel(&env, &client, 10_201);
+    set_bit(&mut lie.player1_attacks, 0);
+    lie.turn = Some(player2.clone());
+    let (digest, sig1, sig2) = sign_checkpoint(&env, &client, &keys, 10_201, &lie, 1);
+    client.checkpoint(&10_201, &digest, &sig1, &sig2, &1);
+    client.force_onchain(&10_201, &player2, &lie);
+    assert_contract_error(&client.try_prove_invalid_transition(&10_201, &player1, &1, &true, &salt), Error::NoFraud);
+    assert_contract_error(&client.try_prove_invalid_transition(&10_201, &player1, &0, &false, &salt), Error::InvalidCellReveal);
+    client.prove_invalid_transition(&10_201, &player1, &0, &true, &salt);
+    assert_eq!(client.get_game(&10_201).winner, Some(player1.clone()));
+    assert_eq!(client.get_channel(&10_201), None);
+
+    // Player 1 forces an older checkpoint while a later co-signed state exists.
+    start_committed_game(&env, &client, 10_202, &player1, &player2);
+    let mut older = client.get_game(&10_202);
+    let keys = open_test_channel(&env, &client, 10_202);
+    set_bit(&mut older.player1_attacks, 1);
+    older.turn = Some(player2.clone());
+    let mut later = older.clone();
+    set_bit(&mut later.player2_attacks, 0);
+    set_bit(&mut later.player2_hit_attacks, 0);
+    later.player2_hits = 1;
+    later.turn = Some(player1.clone());
+    let (digest, sig1, sig2) = sign_checkpoint(&env, &client, &keys, 10_202, &older, 1);
+    client.checkpoint(&10_202, &digest, &sig1, &sig2, &1);
+    client.force_onchain(&10_202, &player1, &older);
+    let (digest, sig1, sig2) = sign_checkpoint(&env, &client, &keys, 10_202, &later, 2);
+    assert_contract_error(&client.try_challenge_stale_checkpoint(&10_202, &player1, &digest, &sig1, &sig2, &2), Error::NotPlayer);
+    client.challenge_stale_checkpoint(&10_202, &player2, &digest, &sig1, &sig2, &2);
+    assert_eq!(client.get_game(&10_202).winner, Some(player2.clone()));
+
+    // Once the window has passed, the forced state stands.
+    start_committed_game(&env, &client, 10_203, &player1, &player2);
+    let opening = client.get_game(&10_203);
+    open_test_channel(&env, &client, 10_203);
+    client.force_onchain(&10_203, &player1, &opening);
+    env.ledger().set_sequence_number(env.ledger().sequence() + CHANNEL_CHALLENGE_LEDGERS + 1);
+    assert_contract_error(&client.try_prove_invalid_transition(&10_203, &player2, &0, &true, &salt), Error::ChallengeWindowClosed);
+}
+
+#[test]
+fn test_channel_travels_with_a_snapshot_and_is_removed_when_the_game_ends() {
+    let (env, client, player1, player2, hub_addr) = setup_test();
+    start_committed_game(&env, &client, 10_204, &player1, &player2);
+    open_test_channel(&env, &client, 10_204);
+    let channel = client.get_channel(&10_204).unwrap();
+
+    let replacement_id = env.register(BattleshipContract, (&Address::generate(&env), &hub_addr, None::<ConfigBundle>));
+    let replacement = BattleshipContractClient::new(&env, &replacement_id);
+    replacement.import_game(&client.export_game(&10_204));
+    assert_eq!(replacement.get_channel(&10_204), Some(channel));
+
+    client.resign(&10_204, &player2);
+    assert_eq!(client.get_channel(&10_204), None);
+}
+
+#[test]
+fn test_fleet_config_fixes_ship_cells_at_commit() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bad = FleetConfig { ship_lengths: vec![&env, 2, 1], total_cells: 4 };
+    assert_contract_error(&client.try_start_game_with_fleet(&10_211, &player1, &player2, &0, &0, &bad), Error::InvalidConfig);
+
+    let fleet = FleetConfig { ship_lengths: vec![&env, 2, 1], total_cells: 3 };
+    client.start_game_with_fleet(&10_211, &player1, &player2, &0, &0, &fleet);
+    assert_eq!(client.get_game(&10_211).fleet, fleet);
+    assert_contract_error(&client.try_commit_board(&10_211, &player1, &build_board(&env, 10, &[0]), &1, &None, &None), Error::FleetMismatch);
+    client.commit_board(&10_211, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&10_211, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert!(client.get_game(&10_211).turn.is_some());
+}
+
+#[test]
+fn test_unsettled_index_tracks_escrowed_games() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    env.ledger().set_min_temp_entry_ttl(10);
+    client.start_game_with_ttl(&10_221, &player1, &player2, &0, &0, &720);
+    client.set_skip_policy(&10_221, &10, &30, &10);
+    client.start_game(&10_222, &player1, &player2, &0, &0);
+    client.set_skip_policy(&10_222, &10, &30, &10);
+    client.start_staked_game(&10_223, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, 10_223, &player1, &player2);
+    assert_eq!(client.get_escrow_index_len(), 3);
+    assert_eq!(client.get_unsettled_games(&0, &10).len(), 0);
+
+    client.resign(&10_222, &player2);
+    testutils::advance_ledgers(&env, 721);
+    let unsettled = client.get_unsettled_games(&0, &10);
+    assert_eq!(unsettled.len(), 2);
+    assert_eq!((unsettled.get(0).unwrap().session_id, unsettled.get(0).unwrap().action), (10_221, KeeperAction::RecoverExpired));
+    assert_eq!((unsettled.get(1).unwrap().session_id, unsettled.get(1).unwrap().action), (10_222, KeeperAction::ReleaseBonds));
+    assert_eq!(client.get_unsettled_games(&1, &1).len(), 1);
+
+    client.recover_expired(&10_221, &Address::generate(&env));
+    client.release_skip_bonds(&10_222, &Address::generate(&env));
+    assert_eq!(client.get_escrow_index_len(), 1);
+    assert_eq!(client.get_unsettled_games(&0, &10).len(), 0);
+    client.commit_board(&10_223, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&10_223, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    play_until_player1_wins(&env, &client, 10_223, &player1, &player2);
+    assert_eq!(client.get_escrow_index_len(), 0);
+}
+
+#[test]
+fn test_device_keys_sign_moves_without_auth_entries() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_231, &player1, &player2);
+    let (device1, device2) = (SigningKey::from_bytes(&[21u8; 32]), SigningKey::from_bytes(&[22u8; 32]));
+    let key1 = BytesN::from_array(&env, &device1.verifying_key().to_bytes());
+    let key2 = BytesN::from_array(&env, &device2.verifying_key().to_bytes());
+    client.add_device_key(&player1, &key1, &DEVICE_SCOPE_ALL, &0);
+    client.add_device_key(&player2, &key2, &DEVICE_SCOPE_ALL, &0);
+    env.set_auths(&[]);
+
+    let attack = device_attack_message(&env, &client.address, 10_231, 0, 0, 0);
+    assert_contract_error(&client.try_attack_signed(&10_231, &player1, &key2, &0, &0, &0, &sign_message(&env, &device2, &attack)), Error::UnknownDeviceKey);
+    client.attack_signed(&10_231, &player1, &key1, &0, &0, &0, &sign_message(&env, &device1, &attack));
+    assert_eq!(client.get_game(&10_231).pending_defender, Some(player2.clone()));
+    assert_eq!(client.get_device_nonce(&player1), 1);
+
+    let proof_hash = BytesN::from_array(&env, &proof_hash_for(&env, true, 0, 0));
+    let resolve = device_resolve_message(&env, &client.address, 10_231, 0, true, &proof_hash);
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    client.resolve_attack_signed(&10_231, &player2, &key2, &true, &salt, &proof_hash, &None, &0, &sign_message(&env, &device2, &resolve));
+    assert_eq!(client.get_game(&10_231).player1_hits, 1);
+
+    let replay = client.try_resolve_attack_signed(&10_231, &player2, &key2, &true, &salt, &proof_hash, &None, &0, &sign_message(&env, &device2, &resolve));
+    assert_contract_error(&replay, Error::InvalidNonce);
+}
+
+#[test]
+fn test_device_key_scopes_expiry_and_rotation() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    start_committed_game(&env, &client, 10_241, &player1, &player2);
+    let (old, new) = (SigningKey::from_bytes(&[31u8; 32]), SigningKey::from_bytes(&[32u8; 32]));
+    let old_key = BytesN::from_array(&env, &old.verifying_key().to_bytes());
+    let new_key = BytesN::from_array(&env, &new.verifying_key().to_bytes());
+    assert_contract_error(&client.try_add_device_key(&player1, &old_key, &4, &0), Error::InvalidConfig);
+    client.add_device_key(&player1, &old_key, &DEVICE_SCOPE_RESOLVE, &500);
+    client.add_device_key(&player2, &old_key, &DEVICE_SCOPE_ATTACK, &0);
+
+    let attack = device_attack_message(&env, &client.address, 10_241, 0, 0, 0);
+    assert_contract_error(&client.try_attack_signed(&10_241, &player1, &old_key, &0, &0, &0, &sign_message(&env, &old, &attack)), Error::DeviceKeyScope);
+    client.add_device_key(&player1, &old_key, &DEVICE_SCOPE_ALL, &500);
+    client.rotate_device_key(&player1, &old_key, &new_key);
+    assert_eq!(client.list_device_keys(&player1), vec![&env, DeviceKey { key: new_key.clone(), scopes: DEVICE_SCOPE_ALL, expires_ledger: 500 }]);
+    assert_contract_error(&client.try_attack_signed(&10_241, &player1, &old_key, &0, &0, &0, &sign_message(&env, &old, &attack)), Error::UnknownDeviceKey);
+
+    env.ledger().set_sequence_number(501);
+    assert_contract_error(&client.try_attack_signed(&10_241, &player1, &new_key, &0, &0, &0, &sign_message(&env, &new, &attack)), Error::DeviceKeyExpired);
+    client.remove_device_key(&player1, &new_key);
+    assert_eq!(client.list_device_keys(&player1).len(), 0);
+    assert_eq!(client.list_device_keys(&player2).len(), 1);
+    assert_contract_error(&client.try_remove_device_key(&player1, &new_key), Error::UnknownDeviceKey);
+}
+
+#[test]
+fn test_rematch_rolls_held_winnings_into_the_next_stake() {
+    let (env, client, player1, player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let token_client = token::Client::new(&env, &bet_token);
+    client.start_staked_game(&10_251, &player1, &player2, &0, &0, &100, &100);
+    fund_game(&client, 10_251, &player1, &player2);
+    client.commit_board(&10_251, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&10_251, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.request_rematch(&10_251, &player2, &10_252, &false);
+    assert_contract_error(&client.try_accept_rematch(&10_251, &player1), Error::GameNotFinished);
+
+    let p1_before = token_client.balance(&player1);
+    play_until_player1_wins(&env, &client, 10_251, &player1, &player2);
+    assert_eq!(token_client.balance(&player1), p1_before);
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 200);
+    assert_eq!(client.get_liabilities(&bet_token), 200);
+
+    assert_contract_error(&client.try_accept_rematch(&10_251, &player2), Error::NotPlayer);
+    assert_eq!(client.accept_rematch(&10_251, &player1), 10_252);
+    let rematch = client.get_game(&10_252);
+    assert_eq!((rematch.player1_backed, rematch.player1_deposited, rematch.player2_deposited), (100, true, false));
+    assert_eq!(client.get_withdrawable(&player1, &bet_token), 100);
+    assert_eq!(client.get_liabilities(&bet_token), 200);
+    client.deposit_stake(&10_252, &player2);
+    assert_eq!(client.get_liabilities(&bet_token), 300);
+    assert_contract_error(&client.try_accept_rematch(&10_251, &player1), Error::NoRematchOffer);
+}
+
+#[test]
+fn test_withdraw_all_sweeps_balances_across_tokens() {
+    let (env, client, player1, _player2, _hub) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let other_token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    token::StellarAssetClient::new(&env, &bet_token).mint(&client.address, &30);
+    token::StellarAssetClient::new(&env, &other_token).mint(&client.address, &70);
+    env.as_contract(&client.address, || {
+        credit_withdrawable(&env, &player1, &bet_token, 30);
+        credit_withdrawable(&env, &player1, &other_token, 70);
+    });
+
+    let balances = client.list_balances(&player1);
+    assert_eq!((balances.get(bet_token.clone()), balances.get(other_token.clone())), (Some(30), Some(70)));
+    let paid = client.withdraw_all(&player1, &vec![&env, other_token.clone()]);
+    assert_eq!(paid.get(other_token.clone()), Some(70));
+    assert_eq!(client.list_balances(&player1).len(), 1);
+
+    let paid = client.withdraw_all(&player1, &Vec::new(&env));
+    assert_eq!(paid.get(bet_token.clone()), Some(30));
+    assert_eq!(token::Client::new(&env, &other_token).balance(&player1), 70);
+    assert_eq!(client.list_balances(&player1).len(), 0);
+    assert_contract_error(&client.try_withdraw_all(&player1, &Vec::new(&env)), Error::NothingToRecover);
+}
+
+#[test]
+fn test_attack_bitmaps_cover_the_largest_board() {
+    let env = Env::default();
+    let mut bitmap = BytesN::from_array(&env, &[0u8; 32]);
+    for index in [0, 9, 99, 255] {
+        set_bit(&mut bitmap, index);
+    }
+    set_bit(&mut bitmap, 256);
+    assert!(has_bit(&bitmap, 99) && has_bit(&bitmap, 255) && !has_bit(&bitmap, 98));
+    assert_eq!(bit_count(&bitmap), 4);
+}
+
+#[test]
+fn test_operator_attribution_shares_the_protocol_fee() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let token_client = token::Client::new(&env, &client.get_bet_token().unwrap());
+    let fee_recipient = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.set_fee_recipient(&fee_recipient);
+    client.set_fee_bps(&1_000);
+
+    let operator_id = symbol_short!("arcade");
+    let points = 10_0000000i128;
+    assert_contract_error(&client.try_start_game_with_operator(&9501, &player1, &player2, &points, &points, &operator_id), Error::UnknownOperator);
+    client.register_operator(&operator_id, &owner);
+    assert_contract_error(&client.try_register_operator(&operator_id, &player1), Error::OperatorTaken);
+    client.set_operator_share(&operator_id, &2_000);
+
+    client.start_game_with_operator(&9501, &player1, &player2, &points, &points, &operator_id);
+    assert_eq!(client.get_game(&9501).operator, Some(operator_id.clone()));
+    fund_game(&client, 9501, &player1, &player2);
+    client.commit_board(&9501, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9501, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    play_until_player1_wins(&env, &client, 9501, &player1, &player2);
+
+    let fee = 2 * points / 10;
+    assert_eq!(token_client.balance(&owner), fee / 5);
+    assert_eq!(token_client.balance(&fee_recipient), fee - fee / 5);
+    let operator = client.get_operator(&operator_id).unwrap();
+    assert_eq!((operator.games, operator.fees_earned), (1, fee / 5));
+}
+
+#[test]
+fn test_dev_mode_shortens_timeouts() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.set_dev_mode(&3);
+    assert_eq!((client.get_dev_mode(), client.get_commit_window()), (3, 3));
+
+    client.start_game(&9601, &player1, &player2, &0, &0);
+    client.commit_board(&9601, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    let deadline = testutils::advance_past_next_deadline(&env, &client, 9601).unwrap();
+    assert_eq!((deadline.obligation, deadline.player), (Obligation::Commit, player2.clone()));
+    client.void_uncommitted_game(&9601, &player1);
+    assert!(client.get_game(&9601).voided);
+
+    client.set_dev_mode(&0);
+    assert_eq!(client.get_commit_window(), 17_280);
+    env.ledger().set_network_id(env.crypto().sha256(&Bytes::from_slice(&env, b"Public Global Stellar Network ; September 2015")).to_array());
+    assert_contract_error(&client.try_set_dev_mode(&3), Error::InvalidConfig);
+}
+
+#[test]
+fn test_game_transitions_emit_events() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let bet_token = client.get_bet_token().unwrap();
+    let emitted = |event: soroban_sdk::xdr::ContractEvent| env.events().all().filter_by_contract(&client.address).events().contains(&event);
+
+    let points = 10_0000000i128;
+    client.start_game(&9701, &player1, &player2, &points, &points);
+    let started = GameStarted { session_id: 9701, player1: player1.clone(), player2: player2.clone(), player1_stake: points, player2_stake: points };
+    assert!(emitted(started.to_xdr(&env, &client.address)));
+    client.deposit_stake(&9701, &player1);
+    let deposited = StakeDeposited { session_id: 9701, player: player1.clone(), from: player1.clone(), token: bet_token.clone(), amount: points };
+    assert!(emitted(deposited.to_xdr(&env, &client.address)));
+    client.deposit_stake(&9701, &player2);
+
+    client.commit_board(&9701, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    assert!(emitted(BoardCommitted { session_id: 9701, player: player1.clone(), board: 0 }.to_xdr(&env, &client.address)));
+    client.commit_board(&9701, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.attack(&9701, &player1, &5, &0);
+    assert!(emitted(AttackMade { session_id: 9701, attacker: player1.clone(), board: 0, x: 5, y: 0 }.to_xdr(&env, &client.address)));
+    resolve_cell(&env, &client, 9701, &player2, true, 5, 0);
+    let resolved = AttackResolved { session_id: 9701, defender: player2.clone(), board: 0, x: 5, y: 0, is_ship: true };
+    assert!(emitted(resolved.to_xdr(&env, &client.address)));
+
+    client.resign(&9701, &player2);
+    assert!(emitted(GameEnded { session_id: 9701, winner: Some(player1.clone()), drawn: false, voided: false }.to_xdr(&env, &client.address)));
+    assert!(emitted(Payout { session_id: 9701, to: player1.clone(), token: bet_token, amount: 2 * points }.to_xdr(&env, &client.address)));
+}
+
+#[test]
+fn test_game_summary_tracks_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.start_game(&9801, &player1, &player2, &0, &0);
+    client.commit_board(&9801, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    let summary = client.get_game_summary(&9801);
+    assert_eq!((summary.status, summary.player1_ready, summary.player2_ready, summary.turn), (GameStatus::Active, true, false, None));
+    assert_eq!(summary.deadlines.get(0).unwrap().obligation, Obligation::Commit);
+
+    client.commit_board(&9801, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.attack(&9801, &player1, &0, &0);
+    let summary = client.get_game_summary(&9801);
+    assert_eq!((summary.turn, summary.pending_defender), (Some(player1.clone()), Some(player2.clone())));
+
+    resolve_cell(&env, &client, 9801, &player2, true, 0, 0);
+    client.resign(&9801, &player2);
+    let summary = client.get_game_summary(&9801);
+    assert_eq!((summary.status, summary.player1_hits, summary.winner), (GameStatus::Finished, 1, Some(player1)));
+    assert_contract_error(&client.try_get_game_summary(&9802), Error::GameNotFound);
+}
+
+#[test]
+fn test_game_phase_follows_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let points = 10_0000000i128;
+    client.start_game(&9901, &player1, &player2, &points, &points);
+    assert_eq!(client.get_game_phase(&9901), GamePhase::AwaitingDeposits);
+    let board1 = build_board(&env, 10, &[0, 1, 2]);
+    assert_contract_error(&client.try_commit_board(&9901, &player1, &board1, &3, &None, &None), Error::StakesNotFunded);
+
+    fund_game(&client, 9901, &player1, &player2);
+    assert_eq!(client.get_game_phase(&9901), GamePhase::AwaitingBoards);
+    assert_contract_error(&client.try_attack(&9901, &player1, &0, &0), Error::BoardsNotReady);
+    client.commit_board(&9901, &player1, &board1, &3, &None, &None);
+    client.commit_board(&9901, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_eq!(client.get_game_summary(&9901).phase, GamePhase::InProgress);
+    assert_contract_error(&client.try_commit_board(&9901, &player1, &board1, &3, &None, &None), Error::BoardAlreadyCommitted);
+    let salt = Bytes::from_array(&env, &[9u8; 32]);
+    let proof = BytesN::from_array(&env, &proof_hash_for(&env, true, 0, 0));
+    assert_contract_error(&client.try_resolve_attack(&9901, &player2, &true, &salt, &proof, &None), Error::NoPendingAttack);
+
+    client.attack(&9901, &player1, &0, &0);
+    assert_eq!(client.get_game_phase(&9901), GamePhase::AwaitingResolve);
+    assert_contract_error(&client.try_attack(&9901, &player1, &1, &0), Error::PendingAttackResolution);
+    resolve_cell(&env, &client, 9901, &player2, true, 0, 0);
+    assert_eq!(client.get_game_phase(&9901), GamePhase::InProgress);
+
+    client.resign(&9901, &player2);
+    assert_eq!(client.get_game_phase(&9901), GamePhase::Finished);
+    assert_contract_error(&client.try_attack(&9901, &player1, &1, &0), Error::GameAlreadyEnded);
+
+    client.start_game(&9902, &player1, &player2, &0, &0);
+    client.void_session(&9902, &String::from_str(&env, "test"));
+    assert_eq!(client.get_game_phase(&9902), GamePhase::Cancelled);
+}
+
+#[test]
+fn test_awaiting_deposits_phase_rejects_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let points = 10_0000000i128;
+    client.start_game(&9911, &player1, &player2, &points, &points);
+    client.set_move_timeout(&9911, &10);
+    let chunk = build_board(&env, 10, &[0, 1, 2]).slice(0..10);
+    assert_contract_error(&client.try_commit_board_chunk(&9911, &player1, &0, &chunk), Error::StakesNotFunded);
+    assert_contract_error(&client.try_claim_timeout_win(&9911, &player1), Error::StakesNotFunded);
+    assert_contract_error(&client.try_release_skip_bonds(&9911, &Address::generate(&env)), Error::GameNotFinished);
+}
+
+#[test]
+fn test_awaiting_boards_phase_rejects_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let points = 10_0000000i128;
+    client.start_game(&9912, &player1, &player2, &points, &points);
+    client.set_move_timeout(&9912, &10);
+    fund_game(&client, 9912, &player1, &player2);
+    assert_contract_error(&client.try_deposit_stake(&9912, &player1), Error::AlreadyDeposited);
+    assert_contract_error(&client.try_queue_attack(&9912, &player1, &BytesN::from_array(&env, &[0; 32])), Error::NoPendingAttack);
+    assert_contract_error(&client.try_claim_timeout_win(&9912, &player1), Error::BoardsNotReady);
+    let key = BytesN::from_array(&env, &[1u8; 32]);
+    assert_contract_error(&client.try_open_channel(&9912, &key, &key), Error::BoardsNotReady);
+}
+
+#[test]
+fn test_in_progress_phase_rejects_setup_and_settlement() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.start_game(&9913, &player1, &player2, &0, &0);
+    let board1 = build_board(&env, 10, &[0, 1, 2]);
+    client.commit_board(&9913, &player1, &board1, &3, &None, &None);
+    client.commit_board(&9913, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    assert_contract_error(&client.try_set_board_size(&9913, &8), Error::BoardAlreadyCommitted);
+    assert_contract_error(&client.try_commit_board_chunk(&9913, &player1, &0, &board1.slice(0..10)), Error::BoardAlreadyCommitted);
+    assert_contract_error(&client.try_void_uncommitted_game(&9913, &player1), Error::BoardAlreadyCommitted);
+    assert_contract_error(&client.try_queue_attack(&9913, &player1, &BytesN::from_array(&env, &[0; 32])), Error::NoPendingAttack);
+    assert_contract_error(&client.try_release_tips(&9913, &player1, &Address::generate(&env)), Error::GameNotFinished);
+}
+
+#[test]
+fn test_awaiting_resolve_phase_rejects_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let points = 10_0000000i128;
+    client.start_game(&9914, &player1, &player2, &points, &points);
+    fund_game(&client, 9914, &player1, &player2);
+    client.commit_board(&9914, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9914, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.attack(&9914, &player1, &0, &0);
+    assert_contract_error(&client.try_attack(&9914, &player2, &0, &0), Error::PendingAttackResolution);
+    assert_contract_error(&client.try_deposit_stake(&9914, &player1), Error::AlreadyDeposited);
+    assert_contract_error(&client.try_set_board_count(&9914, &2), Error::BoardAlreadyCommitted);
+    assert_contract_error(&client.try_withdraw_prover_escrow(&9914, &player1), Error::GameNotFinished);
+}
+
+#[test]
+fn test_finished_phase_rejects_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    client.start_game(&9915, &player1, &player2, &0, &0);
+    client.set_move_timeout(&9915, &10);
+    client.commit_board(&9915, &player1, &build_board(&env, 10, &[0, 1, 2]), &3, &None, &None);
+    client.commit_board(&9915, &player2, &build_board(&env, 10, &[0, 5, 10]), &3, &None, &None);
+    client.resign(&9915, &player2);
+    assert_contract_error(&client.try_resign(&9915, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_offer_draw(&9915, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_queue_attack(&9915, &player1, &BytesN::from_array(&env, &[0; 32])), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_claim_timeout_win(&9915, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_join_spectators(&9915, &Address::generate(&env)), Error::GameAlreadyEnded);
+}
+
+#[test]
+fn test_cancelled_phase_rejects_play() {
+    let (env, client, player1, player2, _hub_addr) = setup_test();
+    let points = 10_0000000i128;
+    client.start_game(&9916, &player1, &player2, &points, &points);
+    client.void_session(&9916, &String::from_str(&env, "test"));
+    assert_contract_error(&client.try_deposit_stake(&9916, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_accept_draw(&9916, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_cancel_game(&9916, &player1), Error::GameAlreadyEnded);
+    assert_contract_error(&client.try_request_rematch(&9916, &player1, &9917, &false), Error::GameAlreadyEnded);
+}
@@ -0,0 +1,28 @@
+use soroban_sdk::{testutils::Ledger, Env};
+
+use crate::{BattleshipContractClient, Deadline};
+
+// Ledgers close about every five seconds; the timestamp is kept in step with the sequence.
+const SECONDS_PER_LEDGER: u64 = 5;
+
+pub fn advance_ledgers(env: &Env, ledgers: u32) {
+  env.ledger().with_mut(|ledger| {
+    ledger.sequence_number = ledger.sequence_number.saturating_add(ledgers);
+    ledger.timestamp = ledger.timestamp.saturating_add(u64::from(ledgers) * SECONDS_PER_LEDGER);
+  });
+}
+
+// Deadlines are the last ledger an obligation can still be met on, so timeouts become
+// claimable one ledger later.
+pub fn advance_past(env: &Env, deadline: u32) {
+  let sequence = env.ledger().sequence();
+  if deadline >= sequence { advance_ledgers(env, deadline - sequence + 1); }
+}
+
+// Steps past the earliest deadline `get_deadlines` reports and returns it, or returns `None`
+// when nothing is pending. With `set_dev_mode` this reaches any timeout in a few ledgers.
+pub fn advance_past_next_deadline(env: &Env, client: &BattleshipContractClient, session_id: u32) -> Option<Deadline> {
+  let next = client.get_deadlines(&session_id).iter().min_by_key(|deadline| deadline.expires_ledger)?;
+  advance_past(env, next.expires_ledger);
+  Some(next)
+}
@@ -0,0 +1,906 @@
+use core::cmp::Reverse;
+
+use soroban_sdk::{contractimpl, contracttype, token, Address, Bytes, BytesN, Env, Map, Vec};
+
+use crate::{
+  adjust_liabilities, create_game, credit_withdrawable, has_committed, missing_game, record_abandon, require_eligible, require_phase, require_token_gates, store_game, validate_token_gates, BattleshipContract, BattleshipContractArgs, BattleshipContractClient, ConfigKey, DataKey, Error, Game,
+  TokenGate, TournamentResultForced, BPS_DENOMINATOR, CAP_ALL, CAP_ZK, ENDED_PHASES, LIVE_PHASES, PERSISTENT_STATE_TTL_LEDGERS,
+};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TournamentStatus {
+  Registration,
+  Running,
+  Finished,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TournamentFormat {
+  SingleElimination,
+  RoundRobin,
+  DoubleElimination,
+}
+
+// Which part of the draw a match belongs to. Outside double elimination every match is
+// `Winners`; `GrandFinalReset` is only played when the losers-bracket finalist wins the final.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bracket {
+  Winners,
+  Losers,
+  GrandFinal,
+  GrandFinalReset,
+}
+
+// How the prize pool is divided by finishing place. `TopThree` pays 60/30/10 and `TopEight`
+// pays 40/25/15/10/2.5/2.5/2.5/2.5; a custom split lists basis points per place from first.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PrizeSplit {
+  WinnerTakesAll,
+  TopThree,
+  TopEight,
+  Custom(Vec<u32>),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TournamentMatch {
+  pub player1: Address,
+  pub player2: Option<Address>,
+  pub session_id: Option<u32>,
+  pub winner: Option<Address>,
+  pub draw: bool,
+  pub bracket: Bracket,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolStanding {
+  pub player: Address,
+  pub pool: u32,
+  pub seed_rank: u32,
+  pub played: u32,
+  pub wins: u32,
+  pub draws: u32,
+  pub losses: u32,
+  pub points: u32,
+  pub hit_difference: i64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TournamentSchedule {
+  pub registration_open: u32,
+  pub registration_close: u32,
+  pub check_in_close: u32,
+  pub round_ledgers: u32,
+  pub entry_fee: i128,
+  pub no_show_penalty_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tournament {
+  pub organizer: Address,
+  pub status: TournamentStatus,
+  pub max_players: u32,
+  pub organizer_commit: BytesN<32>,
+  pub organizer_nonce: Option<BytesN<32>>,
+  pub players: Vec<Address>,
+  pub player_commits: Map<Address, BytesN<32>>,
+  pub player_nonces: Map<Address, BytesN<32>>,
+  pub seed: Option<BytesN<32>>,
+  pub seeding: Vec<Address>,
+  pub round: u32,
+  pub matches: Vec<TournamentMatch>,
+  pub winner: Option<Address>,
+  pub format: TournamentFormat,
+  pub pool_size: u32,
+  pub qualifiers_per_pool: u32,
+  pub pool_stage: bool,
+  pub standings: Vec<PoolStanding>,
+  pub checked_in: Vec<Address>,
+  pub round_deadline: Option<u32>,
+  pub entry_token: Option<Address>,
+  pub prize_pool: i128,
+  pub results: Vec<Address>,
+  pub prize_split: Vec<u32>,
+  pub eliminations: Vec<Vec<Address>>,
+  pub zk_verifier: Option<Address>,
+  pub zk_vk_version: Option<u32>,
+  pub entry_gates: Vec<TokenGate>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameInit {
+  pub match_index: u32,
+  pub session_id: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TournamentKey { NextId, Tournament(u32), Schedule(u32), Round(u32, u32) }
+
+const MIN_TOURNAMENT_PLAYERS: u32 = 2;
+const MAX_TOURNAMENT_PLAYERS: u32 = 64;
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+const MAX_BATCH_GAMES: u32 = MAX_TOURNAMENT_PLAYERS / 2;
+
+#[contractimpl]
+impl BattleshipContract {
+  pub fn create_tournament(env: Env, organizer: Address, max_players: u32, organizer_commit: BytesN<32>) -> Result<u32, Error> {
+    organizer.require_auth();
+    if !(MIN_TOURNAMENT_PLAYERS..=MAX_TOURNAMENT_PLAYERS).contains(&max_players) { return Err(Error::InvalidConfig); }
+
+    let id: u32 = env.storage().instance().get(&TournamentKey::NextId).unwrap_or(1);
+    env.storage().instance().set(&TournamentKey::NextId, &id.saturating_add(1));
+
+    let tournament = Tournament {
+      organizer,
+      status: TournamentStatus::Registration,
+      max_players,
+      organizer_commit,
+      organizer_nonce: None,
+      players: Vec::new(&env),
+      player_commits: Map::new(&env),
+      player_nonces: Map::new(&env),
+      seed: None,
+      seeding: Vec::new(&env),
+      round: 0,
+      matches: Vec::new(&env),
+      winner: None,
+      format: TournamentFormat::SingleElimination,
+      pool_size: 0,
+      qualifiers_per_pool: 0,
+      pool_stage: false,
+      standings: Vec::new(&env),
+      checked_in: Vec::new(&env),
+      round_deadline: None,
+      entry_token: None,
+      prize_pool: 0,
+      results: Vec::new(&env),
+      prize_split: Vec::from_array(&env, [BPS_DENOMINATOR as u32]),
+      eliminations: Vec::new(&env),
+      zk_verifier: None,
+      zk_vk_version: None,
+      entry_gates: Vec::new(&env),
+    };
+    store_tournament(&env, id, &tournament);
+    Ok(id)
+  }
+
+  pub fn set_round_robin(env: Env, tournament_id: u32, pool_size: u32, qualifiers_per_pool: u32) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    if pool_size < MIN_TOURNAMENT_PLAYERS || qualifiers_per_pool == 0 || qualifiers_per_pool > pool_size { return Err(Error::InvalidConfig); }
+
+    tournament.format = TournamentFormat::RoundRobin;
+    tournament.pool_size = pool_size;
+    tournament.qualifiers_per_pool = qualifiers_per_pool;
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  // Every entrant gets a second life: a winners-bracket loss drops the player into the losers
+  // bracket, whose rounds are drawn alongside the winners bracket as each round concludes.
+  pub fn set_double_elimination(env: Env, tournament_id: u32) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+
+    tournament.format = TournamentFormat::DoubleElimination;
+    tournament.pool_size = 0;
+    tournament.qualifiers_per_pool = 0;
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn set_prize_split(env: Env, tournament_id: u32, split: PrizeSplit) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+
+    let split = match split {
+      PrizeSplit::WinnerTakesAll => Vec::from_array(&env, [10_000]),
+      PrizeSplit::TopThree => Vec::from_array(&env, [6_000, 3_000, 1_000]),
+      PrizeSplit::TopEight => Vec::from_array(&env, [4_000, 2_500, 1_500, 1_000, 250, 250, 250, 250]),
+      PrizeSplit::Custom(split) => split,
+    };
+    if split.is_empty() || split.len() > MAX_TOURNAMENT_PLAYERS { return Err(Error::InvalidConfig); }
+    if split.iter().fold(0i128, |total, bps| total.saturating_add(i128::from(bps))) != BPS_DENOMINATOR { return Err(Error::InvalidConfig); }
+    tournament.prize_split = split;
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  // Pins the verifier and VK version configured right now. Every game linked to the tournament
+  // must commit boards through `commit_board_zk` against exactly these, with no signature
+  // fallback, even if the admin rotates the verifier before the bracket finishes.
+  pub fn require_zk_boards(env: Env, tournament_id: u32) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+
+    let verifier: Address = env.storage().instance().get(&DataKey::ZkVerifierContract).ok_or(Error::ZkVerifierNotConfigured)?;
+    tournament.zk_verifier = Some(verifier);
+    tournament.zk_vk_version = Some(env.storage().instance().get(&ConfigKey::ZkVkVersion).unwrap_or(0));
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  // Checked when a player joins and again at check-in, so holdings sold off in between count.
+  pub fn set_tournament_gates(env: Env, tournament_id: u32, gates: Vec<TokenGate>) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    validate_token_gates(&gates)?;
+
+    tournament.entry_gates = gates;
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn set_tournament_schedule(env: Env, tournament_id: u32, schedule: TournamentSchedule) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration || !tournament.players.is_empty() { return Err(Error::InvalidTournamentState); }
+    if schedule.registration_open >= schedule.registration_close || schedule.registration_close >= schedule.check_in_close { return Err(Error::InvalidConfig); }
+    if schedule.round_ledgers == 0 || schedule.entry_fee < 0 || i128::from(schedule.no_show_penalty_bps) > BPS_DENOMINATOR { return Err(Error::InvalidConfig); }
+
+    // Entry fees are held in the bet token configured when the schedule is set.
+    tournament.entry_token = if schedule.entry_fee > 0 {
+      Some(env.storage().instance().get(&ConfigKey::BetToken).ok_or(Error::BetTokenNotConfigured)?)
+    } else {
+      None
+    };
+    store_tournament(&env, tournament_id, &tournament);
+    let key = TournamentKey::Schedule(tournament_id);
+    env.storage().persistent().set(&key, &schedule);
+    env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+    Ok(())
+  }
+
+  pub fn get_tournament_schedule(env: Env, tournament_id: u32) -> Option<TournamentSchedule> {
+    load_schedule(&env, tournament_id)
+  }
+
+  pub fn join_tournament(env: Env, tournament_id: u32, player: Address, nonce_commit: BytesN<32>) -> Result<(), Error> {
+    player.require_auth();
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    if tournament.player_commits.contains_key(player.clone()) { return Err(Error::AlreadyJoined); }
+    if tournament.players.len() >= tournament.max_players { return Err(Error::TournamentFull); }
+    require_eligible(&env, &player)?;
+    require_token_gates(&env, &tournament.entry_gates, &player)?;
+
+    if let Some(schedule) = load_schedule(&env, tournament_id) {
+      let now = env.ledger().sequence();
+      if now < schedule.registration_open || now >= schedule.registration_close { return Err(Error::RegistrationClosed); }
+      if let Some(token_contract) = &tournament.entry_token {
+        let escrow = env.current_contract_address();
+        token::Client::new(&env, token_contract).transfer(&player, &escrow, &schedule.entry_fee);
+        adjust_liabilities(&env, token_contract, schedule.entry_fee);
+        tournament.prize_pool = tournament.prize_pool.saturating_add(schedule.entry_fee);
+      }
+    }
+
+    tournament.players.push_back(player.clone());
+    tournament.player_commits.set(player, nonce_commit);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn reveal_tournament_nonce(env: Env, tournament_id: u32, player: Address, nonce: BytesN<32>) -> Result<(), Error> {
+    player.require_auth();
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    let commit = tournament.player_commits.get(player.clone()).ok_or(Error::NotPlayer)?;
+    if nonce_commitment(&env, &nonce) != commit { return Err(Error::InvalidReveal); }
+
+    tournament.player_nonces.set(player, nonce);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn check_in_tournament(env: Env, tournament_id: u32, player: Address) -> Result<(), Error> {
+    player.require_auth();
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    let schedule = load_schedule(&env, tournament_id).ok_or(Error::InvalidTournamentState)?;
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    let now = env.ledger().sequence();
+    if now < schedule.registration_close || now >= schedule.check_in_close { return Err(Error::InvalidTournamentState); }
+    if !tournament.player_commits.contains_key(player.clone()) { return Err(Error::NotPlayer); }
+    // Checking in confirms a revealed nonce, so every checked-in player contributes to the seed.
+    if !tournament.player_nonces.contains_key(player.clone()) { return Err(Error::InvalidReveal); }
+    if tournament.checked_in.contains(&player) { return Err(Error::AlreadyJoined); }
+    require_token_gates(&env, &tournament.entry_gates, &player)?;
+
+    tournament.checked_in.push_back(player);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn start_tournament(env: Env, tournament_id: u32, organizer_nonce: BytesN<32>) -> Result<Vec<TournamentMatch>, Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Registration { return Err(Error::InvalidTournamentState); }
+    if nonce_commitment(&env, &organizer_nonce) != tournament.organizer_commit { return Err(Error::InvalidReveal); }
+
+    let schedule = load_schedule(&env, tournament_id);
+    if let Some(schedule) = &schedule {
+      if env.ledger().sequence() < schedule.check_in_close { return Err(Error::DeadlineNotReached); }
+    }
+
+    // Players who never revealed (or, on a schedule, never checked in) are dropped rather than allowed to stall the draw.
+    let mut entrants = Vec::new(&env);
+    let mut no_shows = Vec::new(&env);
+    for player in tournament.players.iter() {
+      let present = tournament.player_nonces.contains_key(player.clone()) && (schedule.is_none() || tournament.checked_in.contains(&player));
+      if present { entrants.push_back(player); } else { no_shows.push_back(player); }
+    }
+    if entrants.len() < MIN_TOURNAMENT_PLAYERS { return Err(Error::InvalidTournamentState); }
+    if let Some(schedule) = &schedule { refund_no_shows(&env, &mut tournament, schedule, &no_shows); }
+
+    tournament.organizer_nonce = Some(organizer_nonce);
+    tournament.players = entrants;
+    let seed = tournament_seed(&env, &tournament);
+    tournament.seeding = shuffled_seeding(&env, &seed, &tournament.players);
+    tournament.seed = Some(seed);
+    if tournament.format == TournamentFormat::RoundRobin {
+      start_pools(&env, &mut tournament);
+    } else {
+      tournament.matches = pair_round(&env, &tournament.seeding, Bracket::Winners);
+    }
+    tournament.round = 1;
+    tournament.status = TournamentStatus::Running;
+    start_round_clock(&env, &mut tournament, schedule.as_ref());
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(tournament.matches)
+  }
+
+  pub fn verify_pairings(env: Env, tournament_id: u32) -> Result<bool, Error> {
+    let tournament = load_tournament(&env, tournament_id)?;
+    let Some(seed) = tournament.seed.clone() else { return Ok(false); };
+    if tournament_seed(&env, &tournament) != seed { return Ok(false); }
+    Ok(shuffled_seeding(&env, &seed, &tournament.players) == tournament.seeding)
+  }
+
+  pub fn link_tournament_match(env: Env, tournament_id: u32, match_index: u32, session_id: u32) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Running { return Err(Error::InvalidTournamentState); }
+    let mut entry = tournament.matches.get(match_index).ok_or(Error::InvalidMatch)?;
+    let opponent = entry.player2.clone().ok_or(Error::InvalidMatch)?;
+    if entry.session_id.is_some() { return Err(Error::InvalidMatch); }
+
+    let mut game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    let same_players = (game.player1 == entry.player1 && game.player2 == opponent) || (game.player1 == opponent && game.player2 == entry.player1);
+    if !same_players || game.exhibition { return Err(Error::InvalidMatch); }
+    require_phase(&game, &LIVE_PHASES)?;
+    if tournament.zk_verifier.is_some() {
+      pin_zk_verifier(&tournament, &mut game)?;
+      store_game(&env, session_id, &game);
+    }
+
+    entry.session_id = Some(session_id);
+    tournament.matches.set(match_index, entry);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  // Joining a tournament is each player's standing intent to play its matches, so the organizer
+  // can open a round's games in one call without a signature per seat. Games are unstaked.
+  pub fn start_games_batch(env: Env, tournament_id: u32, games: Vec<GameInit>) -> Result<u32, Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Running { return Err(Error::InvalidTournamentState); }
+    if games.is_empty() || games.len() > MAX_BATCH_GAMES { return Err(Error::InvalidConfig); }
+
+    for init in games.iter() {
+      let mut entry = tournament.matches.get(init.match_index).ok_or(Error::InvalidMatch)?;
+      let opponent = entry.player2.clone().ok_or(Error::InvalidMatch)?;
+      if entry.session_id.is_some() || entry.winner.is_some() || entry.draw { return Err(Error::InvalidMatch); }
+      if env.storage().persistent().has(&DataKey::Tombstone(init.session_id)) { return Err(Error::SessionInUse); }
+
+      create_game(&env, init.session_id, entry.player1.clone(), opponent, 0, 0, 0, 0, CAP_ALL, 0)?;
+      if tournament.zk_verifier.is_some() {
+        let key = DataKey::Game(init.session_id);
+        let mut game: Game = env.storage().temporary().get(&key).ok_or_else(|| missing_game(&env, init.session_id))?;
+        pin_zk_verifier(&tournament, &mut game)?;
+        store_game(&env, init.session_id, &game);
+      }
+      entry.session_id = Some(init.session_id);
+      tournament.matches.set(init.match_index, entry);
+    }
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(games.len())
+  }
+
+  pub fn report_tournament_match(env: Env, tournament_id: u32, match_index: u32) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Running { return Err(Error::InvalidTournamentState); }
+    let mut entry = tournament.matches.get(match_index).ok_or(Error::InvalidMatch)?;
+    if entry.winner.is_some() || entry.draw { return Err(Error::InvalidMatch); }
+    let session_id = entry.session_id.ok_or(Error::InvalidMatch)?;
+    let game: Game = env.storage().temporary().get(&DataKey::Game(session_id)).ok_or_else(|| missing_game(&env, session_id))?;
+    require_phase(&game, &ENDED_PHASES)?;
+    // Only pool play scores a drawn game; a knockout match still needs a winner.
+    if game.drawn && tournament.pool_stage {
+      entry.draw = true;
+    } else {
+      entry.winner = Some(game.winner.clone().ok_or(Error::GameNotFinished)?);
+    }
+    tournament.matches.set(match_index, entry.clone());
+
+    let hits = if game.player1 == entry.player1 { (game.player1_hits, game.player2_hits) } else { (game.player2_hits, game.player1_hits) };
+    if tournament.pool_stage { record_pool_result(&mut tournament, &entry, hits); }
+    advance_tournament(&env, tournament_id, &mut tournament);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn enforce_round_deadline(env: Env, tournament_id: u32) -> Result<u32, Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    if tournament.status != TournamentStatus::Running { return Err(Error::InvalidTournamentState); }
+    let deadline = tournament.round_deadline.ok_or(Error::InvalidTournamentState)?;
+    if env.ledger().sequence() <= deadline { return Err(Error::DeadlineNotReached); }
+
+    let mut forfeits = 0;
+    for match_index in 0..tournament.matches.len() {
+      let mut entry = tournament.matches.get_unchecked(match_index);
+      if entry.winner.is_some() || entry.draw { continue; }
+      let Some(opponent) = entry.player2.clone() else { continue; };
+      let game: Option<Game> = entry.session_id.and_then(|session_id| env.storage().temporary().get(&DataKey::Game(session_id)));
+      let winner = match game.as_ref().and_then(|game| game.winner.clone()) {
+        Some(winner) => winner,
+        None => {
+          forfeits += 1;
+          let staller = game.as_ref().and_then(stalling_player);
+          if let Some(staller) = &staller { record_abandon(&env, staller); }
+          match staller {
+            Some(staller) if staller == entry.player1 => opponent,
+            // An unplayed game with no clear staller goes to the better-seeded player.
+            _ => entry.player1.clone(),
+          }
+        }
+      };
+      entry.winner = Some(winner);
+      tournament.matches.set(match_index, entry.clone());
+      if tournament.pool_stage { record_pool_result(&mut tournament, &entry, (0, 0)); }
+    }
+
+    advance_tournament(&env, tournament_id, &mut tournament);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(forfeits)
+  }
+
+  // The organizer's ruling on one match still open after the round deadline: `winner` takes
+  // the match as a forfeit. Only the bracket moves; the linked game and any escrow in it are
+  // untouched, and no abandon is recorded against the loser.
+  pub fn force_tournament_result(env: Env, tournament_id: u32, match_index: u32, winner: Address) -> Result<(), Error> {
+    let mut tournament = load_tournament(&env, tournament_id)?;
+    tournament.organizer.require_auth();
+    if tournament.status != TournamentStatus::Running { return Err(Error::InvalidTournamentState); }
+    let deadline = tournament.round_deadline.ok_or(Error::InvalidTournamentState)?;
+    if env.ledger().sequence() <= deadline { return Err(Error::DeadlineNotReached); }
+    let mut entry = tournament.matches.get(match_index).ok_or(Error::InvalidMatch)?;
+    if entry.winner.is_some() || entry.draw { return Err(Error::InvalidMatch); }
+    if winner != entry.player1 && Some(&winner) != entry.player2.as_ref() { return Err(Error::NotPlayer); }
+
+    entry.winner = Some(winner.clone());
+    tournament.matches.set(match_index, entry.clone());
+    if tournament.pool_stage { record_pool_result(&mut tournament, &entry, (0, 0)); }
+    TournamentResultForced { tournament_id, match_index, winner, session_id: entry.session_id }.publish(&env);
+    advance_tournament(&env, tournament_id, &mut tournament);
+    store_tournament(&env, tournament_id, &tournament);
+    Ok(())
+  }
+
+  pub fn get_tournament(env: Env, tournament_id: u32) -> Result<Tournament, Error> {
+    load_tournament(&env, tournament_id)
+  }
+
+  pub fn get_standings(env: Env, tournament_id: u32) -> Result<Vec<PoolStanding>, Error> {
+    Ok(load_tournament(&env, tournament_id)?.standings)
+  }
+
+  // Concluded rounds are archived as they are replaced; the current round is read live.
+  pub fn get_tournament_round(env: Env, tournament_id: u32, round: u32) -> Result<Vec<TournamentMatch>, Error> {
+    let tournament = load_tournament(&env, tournament_id)?;
+    if round == tournament.round { return Ok(tournament.matches); }
+    env.storage().persistent().get(&TournamentKey::Round(tournament_id, round)).ok_or(Error::InvalidMatch)
+  }
+}
+
+pub fn nonce_commitment(env: &Env, nonce: &BytesN<32>) -> BytesN<32> {
+  env.crypto().keccak256(&Bytes::from_array(env, &nonce.to_array())).into()
+}
+
+fn tournament_seed(env: &Env, tournament: &Tournament) -> BytesN<32> {
+  let mut material = Bytes::new(env);
+  if let Some(nonce) = &tournament.organizer_nonce { material.append(&Bytes::from_array(env, &nonce.to_array())); }
+  for player in tournament.players.iter() {
+    if let Some(nonce) = tournament.player_nonces.get(player) { material.append(&Bytes::from_array(env, &nonce.to_array())); }
+  }
+  env.crypto().keccak256(&material).into()
+}
+
+fn shuffled_seeding(env: &Env, seed: &BytesN<32>, players: &Vec<Address>) -> Vec<Address> {
+  env.prng().seed(Bytes::from_array(env, &seed.to_array()));
+  let mut seeding = players.clone();
+  env.prng().shuffle(&mut seeding);
+  seeding
+}
+
+fn pair_round(env: &Env, entrants: &Vec<Address>, bracket: Bracket) -> Vec<TournamentMatch> {
+  let mut matches = Vec::new(env);
+  let mut index = 0;
+  while index < entrants.len() {
+    let player2 = entrants.get(index + 1);
+    // An unpaired entrant advances on a bye.
+    let winner = if player2.is_none() { entrants.get(index) } else { None };
+    matches.push_back(TournamentMatch { player1: entrants.get(index).unwrap(), player2, session_id: None, winner, draw: false, bracket });
+    index += 2;
+  }
+  matches
+}
+
+fn advance_round(env: &Env, tournament: &mut Tournament) {
+  let mut winners = Vec::new(env);
+  let mut knocked_out = Vec::new(env);
+  for entry in tournament.matches.iter() {
+    let Some(winner) = entry.winner.clone() else { return; };
+    if let Some(loser) = match_loser(&entry, &winner) { knocked_out.push_back(loser); }
+    winners.push_back(winner);
+  }
+  record_eliminations(tournament, knocked_out);
+  // Knockout winners are kept round by round so bracket predictions can be scored later.
+  tournament.results.append(&winners);
+  if winners.len() == 1 {
+    tournament.winner = winners.get(0);
+    tournament.status = TournamentStatus::Finished;
+    return;
+  }
+  tournament.round = tournament.round.saturating_add(1);
+  tournament.matches = pair_round(env, &winners, Bracket::Winners);
+}
+
+// Both brackets advance together. Winners-bracket losers join the losers bracket behind its
+// survivors; once each bracket is down to one player they meet in the grand final, which the
+// winners-bracket champion (always `player1`) must lose twice.
+fn advance_double_elimination(env: &Env, tournament: &mut Tournament) {
+  if tournament.matches.iter().any(|entry| entry.winner.is_none()) { return; }
+
+  let mut round_winners = Vec::new(env);
+  let mut winners = Vec::new(env);
+  let mut losers = Vec::new(env);
+  let mut drops = Vec::new(env);
+  let mut knocked_out = Vec::new(env);
+  for entry in tournament.matches.iter() {
+    let winner = entry.winner.clone().unwrap();
+    round_winners.push_back(winner.clone());
+    let loser = match_loser(&entry, &winner);
+    match entry.bracket {
+      Bracket::Winners => {
+        winners.push_back(winner);
+        if let Some(loser) = loser { drops.push_back(loser); }
+      }
+      Bracket::Losers => {
+        losers.push_back(winner);
+        if let Some(loser) = loser { knocked_out.push_back(loser); }
+      }
+      Bracket::GrandFinal if winner != entry.player1 => {
+        tournament.results.append(&round_winners);
+        tournament.round = tournament.round.saturating_add(1);
+        tournament.matches = Vec::from_array(env, [TournamentMatch {
+          player1: entry.player1, player2: Some(winner), session_id: None, winner: None, draw: false, bracket: Bracket::GrandFinalReset,
+        }]);
+        return;
+      }
+      Bracket::GrandFinal | Bracket::GrandFinalReset => {
+        if let Some(loser) = loser { record_eliminations(tournament, Vec::from_array(env, [loser])); }
+        tournament.results.append(&round_winners);
+        tournament.winner = Some(winner);
+        tournament.status = TournamentStatus::Finished;
+        return;
+      }
+    }
+  }
+  tournament.results.append(&round_winners);
+  record_eliminations(tournament, knocked_out);
+  losers.append(&drops);
+
+  tournament.round = tournament.round.saturating_add(1);
+  if winners.len() == 1 && losers.len() <= 1 {
+    let Some(challenger) = losers.get(0) else {
+      tournament.winner = winners.get(0);
+      tournament.status = TournamentStatus::Finished;
+      return;
+    };
+    tournament.matches = Vec::from_array(env, [TournamentMatch {
+      player1: winners.get_unchecked(0), player2: Some(challenger), session_id: None, winner: None, draw: false, bracket: Bracket::GrandFinal,
+    }]);
+    return;
+  }
+  // A bracket with a single player left gives them a bye until the other catches up.
+  let mut matches = pair_round(env, &winners, Bracket::Winners);
+  matches.append(&pair_round(env, &losers, Bracket::Losers));
+  tournament.matches = matches;
+}
+
+// A game joins a ZK tournament only if every board it holds, or will hold, goes through the
+// tournament's verifier: single-board, ZK-capable, and with no board committed any other way.
+fn pin_zk_verifier(tournament: &Tournament, game: &mut Game) -> Result<(), Error> {
+  if game.capabilities & CAP_ZK != CAP_ZK || game.board_count > 1 { return Err(Error::InvalidMatch); }
+  let pinned = |committed: bool, verifier: &Option<Address>, vk_version: Option<u32>| {
+    !committed || (*verifier == tournament.zk_verifier && vk_version == tournament.zk_vk_version)
+  };
+  if !pinned(game.player1_board.is_some(), &game.player1_board_verifier, game.player1_board_vk_version)
+    || !pinned(game.player2_board.is_some(), &game.player2_board_verifier, game.player2_board_vk_version)
+  {
+    return Err(Error::InvalidMatch);
+  }
+  game.pinned_verifier = tournament.zk_verifier.clone();
+  game.pinned_vk_version = tournament.zk_vk_version;
+  Ok(())
+}
+
+fn match_loser(entry: &TournamentMatch, winner: &Address) -> Option<Address> {
+  let player2 = entry.player2.clone()?;
+  Some(if player2 == *winner { entry.player1.clone() } else { player2 })
+}
+
+fn record_eliminations(tournament: &mut Tournament, knocked_out: Vec<Address>) {
+  if !knocked_out.is_empty() { tournament.eliminations.push_back(knocked_out); }
+}
+
+fn start_pools(env: &Env, tournament: &mut Tournament) {
+  // Pools are at least `pool_size` strong; leftover entrants are dealt into the existing pools.
+  let pool_count = (tournament.seeding.len() / tournament.pool_size).max(1);
+  let mut standings = Vec::new(env);
+  for (seed_rank, player) in tournament.seeding.iter().enumerate() {
+    let seed_rank = seed_rank as u32;
+    standings.push_back(PoolStanding {
+      player, pool: seed_rank % pool_count, seed_rank,
+      played: 0, wins: 0, draws: 0, losses: 0, points: 0, hit_difference: 0,
+    });
+  }
+
+  let mut matches = Vec::new(env);
+  for pool in 0..pool_count {
+    let members = pool_members(env, &standings, pool);
+    for first in 0..members.len() {
+      for second in (first + 1)..members.len() {
+        matches.push_back(TournamentMatch {
+          player1: members.get_unchecked(first), player2: members.get(second), session_id: None, winner: None, draw: false,
+          bracket: Bracket::Winners,
+        });
+      }
+    }
+  }
+  tournament.standings = standings;
+  tournament.matches = matches;
+  tournament.pool_stage = true;
+}
+
+fn pool_members(env: &Env, standings: &Vec<PoolStanding>, pool: u32) -> Vec<Address> {
+  let mut members = Vec::new(env);
+  for standing in standings.iter() {
+    if standing.pool == pool { members.push_back(standing.player); }
+  }
+  members
+}
+
+// `hits` are the hits scored by the match's player1 and player2, in that order.
+fn record_pool_result(tournament: &mut Tournament, entry: &TournamentMatch, hits: (u32, u32)) {
+  for index in 0..tournament.standings.len() {
+    let mut standing = tournament.standings.get_unchecked(index);
+    let (scored, conceded) = if standing.player == entry.player1 {
+      hits
+    } else if Some(&standing.player) == entry.player2.as_ref() {
+      (hits.1, hits.0)
+    } else {
+      continue;
+    };
+    standing.played = standing.played.saturating_add(1);
+    standing.hit_difference = standing.hit_difference.saturating_add(i64::from(scored) - i64::from(conceded));
+    match &entry.winner {
+      Some(winner) if *winner == standing.player => {
+        standing.wins = standing.wins.saturating_add(1);
+        standing.points = standing.points.saturating_add(WIN_POINTS);
+      }
+      Some(_) => standing.losses = standing.losses.saturating_add(1),
+      None => {
+        standing.draws = standing.draws.saturating_add(1);
+        standing.points = standing.points.saturating_add(DRAW_POINTS);
+      }
+    }
+    tournament.standings.set(index, standing);
+  }
+  rank_standings(&mut tournament.standings);
+}
+
+// Standings are kept grouped by pool and ranked by points, then wins, then hit difference,
+// with the verifiable seeding order as the final tiebreak.
+fn rank_standings(standings: &mut Vec<PoolStanding>) {
+  let rank_key = |standing: &PoolStanding| {
+    (standing.pool, Reverse(standing.points), Reverse(standing.wins), Reverse(standing.hit_difference), standing.seed_rank)
+  };
+  for index in 1..standings.len() {
+    let mut position = index;
+    while position > 0 {
+      let current = standings.get_unchecked(position);
+      let previous = standings.get_unchecked(position - 1);
+      if rank_key(&current) >= rank_key(&previous) { break; }
+      standings.set(position, previous);
+      standings.set(position - 1, current);
+      position -= 1;
+    }
+  }
+}
+
+fn advance_pools(env: &Env, tournament: &mut Tournament) {
+  if tournament.matches.iter().any(|entry| entry.winner.is_none() && !entry.draw) { return; }
+
+  // Qualifiers are ordered by finishing place, then pool, and the knockout pairs the best
+  // remaining qualifier against the worst; with an odd count the top qualifier takes the bye.
+  let mut qualifiers = Vec::new(env);
+  for place in 0..tournament.qualifiers_per_pool {
+    let mut pool = 0;
+    loop {
+      let members = pool_members(env, &tournament.standings, pool);
+      if members.is_empty() { break; }
+      if let Some(player) = members.get(place) { qualifiers.push_back(player); }
+      pool += 1;
+    }
+  }
+
+  // Non-qualifiers go out worst place first, each place across all pools together.
+  let mut place = tournament.standings.len();
+  while place > tournament.qualifiers_per_pool {
+    place -= 1;
+    let mut knocked_out = Vec::new(env);
+    let mut pool = 0;
+    loop {
+      let members = pool_members(env, &tournament.standings, pool);
+      if members.is_empty() { break; }
+      if let Some(player) = members.get(place) { knocked_out.push_back(player); }
+      pool += 1;
+    }
+    record_eliminations(tournament, knocked_out);
+  }
+
+  tournament.pool_stage = false;
+  if qualifiers.len() == 1 {
+    tournament.winner = qualifiers.get(0);
+    tournament.status = TournamentStatus::Finished;
+    return;
+  }
+  let mut bracket = Vec::new(env);
+  let (mut low, mut high) = (qualifiers.len() % 2, qualifiers.len());
+  while low < high {
+    bracket.push_back(qualifiers.get_unchecked(low));
+    bracket.push_back(qualifiers.get_unchecked(high - 1));
+    low += 1;
+    high -= 1;
+  }
+  if qualifiers.len() % 2 == 1 { bracket.push_back(qualifiers.get_unchecked(0)); }
+  tournament.round = tournament.round.saturating_add(1);
+  tournament.matches = pair_round(env, &bracket, Bracket::Winners);
+}
+
+fn advance_tournament(env: &Env, tournament_id: u32, tournament: &mut Tournament) {
+  let round = tournament.round;
+  let concluded = tournament.matches.clone();
+  if tournament.pool_stage {
+    advance_pools(env, tournament);
+  } else if tournament.format == TournamentFormat::DoubleElimination {
+    advance_double_elimination(env, tournament);
+  } else {
+    advance_round(env, tournament);
+  }
+  if tournament.round != round {
+    let key = TournamentKey::Round(tournament_id, round);
+    env.storage().persistent().set(&key, &concluded);
+    env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+  }
+  if tournament.status == TournamentStatus::Finished {
+    tournament.round_deadline = None;
+    pay_prize_pool(env, tournament);
+  } else if tournament.round != round {
+    start_round_clock(env, tournament, load_schedule(env, tournament_id).as_ref());
+  }
+}
+
+fn start_round_clock(env: &Env, tournament: &mut Tournament, schedule: Option<&TournamentSchedule>) {
+  tournament.round_deadline = schedule.map(|schedule| env.ledger().sequence().saturating_add(schedule.round_ledgers));
+}
+
+// The player holding up a linked game: the defender owing a resolution, the player to move,
+// or a player who never committed a board.
+fn stalling_player(game: &Game) -> Option<Address> {
+  if let Some(defender) = &game.pending_defender { return Some(defender.clone()); }
+  if let Some(turn) = &game.turn { return Some(turn.clone()); }
+  let player1_committed = has_committed(game, &game.player1);
+  let player2_committed = has_committed(game, &game.player2);
+  match (player1_committed, player2_committed) {
+    (true, false) => Some(game.player2.clone()),
+    (false, true) => Some(game.player1.clone()),
+    _ => None,
+  }
+}
+
+fn refund_no_shows(env: &Env, tournament: &mut Tournament, schedule: &TournamentSchedule, no_shows: &Vec<Address>) {
+  let Some(token_contract) = tournament.entry_token.clone() else { return; };
+  if no_shows.is_empty() { return; }
+  let token_client = token::Client::new(env, &token_contract);
+  let escrow = env.current_contract_address();
+  let penalty = schedule.entry_fee.saturating_mul(i128::from(schedule.no_show_penalty_bps)) / BPS_DENOMINATOR;
+  let refund = schedule.entry_fee.saturating_sub(penalty);
+  for player in no_shows.iter() {
+    if refund > 0 { token_client.transfer(&escrow, &player, &refund); }
+    if penalty > 0 { token_client.transfer(&escrow, &tournament.organizer, &penalty); }
+  }
+  let released = schedule.entry_fee.saturating_mul(i128::from(no_shows.len()));
+  adjust_liabilities(env, &token_contract, released.saturating_neg());
+  tournament.prize_pool = tournament.prize_pool.saturating_sub(released);
+}
+
+// Places run from the champion back through the eliminations, latest first. Players knocked
+// out together share their places' basis points equally; places nobody reached and rounding
+// dust go to the champion. A prize the token refuses to transfer is left as a withdrawable
+// balance for its owner to claim later.
+fn pay_prize_pool(env: &Env, tournament: &mut Tournament) {
+  let (Some(token_contract), Some(winner)) = (tournament.entry_token.clone(), tournament.winner.clone()) else { return; };
+  if tournament.prize_pool <= 0 { return; }
+  let pool = tournament.prize_pool;
+
+  let winner_share = pool.saturating_mul(i128::from(tournament.prize_split.get(0).unwrap_or(0))) / BPS_DENOMINATOR;
+  let mut awards: Vec<(Address, i128)> = Vec::from_array(env, [(winner.clone(), 0)]);
+  let mut place = 1u32;
+  let mut paid = winner_share;
+  for index in (0..tournament.eliminations.len()).rev() {
+    let group = tournament.eliminations.get_unchecked(index);
+    let mut group_bps = 0i128;
+    for offset in 0..group.len() { group_bps = group_bps.saturating_add(i128::from(tournament.prize_split.get(place + offset).unwrap_or(0))); }
+    place = place.saturating_add(group.len());
+    if group_bps == 0 { continue; }
+    let share = pool.saturating_mul(group_bps) / BPS_DENOMINATOR / i128::from(group.len());
+    for player in group.iter() {
+      awards.push_back((player, share));
+      paid = paid.saturating_add(share);
+    }
+  }
+  awards.set(0, (winner, winner_share.saturating_add(pool.saturating_sub(paid))));
+
+  let token_client = token::Client::new(env, &token_contract);
+  let escrow = env.current_contract_address();
+  for (player, amount) in awards.iter() {
+    if amount <= 0 { continue; }
+    if matches!(token_client.try_transfer(&escrow, &player, &amount), Ok(Ok(()))) {
+      adjust_liabilities(env, &token_contract, amount.saturating_neg());
+    } else {
+      credit_withdrawable(env, &player, &token_contract, amount);
+    }
+  }
+  tournament.prize_pool = 0;
+}
+
+fn load_schedule(env: &Env, tournament_id: u32) -> Option<TournamentSchedule> {
+  env.storage().persistent().get(&TournamentKey::Schedule(tournament_id))
+}
+
+pub(crate) fn load_tournament(env: &Env, tournament_id: u32) -> Result<Tournament, Error> {
+  env.storage().persistent().get(&TournamentKey::Tournament(tournament_id)).ok_or(Error::TournamentNotFound)
+}
+
+fn store_tournament(env: &Env, tournament_id: u32, tournament: &Tournament) {
+  let key = TournamentKey::Tournament(tournament_id);
+  env.storage().persistent().set(&key, tournament);
+  env.storage().persistent().extend_ttl(&key, PERSISTENT_STATE_TTL_LEDGERS, PERSISTENT_STATE_TTL_LEDGERS);
+}
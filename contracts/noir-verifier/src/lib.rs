@@ -1,7 +1,8 @@
 #![no_std]
 
+use battlefield_common::domain::signing_domain;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, Address, Bytes, BytesN, Env,
+    contract, contracterror, contractimpl, contractmeta, contracttype, Address, Bytes, BytesN, Env, Vec,
 };
 
 #[contracterror]
@@ -12,6 +13,7 @@ pub enum Error {
     VerifierNotConfigured = 2,
     InvalidProofLength = 3,
     InvalidHitFlag = 4,
+    InvalidSignerSet = 5,
 }
 
 #[contracttype]
@@ -19,8 +21,18 @@ pub enum Error {
 pub enum DataKey {
     Admin,
     VerifierPubKey,
+    Signers,
+    Threshold,
 }
 
+pub const MAX_SIGNERS: u32 = 16;
+
+// One entry of a threshold proof: the signer's index in the set, then its signature.
+const SIGNER_ENTRY_LEN: u32 = 65;
+
+contractmeta!(key = "Description", val = "Ed25519 attestation verifier for Battleship board and attack proofs");
+contractmeta!(key = "binver", val = env!("CARGO_PKG_VERSION"));
+
 #[contract]
 pub struct NoirVerifierContract;
 
@@ -46,6 +58,39 @@ impl NoirVerifierContract {
         env.storage().instance().get(&DataKey::VerifierPubKey)
     }
 
+    // Replaces the single verifier key with a set of `signers`, any `threshold` of whom must
+    // sign each attestation. While a set is configured it takes precedence over `set_verifier`.
+    pub fn set_signers(env: Env, signers: Vec<BytesN<32>>, threshold: u32) -> Result<(), Error> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("admin not set");
+        admin.require_auth();
+        if threshold == 0 || threshold > signers.len() || signers.len() > MAX_SIGNERS {
+            return Err(Error::InvalidSignerSet);
+        }
+        for (index, signer) in signers.iter().enumerate() {
+            if signers.first_index_of(&signer) != Some(index as u32) {
+                return Err(Error::InvalidSignerSet);
+            }
+        }
+        env.storage().instance().set(&DataKey::Signers, &signers);
+        env.storage().instance().set(&DataKey::Threshold, &threshold);
+        Ok(())
+    }
+
+    pub fn clear_signers(env: Env) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).expect("admin not set");
+        admin.require_auth();
+        env.storage().instance().remove(&DataKey::Signers);
+        env.storage().instance().remove(&DataKey::Threshold);
+    }
+
+    pub fn get_signers(env: Env) -> Vec<BytesN<32>> {
+        env.storage().instance().get(&DataKey::Signers).unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_threshold(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Threshold).unwrap_or(0)
+    }
+
     pub fn verify_board(
         env: Env,
         session_id: u32,
@@ -53,28 +98,14 @@ impl NoirVerifierContract {
         commitment_root: BytesN<32>,
         proof: Bytes,
     ) -> bool {
-        let verifier_key: BytesN<32> = match env.storage().instance().get(&DataKey::VerifierPubKey) {
-            Some(v) => v,
-            None => return false,
-        };
-
-        if proof.len() != 64 {
-            return false;
-        }
-
-        let signature = match bytes_to_sig64(&proof) {
-            Some(sig) => sig,
-            None => return false,
-        };
-
         let mut message = Bytes::new(&env);
         message.push_back(1u8);
+        message.append(&signing_domain(&env, &env.current_contract_address()));
         append_u32_be(&mut message, session_id);
         append_u32_be(&mut message, ship_cells);
         message.append(&Bytes::from_array(&env, &commitment_root.to_array()));
 
-        env.crypto().ed25519_verify(&verifier_key, &message, &signature);
-        true
+        check_attestation(&env, &message, &proof)
     }
 
     pub fn verify_attack(
@@ -85,36 +116,88 @@ impl NoirVerifierContract {
         expected_commitment: BytesN<32>,
         proof: Bytes,
     ) -> bool {
-        let verifier_key: BytesN<32> = match env.storage().instance().get(&DataKey::VerifierPubKey) {
-            Some(v) => v,
-            None => return false,
-        };
-
-        if proof.len() != 65 {
-            return false;
-        }
-
         let is_ship = proof.get(0).unwrap_or(2);
         if is_ship > 1 {
             return false;
         }
 
-        let signature = match proof_tail_to_sig64(&proof) {
-            Some(sig) => sig,
-            None => return false,
-        };
-
         let mut message = Bytes::new(&env);
         message.push_back(2u8);
+        message.append(&signing_domain(&env, &env.current_contract_address()));
         append_u32_be(&mut message, session_id);
         append_u32_be(&mut message, x);
         append_u32_be(&mut message, y);
         message.append(&Bytes::from_array(&env, &expected_commitment.to_array()));
         message.push_back(is_ship);
 
-        env.crypto().ed25519_verify(&verifier_key, &message, &signature);
-        is_ship == 1
+        check_attestation(&env, &message, &proof.slice(1..)) && is_ship == 1
+    }
+
+    pub fn verify_transcript(
+        env: Env,
+        session_id: u32,
+        from_move: u32,
+        to_move: u32,
+        transcript_root: BytesN<32>,
+        proof: Bytes,
+    ) -> bool {
+        if from_move >= to_move {
+            return false;
+        }
+
+        let mut message = Bytes::new(&env);
+        message.push_back(3u8);
+        message.append(&signing_domain(&env, &env.current_contract_address()));
+        append_u32_be(&mut message, session_id);
+        append_u32_be(&mut message, from_move);
+        append_u32_be(&mut message, to_move);
+        message.append(&Bytes::from_array(&env, &transcript_root.to_array()));
+
+        check_attestation(&env, &message, &proof)
+    }
+}
+
+// With a signer set, `signatures` holds at least `threshold` entries of a signer index byte
+// followed by that signer's signature, indices strictly increasing so no signer counts twice.
+// Otherwise it is one signature by the single verifier key.
+fn check_attestation(env: &Env, message: &Bytes, signatures: &Bytes) -> bool {
+    let signers: Vec<BytesN<32>> = match env.storage().instance().get(&DataKey::Signers) {
+        Some(signers) => signers,
+        None => {
+            let verifier_key: BytesN<32> = match env.storage().instance().get(&DataKey::VerifierPubKey) {
+                Some(v) => v,
+                None => return false,
+            };
+            let signature = match bytes_to_sig64(signatures) {
+                Some(sig) => sig,
+                None => return false,
+            };
+            env.crypto().ed25519_verify(&verifier_key, message, &signature);
+            return true;
+        }
+    };
+
+    let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap_or(u32::MAX);
+    if signatures.is_empty() || !signatures.len().is_multiple_of(SIGNER_ENTRY_LEN) || signatures.len() / SIGNER_ENTRY_LEN < threshold {
+        return false;
     }
+    let mut next_index = 0u32;
+    let mut offset = 0u32;
+    while offset < signatures.len() {
+        let index = signatures.get(offset).unwrap_or(u8::MAX) as u32;
+        let signer = match signers.get(index) {
+            Some(signer) if index >= next_index => signer,
+            _ => return false,
+        };
+        let signature = match bytes_to_sig64(&signatures.slice(offset + 1..offset + SIGNER_ENTRY_LEN)) {
+            Some(sig) => sig,
+            None => return false,
+        };
+        env.crypto().ed25519_verify(&signer, message, &signature);
+        next_index = index + 1;
+        offset += SIGNER_ENTRY_LEN;
+    }
+    true
 }
 
 fn append_u32_be(bytes: &mut Bytes, value: u32) {
@@ -137,15 +220,4 @@ fn bytes_to_sig64(bytes: &Bytes) -> Option<BytesN<64>> {
     Some(BytesN::from_array(bytes.env(), &raw))
 }
 
-fn proof_tail_to_sig64(bytes: &Bytes) -> Option<BytesN<64>> {
-    if bytes.len() != 65 {
-        return None;
-    }
-    let mut raw = [0u8; 64];
-    let mut i = 0;
-    while i < 64 {
-        raw[i] = bytes.get((i + 1) as u32).unwrap_or(0);
-        i += 1;
-    }
-    Some(BytesN::from_array(bytes.env(), &raw))
-}
+mod test;
@@ -0,0 +1,171 @@
+#![cfg(test)]
+
+use crate::{append_u32_be, Error, NoirVerifierContract, NoirVerifierContractClient};
+use battlefield_common::domain::signing_domain;
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Bytes, BytesN, Env, Vec};
+
+fn setup_test() -> (Env, NoirVerifierContractClient<'static>, [SigningKey; 3]) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(NoirVerifierContract, (&Address::generate(&env), &Address::generate(&env)));
+    let client = NoirVerifierContractClient::new(&env, &contract_id);
+    let keys = [SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]), SigningKey::from_bytes(&[3u8; 32])];
+    (env, client, keys)
+}
+
+fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::from_array(env, &key.verifying_key().to_bytes())
+}
+
+fn signer_set(env: &Env, keys: &[SigningKey]) -> Vec<BytesN<32>> {
+    let mut signers = Vec::new(env);
+    for key in keys {
+        signers.push_back(public_key(env, key));
+    }
+    signers
+}
+
+fn board_message(env: &Env, client: &NoirVerifierContractClient, session_id: u32, ship_cells: u32, root: &BytesN<32>) -> Bytes {
+    let mut message = Bytes::new(env);
+    message.push_back(1u8);
+    message.append(&signing_domain(env, &client.address));
+    append_u32_be(&mut message, session_id);
+    append_u32_be(&mut message, ship_cells);
+    message.append(&Bytes::from_array(env, &root.to_array()));
+    message
+}
+
+fn sign(key: &SigningKey, message: &Bytes) -> [u8; 64] {
+    let mut raw = [0u8; 256];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut raw[..len]);
+    key.sign(&raw[..len]).to_bytes()
+}
+
+// One threshold entry per `(index, key)`, in the order given.
+fn entries(env: &Env, message: &Bytes, signed: &[(u8, &SigningKey)]) -> Bytes {
+    let mut proof = Bytes::new(env);
+    for (index, key) in signed {
+        proof.push_back(*index);
+        proof.extend_from_array(&sign(key, message));
+    }
+    proof
+}
+
+#[test]
+fn test_set_signers_rejects_invalid_sets() {
+    let (env, client, keys) = setup_test();
+    let signers = signer_set(&env, &keys);
+    assert_eq!(client.try_set_signers(&signers, &0), Err(Ok(Error::InvalidSignerSet)));
+    assert_eq!(client.try_set_signers(&signers, &4), Err(Ok(Error::InvalidSignerSet)));
+    let duplicated = vec![&env, public_key(&env, &keys[0]), public_key(&env, &keys[0])];
+    assert_eq!(client.try_set_signers(&duplicated, &1), Err(Ok(Error::InvalidSignerSet)));
+
+    client.set_signers(&signers, &2);
+    assert_eq!(client.get_signers(), signers);
+    assert_eq!(client.get_threshold(), 2);
+}
+
+#[test]
+fn test_threshold_attestation_needs_distinct_increasing_signers() {
+    let (env, client, keys) = setup_test();
+    client.set_signers(&signer_set(&env, &keys), &2);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+    let message = board_message(&env, &client, 1, 3, &root);
+
+    assert!(client.verify_board(&1, &3, &root, &entries(&env, &message, &[(0, &keys[0]), (2, &keys[2])])));
+    assert!(client.verify_board(&1, &3, &root, &entries(&env, &message, &[(0, &keys[0]), (1, &keys[1]), (2, &keys[2])])));
+    assert!(!client.verify_board(&1, &3, &root, &entries(&env, &message, &[(2, &keys[2]), (0, &keys[0])])));
+    assert!(!client.verify_board(&1, &3, &root, &entries(&env, &message, &[(1, &keys[1]), (1, &keys[1])])));
+    assert!(!client.verify_board(&1, &3, &root, &entries(&env, &message, &[(0, &keys[0]), (3, &keys[2])])));
+}
+
+#[test]
+fn test_threshold_attestation_below_threshold_is_rejected() {
+    let (env, client, keys) = setup_test();
+    client.set_signers(&signer_set(&env, &keys), &2);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+    let message = board_message(&env, &client, 1, 3, &root);
+
+    assert!(!client.verify_board(&1, &3, &root, &entries(&env, &message, &[(1, &keys[1])])));
+    assert!(!client.verify_board(&1, &3, &root, &Bytes::new(&env)));
+}
+
+#[test]
+fn test_threshold_attestation_with_a_bad_signature_traps() {
+    let (env, client, keys) = setup_test();
+    client.set_signers(&signer_set(&env, &keys), &2);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+    let message = board_message(&env, &client, 1, 3, &root);
+
+    // Entry 1 claims signer 1 but carries signer 2's signature.
+    let forged = entries(&env, &message, &[(0, &keys[0]), (1, &keys[2])]);
+    assert!(client.try_verify_board(&1, &3, &root, &forged).is_err());
+    let other_message = board_message(&env, &client, 2, 3, &root);
+    let replayed = entries(&env, &other_message, &[(0, &keys[0]), (1, &keys[1])]);
+    assert!(client.try_verify_board(&1, &3, &root, &replayed).is_err());
+}
+
+#[test]
+fn test_threshold_attestation_with_a_wrong_entry_length_is_rejected() {
+    let (env, client, keys) = setup_test();
+    client.set_signers(&signer_set(&env, &keys), &1);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+    let message = board_message(&env, &client, 1, 3, &root);
+
+    let valid = entries(&env, &message, &[(0, &keys[0]), (1, &keys[1])]);
+    assert!(!client.verify_board(&1, &3, &root, &valid.slice(0..valid.len() - 1)));
+    assert!(!client.verify_board(&1, &3, &root, &Bytes::from_array(&env, &sign(&keys[0], &message))));
+    let mut padded = valid.clone();
+    padded.push_back(0);
+    assert!(!client.verify_board(&1, &3, &root, &padded));
+}
+
+#[test]
+fn test_clear_signers_falls_back_to_the_single_verifier_key() {
+    let (env, client, keys) = setup_test();
+    client.set_verifier(&public_key(&env, &keys[0]));
+    client.set_signers(&signer_set(&env, &keys[1..]), &1);
+    let root = BytesN::from_array(&env, &[7u8; 32]);
+    let message = board_message(&env, &client, 1, 3, &root);
+    let single = Bytes::from_array(&env, &sign(&keys[0], &message));
+
+    assert!(!client.verify_board(&1, &3, &root, &single));
+    client.clear_signers();
+    assert!(client.get_signers().is_empty());
+    assert_eq!(client.get_threshold(), 0);
+    assert!(client.verify_board(&1, &3, &root, &single));
+}
+
+#[test]
+fn test_verify_attack_reads_the_hit_flag_before_the_signer_entries() {
+    let (env, client, keys) = setup_test();
+    client.set_signers(&signer_set(&env, &keys), &2);
+    let commitment = BytesN::from_array(&env, &[9u8; 32]);
+    let attack_message = |is_ship: u8| {
+        let mut message = Bytes::new(&env);
+        message.push_back(2u8);
+        message.append(&signing_domain(&env, &client.address));
+        append_u32_be(&mut message, 4);
+        append_u32_be(&mut message, 3);
+        append_u32_be(&mut message, 5);
+        message.append(&Bytes::from_array(&env, &commitment.to_array()));
+        message.push_back(is_ship);
+        message
+    };
+    let attack_proof = |is_ship: u8| {
+        let mut proof = Bytes::from_array(&env, &[is_ship]);
+        proof.append(&entries(&env, &attack_message(is_ship), &[(0, &keys[0]), (1, &keys[1])]));
+        proof
+    };
+
+    assert!(client.verify_attack(&4, &3, &5, &commitment, &attack_proof(1)));
+    assert!(!client.verify_attack(&4, &3, &5, &commitment, &attack_proof(0)));
+    let mut bad_flag = attack_proof(1);
+    bad_flag.set(0, 2);
+    assert!(!client.verify_attack(&4, &3, &5, &commitment, &bad_flag));
+    let unflagged = entries(&env, &attack_message(1), &[(0, &keys[0]), (1, &keys[1])]);
+    assert!(!client.verify_attack(&4, &3, &5, &commitment, &unflagged));
+}
@@ -0,0 +1,41 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+use crate::domain::signing_domain;
+
+/// Version of the canonical state encoding. Bumped whenever the layout below changes; adding a
+/// field to the encoded state also changes every digest, so clients must use matching bindings.
+pub const STATE_ENCODING_VERSION: u32 = 1;
+
+const STATE_TAG: &[u8; 16] = b"battlefield:game";
+const CHECKPOINT_TAG: &[u8; 16] = b"battlefield:ckpt";
+
+/// Canonical bytes of a session's state, for co-signing off-chain:
+///
+/// `"battlefield:game" || version (u32 BE) || signing_domain(contract) || session_id (u32 BE) || XDR(state)`
+///
+/// `state` is encoded as its XDR `ScVal`. Contract types encode as maps keyed by field name in
+/// sorted order, so the bytes do not depend on declaration order and are reproducible from the
+/// generated bindings in any language. The signing domain binds the encoding to one network
+/// and one deployment, as for verifier signatures.
+pub fn canonical_state<T: ToXdr>(env: &Env, contract: &Address, session_id: u32, state: T) -> Bytes {
+  let mut bytes = Bytes::from_array(env, STATE_TAG);
+  bytes.extend_from_array(&STATE_ENCODING_VERSION.to_be_bytes());
+  bytes.append(&signing_domain(env, contract));
+  bytes.extend_from_array(&session_id.to_be_bytes());
+  bytes.append(&state.to_xdr(env));
+  bytes
+}
+
+/// SHA-256 of `canonical_state`.
+pub fn state_digest<T: ToXdr>(env: &Env, contract: &Address, session_id: u32, state: T) -> BytesN<32> {
+  env.crypto().sha256(&canonical_state(env, contract, session_id, state)).into()
+}
+
+/// Message both players sign to checkpoint a channel: `"battlefield:ckpt" || state_digest || move_count (u32 BE)`.
+/// The digest already binds the deployment and session, so only the move counter is added.
+pub fn checkpoint_message(env: &Env, state_digest: &BytesN<32>, move_count: u32) -> Bytes {
+  let mut bytes = Bytes::from_array(env, CHECKPOINT_TAG);
+  bytes.append(&Bytes::from(state_digest.clone()));
+  bytes.extend_from_array(&move_count.to_be_bytes());
+  bytes
+}
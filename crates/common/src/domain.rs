@@ -0,0 +1,11 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, Env};
+
+/// Prefix bound into every verifier-signed message: the network id
+/// (sha256 of the network passphrase) followed by the XDR `ScVal` encoding of
+/// the contract that checks the signature. A signature is therefore only
+/// valid on one network and for one deployment.
+pub fn signing_domain(env: &Env, verifier: &Address) -> Bytes {
+  let mut domain = Bytes::from_array(env, &env.ledger().network_id().to_array());
+  domain.append(&verifier.clone().to_xdr(env));
+  domain
+}
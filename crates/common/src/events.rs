@@ -0,0 +1,391 @@
+//! Event payloads shared by the Battlefield contracts.
+//!
+//! Topic names are stable identifiers; indexers should match on them rather
+//! than on struct names. Payload fields may only be appended within a schema
+//! version. Renaming, removing or retyping a field, or changing a topic,
+//! requires bumping `EVENT_SCHEMA_VERSION`.
+
+use soroban_sdk::{contractevent, Address, Bytes, BytesN, String, Symbol};
+
+use crate::ConfigBundle;
+
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+pub const TOPIC_CONFIG: &str = "config";
+pub const TOPIC_HUB_RETRY: &str = "hub_retry";
+pub const TOPIC_KEEPER_PAID: &str = "keeper_paid";
+pub const TOPIC_SOLVENCY_ALERT: &str = "solvency_alert";
+pub const TOPIC_ANNOTATION: &str = "annotation";
+pub const TOPIC_EARLY_END: &str = "early_end";
+pub const TOPIC_VOIDED: &str = "voided";
+pub const TOPIC_REWARD_MULTIPLIED: &str = "reward_multiplied";
+pub const TOPIC_REPLAY: &str = "replay";
+pub const TOPIC_SESSION_VOIDED: &str = "session_voided";
+pub const TOPIC_RANDOMNESS: &str = "randomness";
+pub const TOPIC_ESCROW_RECOVERED: &str = "escrow_recovered";
+pub const TOPIC_ADJUDICATED: &str = "adjudicated";
+pub const TOPIC_SETTLEMENT: &str = "settlement";
+pub const TOPIC_HUB_ROTATED: &str = "hub_rotated";
+pub const TOPIC_WEAK_BOARD: &str = "weak_board";
+pub const TOPIC_UPGRADE_PROPOSED: &str = "upgrade_proposed";
+pub const TOPIC_UPGRADE_EXECUTED: &str = "upgrade_executed";
+pub const TOPIC_RESULT_FORCED: &str = "result_forced";
+pub const TOPIC_TIP: &str = "tip";
+pub const TOPIC_TIMEOUT_WIN: &str = "timeout_win";
+pub const TOPIC_VERIFIER_OUTAGE: &str = "verifier_outage";
+pub const TOPIC_RESIGNED: &str = "resigned";
+pub const TOPIC_DRAWN: &str = "drawn";
+pub const TOPIC_CHECKPOINT: &str = "checkpoint";
+pub const TOPIC_FRAUD: &str = "fraud";
+pub const TOPIC_OPERATOR_GAME: &str = "operator_game";
+pub const TOPIC_GAME_STARTED: &str = "game_started";
+pub const TOPIC_BOARD_COMMITTED: &str = "board_committed";
+pub const TOPIC_ATTACK: &str = "attack";
+pub const TOPIC_ATTACK_RESOLVED: &str = "attack_resolved";
+pub const TOPIC_GAME_ENDED: &str = "game_ended";
+pub const TOPIC_STAKE_DEPOSITED: &str = "stake_deposited";
+pub const TOPIC_PAYOUT: &str = "payout";
+
+#[contractevent(topics = ["config"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigChanged {
+  pub config: ConfigBundle,
+}
+
+#[contractevent(topics = ["hub_retry"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HubNotificationQueued {
+  #[topic]
+  pub session_id: u32,
+  pub attempts: u32,
+  pub next_retry_ledger: u32,
+}
+
+#[contractevent(topics = ["keeper_paid"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeeperPaid {
+  #[topic]
+  pub keeper: Address,
+  pub token: Address,
+  pub amount: i128,
+}
+
+#[contractevent(topics = ["solvency_alert"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolvencyAlert {
+  #[topic]
+  pub token: Address,
+  pub balance: i128,
+  pub required: i128,
+}
+
+#[contractevent(topics = ["annotation"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnnotationAdded {
+  #[topic]
+  pub session_id: u32,
+  pub author: Address,
+  pub move_index: u32,
+}
+
+#[contractevent(topics = ["early_end"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EarlyTermination {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Address,
+  pub unreachable_hits: u32,
+}
+
+#[contractevent(topics = ["voided"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameVoided {
+  #[topic]
+  pub session_id: u32,
+  pub by: Address,
+}
+
+#[contractevent(topics = ["reward_multiplied"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RewardMultiplied {
+  #[topic]
+  pub session_id: u32,
+  pub multiplier_bps: u32,
+  pub player1_points: i128,
+  pub player2_points: i128,
+}
+
+#[contractevent(topics = ["replay"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplayAttached {
+  #[topic]
+  pub session_id: u32,
+  pub player: Address,
+  pub content_hash: Bytes,
+}
+
+#[contractevent(topics = ["session_voided"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionVoided {
+  #[topic]
+  pub session_id: u32,
+  pub reason: String,
+}
+
+#[contractevent(topics = ["randomness"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RandomnessFulfilled {
+  #[topic]
+  pub session_id: u32,
+  pub beacon: Address,
+  pub randomness: BytesN<32>,
+}
+
+#[contractevent(topics = ["escrow_recovered"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowRecovered {
+  #[topic]
+  pub session_id: u32,
+  pub token: Address,
+  pub amount: i128,
+}
+
+#[contractevent(topics = ["adjudicated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameAdjudicated {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Option<Address>,
+  pub player1_hits: u32,
+  pub player2_hits: u32,
+}
+
+// `fee_bps` is the configured rate; `fee_amount` is what was actually charged after pass discounts.
+#[contractevent(topics = ["settlement"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settlement {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Address,
+  pub token: Address,
+  pub pot: i128,
+  pub fee_bps: u32,
+  pub fee_amount: i128,
+  pub organizer_share: i128,
+  pub keeper_share: i128,
+  pub rebates: i128,
+  pub fee_recipient_amount: i128,
+  pub winner_amount: i128,
+  pub operator: Option<Symbol>,
+  pub operator_share: i128,
+}
+
+// Warns that a game ended after a hub rotation; its result goes to the hub that started it.
+#[contractevent(topics = ["hub_rotated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HubRotated {
+  #[topic]
+  pub session_id: u32,
+  pub original_hub: Address,
+  pub current_hub: Address,
+}
+
+// A committed board whose cells share so few commitment values that salts were evidently
+// reused; learning one salt would reveal the rest of the board.
+#[contractevent(topics = ["weak_board"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeakBoardCommitment {
+  #[topic]
+  pub session_id: u32,
+  pub player: Address,
+  pub distinct_commitments: u32,
+}
+
+#[contractevent(topics = ["upgrade_proposed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeProposed {
+  pub wasm_hash: BytesN<32>,
+  pub executable_ledger: u32,
+}
+
+#[contractevent(topics = ["upgrade_executed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeExecuted {
+  pub wasm_hash: BytesN<32>,
+}
+
+// An organizer ruling on a match left unresolved past its round deadline. The linked game,
+// if any, is left as it was.
+#[contractevent(topics = ["result_forced"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TournamentResultForced {
+  #[topic]
+  pub tournament_id: u32,
+  pub match_index: u32,
+  pub winner: Address,
+  pub session_id: Option<u32>,
+}
+
+// Audience tip to a player of a live game, for stream overlays. `held` tips become withdrawable
+// only once the game ends.
+#[contractevent(topics = ["tip"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TipReceived {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub player: Address,
+  pub from: Address,
+  pub amount: i128,
+  pub held: bool,
+}
+
+// A game won because the opponent let the per-move timeout pass without attacking or resolving.
+#[contractevent(topics = ["timeout_win"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeoutWin {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Address,
+  pub staller: Address,
+}
+
+// The pending defender's clock was restarted because the ZK verifier could not be reached.
+#[contractevent(topics = ["verifier_outage"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifierOutage {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub defender: Address,
+  pub deadline_ledger: u32,
+}
+
+#[contractevent(topics = ["resigned"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resigned {
+  #[topic]
+  pub session_id: u32,
+  pub player: Address,
+  pub winner: Address,
+}
+
+// Both players agreed to a draw; `fee` is the total kept from the refunded stakes.
+#[contractevent(topics = ["drawn"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameDrawn {
+  #[topic]
+  pub session_id: u32,
+  pub offered_by: Address,
+  pub fee: i128,
+}
+
+#[contractevent(topics = ["checkpoint"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpointed {
+  #[topic]
+  pub session_id: u32,
+  pub state_digest: BytesN<32>,
+  pub move_count: u32,
+}
+
+#[contractevent(topics = ["fraud"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FraudProven {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Address,
+  pub cheater: Address,
+}
+
+#[contractevent(topics = ["operator_game"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OperatorGameStarted {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub operator: Symbol,
+  pub player1: Address,
+  pub player2: Address,
+}
+
+#[contractevent(topics = ["game_started"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameStarted {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub player1: Address,
+  #[topic]
+  pub player2: Address,
+  pub player1_stake: i128,
+  pub player2_stake: i128,
+}
+
+#[contractevent(topics = ["board_committed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BoardCommitted {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub player: Address,
+  pub board: u32,
+}
+
+#[contractevent(topics = ["attack"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttackMade {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub attacker: Address,
+  pub board: u32,
+  pub x: u32,
+  pub y: u32,
+}
+
+#[contractevent(topics = ["attack_resolved"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttackResolved {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub defender: Address,
+  pub board: u32,
+  pub x: u32,
+  pub y: u32,
+  pub is_ship: bool,
+}
+
+#[contractevent(topics = ["game_ended"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameEnded {
+  #[topic]
+  pub session_id: u32,
+  pub winner: Option<Address>,
+  pub drawn: bool,
+  pub voided: bool,
+}
+
+#[contractevent(topics = ["stake_deposited"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StakeDeposited {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub player: Address,
+  pub from: Address,
+  pub token: Address,
+  pub amount: i128,
+}
+
+#[contractevent(topics = ["payout"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Payout {
+  #[topic]
+  pub session_id: u32,
+  #[topic]
+  pub to: Address,
+  pub token: Address,
+  pub amount: i128,
+}
@@ -0,0 +1,26 @@
+#![no_std]
+
+use soroban_sdk::{contracttype, Address, BytesN};
+
+pub mod digest;
+pub mod domain;
+pub mod events;
+pub mod placement;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigBundle {
+  pub hub: Option<Address>,
+  pub bet_token: Option<Address>,
+  pub fee_bps: Option<u32>,
+  pub fee_recipient: Option<Address>,
+  pub verifier_pub_key: Option<BytesN<32>>,
+  pub zk_verifier: Option<Address>,
+  pub zk_vk_version: Option<u32>,
+  // Feature flags: the dev-mode timeout cap (0 turns it off) and hub stats reporting.
+  pub dev_mode: Option<u32>,
+  pub hub_stats: Option<bool>,
+  pub clear_bet_token: bool,
+  pub clear_verifier: bool,
+  pub clear_zk_verifier: bool,
+}
@@ -0,0 +1,44 @@
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// Ship lengths of the classic fleet (carrier, battleship, cruiser, submarine, destroyer).
+pub const CLASSIC_FLEET: [u32; 5] = [5, 4, 3, 3, 2];
+
+const PLACEMENT_ATTEMPTS: u32 = 64;
+
+/// Places `fleet` on a `board_size` x `board_size` board without overlaps and returns the
+/// occupied cell indexes (`y * board_size + x`) in ascending order, or `None` if the fleet
+/// cannot be fitted.
+///
+/// The frame PRNG is reseeded from `seed`, so the same seed always yields the same board on
+/// the same host. Any PRNG draws made after this call in the same frame follow from the seed
+/// too, so a contract must not call it ahead of draws that need to stay unpredictable.
+pub fn suggest_board(env: &Env, seed: &BytesN<32>, board_size: u32, fleet: &[u32]) -> Option<Vec<u32>> {
+  env.prng().seed(Bytes::from_array(env, &seed.to_array()));
+  let cells = board_size.checked_mul(board_size)?;
+  let mut occupied = Vec::new(env);
+  for &length in fleet {
+    if length == 0 || length > board_size { return None; }
+    let mut placed = false;
+    for _ in 0..PLACEMENT_ATTEMPTS {
+      let horizontal = env.prng().gen_range::<u64>(0..2) == 0;
+      let (max_x, max_y) = if horizontal { (board_size - length, board_size - 1) } else { (board_size - 1, board_size - length) };
+      let x = env.prng().gen_range::<u64>(0..=u64::from(max_x)) as u32;
+      let y = env.prng().gen_range::<u64>(0..=u64::from(max_y)) as u32;
+      let step = if horizontal { 1 } else { board_size };
+      let start = y * board_size + x;
+      if (0..length).any(|offset| occupied.contains(start + offset * step)) { continue; }
+      for offset in 0..length {
+        occupied.push_back(start + offset * step);
+      }
+      placed = true;
+      break;
+    }
+    if !placed { return None; }
+  }
+
+  let mut sorted = Vec::new(env);
+  for index in 0..cells {
+    if occupied.contains(index) { sorted.push_back(index); }
+  }
+  Some(sorted)
+}
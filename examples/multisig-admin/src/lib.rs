@@ -0,0 +1,94 @@
+#![no_std]
+
+//! Sample M-of-N ed25519 custom account, usable as the admin of the battleship contract.
+
+use soroban_sdk::{
+    auth::{Context, CustomAccountInterface},
+    contract, contracterror, contractimpl, contracttype,
+    crypto::Hash,
+    BytesN, Env, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    InvalidThreshold = 1,
+    UnknownSigner = 2,
+    SignersNotOrdered = 3,
+    NotEnoughSignatures = 4,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Signers,
+    Threshold,
+}
+
+#[contract]
+pub struct MultisigAdmin;
+
+#[contractimpl]
+impl MultisigAdmin {
+    pub fn __constructor(env: Env, signers: Vec<BytesN<32>>, threshold: u32) -> Result<(), Error> {
+        if threshold == 0 || threshold > signers.len() {
+            return Err(Error::InvalidThreshold);
+        }
+        env.storage().instance().set(&DataKey::Signers, &signers);
+        env.storage().instance().set(&DataKey::Threshold, &threshold);
+        Ok(())
+    }
+
+    pub fn get_signers(env: Env) -> Vec<BytesN<32>> {
+        env.storage().instance().get(&DataKey::Signers).unwrap_or(Vec::new(&env))
+    }
+
+    pub fn get_threshold(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Threshold).unwrap_or(0)
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for MultisigAdmin {
+    type Signature = Vec<Signature>;
+    type Error = Error;
+
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        signature_payload: Hash<32>,
+        signatures: Vec<Signature>,
+        _auth_contexts: Vec<Context>,
+    ) -> Result<(), Error> {
+        let signers: Vec<BytesN<32>> = env.storage().instance().get(&DataKey::Signers).unwrap_or(Vec::new(&env));
+        let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap_or(u32::MAX);
+        let payload = signature_payload.to_bytes().into();
+
+        let mut previous: Option<BytesN<32>> = None;
+        for entry in signatures.iter() {
+            if let Some(previous) = previous {
+                if previous >= entry.public_key {
+                    return Err(Error::SignersNotOrdered);
+                }
+            }
+            if !signers.contains(&entry.public_key) {
+                return Err(Error::UnknownSigner);
+            }
+            env.crypto().ed25519_verify(&entry.public_key, &payload, &entry.signature);
+            previous = Some(entry.public_key);
+        }
+
+        if signatures.len() < threshold {
+            return Err(Error::NotEnoughSignatures);
+        }
+        Ok(())
+    }
+}
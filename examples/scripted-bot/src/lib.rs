@@ -0,0 +1,99 @@
+#![no_std]
+
+//! Sample on-chain bot that holds a battleship seat. Its shots follow a fixed script and are
+//! fired by the bot contract itself; everything else it authorizes (joining a game,
+//! committing, revealing) goes through `__check_auth` with a signature from its operator key.
+
+use soroban_sdk::{
+    auth::{Context, CustomAccountInterface},
+    contract, contractclient, contracterror, contractimpl, contracttype,
+    crypto::Hash,
+    Address, BytesN, Env, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    EmptyScript = 1,
+    ScriptExhausted = 2,
+    UnexpectedContext = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Shot {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Operator,
+    Game,
+    Script,
+    NextShot(u32),
+}
+
+#[contractclient(name = "BattleshipClient")]
+pub trait Battleship {
+    fn attack(env: Env, session_id: u32, attacker: Address, x: u32, y: u32);
+}
+
+#[contract]
+pub struct ScriptedBot;
+
+#[contractimpl]
+impl ScriptedBot {
+    pub fn __constructor(env: Env, operator: BytesN<32>, game: Address, script: Vec<Shot>) -> Result<(), Error> {
+        if script.is_empty() {
+            return Err(Error::EmptyScript);
+        }
+        env.storage().instance().set(&DataKey::Operator, &operator);
+        env.storage().instance().set(&DataKey::Game, &game);
+        env.storage().instance().set(&DataKey::Script, &script);
+        Ok(())
+    }
+
+    /// Fires the next scripted shot in `session_id`. Anyone may trigger it: the target is fixed
+    /// by the script, and the bot authorizes the attack as the direct caller.
+    pub fn play(env: Env, session_id: u32) -> Result<Shot, Error> {
+        let script: Vec<Shot> = env.storage().instance().get(&DataKey::Script).unwrap_or(Vec::new(&env));
+        let key = DataKey::NextShot(session_id);
+        let next: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+        let shot = script.get(next).ok_or(Error::ScriptExhausted)?;
+
+        let game: Address = env.storage().instance().get(&DataKey::Game).unwrap();
+        BattleshipClient::new(&env, &game).attack(&session_id, &env.current_contract_address(), &shot.x, &shot.y);
+        env.storage().persistent().set(&key, &(next + 1));
+        Ok(shot)
+    }
+
+    pub fn get_next_shot(env: Env, session_id: u32) -> u32 {
+        env.storage().persistent().get(&DataKey::NextShot(session_id)).unwrap_or(0)
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for ScriptedBot {
+    type Signature = BytesN<64>;
+    type Error = Error;
+
+    // The operator key may only sign for calls into the configured game contract, so a leaked
+    // key cannot move anything else the bot holds.
+    #[allow(non_snake_case)]
+    fn __check_auth(env: Env, signature_payload: Hash<32>, signature: BytesN<64>, auth_contexts: Vec<Context>) -> Result<(), Error> {
+        let game: Address = env.storage().instance().get(&DataKey::Game).unwrap();
+        for context in auth_contexts.iter() {
+            match context {
+                Context::Contract(call) if call.contract == game => {}
+                _ => return Err(Error::UnexpectedContext),
+            }
+        }
+
+        let operator: BytesN<32> = env.storage().instance().get(&DataKey::Operator).unwrap();
+        env.crypto().ed25519_verify(&operator, &signature_payload.to_bytes().into(), &signature);
+        Ok(())
+    }
+}
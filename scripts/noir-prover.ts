@@ -1,6 +1,6 @@
 #!/usr/bin/env bun
 
-import { Keypair, hash } from '@stellar/stellar-sdk';
+import { Address, Keypair, hash } from '@stellar/stellar-sdk';
 import { readEnvFile, getEnvValue } from './utils/env';
 
 function appendU32BE(bytes: number[], value: number) {
@@ -30,6 +30,25 @@ if (!proverSecret) {
 
 const keypair = Keypair.fromSecret(proverSecret);
 const verifierPubKeyHex = Buffer.from(keypair.rawPublicKey()).toString('hex');
+const networkPassphrase = process.env.VITE_NETWORK_PASSPHRASE || getEnvValue(env, 'VITE_NETWORK_PASSPHRASE', 'Test SDF Network ; September 2015');
+const defaultVerifierContractId = process.env.NOIR_ZK_VERIFIER_CONTRACT_ID || getEnvValue(env, 'NOIR_ZK_VERIFIER_CONTRACT_ID');
+const networkId = new Uint8Array(hash(Buffer.from(networkPassphrase)));
+// This prover's position in the verifier's signer set, when it is one of several threshold signers.
+const signerIndexValue = process.env.NOIR_PROVER_SIGNER_INDEX || getEnvValue(env, 'NOIR_PROVER_SIGNER_INDEX');
+const signerIndex = signerIndexValue ? Number(signerIndexValue) : undefined;
+
+// A threshold proof is the concatenation of these entries from enough signers, in index order.
+function signerEntryHex(signature: Uint8Array) {
+  return signerIndex === undefined ? undefined : toHex(Uint8Array.of(signerIndex, ...signature));
+}
+
+// Mirrors battlefield-common `signing_domain`: network id || ScVal XDR of the verifying contract.
+function signingDomain(verifierContractId?: string): number[] {
+  const contractId = verifierContractId || defaultVerifierContractId;
+  if (!contractId) throw new Error('Missing verifierContractId (set NOIR_ZK_VERIFIER_CONTRACT_ID)');
+  return [...networkId, ...new Address(contractId).toScVal().toXDR()];
+}
+
 const port = Number(process.env.NOIR_PROVER_PORT || getEnvValue(env, 'NOIR_PROVER_PORT', '8787'));
 
 const CORS_HEADERS: Record<string, string> = {
@@ -69,7 +88,7 @@ Bun.serve({
     }
 
     if (pathname === '/verifier' && req.method === 'GET') {
-      return jsonCors({ verifierPubKeyHex, signerAddress: keypair.publicKey() });
+      return jsonCors({ verifierPubKeyHex, signerAddress: keypair.publicKey(), signerIndex });
     }
 
     if (pathname === '/board-proof' && req.method === 'POST') {
@@ -78,6 +97,7 @@ Bun.serve({
           sessionId: number;
           shipCells: number;
           commitmentRootHex: string;
+          verifierContractId?: string;
         };
 
         const commitmentRoot = fromHex(body.commitmentRootHex);
@@ -90,7 +110,7 @@ Bun.serve({
           commitmentRoot,
         ]);
 
-        const messageBytes: number[] = [1];
+        const messageBytes: number[] = [1, ...signingDomain(body.verifierContractId)];
         appendU32BE(messageBytes, body.sessionId >>> 0);
         appendU32BE(messageBytes, body.shipCells >>> 0);
         messageBytes.push(...commitmentRoot);
@@ -101,6 +121,7 @@ Bun.serve({
           proofHashHex: toHex(proofHash),
           signatureHex: toHex(signature),
           zkProofHex: toHex(signature),
+          signerEntryHex: signerEntryHex(signature),
         });
       } catch (err) {
         return withCors(new Response(err instanceof Error ? err.message : 'Invalid request', { status: 400 }));
@@ -116,6 +137,7 @@ Bun.serve({
           isShip: boolean;
           proofHashHex: string;
           expectedCommitmentHex?: string;
+          verifierContractId?: string;
         };
 
         const proofHash = fromHex(body.proofHashHex);
@@ -132,7 +154,7 @@ Bun.serve({
           return new Response('Invalid expectedCommitmentHex length', { status: 400 });
         }
 
-        const messageBytes: number[] = [2];
+        const messageBytes: number[] = [2, ...signingDomain(body.verifierContractId)];
         appendU32BE(messageBytes, body.sessionId >>> 0);
         appendU32BE(messageBytes, body.x >>> 0);
         appendU32BE(messageBytes, body.y >>> 0);
@@ -149,6 +171,43 @@ Bun.serve({
           proofHashHex: toHex(proofHash),
           signatureHex: toHex(signature),
           zkProofHex: toHex(zkProofBytes),
+          signerEntryHex: signerEntryHex(signature),
+        });
+      } catch (err) {
+        return withCors(new Response(err instanceof Error ? err.message : 'Invalid request', { status: 400 }));
+      }
+    }
+
+    if (pathname === '/transcript-proof' && req.method === 'POST') {
+      try {
+        const body = await req.json() as {
+          sessionId: number;
+          fromMove: number;
+          toMove: number;
+          transcriptRootHex: string;
+          verifierContractId?: string;
+        };
+
+        const transcriptRoot = fromHex(body.transcriptRootHex);
+        if (transcriptRoot.length !== 32) {
+          return new Response('Invalid transcriptRootHex length', { status: 400 });
+        }
+        if (body.fromMove >= body.toMove) {
+          return new Response('Empty move range', { status: 400 });
+        }
+
+        const messageBytes: number[] = [3, ...signingDomain(body.verifierContractId)];
+        appendU32BE(messageBytes, body.sessionId >>> 0);
+        appendU32BE(messageBytes, body.fromMove >>> 0);
+        appendU32BE(messageBytes, body.toMove >>> 0);
+        messageBytes.push(...transcriptRoot);
+
+        const signature = keypair.sign(Buffer.from(messageBytes));
+
+        return jsonCors({
+          signatureHex: toHex(signature),
+          zkProofHex: toHex(signature),
+          signerEntryHex: signerEntryHex(signature),
         });
       } catch (err) {
         return withCors(new Response(err instanceof Error ? err.message : 'Invalid request', { status: 400 }));
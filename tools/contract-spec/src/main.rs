@@ -0,0 +1,86 @@
+use std::{env, fs, path::PathBuf, process};
+
+mod spec;
+
+mod test;
+
+const WASM_TARGET: &str = "wasm32v1-none";
+const DEFAULT_OUT_DIR: &str = "target/contract-spec";
+const CONTRACTS: [&str; 2] = ["battleship", "noir-verifier"];
+
+struct Options {
+    build: bool,
+    out_dir: PathBuf,
+    packages: Vec<String>,
+}
+
+fn main() {
+    let options = parse_args();
+    fs::create_dir_all(&options.out_dir).unwrap_or_else(|err| {
+        fail(&format!("cannot create {}: {err}", options.out_dir.display()));
+    });
+
+    for package in &options.packages {
+        if options.build {
+            build_package(package);
+        }
+
+        let wasm_name = package.replace('-', "_");
+        let wasm_path = PathBuf::from(format!("target/{WASM_TARGET}/release/{wasm_name}.wasm"));
+        let wasm = fs::read(&wasm_path).unwrap_or_else(|err| {
+            fail(&format!("cannot read {}: {err}", wasm_path.display()));
+        });
+        let document = spec::extract(package, &wasm).unwrap_or_else(|err| fail(&format!("{package}: {err}")));
+
+        let out_path = options.out_dir.join(format!("{package}.json"));
+        let text = serde_json::to_string_pretty(&document).unwrap_or_else(|err| fail(&format!("{package}: {err}")));
+        fs::write(&out_path, text + "\n").unwrap_or_else(|err| {
+            fail(&format!("cannot write {}: {err}", out_path.display()));
+        });
+        println!(
+            "{package}: {} functions, {} types, {} events -> {}",
+            document["functions"].as_array().map_or(0, Vec::len),
+            document["types"].as_array().map_or(0, Vec::len),
+            document["events"].as_array().map_or(0, Vec::len),
+            out_path.display(),
+        );
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options { build: true, out_dir: PathBuf::from(DEFAULT_OUT_DIR), packages: Vec::new() };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-build" => options.build = false,
+            "--out" => {
+                options.out_dir = PathBuf::from(args.next().unwrap_or_else(|| fail("--out needs a directory")));
+            }
+            "-h" | "--help" => {
+                println!("Usage: contract-spec [--no-build] [--out <dir>] [package...]");
+                process::exit(0);
+            }
+            other if other.starts_with('-') => fail(&format!("unknown argument: {other}")),
+            package => options.packages.push(package.to_string()),
+        }
+    }
+    if options.packages.is_empty() {
+        options.packages = CONTRACTS.iter().map(|package| package.to_string()).collect();
+    }
+    options
+}
+
+fn build_package(package: &str) {
+    let status = process::Command::new("cargo")
+        .args(["build", "--release", "--target", WASM_TARGET, "-p", package])
+        .status()
+        .unwrap_or_else(|err| fail(&format!("failed to run cargo: {err}")));
+    if !status.success() {
+        fail(&format!("building {package} failed"));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("contract-spec: {message}");
+    process::exit(1);
+}
@@ -0,0 +1,74 @@
+//! Reads the contract interface and metadata sections of a contract WASM into JSON.
+
+use std::io::Cursor;
+
+use serde_json::{json, Map, Value};
+use stellar_xdr::curr::{Limited, Limits, ReadXdr, ScMetaEntry, ScSpecEntry};
+use wasmparser::{Parser, Payload};
+
+const SPEC_SECTION: &str = "contractspecv0";
+const META_SECTION: &str = "contractmetav0";
+
+/// Builds the spec document for one contract: its `contractmeta!` entries plus every function,
+/// user-defined type and event, each in the stellar-xdr JSON encoding and sorted by name so the
+/// output is stable across builds.
+pub fn extract(package: &str, wasm: &[u8]) -> Result<Value, String> {
+    let mut spec_bytes = None;
+    let mut meta_bytes = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::CustomSection(section) = payload.map_err(|err| format!("invalid wasm: {err}"))? {
+            match section.name() {
+                SPEC_SECTION => spec_bytes = Some(section.data().to_vec()),
+                META_SECTION => meta_bytes.extend_from_slice(section.data()),
+                _ => {}
+            }
+        }
+    }
+    let spec_bytes = spec_bytes.ok_or_else(|| format!("no {SPEC_SECTION} section"))?;
+
+    let mut meta = Map::new();
+    for entry in read_entries::<ScMetaEntry>(&meta_bytes)? {
+        let ScMetaEntry::ScMetaV0(entry) = entry;
+        meta.insert(entry.key.to_utf8_string_lossy(), Value::String(entry.val.to_utf8_string_lossy()));
+    }
+
+    let (mut functions, mut types, mut events) = (Vec::new(), Vec::new(), Vec::new());
+    for entry in read_entries::<ScSpecEntry>(&spec_bytes)? {
+        let name = entry_name(&entry);
+        let value = serde_json::to_value(&entry).map_err(|err| err.to_string())?;
+        match entry {
+            ScSpecEntry::FunctionV0(_) => functions.push((name, value)),
+            ScSpecEntry::EventV0(_) => events.push((name, value)),
+            _ => types.push((name, value)),
+        }
+    }
+
+    Ok(json!({
+        "contract": package,
+        "meta": meta,
+        "functions": sorted(functions),
+        "types": sorted(types),
+        "events": sorted(events),
+    }))
+}
+
+fn read_entries<T: ReadXdr>(bytes: &[u8]) -> Result<Vec<T>, String> {
+    let mut reader = Limited::new(Cursor::new(bytes), Limits { depth: 500, len: bytes.len() });
+    T::read_xdr_iter(&mut reader).collect::<Result<Vec<_>, _>>().map_err(|err| format!("invalid xdr: {err}"))
+}
+
+fn entry_name(entry: &ScSpecEntry) -> String {
+    match entry {
+        ScSpecEntry::FunctionV0(function) => function.name.to_utf8_string_lossy(),
+        ScSpecEntry::UdtStructV0(udt) => udt.name.to_utf8_string_lossy(),
+        ScSpecEntry::UdtUnionV0(udt) => udt.name.to_utf8_string_lossy(),
+        ScSpecEntry::UdtEnumV0(udt) => udt.name.to_utf8_string_lossy(),
+        ScSpecEntry::UdtErrorEnumV0(udt) => udt.name.to_utf8_string_lossy(),
+        ScSpecEntry::EventV0(event) => event.name.to_utf8_string_lossy(),
+    }
+}
+
+fn sorted(mut entries: Vec<(String, Value)>) -> Vec<Value> {
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries.into_iter().map(|(_, value)| value).collect()
+}
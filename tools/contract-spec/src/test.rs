@@ -0,0 +1,82 @@
+#![cfg(test)]
+
+use stellar_xdr::curr::{
+    Limits, ScMetaEntry, ScMetaV0, ScSpecEntry, ScSpecFunctionInputV0, ScSpecFunctionV0, ScSpecTypeDef, ScSpecUdtStructFieldV0,
+    ScSpecUdtStructV0, WriteXdr,
+};
+
+use crate::spec::extract;
+
+fn custom_section(out: &mut Vec<u8>, name: &str, body: &[u8]) {
+    let mut payload = vec![name.len() as u8];
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(body);
+    out.push(0);
+    let mut len = payload.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out.extend_from_slice(&payload);
+}
+
+fn function(name: &str) -> ScSpecEntry {
+    ScSpecEntry::FunctionV0(ScSpecFunctionV0 {
+        doc: Default::default(),
+        name: name.try_into().unwrap(),
+        inputs: vec![ScSpecFunctionInputV0 {
+            doc: Default::default(),
+            name: "session_id".try_into().unwrap(),
+            type_: ScSpecTypeDef::U32,
+        }]
+        .try_into()
+        .unwrap(),
+        outputs: Default::default(),
+    })
+}
+
+#[test]
+fn test_extract_reads_meta_and_sorts_spec_entries() {
+    let mut spec = Vec::new();
+    for entry in [
+        function("start_game"),
+        ScSpecEntry::UdtStructV0(ScSpecUdtStructV0 {
+            doc: Default::default(),
+            lib: Default::default(),
+            name: "Game".try_into().unwrap(),
+            fields: vec![ScSpecUdtStructFieldV0 { doc: Default::default(), name: "winner".try_into().unwrap(), type_: ScSpecTypeDef::Address }]
+                .try_into()
+                .unwrap(),
+        }),
+        function("attack"),
+    ] {
+        spec.extend(entry.to_xdr(Limits::none()).unwrap());
+    }
+    let meta = ScMetaEntry::ScMetaV0(ScMetaV0 { key: "binver".try_into().unwrap(), val: "0.1.0".try_into().unwrap() });
+
+    let mut module = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    custom_section(&mut module, "contractmetav0", &meta.to_xdr(Limits::none()).unwrap());
+    custom_section(&mut module, "contractspecv0", &spec);
+    custom_section(&mut module, "producers", &[1, 2, 3]);
+
+    let document = extract("battleship", &module).unwrap();
+    assert_eq!(document["contract"], "battleship");
+    assert_eq!(document["meta"]["binver"], "0.1.0");
+    let functions = document["functions"].as_array().unwrap();
+    assert_eq!(functions.len(), 2);
+    assert_eq!(functions[0]["function_v0"]["name"], "attack");
+    assert_eq!(functions[1]["function_v0"]["name"], "start_game");
+    assert_eq!(document["types"][0]["udt_struct_v0"]["name"], "Game");
+    assert!(document["events"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_extract_requires_a_spec_section() {
+    let module = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    assert!(extract("battleship", &module).unwrap_err().contains("contractspecv0"));
+}
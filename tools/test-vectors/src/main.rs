@@ -0,0 +1,148 @@
+use std::{env, fs, process};
+
+use battleship::{
+    attack_proof_hash, build_attack_proof_message, build_board_proof_message, build_merkle_proof,
+    compute_commitment_root, compute_merkle_root, indexed_cell_commitment, HashAlgorithm, COMMITMENT_V2,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Value};
+use soroban_sdk::testutils::Ledger as _;
+use soroban_sdk::{Address, Bytes, BytesN, Env, Vec};
+
+mod test;
+
+const SIGNING_KEY_SEED: [u8; 32] = [7u8; 32];
+const SALT_SEED: [u8; 32] = [9u8; 32];
+const SESSION_ID: u32 = 77;
+const NETWORK_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+const VERIFIER_CONTRACT: &str = "CAIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRCEIRDB3V";
+const BOARD_SIZE: u32 = 10;
+const SHIP_INDEXES: [u32; 17] = [0, 1, 2, 3, 4, 20, 21, 22, 23, 40, 41, 42, 60, 61, 62, 80, 81];
+const ATTACKS: [(u32, u32); 3] = [(0, 0), (9, 9), (2, 4)];
+
+fn main() {
+    let json = render_vectors();
+    match env::args().nth(1) {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, json) {
+                eprintln!("failed to write {path}: {err}");
+                process::exit(1);
+            }
+        }
+        None => print!("{json}"),
+    }
+}
+
+pub fn render_vectors() -> String {
+    let mut out = serde_json::to_string_pretty(&generate_vectors()).expect("vectors serialize");
+    out.push('\n');
+    out
+}
+
+pub fn generate_vectors() -> Value {
+    let env = Env::default();
+    let network_id = env.crypto().sha256(&Bytes::from_slice(&env, NETWORK_PASSPHRASE.as_bytes())).to_array();
+    env.ledger().set_network_id(network_id);
+    let verifier = Address::from_str(&env, VERIFIER_CONTRACT);
+    let signing_key = SigningKey::from_bytes(&SIGNING_KEY_SEED);
+
+    let mut salts = std::vec::Vec::new();
+    let mut commitments = Vec::new(&env);
+    let mut cells = std::vec::Vec::new();
+    for index in 0..BOARD_SIZE * BOARD_SIZE {
+        let salt = cell_salt(&env, index);
+        let is_ship = SHIP_INDEXES.contains(&index);
+        let commitment = indexed_cell_commitment(&env, HashAlgorithm::Keccak256, index, is_ship, &salt);
+        cells.push(json!({
+            "index": index,
+            "is_ship": is_ship,
+            "salt": hex_bytes(&salt),
+            "commitment": hex(&commitment.to_array()),
+        }));
+        salts.push(salt);
+        commitments.push_back(commitment);
+    }
+
+    let commitment_root = compute_commitment_root(&env, HashAlgorithm::Keccak256, &commitments);
+    let merkle_root = compute_merkle_root(&env, HashAlgorithm::Keccak256, &commitments);
+    let board_proof_hash = BytesN::from_array(
+        &env,
+        &env.crypto().keccak256(&Bytes::from_array(&env, &commitment_root.to_array())).to_array(),
+    );
+    let ship_cells = SHIP_INDEXES.len() as u32;
+    let board_message =
+        build_board_proof_message(&env, HashAlgorithm::Keccak256, &verifier, SESSION_ID, ship_cells, &commitment_root, &board_proof_hash);
+
+    let mut attacks = std::vec::Vec::new();
+    for (x, y) in ATTACKS {
+        let index = y * BOARD_SIZE + x;
+        let is_ship = SHIP_INDEXES.contains(&index);
+        let salt = &salts[index as usize];
+        let proof_hash = attack_proof_hash(&env, HashAlgorithm::Keccak256, is_ship, salt, x, y);
+        let message = build_attack_proof_message(&env, HashAlgorithm::Keccak256, &verifier, SESSION_ID, x, y, is_ship, &proof_hash);
+        let merkle_proof: std::vec::Vec<String> =
+            build_merkle_proof(&env, HashAlgorithm::Keccak256, &commitments, index).iter().map(|node| hex(&node.to_array())).collect();
+        attacks.push(json!({
+            "x": x,
+            "y": y,
+            "is_ship": is_ship,
+            "salt": hex_bytes(salt),
+            "proof_hash": hex(&proof_hash.to_array()),
+            "message": hex_bytes(&message),
+            "signature": sign(&signing_key, &message),
+            "merkle_proof": merkle_proof,
+        }));
+    }
+
+    json!({
+        "session_id": SESSION_ID,
+        "network_passphrase": NETWORK_PASSPHRASE,
+        "network_id": hex(&network_id),
+        "verifier_contract": VERIFIER_CONTRACT,
+        "board_size": BOARD_SIZE,
+        "signer": {
+            "secret_key": hex(&SIGNING_KEY_SEED),
+            "public_key": hex(&signing_key.verifying_key().to_bytes()),
+        },
+        "salt_derivation": "keccak256(salt_seed || u32_be(index))",
+        "commitment_version": COMMITMENT_V2,
+        "commitment_scheme": "keccak256(u8(is_ship) || u32_be(index) || salt)",
+        "merkle_scheme": "leaves padded with 32 zero bytes to 2^depth; node = keccak256(0x01 || left || right)",
+        "salt_seed": hex(&SALT_SEED),
+        "board": {
+            "ship_indexes": SHIP_INDEXES.to_vec(),
+            "ship_cells": ship_cells,
+            "cells": cells,
+            "commitment_root": hex(&commitment_root.to_array()),
+            "merkle_root": hex(&merkle_root.to_array()),
+            "proof_hash": hex(&board_proof_hash.to_array()),
+            "message": hex_bytes(&board_message),
+            "signature": sign(&signing_key, &board_message),
+        },
+        "attacks": attacks,
+    })
+}
+
+fn cell_salt(env: &Env, index: u32) -> Bytes {
+    let mut seed = Bytes::from_array(env, &SALT_SEED);
+    seed.extend_from_array(&index.to_be_bytes());
+    Bytes::from_array(env, &env.crypto().keccak256(&seed).to_array())
+}
+
+fn sign(key: &SigningKey, message: &Bytes) -> String {
+    let raw: std::vec::Vec<u8> = message.iter().collect();
+    hex(&key.sign(&raw).to_bytes())
+}
+
+fn hex_bytes(bytes: &Bytes) -> String {
+    let raw: std::vec::Vec<u8> = bytes.iter().collect();
+    hex(&raw)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
@@ -0,0 +1,13 @@
+#![cfg(test)]
+
+use crate::render_vectors;
+
+#[test]
+fn test_checked_in_vectors_match_generator() {
+    let checked_in = include_str!("../vectors.json");
+    assert_eq!(
+        render_vectors(),
+        checked_in,
+        "test vectors drifted; run `cargo run -p test-vectors -- tools/test-vectors/vectors.json`"
+    );
+}
@@ -0,0 +1,102 @@
+use std::{env, fs, path::PathBuf, process};
+
+use serde_json::Value;
+
+mod wasm;
+
+mod test;
+
+const WASM_TARGET: &str = "wasm32v1-none";
+const DEFAULT_BUDGETS: &str = "tools/wasm-budget/budgets.json";
+
+struct Options {
+    build: bool,
+    budgets: PathBuf,
+}
+
+fn main() {
+    let options = parse_args();
+    let budgets_text = fs::read_to_string(&options.budgets).unwrap_or_else(|err| {
+        fail(&format!("cannot read {}: {err}", options.budgets.display()));
+    });
+    let budgets: Value = serde_json::from_str(&budgets_text).unwrap_or_else(|err| {
+        fail(&format!("invalid budgets file: {err}"));
+    });
+    let budgets = budgets.as_object().unwrap_or_else(|| fail("budgets file must be an object"));
+
+    let mut over_budget = false;
+    for (package, budget) in budgets {
+        if options.build {
+            build_package(package);
+        }
+
+        let wasm_name = package.replace('-', "_");
+        let wasm_path = PathBuf::from(format!("target/{WASM_TARGET}/release/{wasm_name}.wasm"));
+        let raw = fs::read(&wasm_path).unwrap_or_else(|err| {
+            fail(&format!("cannot read {}: {err}", wasm_path.display()));
+        });
+        let raw_report = wasm::inspect(&raw).unwrap_or_else(|err| fail(&format!("{package}: {err}")));
+        let stripped = wasm::strip(&raw).unwrap_or_else(|err| fail(&format!("{package}: {err}")));
+        let report = wasm::inspect(&stripped).unwrap_or_else(|err| fail(&format!("{package}: {err}")));
+
+        let out_path = PathBuf::from(format!("target/{WASM_TARGET}/release/{wasm_name}.stripped.wasm"));
+        fs::write(&out_path, &stripped).unwrap_or_else(|err| {
+            fail(&format!("cannot write {}: {err}", out_path.display()));
+        });
+
+        let max_bytes = budget["max_wasm_bytes"].as_u64().unwrap_or(u64::MAX) as usize;
+        let max_exports = budget["max_exports"].as_u64().unwrap_or(u64::MAX) as usize;
+        let size_ok = stripped.len() <= max_bytes;
+        let exports_ok = report.function_exports <= max_exports;
+        over_budget |= !size_ok || !exports_ok;
+
+        println!(
+            "{package}: {} -> {} bytes (budget {max_bytes}){}, {} function exports (budget {max_exports}){}, stripped {} custom sections",
+            raw.len(),
+            stripped.len(),
+            if size_ok { "" } else { " OVER" },
+            report.function_exports,
+            if exports_ok { "" } else { " OVER" },
+            raw_report.custom_sections - report.custom_sections,
+        );
+        println!("  wrote {}", out_path.display());
+    }
+
+    if over_budget {
+        process::exit(1);
+    }
+}
+
+fn parse_args() -> Options {
+    let mut options = Options { build: true, budgets: PathBuf::from(DEFAULT_BUDGETS) };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-build" => options.build = false,
+            "--budgets" => {
+                options.budgets = PathBuf::from(args.next().unwrap_or_else(|| fail("--budgets needs a path")));
+            }
+            "-h" | "--help" => {
+                println!("Usage: wasm-budget [--no-build] [--budgets <path>]");
+                process::exit(0);
+            }
+            other => fail(&format!("unknown argument: {other}")),
+        }
+    }
+    options
+}
+
+fn build_package(package: &str) {
+    let status = process::Command::new("cargo")
+        .args(["build", "--release", "--target", WASM_TARGET, "-p", package])
+        .status()
+        .unwrap_or_else(|err| fail(&format!("failed to run cargo: {err}")));
+    if !status.success() {
+        fail(&format!("building {package} failed"));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("wasm-budget: {message}");
+    process::exit(1);
+}
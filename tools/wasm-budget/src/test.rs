@@ -0,0 +1,46 @@
+#![cfg(test)]
+
+use crate::wasm::{inspect, strip, write_section, write_uleb, Report};
+
+fn custom_section(out: &mut Vec<u8>, name: &str, body: &[u8]) {
+    let mut payload = Vec::new();
+    write_uleb(&mut payload, name.len() as u64);
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(body);
+    write_section(out, 0, &payload);
+}
+
+fn export_entry(payload: &mut Vec<u8>, name: &str, kind: u8, index: u64) {
+    write_uleb(payload, name.len() as u64);
+    payload.extend_from_slice(name.as_bytes());
+    payload.push(kind);
+    write_uleb(payload, index);
+}
+
+#[test]
+fn test_strip_drops_debug_sections_and_global_exports() {
+    let mut module = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    custom_section(&mut module, "contractspecv0", &[1, 2, 3]);
+    custom_section(&mut module, "producers", &[4; 40]);
+    custom_section(&mut module, "name", &[5; 20]);
+
+    let mut exports = Vec::new();
+    write_uleb(&mut exports, 4);
+    export_entry(&mut exports, "memory", 2, 0);
+    export_entry(&mut exports, "start_game", 0, 3);
+    export_entry(&mut exports, "__data_end", 3, 1);
+    export_entry(&mut exports, "__heap_base", 3, 2);
+    write_section(&mut module, 7, &exports);
+
+    let before = inspect(&module).unwrap();
+    assert_eq!(before, Report { function_exports: 1, other_exports: 3, custom_sections: 3 });
+
+    let stripped = strip(&module).unwrap();
+    assert!(stripped.len() < module.len());
+    assert_eq!(inspect(&stripped).unwrap(), Report { function_exports: 1, other_exports: 1, custom_sections: 1 });
+}
+
+#[test]
+fn test_rejects_non_wasm_input() {
+    assert!(inspect(b"not wasm").is_err());
+}
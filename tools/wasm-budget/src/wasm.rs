@@ -0,0 +1,152 @@
+//! Minimal section-level reader/rewriter for contract WASM files.
+
+const MAGIC: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+const CUSTOM_SECTION: u8 = 0;
+const EXPORT_SECTION: u8 = 7;
+const EXPORT_KIND_FUNC: u8 = 0;
+const EXPORT_KIND_MEMORY: u8 = 2;
+
+/// Custom sections the Soroban host reads; everything else is dropped by `strip`.
+const REQUIRED_CUSTOM_SECTIONS: [&str; 3] = ["contractspecv0", "contractenvmetav0", "contractmetav0"];
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub function_exports: usize,
+    pub other_exports: usize,
+    pub custom_sections: usize,
+}
+
+pub fn inspect(wasm: &[u8]) -> Result<Report, String> {
+    let mut report = Report::default();
+    for section in sections(wasm)? {
+        match section.id {
+            CUSTOM_SECTION => report.custom_sections += 1,
+            EXPORT_SECTION => {
+                for export in exports(section.payload)? {
+                    if export.kind == EXPORT_KIND_FUNC {
+                        report.function_exports += 1;
+                    } else {
+                        report.other_exports += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(report)
+}
+
+/// Drops non-required custom sections and every non-function export except memory.
+pub fn strip(wasm: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = MAGIC.to_vec();
+    for section in sections(wasm)? {
+        match section.id {
+            CUSTOM_SECTION => {
+                let (name, _) = read_name(section.payload, 0)?;
+                if REQUIRED_CUSTOM_SECTIONS.contains(&name.as_str()) {
+                    write_section(&mut out, section.id, section.payload);
+                }
+            }
+            EXPORT_SECTION => {
+                let kept: Vec<Export> = exports(section.payload)?
+                    .into_iter()
+                    .filter(|e| e.kind == EXPORT_KIND_FUNC || e.kind == EXPORT_KIND_MEMORY)
+                    .collect();
+                let mut payload = Vec::new();
+                write_uleb(&mut payload, kept.len() as u64);
+                for export in kept {
+                    write_uleb(&mut payload, export.name.len() as u64);
+                    payload.extend_from_slice(export.name.as_bytes());
+                    payload.push(export.kind);
+                    write_uleb(&mut payload, export.index as u64);
+                }
+                write_section(&mut out, section.id, &payload);
+            }
+            _ => write_section(&mut out, section.id, section.payload),
+        }
+    }
+    Ok(out)
+}
+
+struct Section<'a> {
+    id: u8,
+    payload: &'a [u8],
+}
+
+struct Export {
+    name: String,
+    kind: u8,
+    index: u32,
+}
+
+fn sections(wasm: &[u8]) -> Result<Vec<Section<'_>>, String> {
+    if wasm.len() < MAGIC.len() || wasm[..MAGIC.len()] != MAGIC {
+        return Err("not a wasm v1 module".into());
+    }
+    let mut pos = MAGIC.len();
+    let mut out = Vec::new();
+    while pos < wasm.len() {
+        let id = wasm[pos];
+        let (size, next) = read_uleb(wasm, pos + 1)?;
+        let end = next.checked_add(size as usize).filter(|end| *end <= wasm.len()).ok_or("section overruns module")?;
+        out.push(Section { id, payload: &wasm[next..end] });
+        pos = end;
+    }
+    Ok(out)
+}
+
+fn exports(payload: &[u8]) -> Result<Vec<Export>, String> {
+    let (count, mut pos) = read_uleb(payload, 0)?;
+    let mut out = Vec::new();
+    for _ in 0..count {
+        let (name, next) = read_name(payload, pos)?;
+        let kind = *payload.get(next).ok_or("truncated export")?;
+        let (index, next) = read_uleb(payload, next + 1)?;
+        out.push(Export { name, kind, index: index as u32 });
+        pos = next;
+    }
+    Ok(out)
+}
+
+fn read_name(bytes: &[u8], pos: usize) -> Result<(String, usize), String> {
+    let (len, start) = read_uleb(bytes, pos)?;
+    let end = start + len as usize;
+    let raw = bytes.get(start..end).ok_or("truncated name")?;
+    let name = String::from_utf8(raw.to_vec()).map_err(|_| "name is not utf-8")?;
+    Ok((name, end))
+}
+
+fn read_uleb(bytes: &[u8], mut pos: usize) -> Result<(u64, usize), String> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(pos).ok_or("truncated leb128")?;
+        pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, pos));
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err("leb128 overflow".into());
+        }
+    }
+}
+
+pub(crate) fn write_uleb(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+pub(crate) fn write_section(out: &mut Vec<u8>, id: u8, payload: &[u8]) {
+    out.push(id);
+    write_uleb(out, payload.len() as u64);
+    out.extend_from_slice(payload);
+}